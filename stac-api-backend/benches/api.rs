@@ -0,0 +1,183 @@
+//! Benchmarks for the API's hot path: serving pages of items, and single
+//! items.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde_json::json;
+use serde_json::value::RawValue;
+use stac::{Catalog, Collection, Item, Link};
+use stac_api_backend::{
+    Api, Items, MemoryBackend, RawItem, ReadBackend, Search, Token, WriteBackend,
+};
+use std::collections::HashMap;
+use tokio::runtime::Runtime;
+
+type Paging = <MemoryBackend as ReadBackend>::Paging;
+
+const COLLECTION_ID: &str = "synthetic";
+const SIZES: [usize; 2] = [1_000, 10_000];
+
+/// Creates an API over a memory backend with this many items, spread over a
+/// grid that covers the world.
+fn api(runtime: &Runtime, count: usize) -> Api<MemoryBackend> {
+    let mut backend = MemoryBackend::new();
+    runtime.block_on(async {
+        let _ = backend
+            .add_collection(Collection::new(COLLECTION_ID, "Synthetic items"))
+            .await
+            .unwrap();
+        backend.add_items(items(count)).await.unwrap();
+    });
+    let api = Api::new(
+        backend,
+        Catalog::new("benchmarks", "A catalog for benchmarking"),
+        "http://stac-api-backend.test",
+    )
+    .unwrap();
+    api.max_limit(1_000)
+}
+
+fn items(count: usize) -> Vec<Item> {
+    let side = (count as f64).sqrt().ceil() as usize;
+    (0..count)
+        .map(|i| {
+            let x = -180.0 + 360.0 * ((i % side) as f64 + 0.5) / side as f64;
+            let y = -90.0 + 180.0 * ((i / side) as f64 + 0.5) / side as f64;
+            serde_json::from_value(json!({
+                "type": "Feature",
+                "stac_version": "1.0.0",
+                "id": format!("item-{}", i),
+                "collection": COLLECTION_ID,
+                "geometry": {"type": "Point", "coordinates": [x, y]},
+                "bbox": [x, y, x, y],
+                "properties": {
+                    "datetime": format!("2023-01-01T00:00:{:02}Z", i % 60),
+                    "eo:cloud_cover": (i % 100) as f64,
+                },
+                "links": [],
+                "assets": {
+                    "data": {"href": format!("http://stac-api-backend.test/data/{}.tif", i)}
+                }
+            }))
+            .unwrap()
+        })
+        .collect()
+}
+
+fn query(limit: u64, skip: Option<usize>) -> Items<Paging> {
+    let mut query = format!("limit={}", limit);
+    if let Some(skip) = skip {
+        let token = Token::encode(&HashMap::from([("skip", skip)])).unwrap();
+        query = format!("{}&token={}", query, token.token.unwrap());
+    }
+    Items::from_get_query(&query).unwrap()
+}
+
+/// Pages of items, which get item, paging, and collection links.
+fn items_links(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("items");
+    for count in SIZES {
+        let api = api(&runtime, count);
+        for limit in [10, 250] {
+            let _ = group.bench_with_input(
+                BenchmarkId::new(format!("limit={}", limit), count),
+                &limit,
+                |b, &limit| {
+                    b.to_async(&runtime)
+                        .iter(|| api.raw_items(COLLECTION_ID, query(limit, None)))
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+/// A page from the middle of the items, which has both `next` and `prev`
+/// links built from paging tokens.
+fn paging_links(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("paging");
+    for count in SIZES {
+        let api = api(&runtime, count);
+        let _ = group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, &count| {
+            b.to_async(&runtime)
+                .iter(|| api.items(COLLECTION_ID, query(10, Some(count / 2))))
+        });
+    }
+    group.finish();
+}
+
+/// Searches straight against the memory backend, without any links.
+fn memory_search(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("memory-search");
+    for count in SIZES {
+        let backend = api(&runtime, count).backend;
+        let search = |search: serde_json::Value| -> Search<Paging> {
+            Search {
+                search: serde_json::from_value(search).unwrap(),
+                paging: Paging::default(),
+            }
+        };
+        let cases = [
+            ("all", search(json!({"limit": 100}))),
+            (
+                "bbox",
+                search(json!({"limit": 100, "bbox": [-10.0, -10.0, 10.0, 10.0]})),
+            ),
+            (
+                "datetime",
+                search(
+                    json!({"limit": 100, "datetime": "2023-01-01T00:00:00Z/2023-01-01T00:00:09Z"}),
+                ),
+            ),
+        ];
+        for (name, search) in cases {
+            let _ = group.bench_with_input(BenchmarkId::new(name, count), &search, |b, search| {
+                b.to_async(&runtime).iter(|| backend.search(search.clone()))
+            });
+        }
+    }
+    group.finish();
+}
+
+/// An item's JSON, as it comes out of a database, turned into a response body
+/// with generated links, either parsed into an [Item] or kept raw.
+fn item_json(c: &mut Criterion) {
+    let mut group = c.benchmark_group("item-json");
+    let mut item = items(1).remove(0);
+    // A footprint, which is mostly what makes real items expensive to parse.
+    let ring: Vec<[f64; 2]> = (0..=200)
+        .map(|i| {
+            let angle = std::f64::consts::TAU * f64::from(i) / 200.0;
+            [angle.cos() * 0.123_456_789, angle.sin() * 0.123_456_789]
+        })
+        .collect();
+    item.geometry =
+        serde_json::from_value(json!({"type": "Polygon", "coordinates": [ring]})).unwrap();
+    let json = serde_json::to_string(&item).unwrap();
+    let links = vec![
+        Link::root("http://stac-api-backend.test/"),
+        Link::collection("http://stac-api-backend.test/collections/synthetic"),
+        Link::self_("http://stac-api-backend.test/collections/synthetic/items/item-0"),
+    ];
+    let _ = group.bench_function("parsed", |b| {
+        b.iter(|| {
+            let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+            let mut item: Item = serde_json::from_value(value).unwrap();
+            item.links.extend(links.iter().cloned());
+            serde_json::to_vec(&item).unwrap()
+        })
+    });
+    let _ = group.bench_function("raw", |b| {
+        b.iter(|| {
+            let mut item = RawItem::new(RawValue::from_string(json.clone()).unwrap());
+            item.links = links.clone();
+            serde_json::to_vec(&item).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, items_links, paging_links, memory_search, item_json);
+criterion_main!(benches);
@@ -1,10 +1,19 @@
-use crate::{Backend, Error, Result, DEFAULT_SERVICE_DESC_MEDIA_TYPE};
-use stac::Catalog;
+use super::{ApiBuilder, LinkDecorator, StacExtension};
+use crate::{ApiLimits, CollectionDefaults, Error, ReadBackend, Result};
+use stac::{Catalog, Collection, Link};
 use stac_api::UrlBuilder;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The cached listing of the backend's collections, and when it was fetched.
+pub(crate) type CollectionsCache = Arc<Mutex<Option<(Instant, Vec<Collection>)>>>;
 
 /// A structure for generating STAC API endpoints.
 #[derive(Clone, Debug)]
-pub struct Api<B: Backend> {
+pub struct Api<B: ReadBackend> {
     /// The backend for this API.
     pub backend: B,
 
@@ -16,33 +25,81 @@ pub struct Api<B: Backend> {
     /// We don't support _just_ collections.
     pub features: bool,
 
+    /// The number of items per page when the request doesn't set a limit.
+    ///
+    /// Defaults to [DEFAULT_LIMIT].
+    pub default_limit: u64,
+
+    /// The largest number of items per page.
+    ///
+    /// Larger limits are clamped to this value, as the [OGC API - Features
+    /// spec](https://docs.ogc.org/is/17-069r4/17-069r4.html#_parameter_limit)
+    /// recommends.
+    pub max_limit: Option<u64>,
+
+    /// If true, item pages include the context object from the deprecated
+    /// [context extension](https://github.com/stac-api-extensions/context),
+    /// as well as `numberMatched` and `numberReturned`.
+    ///
+    /// Defaults to true.
+    pub context: bool,
+
+    /// How long a listing of the backend's collections is reused by the root
+    /// and collections endpoints.
+    ///
+    /// Collections that are added or changed while a listing is cached show
+    /// up once it expires. Defaults to `None`, which doesn't cache.
+    pub collections_ttl: Option<Duration>,
+
+    pub(crate) collections_cache: CollectionsCache,
+
+    /// Per-collection paging defaults, by collection id.
+    pub collection_defaults: HashMap<String, CollectionDefaults>,
+
+    /// What items queries may ask for.
+    ///
+    /// By default, the supported parameters come from the backend.
+    pub limits: ApiLimits,
+
     /// The media type for the `service-desc` endpoint.
     ///
     /// Defaults to [DEFAULT_SERVICE_DESC_MEDIA_TYPE].
     pub service_desc_media_type: String,
 
+    /// Extra conformance classes, advertised after the ones the API generates.
+    pub conformance_classes: Vec<String>,
+
+    /// Extra links for the landing page, added after the ones the API generates.
+    pub landing_links: Vec<Link>,
+
+    /// Hooks that customize generated links, items, and collections.
+    pub link_decorators: Vec<Arc<dyn LinkDecorator>>,
+
+    /// Registered extensions.
+    pub extensions: Vec<Arc<dyn StacExtension>>,
+
     /// The base catalog for this api.
     pub catalog: Catalog,
 }
 
-impl<B: Backend> Api<B>
+impl<B: ReadBackend> Api<B>
 where
-    Error: From<<B as Backend>::Error>,
+    Error: From<<B as ReadBackend>::Error>,
 {
     /// Creates a new endpoint generator with the given backend, catalog, and root url.
     ///
     /// The catalog is used as the root endpoint. By default, the API will
     /// include links for
     /// [Features](https://github.com/radiantearth/stac-api-spec/tree/main/ogcapi-features)
-    /// -- set `features` to `False` to disable this behavior.
+    /// -- set `features` to `False` to disable this behavior. Use
+    /// [Api::builder] for anything more involved.
     pub fn new(backend: B, catalog: Catalog, url: &str) -> Result<Api<B>> {
-        Ok(Api {
-            backend,
-            catalog,
-            features: true,
-            service_desc_media_type: DEFAULT_SERVICE_DESC_MEDIA_TYPE.to_string(),
-            url_builder: UrlBuilder::new(url)?,
-        })
+        Api::builder(backend, catalog, url).build()
+    }
+
+    /// Returns an [ApiBuilder] with the given backend, catalog, and root url.
+    pub fn builder(backend: B, catalog: Catalog, url: &str) -> ApiBuilder<B> {
+        ApiBuilder::new(backend, catalog, url)
     }
 
     /// Sets the value of `features`.
@@ -50,4 +107,73 @@ where
         self.features = features;
         self
     }
+
+    /// Sets the value of `default_limit`.
+    pub fn default_limit(mut self, default_limit: u64) -> Api<B> {
+        self.default_limit = default_limit;
+        self
+    }
+
+    /// Sets the value of `max_limit`.
+    pub fn max_limit(mut self, max_limit: impl Into<Option<u64>>) -> Api<B> {
+        self.max_limit = max_limit.into();
+        self
+    }
+
+    /// Adds conformance classes, e.g. for extensions implemented outside of this crate.
+    ///
+    /// Unlike [ApiBuilder::conformance_class], these aren't checked.
+    pub fn with_conformance_classes(
+        mut self,
+        conformance_classes: impl IntoIterator<Item = impl ToString>,
+    ) -> Api<B> {
+        self.conformance_classes.extend(
+            conformance_classes
+                .into_iter()
+                .map(|conformance_class| conformance_class.to_string()),
+        );
+        self
+    }
+
+    /// Adds links to the landing page, e.g. to deployment-specific documentation.
+    ///
+    /// Unlike [ApiBuilder::landing_link], these aren't checked.
+    pub fn with_landing_links(mut self, landing_links: impl IntoIterator<Item = Link>) -> Api<B> {
+        self.landing_links.extend(landing_links);
+        self
+    }
+
+    /// Clamps the requested limit to `max_limit`.
+    ///
+    /// If there's no requested limit, `default_limit` is used (also clamped).
+    /// Whatever the other limits are, the result is never larger than
+    /// [ApiLimits::max_limit], so no request can ask a backend for an
+    /// enormous page.
+    pub fn limit(&self, limit: Option<u64>) -> u64 {
+        self.clamp(limit.unwrap_or(self.default_limit), self.max_limit)
+    }
+
+    /// Clamps the requested limit for a collection's items.
+    ///
+    /// Like [Api::limit], but the collection's [CollectionDefaults] win over
+    /// the api-wide ones.
+    pub fn collection_limit(&self, collection_id: &str, limit: Option<u64>) -> u64 {
+        let defaults = self.collection_defaults.get(collection_id);
+        let limit = limit
+            .or_else(|| defaults.and_then(|defaults| defaults.default_limit))
+            .unwrap_or(self.default_limit);
+        let max_limit = defaults
+            .and_then(|defaults| defaults.max_limit)
+            .or(self.max_limit);
+        self.clamp(limit, max_limit)
+    }
+
+    fn clamp(&self, limit: u64, max_limit: Option<u64>) -> u64 {
+        let limit = if let Some(max_limit) = max_limit {
+            limit.min(max_limit)
+        } else {
+            limit
+        };
+        limit.min(self.limits.max_limit)
+    }
 }
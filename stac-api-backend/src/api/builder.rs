@@ -0,0 +1,370 @@
+use super::{Api, LinkDecorator, StacExtension};
+use crate::{
+    ApiLimits, CollectionDefaults, Error, ReadBackend, Result, DEFAULT_LIMIT,
+    DEFAULT_SERVICE_DESC_MEDIA_TYPE,
+};
+use stac::{Catalog, Link};
+use stac_api::UrlBuilder;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use url::Url;
+
+/// Link relations that the API adds to the landing page itself.
+const RESERVED_RELS: [&str; 6] = [
+    "root",
+    "self",
+    "service-desc",
+    "service-doc",
+    "data",
+    "conformance",
+];
+
+/// Builds an [Api].
+///
+/// Nothing is checked until [ApiBuilder::build], so the setters can be called
+/// in any order.
+#[derive(Clone, Debug)]
+pub struct ApiBuilder<B: ReadBackend> {
+    backend: B,
+    catalog: Catalog,
+    url: String,
+    features: bool,
+    default_limit: u64,
+    max_limit: Option<u64>,
+    context: bool,
+    collections_ttl: Option<Duration>,
+    collection_defaults: HashMap<String, CollectionDefaults>,
+    limits: Option<ApiLimits>,
+    limit_ceiling: Option<u64>,
+    strict: bool,
+    parameters: Vec<String>,
+    service_desc_media_type: String,
+    conformance_classes: Vec<String>,
+    landing_links: Vec<Link>,
+    link_decorators: Vec<Arc<dyn LinkDecorator>>,
+    extensions: Vec<Arc<dyn StacExtension>>,
+}
+
+impl<B: ReadBackend> ApiBuilder<B>
+where
+    Error: From<<B as ReadBackend>::Error>,
+{
+    /// Creates a new builder with the given backend, catalog, and root url.
+    ///
+    /// Features are enabled by default.
+    pub fn new(backend: B, catalog: Catalog, url: impl ToString) -> ApiBuilder<B> {
+        ApiBuilder {
+            backend,
+            catalog,
+            url: url.to_string(),
+            features: true,
+            default_limit: DEFAULT_LIMIT,
+            max_limit: None,
+            context: true,
+            collections_ttl: None,
+            collection_defaults: HashMap::new(),
+            limits: None,
+            limit_ceiling: None,
+            strict: false,
+            parameters: Vec::new(),
+            service_desc_media_type: DEFAULT_SERVICE_DESC_MEDIA_TYPE.to_string(),
+            conformance_classes: Vec::new(),
+            landing_links: Vec::new(),
+            link_decorators: Vec::new(),
+            extensions: Vec::new(),
+        }
+    }
+
+    /// Should the API include the [Features](https://github.com/radiantearth/stac-api-spec/tree/main/ogcapi-features) endpoints?
+    pub fn features(mut self, features: bool) -> ApiBuilder<B> {
+        self.features = features;
+        self
+    }
+
+    /// Sets the number of items per page when the request doesn't set a limit.
+    pub fn default_limit(mut self, default_limit: u64) -> ApiBuilder<B> {
+        self.default_limit = default_limit;
+        self
+    }
+
+    /// Sets the largest number of items per page.
+    pub fn max_limit(mut self, max_limit: impl Into<Option<u64>>) -> ApiBuilder<B> {
+        self.max_limit = max_limit.into();
+        self
+    }
+
+    /// Should item pages include the deprecated context object?
+    ///
+    /// `numberMatched` and `numberReturned` are always included.
+    pub fn context(mut self, context: bool) -> ApiBuilder<B> {
+        self.context = context;
+        self
+    }
+
+    /// Reuses the backend's collections listing for this long.
+    ///
+    /// Large catalogs can be slow to list, and the root endpoint lists every
+    /// collection. Changes to the collections show up once the listing
+    /// expires.
+    pub fn collections_ttl(mut self, collections_ttl: Duration) -> ApiBuilder<B> {
+        self.collections_ttl = Some(collections_ttl);
+        self
+    }
+
+    /// Sets paging defaults for one collection's items.
+    pub fn collection_defaults(
+        mut self,
+        collection_id: impl ToString,
+        collection_defaults: CollectionDefaults,
+    ) -> ApiBuilder<B> {
+        let _ = self
+            .collection_defaults
+            .insert(collection_id.to_string(), collection_defaults);
+        self
+    }
+
+    /// Sets what items queries may ask for.
+    ///
+    /// By default, the limits allow the parameters that the backend supports.
+    pub fn limits(mut self, limits: ApiLimits) -> ApiBuilder<B> {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Sets the largest limit the API will ever serve, whatever the other
+    /// limits are.
+    ///
+    /// Requests for more items are rejected, and the default and maximum
+    /// limits (including per-collection ones) are clamped to this. Defaults
+    /// to [MAX_LIMIT](crate::MAX_LIMIT), and overrides the `max_limit` of [ApiBuilder::limits].
+    pub fn limit_ceiling(mut self, limit_ceiling: u64) -> ApiBuilder<B> {
+        self.limit_ceiling = Some(limit_ceiling);
+        self
+    }
+
+    /// Rejects items queries with parameters that nothing handles, instead of
+    /// ignoring them.
+    ///
+    /// Overrides the `strict` of [ApiBuilder::limits] if true. Parameters
+    /// that are handled outside of the api can be allowed with
+    /// [ApiBuilder::parameter].
+    pub fn strict(mut self, strict: bool) -> ApiBuilder<B> {
+        self.strict = strict;
+        self
+    }
+
+    /// Allows an items query parameter that's handled outside of the api,
+    /// e.g. a server's output format.
+    ///
+    /// Like an extension's parameters, it's allowed even if
+    /// [ApiBuilder::limits] is set.
+    pub fn parameter(mut self, parameter: impl ToString) -> ApiBuilder<B> {
+        self.parameters.push(parameter.to_string());
+        self
+    }
+
+    /// Sets the media type of the `service-desc` link.
+    pub fn service_desc_media_type(mut self, media_type: impl ToString) -> ApiBuilder<B> {
+        self.service_desc_media_type = media_type.to_string();
+        self
+    }
+
+    /// Adds a conformance class on top of the ones the API advertises itself.
+    pub fn conformance_class(mut self, conformance_class: impl ToString) -> ApiBuilder<B> {
+        self.conformance_classes.push(conformance_class.to_string());
+        self
+    }
+
+    /// Adds a link to the landing page, after the links the API generates.
+    pub fn landing_link(mut self, link: Link) -> ApiBuilder<B> {
+        self.landing_links.push(link);
+        self
+    }
+
+    /// Adds a [LinkDecorator], which runs after any that were already added.
+    pub fn link_decorator(mut self, link_decorator: impl LinkDecorator + 'static) -> ApiBuilder<B> {
+        self.link_decorators.push(Arc::new(link_decorator));
+        self
+    }
+
+    /// Registers a [StacExtension].
+    ///
+    /// The extension's parameters are allowed even if [ApiBuilder::limits] is set.
+    pub fn extension(mut self, extension: impl StacExtension + 'static) -> ApiBuilder<B> {
+        self.extensions.push(Arc::new(extension));
+        self
+    }
+
+    /// Checks the configuration and builds the [Api].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the url can't be parsed, if any limit is zero,
+    /// if the service-desc media type is empty, if a conformance class isn't a url, or if a landing link has
+    /// no href or uses a relation that the API already generates.
+    pub fn build(self) -> Result<Api<B>> {
+        let url_builder = UrlBuilder::new(&self.url)?;
+        if self.default_limit == 0
+            || self.max_limit == Some(0)
+            || self.limit_ceiling == Some(0)
+            || self.limits.as_ref().map(|limits| limits.max_limit) == Some(0)
+        {
+            return Err(Error::InvalidApi(
+                "limits must be greater than zero".to_string(),
+            ));
+        }
+        for (collection_id, collection_defaults) in &self.collection_defaults {
+            if collection_defaults.default_limit == Some(0)
+                || collection_defaults.max_limit == Some(0)
+            {
+                return Err(Error::InvalidApi(format!(
+                    "limits for collection {} must be greater than zero",
+                    collection_id
+                )));
+            }
+        }
+        if self.service_desc_media_type.trim().is_empty() {
+            return Err(Error::InvalidApi(
+                "service-desc media type is empty".to_string(),
+            ));
+        }
+        for conformance_class in &self.conformance_classes {
+            if Url::parse(conformance_class).is_err() {
+                return Err(Error::InvalidApi(format!(
+                    "conformance class is not a url: {}",
+                    conformance_class
+                )));
+            }
+        }
+        for link in &self.landing_links {
+            if link.href.is_empty() {
+                return Err(Error::InvalidApi(format!(
+                    "landing link with rel {} has no href",
+                    link.rel
+                )));
+            }
+            if RESERVED_RELS.contains(&link.rel.as_str()) {
+                return Err(Error::InvalidApi(format!(
+                    "the api generates the {} landing link",
+                    link.rel
+                )));
+            }
+        }
+        let mut limits = self.limits.unwrap_or_else(|| ApiLimits {
+            parameters: self
+                .backend
+                .supported_parameters()
+                .iter()
+                .map(|parameter| parameter.to_string())
+                .collect(),
+            ..Default::default()
+        });
+        if let Some(limit_ceiling) = self.limit_ceiling {
+            limits.max_limit = limit_ceiling;
+        }
+        if self.strict {
+            limits.strict = true;
+        }
+        let parameters = self
+            .extensions
+            .iter()
+            .flat_map(|extension| extension.parameters())
+            .chain(self.parameters);
+        for parameter in parameters {
+            if !limits.parameters.contains(&parameter) {
+                limits.parameters.push(parameter);
+            }
+        }
+        Ok(Api {
+            backend: self.backend,
+            url_builder,
+            features: self.features,
+            default_limit: self.default_limit,
+            max_limit: self.max_limit,
+            context: self.context,
+            collections_ttl: self.collections_ttl,
+            collections_cache: Default::default(),
+            collection_defaults: self.collection_defaults,
+            limits,
+            service_desc_media_type: self.service_desc_media_type,
+            conformance_classes: self.conformance_classes,
+            landing_links: self.landing_links,
+            link_decorators: self.link_decorators,
+            extensions: self.extensions,
+            catalog: self.catalog,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::ApiBuilder;
+    use crate::{assert_link, memory::MemoryBackend, CollectionDefaults, Error};
+    use stac::{Catalog, Link};
+
+    fn builder() -> ApiBuilder<MemoryBackend> {
+        ApiBuilder::new(
+            MemoryBackend::new(),
+            Catalog::new("test-catalog", "A catalog for testing"),
+            "http://stac-api-backend.test",
+        )
+    }
+
+    #[tokio::test]
+    async fn extras() {
+        let api = builder()
+            .conformance_class("https://api.stacspec.org/v1.0.0/item-search")
+            .landing_link(Link::new("http://stac-api-backend.test/search", "search").geojson())
+            .service_desc_media_type("application/json")
+            .build()
+            .unwrap();
+        let root = api.root().await.unwrap();
+        assert!(root
+            .conformance
+            .conforms_to
+            .contains(&"https://api.stacspec.org/v1.0.0/item-search".to_string()));
+        assert_link!(
+            root.catalog,
+            "search",
+            "http://stac-api-backend.test/search",
+            "application/geo+json"
+        );
+        assert_link!(
+            root.catalog,
+            "service-desc",
+            "http://stac-api-backend.test/api",
+            "application/json"
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        for builder in [
+            builder().default_limit(0),
+            builder().max_limit(0),
+            builder().limit_ceiling(0),
+            builder().collection_defaults(
+                "an-id",
+                CollectionDefaults {
+                    max_limit: Some(0),
+                    ..Default::default()
+                },
+            ),
+            builder().service_desc_media_type(""),
+            builder().conformance_class("not a url"),
+            builder().landing_link(Link::new("", "search")),
+            builder().landing_link(Link::new("http://stac-api-backend.test", "self")),
+        ] {
+            assert!(matches!(builder.build().unwrap_err(), Error::InvalidApi(_)));
+        }
+        assert!(matches!(
+            ApiBuilder::new(
+                MemoryBackend::new(),
+                Catalog::new("an-id", "a description"),
+                ""
+            )
+            .build()
+            .unwrap_err(),
+            Error::UrlParse(_)
+        ));
+    }
+}
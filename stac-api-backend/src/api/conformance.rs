@@ -1,13 +1,13 @@
 use super::Api;
-use crate::{Backend, Error};
+use crate::{Error, ReadBackend};
 use stac_api::{
     Conformance, COLLECTIONS_URI, CORE_URI, FEATURES_URI, GEOJSON_URI, OGC_API_FEATURES_URI,
 };
 
 impl<B> Api<B>
 where
-    B: Backend,
-    Error: From<<B as Backend>::Error>,
+    B: ReadBackend,
+    Error: From<<B as ReadBackend>::Error>,
 {
     /// Returns the conformance structure.
     pub fn conformance(&self) -> Conformance {
@@ -20,6 +20,10 @@ where
                 GEOJSON_URI.to_string(),
             ])
         }
+        conforms_to.extend(self.conformance_classes.iter().cloned());
+        for extension in &self.extensions {
+            conforms_to.extend(extension.conformance_classes());
+        }
         Conformance { conforms_to }
     }
 }
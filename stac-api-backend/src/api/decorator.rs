@@ -0,0 +1,207 @@
+use super::Api;
+use crate::{Error, ReadBackend, Result};
+use stac::{Catalog, Collection, Link};
+use std::fmt::Debug;
+
+/// Customizes the links, items, and collections that an [Api] generates.
+///
+/// Every method has a no-op default, so implementors only override what they
+/// need. [LinkDecorator::link] is called for every link the [Api] generates
+/// (but not for links that were stored with the object), and the object hooks
+/// are called last, right before the object is returned. Decorators run in
+/// the order they were added.
+///
+/// # Examples
+///
+/// Rewrite self links to point at a CDN:
+///
+/// ```
+/// use stac::Link;
+/// use stac_api_backend::LinkDecorator;
+///
+/// #[derive(Debug)]
+/// struct Cdn;
+///
+/// impl LinkDecorator for Cdn {
+///     fn link(&self, link: &mut Link) {
+///         if link.is_self() {
+///             link.href = link.href.replace("http://stac-api-backend.test", "https://cdn.test");
+///         }
+///     }
+/// }
+/// ```
+pub trait LinkDecorator: Debug + Send + Sync {
+    /// Modifies a generated link.
+    fn link(&self, _link: &mut Link) {}
+
+    /// Modifies the landing page catalog.
+    fn catalog(&self, _catalog: &mut Catalog) {}
+
+    /// Modifies a collection, from either the collections or collection endpoint.
+    fn collection(&self, _collection: &mut Collection) {}
+
+    /// Modifies an item, from either the items or item endpoint.
+    fn item(&self, _item: &mut stac_api::Item) {}
+}
+
+impl<B> Api<B>
+where
+    B: ReadBackend,
+    Error: From<<B as ReadBackend>::Error>,
+{
+    pub(crate) fn decorate_links(&self, links: impl IntoIterator<Item = Link>) -> Vec<Link> {
+        links
+            .into_iter()
+            .map(|mut link| {
+                self.decorate_link(&mut link);
+                link
+            })
+            .collect()
+    }
+
+    pub(crate) fn decorate_link(&self, link: &mut Link) {
+        for decorator in &self.link_decorators {
+            decorator.link(link);
+        }
+    }
+
+    pub(crate) fn decorate_catalog(&self, catalog: &mut Catalog) {
+        for decorator in &self.link_decorators {
+            decorator.catalog(catalog);
+        }
+    }
+
+    pub(crate) fn decorate_collection(&self, collection: &mut Collection) {
+        for decorator in &self.link_decorators {
+            decorator.collection(collection);
+        }
+    }
+
+    pub(crate) fn decorate_item(&self, item: &mut stac_api::Item) {
+        for decorator in &self.link_decorators {
+            decorator.item(item);
+        }
+    }
+
+    pub(crate) fn decorate_stac_item(&self, item: stac::Item) -> Result<stac::Item> {
+        if self.link_decorators.is_empty() {
+            Ok(item)
+        } else {
+            let mut item = stac_api::Item::try_from(item)?;
+            self.decorate_item(&mut item);
+            let item = stac::Item::try_from(item)?;
+            Ok(item)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::LinkDecorator;
+    use crate::{assert_link, memory::MemoryBackend, Api, Items, WriteBackend};
+    use serde_json::Value;
+    use stac::{Catalog, Collection, Item, Link};
+
+    #[derive(Debug)]
+    struct Cdn;
+
+    impl LinkDecorator for Cdn {
+        fn link(&self, link: &mut Link) {
+            link.href = link
+                .href
+                .replace("http://stac-api-backend.test", "https://cdn.test");
+        }
+
+        fn collection(&self, collection: &mut Collection) {
+            collection.links.push(
+                Link::new(
+                    format!("https://cdn.test/collections/{}.html", collection.id),
+                    "alternate",
+                )
+                .r#type("text/html".to_string()),
+            );
+        }
+
+        fn item(&self, item: &mut stac_api::Item) {
+            let _ = item.insert("decorated".to_string(), Value::Bool(true));
+        }
+    }
+
+    async fn api() -> Api<MemoryBackend> {
+        let mut api = Api::builder(
+            MemoryBackend::new(),
+            Catalog::new("test-catalog", "A catalog for testing"),
+            "http://stac-api-backend.test",
+        )
+        .link_decorator(Cdn)
+        .build()
+        .unwrap();
+        let _ = api
+            .backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        api.backend
+            .add_item(Item::new("item-id").collection("an-id"))
+            .await
+            .unwrap();
+        api
+    }
+
+    #[tokio::test]
+    async fn root() {
+        let root = api().await.root().await.unwrap();
+        assert_link!(
+            root.catalog,
+            "self",
+            "https://cdn.test/",
+            "application/json"
+        );
+    }
+
+    #[tokio::test]
+    async fn collection() {
+        let collection = api().await.collection("an-id").await.unwrap().unwrap();
+        assert_link!(
+            collection,
+            "self",
+            "https://cdn.test/collections/an-id",
+            "application/json"
+        );
+        assert_link!(
+            collection,
+            "alternate",
+            "https://cdn.test/collections/an-id.html",
+            "text/html"
+        );
+    }
+
+    #[tokio::test]
+    async fn items() {
+        let api = api().await;
+        let items = api.items("an-id", Items::default()).await.unwrap().unwrap();
+        assert_link!(
+            items,
+            "self",
+            "https://cdn.test/collections/an-id/items",
+            "application/geo+json"
+        );
+        assert_eq!(items.items[0]["decorated"], Value::Bool(true));
+        let item: Item = items.items[0].clone().try_into().unwrap();
+        assert_link!(
+            item,
+            "self",
+            "https://cdn.test/collections/an-id/items/item-id",
+            "application/geo+json"
+        );
+
+        let item = api.item("an-id", "item-id").await.unwrap().unwrap();
+        assert_link!(
+            item,
+            "collection",
+            "https://cdn.test/collections/an-id",
+            "application/json"
+        );
+        assert_eq!(item.additional_fields["decorated"], Value::Bool(true));
+    }
+}
@@ -0,0 +1,181 @@
+use super::Api;
+use crate::{Error, ReadBackend, Result};
+use stac::Link;
+use stac_api::UrlBuilder;
+use std::fmt::Debug;
+
+/// A pluggable unit of STAC API functionality, e.g. sort, fields, or aggregations.
+///
+/// Extensions are registered with [ApiBuilder::extension](crate::ApiBuilder::extension).
+/// Every method but [StacExtension::name] has a no-op default, so an
+/// extension only describes what it adds:
+///
+/// - its conformance classes are advertised on `/` and `/conformance`
+/// - its query parameters are allowed by [Items::validate](crate::Items::validate)
+/// - its items hook sees every items query before the backend does
+/// - its landing links are added to `/`
+///
+/// Servers can serve extra routes for an extension, e.g. with
+/// `stac_server::ExtensionRoutes`.
+///
+/// # Examples
+///
+/// ```
+/// use stac_api_backend::StacExtension;
+///
+/// #[derive(Debug)]
+/// struct Sort;
+///
+/// impl StacExtension for Sort {
+///     fn name(&self) -> &str {
+///         "sort"
+///     }
+///
+///     fn conformance_classes(&self) -> Vec<String> {
+///         vec!["https://api.stacspec.org/v1.0.0/ogcapi-features#sort".to_string()]
+///     }
+///
+///     fn parameters(&self) -> Vec<String> {
+///         vec!["sortby".to_string()]
+///     }
+/// }
+/// ```
+pub trait StacExtension: Debug + Send + Sync {
+    /// A short name for this extension, e.g. `sort`.
+    fn name(&self) -> &str;
+
+    /// The conformance classes that this extension implements.
+    fn conformance_classes(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The items query parameters that this extension handles, e.g. `sortby`.
+    fn parameters(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Checks or modifies an items query before it's sent to the backend.
+    fn items(&self, _items: &mut stac_api::Items) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns links to add to the landing page.
+    fn landing_links(&self, _url_builder: &UrlBuilder) -> Result<Vec<Link>> {
+        Ok(Vec::new())
+    }
+}
+
+impl<B> Api<B>
+where
+    B: ReadBackend,
+    Error: From<<B as ReadBackend>::Error>,
+{
+    pub(crate) fn extension_landing_links(&self) -> Result<Vec<Link>> {
+        let mut links = Vec::new();
+        for extension in &self.extensions {
+            links.extend(extension.landing_links(&self.url_builder)?);
+        }
+        Ok(links)
+    }
+
+    pub(crate) fn extend_items(&self, items: &mut stac_api::Items) -> Result<()> {
+        for extension in &self.extensions {
+            extension.items(items)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::StacExtension;
+    use crate::{assert_link, memory::MemoryBackend, Api, Error, Items, Result, WriteBackend};
+    use stac::{Catalog, Collection, Item, Link};
+    use stac_api::UrlBuilder;
+
+    #[derive(Debug)]
+    struct OneAtATime;
+
+    impl StacExtension for OneAtATime {
+        fn name(&self) -> &str {
+            "one-at-a-time"
+        }
+
+        fn conformance_classes(&self) -> Vec<String> {
+            vec!["https://stac-api-backend.test/one-at-a-time".to_string()]
+        }
+
+        fn parameters(&self) -> Vec<String> {
+            vec!["sortby".to_string()]
+        }
+
+        fn items(&self, items: &mut stac_api::Items) -> Result<()> {
+            if items.limit.unwrap_or(1) > 1 {
+                return Err(Error::InvalidQuery("one at a time".to_string()));
+            }
+            items.limit = Some(1);
+            Ok(())
+        }
+
+        fn landing_links(&self, url_builder: &UrlBuilder) -> Result<Vec<Link>> {
+            Ok(vec![Link::new(
+                url_builder.root().join("one-at-a-time")?,
+                "describedby",
+            )
+            .r#type("text/html".to_string())])
+        }
+    }
+
+    async fn api() -> Api<MemoryBackend> {
+        let mut api = Api::builder(
+            MemoryBackend::new(),
+            Catalog::new("test-catalog", "A catalog for testing"),
+            "http://stac-api-backend.test",
+        )
+        .extension(OneAtATime)
+        .build()
+        .unwrap();
+        let _ = api
+            .backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        api.backend
+            .add_items(vec![
+                Item::new("item-a").collection("an-id"),
+                Item::new("item-b").collection("an-id"),
+            ])
+            .await
+            .unwrap();
+        api
+    }
+
+    #[tokio::test]
+    async fn root() {
+        let root = api().await.root().await.unwrap();
+        assert!(root
+            .conformance
+            .conforms_to
+            .contains(&"https://stac-api-backend.test/one-at-a-time".to_string()));
+        assert_link!(
+            root.catalog,
+            "describedby",
+            "http://stac-api-backend.test/one-at-a-time",
+            "text/html"
+        );
+    }
+
+    #[tokio::test]
+    async fn items() {
+        let api = api().await;
+        assert!(api.limits.parameters.contains(&"sortby".to_string()));
+        let items = api.items("an-id", Items::default()).await.unwrap().unwrap();
+        assert_eq!(items.items.len(), 1);
+        let mut query = Items::default();
+        query.items.limit = Some(2);
+        assert!(matches!(
+            api.items("an-id", query).await.unwrap_err(),
+            Error::InvalidQuery(_)
+        ));
+    }
+}
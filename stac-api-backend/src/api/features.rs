@@ -1,34 +1,38 @@
-use super::Api;
-use crate::{Backend, Error, Items, Result};
+use super::{
+    links::{set_link_values, set_links},
+    Api,
+};
+use crate::{
+    CollectionSummary, CountMode, Error, Items, ItemsResult, Page, RawItem, RawItemCollection,
+    ReadBackend, Result, Token,
+};
 use http::Method;
+use serde::Deserialize;
 use serde_json::Value;
 use stac::{Collection, Item, Link};
 use stac_api::{Collections, ItemCollection};
+use std::{collections::HashMap, time::Instant};
+use url::Url;
 
 impl<B> Api<B>
 where
-    B: Backend,
-    Error: From<<B as Backend>::Error>,
+    B: ReadBackend,
+    Error: From<<B as ReadBackend>::Error>,
 {
     /// Returns collections.
     pub async fn collections(&self) -> Result<Collections> {
         // TODO collection pagination
         // https://github.com/radiantearth/stac-api-spec/tree/release/v1.0.0/ogcapi-features#collection-pagination
-        let mut collections = self.backend.collections().await?;
+        let mut collections = self.backend_collections().await?;
         for collection in &mut collections {
-            collection.links.extend([
-                Link::root(self.url_builder.root()).title(self.catalog.title.clone()),
-                Link::parent(self.url_builder.root()).title(self.catalog.title.clone()),
-                Link::self_(self.url_builder.collection(&collection.id)?)
-                    .title(collection.title.clone()),
-                Link::new(self.url_builder.items(&collection.id)?, "items")
-                    .title("Items".to_string()),
-            ]);
+            let links = self.collection_links(collection)?;
+            set_links(&mut collection.links, links);
+            self.decorate_collection(collection);
         }
-        let links = vec![
+        let links = self.decorate_links([
             Link::root(self.url_builder.root()).title(self.catalog.title.clone()),
             Link::self_(self.url_builder.collections()).title("Collections".to_string()),
-        ];
+        ]);
         Ok(Collections {
             collections,
             links,
@@ -36,18 +40,64 @@ where
         })
     }
 
+    /// Returns a collection's generated links, the same whether the
+    /// collection is served by itself or in a list.
+    fn collection_links(&self, collection: &Collection) -> Result<Vec<Link>> {
+        let mut links = vec![
+            Link::root(self.url_builder.root()).title(self.catalog.title.clone()),
+            Link::parent(self.url_builder.root()).title(self.catalog.title.clone()),
+            Link::self_(self.url_builder.collection(&collection.id)?)
+                .title(collection.title.clone()),
+            Link::new(self.url_builder.items(&collection.id)?, "items")
+                .title("Items".to_string())
+                .geojson(),
+        ];
+        links.extend(self.queryables_link(Some(&collection.id))?);
+        Ok(self.decorate_links(links))
+    }
+
+    /// Lists the backend's collections, reusing the cached listing if it
+    /// hasn't expired.
+    pub(crate) async fn backend_collections(&self) -> Result<Vec<Collection>> {
+        let ttl = if let Some(ttl) = self.collections_ttl {
+            ttl
+        } else {
+            return self.backend.collections().await.map_err(Error::from);
+        };
+        if let Some((fetched, collections)) = &*self.collections_cache.lock().unwrap() {
+            if fetched.elapsed() < ttl {
+                return Ok(collections.clone());
+            }
+        }
+        let collections = self.backend.collections().await?;
+        *self.collections_cache.lock().unwrap() = Some((Instant::now(), collections.clone()));
+        Ok(collections)
+    }
+
+    /// Lists the ids and titles of the backend's collections.
+    ///
+    /// If collections listings are cached, the cached listing is used.
+    pub(crate) async fn collection_summaries(&self) -> Result<Vec<CollectionSummary>> {
+        if self.collections_ttl.is_some() {
+            let collections = self.backend_collections().await?;
+            Ok(collections
+                .into_iter()
+                .map(CollectionSummary::from)
+                .collect())
+        } else {
+            self.backend
+                .collection_summaries()
+                .await
+                .map_err(Error::from)
+        }
+    }
+
     /// Returns a collection or None.
     pub async fn collection(&self, id: &str) -> Result<Option<Collection>> {
         if let Some(mut collection) = self.backend.collection(id).await? {
-            collection.links.extend([
-                Link::root(self.url_builder.root()).title(self.catalog.title.clone()),
-                Link::parent(self.url_builder.root()).title(self.catalog.title.clone()),
-                Link::self_(self.url_builder.collection(&collection.id)?)
-                    .title(collection.title.clone()),
-                Link::new(self.url_builder.items(&collection.id)?, "items")
-                    .title("Items".to_string())
-                    .geojson(),
-            ]);
+            let links = self.collection_links(&collection)?;
+            set_links(&mut collection.links, links);
+            self.decorate_collection(&mut collection);
             Ok(Some(collection))
         } else {
             Ok(None)
@@ -55,42 +105,49 @@ where
     }
 
     /// Returns items.
+    ///
+    /// The query's limit is clamped to `max_limit`, and if the query has no
+    /// limit the backend is asked for `default_limit` items. Paging links
+    /// carry the backend's paging structure as a [Token].
     pub async fn items(&self, id: &str, items: Items<B::Paging>) -> Result<Option<ItemCollection>> {
-        if let Some(page) = self.backend.items(id, items.clone()).await? {
-            let mut url = self.url_builder.items(id)?;
-
-            let get_items = stac_api::GetItems::try_from(items.items)?;
-            let query = serde_urlencoded::to_string(&get_items)?;
-            if !query.is_empty() {
-                url.set_query(Some(&query));
+        if let Some((page, url, current)) = self.page(id, items).await? {
+            let mut item_collection = page.into_item_collection(&url, &Method::GET, current)?;
+            set_links(&mut item_collection.links, self.item_collection_links(id)?);
+            item_collection.links = self.decorate_links(std::mem::take(&mut item_collection.links));
+            let shared_links = self.shared_item_links(id)?;
+            for item in &mut item_collection.items {
+                self.link_item(item, id, &shared_links)?;
             }
-            let mut item_collection =
-                page.into_item_collection(&url, &Method::GET, items.paging)?;
-            item_collection.links.extend([
-                Link::root(self.url_builder.root()).title(self.catalog.title.clone()),
-                Link::collection(self.url_builder.collection(id)?),
-            ]);
+            Ok(Some(item_collection))
+        } else {
+            Ok(None)
+        }
+    }
 
+    /// Returns items without parsing them, if the backend provides them as raw JSON.
+    ///
+    /// The result serializes to the same JSON as [Api::items]. If there are
+    /// any [LinkDecorator](crate::LinkDecorator)s the items have to be parsed
+    /// anyways, so this falls back to [Api::items].
+    pub async fn raw_items(
+        &self,
+        id: &str,
+        items: Items<B::Paging>,
+    ) -> Result<Option<RawItemCollection>> {
+        if !self.link_decorators.is_empty() {
+            return self
+                .items(id, items)
+                .await?
+                .map(RawItemCollection::try_from)
+                .transpose();
+        }
+        if let Some((page, url, current)) = self.page(id, items).await? {
+            let mut item_collection = page.into_raw_item_collection(&url, &Method::GET, current)?;
+            set_links(&mut item_collection.links, self.item_collection_links(id)?);
+            let shared_links = self.shared_item_links(id)?;
             for item in &mut item_collection.items {
-                let mut links = vec![
-                    serde_json::to_value(
-                        Link::root(self.url_builder.root()).title(self.catalog.title.clone()),
-                    )?,
-                    serde_json::to_value(Link::parent(self.url_builder.collection(id)?))?,
-                    serde_json::to_value(Link::collection(self.url_builder.collection(id)?))?,
-                ];
-                if let Some(item_id) = item.get("id").and_then(|value| value.as_str()) {
-                    links.push(serde_json::to_value(
-                        Link::self_(self.url_builder.item(id, item_id)?).geojson(),
-                    )?);
-                }
-                if let Some(existing_links) =
-                    item.get_mut("links").and_then(|value| value.as_array_mut())
-                {
-                    existing_links.extend(links);
-                } else {
-                    let _ = item.insert("links".to_string(), Value::Array(links));
-                }
+                let item_id = item.id()?;
+                item.links = self.item_links(id, item_id.as_deref(), &shared_links)?;
             }
             Ok(Some(item_collection))
         } else {
@@ -98,16 +155,179 @@ where
         }
     }
 
+    async fn page(
+        &self,
+        id: &str,
+        mut items: Items<B::Paging>,
+    ) -> Result<Option<(Page<Token>, Url, Token)>> {
+        self.extend_items(&mut items.items)?;
+        if items.items.limit.is_some() {
+            items.items.limit = Some(self.collection_limit(id, items.items.limit));
+        }
+        let mut query = items.clone();
+        let limit = self.collection_limit(id, query.items.limit);
+        query.items.limit = Some(limit);
+        let skip_geometry = items.skip_geometry();
+        if skip_geometry && self.backend.supported_parameters().contains(&"fields") {
+            // Backends that support fields can leave geometries out of their queries.
+            query
+                .items
+                .fields
+                .get_or_insert_with(Default::default)
+                .exclude
+                .push("geometry".to_string());
+        }
+        if query.items.sortby.is_none() {
+            query.items.sortby = self
+                .collection_defaults
+                .get(id)
+                .and_then(|collection_defaults| collection_defaults.sortby.clone());
+        }
+        let result = self.backend.items(id, query.clone()).await?;
+        if let ItemsResult::Page(mut page) = result {
+            if skip_geometry {
+                page.null_geometries()?;
+            }
+            if query.count() == Some(CountMode::Off) {
+                // Backends that count every page anyway don't report it either.
+                page.item_collection.number_matched = None;
+                if let Some(context) = page.item_collection.context.as_mut() {
+                    context.matched = None;
+                }
+            } else if page.item_collection.number_matched.is_none() {
+                page.item_collection.number_matched =
+                    self.backend.matched_count(id, &query).await?;
+            }
+            page.set_counts(limit, self.context);
+            let mut url = self.url_builder.items(id)?;
+
+            // Paging links start from the client's own query, if there was
+            // one, so only the token changes between them.
+            let query = if let Some(query) = items.query {
+                query
+            } else {
+                let additional_fields = items.items.additional_fields.clone();
+                let mut get_items = stac_api::GetItems::try_from(items.items)?;
+                unquote_strings(&additional_fields, &mut get_items.additional_fields);
+                serde_urlencoded::to_string(&get_items)?
+            };
+            if !query.is_empty() {
+                url.set_query(Some(&query));
+            }
+            // Clients see an opaque token, whatever the backend's paging looks like.
+            let page = page.into_token_page()?;
+            let current = Token::encode(&items.paging)?;
+            Ok(Some((page, url, current)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Sets an item's generated links and runs the item decorators.
+    ///
+    /// `shared_links` are the collection's [Api::shared_item_links].
+    pub(super) fn link_item(
+        &self,
+        item: &mut stac_api::Item,
+        collection_id: &str,
+        shared_links: &[Link],
+    ) -> Result<()> {
+        let item_id = item.get("id").and_then(|value| value.as_str());
+        let links = self.item_links(collection_id, item_id, shared_links)?;
+        if let Some(existing_links) = item.get_mut("links").and_then(|value| value.as_array_mut()) {
+            set_link_values(existing_links, links)?;
+        } else {
+            let mut values = Vec::new();
+            set_link_values(&mut values, links)?;
+            let _ = item.insert("links".to_string(), Value::Array(values));
+        }
+        self.decorate_item(item);
+        Ok(())
+    }
+
+    fn item_collection_links(&self, id: &str) -> Result<Vec<Link>> {
+        Ok(vec![
+            Link::root(self.url_builder.root()).title(self.catalog.title.clone()),
+            Link::collection(self.url_builder.collection(id)?),
+        ])
+    }
+
+    /// Returns the generated links that are the same for every item in a
+    /// collection.
+    ///
+    /// Pages build these once and clone them onto each item, instead of
+    /// building the same urls for every item.
+    pub(super) fn shared_item_links(&self, collection_id: &str) -> Result<Vec<Link>> {
+        let collection_url = self.url_builder.collection(collection_id)?;
+        Ok(self.decorate_links([
+            Link::root(self.url_builder.root()).title(self.catalog.title.clone()),
+            Link::parent(collection_url.clone()),
+            Link::collection(collection_url),
+        ]))
+    }
+
+    fn item_links(
+        &self,
+        collection_id: &str,
+        item_id: Option<&str>,
+        shared_links: &[Link],
+    ) -> Result<Vec<Link>> {
+        let mut links = Vec::with_capacity(shared_links.len() + 1);
+        links.extend_from_slice(shared_links);
+        if let Some(item_id) = item_id {
+            let mut link = Link::self_(self.url_builder.item(collection_id, item_id)?).geojson();
+            self.decorate_link(&mut link);
+            links.push(link);
+        }
+        Ok(links)
+    }
+
     /// Returns an item.
+    ///
+    /// An item whose `collection` isn't `collection_id`, which a misconfigured
+    /// backend might return, isn't found rather than served under the wrong
+    /// collection.
     pub async fn item(&self, collection_id: &str, id: &str) -> Result<Option<Item>> {
         if let Some(mut item) = self.backend.item(collection_id, id).await? {
-            let collection_url = self.url_builder.collection(collection_id)?;
-            item.links.extend([
-                Link::root(self.url_builder.root()).title(self.catalog.title.clone()),
-                Link::parent(collection_url.clone()),
-                Link::collection(collection_url),
-                Link::self_(self.url_builder.item(collection_id, id)?).geojson(),
-            ]);
+            if in_other_collection(item.collection.as_deref(), collection_id, id) {
+                return Ok(None);
+            }
+            let links = self.item_links(
+                collection_id,
+                Some(id),
+                &self.shared_item_links(collection_id)?,
+            )?;
+            set_links(&mut item.links, links);
+            self.decorate_stac_item(item).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns an item as raw JSON, with its generated links.
+    ///
+    /// Unless there are link decorators, which need the parsed item, the
+    /// backend's JSON is kept as-is and the links are added when the item is
+    /// serialized.
+    pub async fn raw_item(&self, collection_id: &str, id: &str) -> Result<Option<RawItem>> {
+        if !self.link_decorators.is_empty() {
+            return self
+                .item(collection_id, id)
+                .await?
+                .map(|item| Ok(RawItem::new(serde_json::value::to_raw_value(&item)?)))
+                .transpose();
+        }
+        if let Some(raw) = self.backend.raw_item(collection_id, id).await? {
+            let ItemCollectionId { collection } = serde_json::from_str(raw.get())?;
+            if in_other_collection(collection.as_deref(), collection_id, id) {
+                return Ok(None);
+            }
+            let mut item = RawItem::new(raw);
+            item.links = self.item_links(
+                collection_id,
+                Some(id),
+                &self.shared_item_links(collection_id)?,
+            )?;
             Ok(Some(item))
         } else {
             Ok(None)
@@ -115,12 +335,67 @@ where
     }
 }
 
+/// The only field of an item needed to check which collection it's in.
+#[derive(Deserialize)]
+struct ItemCollectionId {
+    collection: Option<String>,
+}
+
+/// Returns true, and warns, if an item fetched from `collection_id` says it's
+/// in another collection.
+fn in_other_collection(collection: Option<&str>, collection_id: &str, id: &str) -> bool {
+    match collection {
+        Some(collection) if collection != collection_id => {
+            tracing::warn!(
+                collection_id,
+                id,
+                collection,
+                "backend returned an item from another collection"
+            );
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Puts string query parameters back into a GET query without JSON quotes.
+///
+/// Converting to a GET query turns every additional field into its JSON
+/// string, so `f=ndjson` would otherwise come back as `f="ndjson"`.
+pub(super) fn unquote_strings(
+    additional_fields: &serde_json::Map<String, Value>,
+    query: &mut HashMap<String, String>,
+) {
+    for (key, value) in additional_fields {
+        if let Value::String(value) = value {
+            let _ = query.insert(key.clone(), value.clone());
+        }
+    }
+}
+
 #[cfg(all(test, feature = "memory"))]
 mod tests {
     use super::super::tests;
-    use crate::{assert_link, memory::Paging, Backend, Items};
-    use stac::{Collection, Item, Links};
+    use crate::{
+        assert_link,
+        memory::{MemoryBackend, Paging},
+        Api, CollectionDefaults, Error, Items, ItemsResult, Page, ReadBackend, Result, Search,
+        Token, WriteBackend,
+    };
+    use async_trait::async_trait;
+    use stac::{Catalog, Collection, Item, Link, Links};
     use stac_validate::Validate;
+    use std::time::Duration;
+
+    fn token(skip: usize, take: usize) -> String {
+        Token::encode(&Paging {
+            skip: Some(skip),
+            take: Some(take),
+        })
+        .unwrap()
+        .token
+        .unwrap()
+    }
 
     #[tokio::test]
     async fn root_links_with_features() {
@@ -180,6 +455,49 @@ mod tests {
         assert_eq!(api.collections().await.unwrap().collections.len(), 1);
     }
 
+    #[tokio::test]
+    async fn collection_links_match_collections_links() {
+        let mut api = tests::api();
+        let _ = api
+            .backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let collection = api.collection("an-id").await.unwrap().unwrap();
+        let collections = api.collections().await.unwrap();
+        assert_eq!(collection.links, collections.collections[0].links);
+        assert_link!(
+            collection,
+            "items",
+            "http://stac-api-backend.test/collections/an-id/items",
+            "application/geo+json"
+        );
+    }
+
+    #[tokio::test]
+    async fn collections_ttl() {
+        let mut api = tests::api();
+        api.collections_ttl = Some(Duration::from_secs(60));
+        assert!(api.collections().await.unwrap().collections.is_empty());
+        let _ = api
+            .backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        assert!(api.collections().await.unwrap().collections.is_empty());
+        assert!(api
+            .root()
+            .await
+            .unwrap()
+            .catalog
+            .links
+            .iter()
+            .all(|link| link.rel != "child"));
+
+        api.collections_ttl = Some(Duration::ZERO);
+        assert_eq!(api.collections().await.unwrap().collections.len(), 1);
+    }
+
     #[tokio::test]
     async fn collection_miss() {
         assert!(tests::api().collection("id").await.unwrap().is_none());
@@ -294,6 +612,42 @@ mod tests {
         item.validate().unwrap();
     }
 
+    #[tokio::test]
+    async fn item_paging_links_keep_the_query() {
+        let mut api = tests::api();
+        let _ = api
+            .backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let items = ["item-a", "item-b", "item-c"].map(|id| Item::new(id).collection("an-id"));
+        api.backend.add_items(items.to_vec()).await.unwrap();
+        let query = format!(
+            "limit=1&token={}&datetime=..%2F2100-01-01T00%3A00%3A00Z",
+            token(1, 1)
+        );
+        let items: Items<Paging> = Items::from_get_query(&query).unwrap();
+        let items = api.items("an-id", items).await.unwrap().unwrap();
+        assert_link!(
+            items,
+            "self",
+            format!(
+                "http://stac-api-backend.test/collections/an-id/items?limit=1&datetime=..%2F2100-01-01T00%3A00%3A00Z&token={}",
+                token(1, 1)
+            ),
+            "application/geo+json"
+        );
+        assert_link!(
+            items,
+            "next",
+            format!(
+                "http://stac-api-backend.test/collections/an-id/items?limit=1&datetime=..%2F2100-01-01T00%3A00%3A00Z&token={}",
+                token(2, 1)
+            ),
+            "application/geo+json"
+        );
+    }
+
     #[tokio::test]
     async fn item_paging() {
         let mut api = tests::api();
@@ -313,9 +667,303 @@ mod tests {
         assert_link!(
             items,
             "next",
-            "http://stac-api-backend.test/collections/an-id/items?skip=1&take=1",
+            format!(
+                "http://stac-api-backend.test/collections/an-id/items?token={}",
+                token(1, 1)
+            ),
+            "application/geo+json"
+        );
+        assert_link!(
+            items,
+            "last",
+            format!(
+                "http://stac-api-backend.test/collections/an-id/items?token={}",
+                token(1, 1)
+            ),
+            "application/geo+json"
+        );
+        let first = Token::encode(&Paging {
+            skip: None,
+            take: Some(1),
+        })
+        .unwrap();
+        assert_link!(
+            items,
+            "first",
+            format!(
+                "http://stac-api-backend.test/collections/an-id/items?token={}",
+                first.token.unwrap()
+            ),
+            "application/geo+json"
+        );
+    }
+
+    #[tokio::test]
+    async fn item_paging_additional_fields() {
+        let mut api = tests::api();
+        let _ = api
+            .backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let mut items: Items<Paging> = Items::default();
+        let _ = items
+            .items
+            .additional_fields
+            .insert("f".to_string(), "ndjson".into());
+        let items = api.items("an-id", items).await.unwrap().unwrap();
+        assert_link!(
+            items,
+            "self",
+            "http://stac-api-backend.test/collections/an-id/items?f=ndjson",
             "application/geo+json"
+        );
+    }
+
+    #[tokio::test]
+    async fn skip_geometry() {
+        let mut api = tests::api();
+        let _ = api
+            .backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let mut item = Item::new("item-id").collection("an-id");
+        item.geometry = Some(stac::Geometry::point(-105.1, 41.0));
+        api.backend.add_items(vec![item]).await.unwrap();
+        let items: Items<Paging> = Items::from_get_query("skip-geometry=true").unwrap();
+        let item_collection = api.items("an-id", items.clone()).await.unwrap().unwrap();
+        assert!(item_collection.items[0]["geometry"].is_null());
+        assert_link!(
+            item_collection,
+            "self",
+            "http://stac-api-backend.test/collections/an-id/items?skip-geometry=true",
+            "application/geo+json"
+        );
+        let raw_items = api.raw_items("an-id", items).await.unwrap().unwrap();
+        let value = serde_json::to_value(raw_items).unwrap();
+        assert!(value["features"][0]["geometry"].is_null());
+        assert_eq!(value["features"][0]["id"], "item-id");
+    }
+
+    #[tokio::test]
+    async fn counts() {
+        let mut api = tests::api().default_limit(2);
+        let _ = api
+            .backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let items = (0..3)
+            .map(|i| Item::new(format!("item-{}", i)).collection("an-id"))
+            .collect();
+        api.backend.add_items(items).await.unwrap();
+        let items = api
+            .items("an-id", Items::<Paging>::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(items.number_matched, Some(3));
+        assert_eq!(items.number_returned, Some(2));
+        let context = items.context.unwrap();
+        assert_eq!(context.matched, Some(3));
+        assert_eq!(context.returned, 2);
+        assert_eq!(context.limit, Some(2));
+
+        api.context = false;
+        let items = api
+            .items("an-id", Items::<Paging>::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(items.number_returned, Some(2));
+        assert!(items.context.is_none());
+
+        let items = api
+            .items(
+                "an-id",
+                Items::<Paging>::from_get_query("count=none").unwrap(),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(items.number_matched, None);
+        assert_eq!(items.number_returned, Some(2));
+        let next = items.links.iter().find(|link| link.rel == "next").unwrap();
+        assert!(next.href.contains("count=none"), "{}", next.href);
+    }
+
+    #[tokio::test]
+    async fn item_limits() {
+        let mut api = tests::api().default_limit(2).max_limit(3);
+        let _ = api
+            .backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let items = (0..5)
+            .map(|i| Item::new(format!("item-{}", i)).collection("an-id"))
+            .collect();
+        api.backend.add_items(items).await.unwrap();
+
+        let items = api
+            .items("an-id", Items::<Paging>::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(items.items.len(), 2);
+
+        let mut query: Items<Paging> = Items::default();
+        query.items.limit = Some(4);
+        let items = api.items("an-id", query).await.unwrap().unwrap();
+        assert_eq!(items.items.len(), 3);
+        assert_link!(
+            items,
+            "next",
+            format!(
+                "http://stac-api-backend.test/collections/an-id/items?limit=3&token={}",
+                token(3, 3)
+            ),
+            "application/geo+json"
+        );
+    }
+
+    #[tokio::test]
+    async fn collection_defaults() {
+        let mut api = tests::api().default_limit(2).max_limit(3);
+        let _ = api.collection_defaults.insert(
+            "big".to_string(),
+            CollectionDefaults {
+                default_limit: Some(1),
+                max_limit: Some(2),
+                sortby: None,
+            },
+        );
+        for id in ["big", "small"] {
+            let _ = api
+                .backend
+                .add_collection(Collection::new(id, "a description"))
+                .await
+                .unwrap();
+            let items = (0..5)
+                .map(|i| Item::new(format!("item-{}", i)).collection(id))
+                .collect();
+            api.backend.add_items(items).await.unwrap();
+        }
+
+        let items = api
+            .items("big", Items::<Paging>::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(items.items.len(), 1);
+        let mut query: Items<Paging> = Items::default();
+        query.items.limit = Some(3);
+        let items = api.items("big", query.clone()).await.unwrap().unwrap();
+        assert_eq!(items.items.len(), 2);
+        let items = api.items("small", query).await.unwrap().unwrap();
+        assert_eq!(items.items.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn limit_ceiling() {
+        let mut api = Api::builder(
+            MemoryBackend::new(),
+            Catalog::new("test-catalog", "A catalog for testing"),
+            "http://stac-api-backend.test",
         )
+        .default_limit(20)
+        .limit_ceiling(3)
+        .collection_defaults(
+            "an-id",
+            CollectionDefaults {
+                max_limit: Some(100),
+                ..Default::default()
+            },
+        )
+        .build()
+        .unwrap();
+        let _ = api
+            .backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let items = (0..5)
+            .map(|i| Item::new(format!("item-{}", i)).collection("an-id"))
+            .collect();
+        api.backend.add_items(items).await.unwrap();
+
+        assert_eq!(api.limit(Some(1_000_000)), 3);
+        let items = api
+            .items("an-id", Items::<Paging>::default())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(items.items.len(), 3);
+        let mut query: Items<Paging> = Items::default();
+        query.items.limit = Some(1_000_000);
+        let items = api.items("an-id", query.clone()).await.unwrap().unwrap();
+        assert_eq!(items.items.len(), 3);
+        assert!(query.validate(&api.limits).is_err());
+    }
+
+    /// A memory backend that only counts matches with `matched_count`.
+    #[derive(Clone, Debug)]
+    struct Counted(MemoryBackend);
+
+    #[async_trait]
+    impl ReadBackend for Counted {
+        type Error = Error;
+        type Paging = Paging;
+
+        async fn collections(&self) -> Result<Vec<Collection>> {
+            Ok(self.0.collections().await?)
+        }
+
+        async fn collection(&self, id: &str) -> Result<Option<Collection>> {
+            Ok(self.0.collection(id).await?)
+        }
+
+        async fn items(&self, id: &str, items: Items<Paging>) -> Result<ItemsResult<Paging>> {
+            let mut result = self.0.items(id, items).await?;
+            if let ItemsResult::Page(page) = &mut result {
+                page.item_collection.number_matched = None;
+            }
+            Ok(result)
+        }
+
+        async fn matched_count(&self, _: &str, _: &Items<Paging>) -> Result<Option<u64>> {
+            Ok(Some(42))
+        }
+
+        async fn search(&self, search: Search<Paging>) -> Result<Page<Paging>> {
+            Ok(self.0.search(search).await?)
+        }
+
+        async fn item(&self, collection_id: &str, id: &str) -> Result<Option<Item>> {
+            Ok(self.0.item(collection_id, id).await?)
+        }
+    }
+
+    #[tokio::test]
+    async fn matched_count() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        backend
+            .add_items(vec![Item::new("item-a").collection("an-id")])
+            .await
+            .unwrap();
+        let api = Api::new(
+            Counted(backend),
+            Catalog::new("test-catalog", "A catalog for testing"),
+            "http://stac-api-backend.test",
+        )
+        .unwrap();
+        let items = api.items("an-id", Items::default()).await.unwrap().unwrap();
+        assert_eq!(items.number_matched, Some(42));
     }
 
     #[tokio::test]
@@ -355,4 +1003,116 @@ mod tests {
         );
         item.validate().unwrap();
     }
+
+    /// Returns items from the first collection no matter which is asked for.
+    #[derive(Clone, Debug)]
+    struct Misconfigured(MemoryBackend);
+
+    #[async_trait]
+    impl ReadBackend for Misconfigured {
+        type Error = Error;
+        type Paging = Paging;
+
+        async fn collections(&self) -> Result<Vec<Collection>> {
+            Ok(self.0.collections().await?)
+        }
+
+        async fn collection(&self, id: &str) -> Result<Option<Collection>> {
+            Ok(self.0.collection(id).await?)
+        }
+
+        async fn items(&self, id: &str, items: Items<Paging>) -> Result<ItemsResult<Paging>> {
+            Ok(self.0.items(id, items).await?)
+        }
+
+        async fn search(&self, search: Search<Paging>) -> Result<Page<Paging>> {
+            Ok(self.0.search(search).await?)
+        }
+
+        async fn item(&self, _: &str, id: &str) -> Result<Option<Item>> {
+            Ok(self.0.item("an-id", id).await?)
+        }
+    }
+
+    #[tokio::test]
+    async fn item_from_other_collection() {
+        let mut backend = MemoryBackend::new();
+        for id in ["an-id", "another-id"] {
+            let _ = backend
+                .add_collection(Collection::new(id, "a description"))
+                .await
+                .unwrap();
+        }
+        backend
+            .add_item(Item::new("item-id").collection("an-id"))
+            .await
+            .unwrap();
+        let api = Api::new(
+            Misconfigured(backend),
+            Catalog::new("test-catalog", "A catalog for testing"),
+            "http://stac-api-backend.test",
+        )
+        .unwrap();
+        assert!(api.item("an-id", "item-id").await.unwrap().is_some());
+        assert!(api.item("another-id", "item-id").await.unwrap().is_none());
+        assert!(api
+            .raw_item("another-id", "item-id")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn raw_item() {
+        let mut api = tests::api();
+        api.backend = MemoryBackend::new().preserve_links(true);
+        let _ = api
+            .backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let mut item = Item::new("item-id").collection("an-id");
+        item.links.push(Link::self_("./item-id.json"));
+        api.backend.add_item(item.clone()).await.unwrap();
+
+        let raw = api.raw_item("an-id", "item-id").await.unwrap().unwrap();
+        let raw: Item = serde_json::from_value(serde_json::to_value(raw).unwrap()).unwrap();
+        assert_eq!(raw, api.item("an-id", "item-id").await.unwrap().unwrap());
+        assert_eq!(raw.links.iter().filter(|link| link.is_self()).count(), 1);
+        assert!(api.raw_item("an-id", "not-an-id").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn preserved_links() {
+        let mut api = tests::api();
+        api.backend = MemoryBackend::new().preserve_links(true);
+        let _ = api
+            .backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let mut item = Item::new("item-id").collection("an-id");
+        item.links.extend([
+            Link::self_("./item-id.json"),
+            Link::new("./source.json", "derived_from"),
+            Link::new("./next.json", "next"),
+        ]);
+        api.backend.add_item(item).await.unwrap();
+
+        let item = api.item("an-id", "item-id").await.unwrap().unwrap();
+        assert_eq!(item.links.iter().filter(|link| link.is_self()).count(), 1);
+        assert_link!(
+            item,
+            "self",
+            "http://stac-api-backend.test/collections/an-id/items/item-id",
+            "application/geo+json"
+        );
+        assert!(item.link("derived_from").is_some());
+        assert!(item.link("next").is_some());
+
+        let items = api.items("an-id", Items::default()).await.unwrap().unwrap();
+        let item: Item = items.items[0].clone().try_into().unwrap();
+        assert_eq!(item.links.iter().filter(|link| link.is_self()).count(), 1);
+        assert!(item.link("next").is_some());
+    }
 }
@@ -0,0 +1,70 @@
+use serde_json::Value;
+use stac::Link;
+
+/// Link relations that can appear more than once on an object.
+const MULTIPLE_RELS: [&str; 2] = ["child", "item"];
+
+/// Adds generated links, replacing existing links with the same rel.
+///
+/// Links with a rel that can appear more than once, like `child`, are only
+/// replaced if they also have the same href, so injection is idempotent
+/// without dropping unrelated children.
+pub(crate) fn set_links(links: &mut Vec<Link>, generated: Vec<Link>) {
+    links.retain(|link| !is_replaced(&generated, &link.rel, &link.href));
+    links.extend(generated);
+}
+
+/// [set_links] for links that are still JSON values, e.g. on an items map.
+pub(crate) fn set_link_values(
+    links: &mut Vec<Value>,
+    generated: Vec<Link>,
+) -> serde_json::Result<()> {
+    links.retain(|link| {
+        let rel = link.get("rel").and_then(Value::as_str).unwrap_or_default();
+        let href = link.get("href").and_then(Value::as_str).unwrap_or_default();
+        !is_replaced(&generated, rel, href)
+    });
+    for link in generated {
+        links.push(serde_json::to_value(link)?);
+    }
+    Ok(())
+}
+
+/// Returns true if a link with this rel and href would be replaced by the generated links.
+pub(crate) fn is_replaced(generated: &[Link], rel: &str, href: &str) -> bool {
+    generated
+        .iter()
+        .any(|link| link.rel == rel && (!MULTIPLE_RELS.contains(&rel) || link.href == href))
+}
+
+#[cfg(test)]
+mod tests {
+    use stac::Link;
+
+    #[test]
+    fn set_links() {
+        let mut links = vec![
+            Link::root("./catalog.json"),
+            Link::child("./a/catalog.json"),
+            Link::child("./b/catalog.json"),
+            Link::new("./license.txt", "license"),
+        ];
+        super::set_links(
+            &mut links,
+            vec![
+                Link::root("http://stac-api-backend.test/"),
+                Link::child("./a/catalog.json"),
+            ],
+        );
+        let hrefs: Vec<_> = links.iter().map(|link| link.href.as_str()).collect();
+        assert_eq!(
+            hrefs,
+            [
+                "./b/catalog.json",
+                "./license.txt",
+                "http://stac-api-backend.test/",
+                "./a/catalog.json"
+            ]
+        );
+    }
+}
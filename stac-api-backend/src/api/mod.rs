@@ -1,9 +1,23 @@
 mod api;
+mod builder;
 mod conformance;
+mod decorator;
+mod extension;
 mod features;
+mod links;
+mod queryables;
 mod root;
+mod search;
 
-pub use api::Api;
+pub(crate) use links::is_replaced;
+pub use {api::Api, builder::ApiBuilder, decorator::LinkDecorator, extension::StacExtension};
+
+/// The default number of items returned per page, if the request doesn't set a limit.
+pub const DEFAULT_LIMIT: u64 = 20;
+
+/// The largest limit a client may ask for, from the [STAC API
+/// spec](https://github.com/radiantearth/stac-api-spec/tree/main/item-search#query-parameter-table).
+pub const MAX_LIMIT: u64 = 10_000;
 
 /// The default media type for the `service-desc` links.
 pub const DEFAULT_SERVICE_DESC_MEDIA_TYPE: &str = "application/vnd.oai.openapi+json;version=3.1";
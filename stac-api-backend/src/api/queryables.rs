@@ -0,0 +1,125 @@
+use super::Api;
+use crate::{Error, ReadBackend, Result};
+use serde_json::{json, Value};
+use stac::Link;
+
+/// The link relation for a queryables document.
+pub(crate) const QUERYABLES_REL: &str = "http://www.opengis.net/def/rel/ogc/1.0/queryables";
+
+/// The media type of a queryables document.
+pub(crate) const QUERYABLES_MEDIA_TYPE: &str = "application/schema+json";
+
+impl<B> Api<B>
+where
+    B: ReadBackend,
+    Error: From<<B as ReadBackend>::Error>,
+{
+    /// Returns the [queryables](https://github.com/stac-api-extensions/filter#queryables)
+    /// for one collection, or (if `collection_id` is `None`) for every collection.
+    ///
+    /// Returns `None` if the collection doesn't exist or the backend doesn't
+    /// know its queryables.
+    pub async fn queryables(&self, collection_id: Option<&str>) -> Result<Option<Value>> {
+        let (url, title) = if let Some(collection_id) = collection_id {
+            if self.backend.collection(collection_id).await?.is_none() {
+                return Ok(None);
+            }
+            (
+                self.queryables_url(Some(collection_id))?,
+                format!("Queryables for {}", collection_id),
+            )
+        } else {
+            (
+                self.queryables_url(None)?,
+                format!(
+                    "Queryables for {}",
+                    self.catalog.title.as_deref().unwrap_or(&self.catalog.id)
+                ),
+            )
+        };
+        if let Some(properties) = self.backend.queryables(collection_id).await? {
+            Ok(Some(json!({
+                "$schema": "https://json-schema.org/draft/2019-09/schema",
+                "$id": url,
+                "type": "object",
+                "title": title,
+                "properties": properties,
+                "additionalProperties": true,
+            })))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Returns a `queryables` link, if the backend supports filtering.
+    pub(crate) fn queryables_link(&self, collection_id: Option<&str>) -> Result<Option<Link>> {
+        if self.features && self.backend.supported_parameters().contains(&"filter") {
+            Ok(Some(
+                Link::new(self.queryables_url(collection_id)?, QUERYABLES_REL)
+                    .r#type(QUERYABLES_MEDIA_TYPE.to_string())
+                    .title("Queryables".to_string()),
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn queryables_url(&self, collection_id: Option<&str>) -> Result<String> {
+        if let Some(collection_id) = collection_id {
+            Ok(format!(
+                "{}/queryables",
+                self.url_builder.collection(collection_id)?
+            ))
+        } else {
+            Ok(self.url_builder.root().join("queryables")?.to_string())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::super::tests;
+    use crate::WriteBackend;
+    use stac::{Collection, Item};
+
+    #[tokio::test]
+    async fn queryables() {
+        let mut api = tests::api();
+        let _ = api
+            .backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let mut item = Item::new("an-item").collection("an-id");
+        let _ = item
+            .properties
+            .additional_fields
+            .insert("eo:cloud_cover".to_string(), 12.into());
+        api.backend.add_items(vec![item]).await.unwrap();
+
+        let queryables = api.queryables(Some("an-id")).await.unwrap().unwrap();
+        assert_eq!(
+            queryables["$id"],
+            "http://stac-api-backend.test/collections/an-id/queryables"
+        );
+        assert_eq!(
+            queryables["properties"]["eo:cloud_cover"]["type"],
+            "integer"
+        );
+        let queryables = api.queryables(None).await.unwrap().unwrap();
+        assert_eq!(queryables["$id"], "http://stac-api-backend.test/queryables");
+        assert!(queryables["properties"].get("eo:cloud_cover").is_some());
+        assert!(api
+            .queryables(Some("not-a-collection"))
+            .await
+            .unwrap()
+            .is_none());
+
+        let root = api.root().await.unwrap();
+        assert!(root
+            .catalog
+            .links
+            .iter()
+            .any(|link| link.rel == super::QUERYABLES_REL));
+    }
+}
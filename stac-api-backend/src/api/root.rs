@@ -1,16 +1,17 @@
-use crate::{Api, Backend, Error, Result};
+use super::links::set_links;
+use crate::{Api, Error, ReadBackend, Result};
 use stac::Link;
 use stac_api::Root;
 
 impl<B> Api<B>
 where
-    B: Backend,
-    Error: From<<B as Backend>::Error>,
+    B: ReadBackend,
+    Error: From<<B as ReadBackend>::Error>,
 {
     /// Returns the [root endpoint](https://github.com/radiantearth/stac-api-spec/tree/main/core#endpoints).
     pub async fn root(&self) -> Result<Root> {
         let mut catalog = self.catalog.clone();
-        catalog.links.extend([
+        let mut links = vec![
             Link::root(self.url_builder.root()),
             Link::self_(self.url_builder.root()),
             Link::new(self.url_builder.service_desc(), "service-desc")
@@ -20,24 +21,34 @@ where
                 "service-doc",
             )
             .r#type("text/html".to_string()),
-        ]);
+        ];
         if self.features {
-            catalog.links.push(
+            links.push(
                 Link::new(self.url_builder.collections(), "data")
                     .json()
                     .title("Collections".to_string()),
             );
-            catalog.links.push(
+            links.push(
                 Link::new(self.url_builder.conformance(), "conformance")
                     .json()
                     .title("Conformance".to_string()),
             );
         }
-        for collection in self.backend.collections().await? {
-            catalog.links.push(
+        links.extend(self.queryables_link(None)?);
+        set_links(&mut catalog.links, self.decorate_links(links));
+        set_links(&mut catalog.links, self.landing_links.clone());
+        set_links(
+            &mut catalog.links,
+            self.decorate_links(self.extension_landing_links()?),
+        );
+        let mut child_links = Vec::new();
+        for collection in self.collection_summaries().await? {
+            child_links.push(
                 Link::child(self.url_builder.collection(&collection.id)?).title(collection.title),
             )
         }
+        set_links(&mut catalog.links, self.decorate_links(child_links));
+        self.decorate_catalog(&mut catalog);
         Ok(Root {
             catalog,
             conformance: self.conformance(),
@@ -48,8 +59,8 @@ where
 #[cfg(all(test, feature = "memory"))]
 mod tests {
     use super::super::tests;
-    use crate::{assert_link, Backend, DEFAULT_SERVICE_DESC_MEDIA_TYPE};
-    use stac::{Collection, Links};
+    use crate::{assert_link, WriteBackend, DEFAULT_SERVICE_DESC_MEDIA_TYPE};
+    use stac::{Collection, Link, Links};
     use stac_api::{COLLECTIONS_URI, CORE_URI, FEATURES_URI, GEOJSON_URI, OGC_API_FEATURES_URI};
     use stac_validate::Validate;
 
@@ -108,19 +119,120 @@ mod tests {
 
     #[tokio::test]
     async fn child() {
+        let mut api = tests::api();
+        let mut collection = Collection::new("an-id", "a description");
+        collection.title = Some("A title".to_string());
+        let _ = api.backend.add_collection(collection).await.unwrap();
+        let root = api.root().await.unwrap();
+        assert_eq!(root.catalog.iter_child_links().count(), 1);
+        assert_eq!(
+            root.catalog
+                .iter_child_links()
+                .next()
+                .unwrap()
+                .title
+                .as_deref(),
+            Some("A title")
+        );
+        assert_link!(
+            root.catalog,
+            "child",
+            "http://stac-api-backend.test/collections/an-id",
+            "application/json"
+        );
+    }
+
+    #[tokio::test]
+    async fn existing_links() {
         let mut api = tests::api();
         let _ = api
             .backend
             .add_collection(Collection::new("an-id", "a description"))
             .await
             .unwrap();
+        api.catalog.links.extend([
+            Link::root("./catalog.json"),
+            Link::child("http://stac-api-backend.test/collections/an-id"),
+            Link::child("./another-catalog.json"),
+        ]);
         let root = api.root().await.unwrap();
-        assert_eq!(root.catalog.iter_child_links().count(), 1);
+        assert_eq!(
+            root.catalog
+                .links
+                .iter()
+                .filter(|link| link.is_root())
+                .count(),
+            1
+        );
         assert_link!(
             root.catalog,
-            "child",
-            "http://stac-api-backend.test/collections/an-id",
+            "root",
+            "http://stac-api-backend.test/",
             "application/json"
         );
+        assert_eq!(root.catalog.iter_child_links().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn catalog_json() {
+        let mut api = tests::api();
+        api.catalog = serde_json::from_value(serde_json::json!({
+            "type": "Catalog",
+            "stac_version": "1.0.0",
+            "id": "a-static-catalog",
+            "description": "A catalog that was served from a bucket",
+            "links": [
+                {"rel": "root", "href": "./catalog.json", "type": "application/json"},
+                {"rel": "self", "href": "https://example.com/catalog.json", "type": "application/json"},
+                {"rel": "service-desc", "href": "https://example.com/openapi.json"},
+                {"rel": "license", "href": "https://example.com/license.txt"}
+            ]
+        }))
+        .unwrap();
+        let root = api.root().await.unwrap();
+        for rel in ["root", "self", "service-desc", "service-doc", "license"] {
+            assert_eq!(
+                root.catalog
+                    .links
+                    .iter()
+                    .filter(|link| link.rel == rel)
+                    .count(),
+                1,
+                "{}",
+                rel
+            );
+        }
+        assert_link!(
+            root.catalog,
+            "self",
+            "http://stac-api-backend.test/",
+            "application/json"
+        );
+        assert_link!(
+            root.catalog,
+            "service-desc",
+            "http://stac-api-backend.test/api",
+            DEFAULT_SERVICE_DESC_MEDIA_TYPE
+        );
+    }
+
+    #[tokio::test]
+    async fn with_extras() {
+        let api = tests::api()
+            .with_conformance_classes(["https://example.com/spec/v1.0.0/an-extension"])
+            .with_landing_links([Link::new("https://example.com/docs", "describedby")
+                .r#type("text/html".to_string())]);
+        let root = api.root().await.unwrap();
+        assert!(root
+            .conformance
+            .conforms_to
+            .contains(&"https://example.com/spec/v1.0.0/an-extension".to_string()));
+        assert_eq!(root.conformance.conforms_to, api.conformance().conforms_to);
+        assert_link!(
+            root.catalog,
+            "describedby",
+            "https://example.com/docs",
+            "text/html"
+        );
     }
 }
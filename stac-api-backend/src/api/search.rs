@@ -0,0 +1,137 @@
+use super::{features::unquote_strings, links::set_links, Api};
+use crate::{Error, ReadBackend, Result, Search, Token};
+use http::Method;
+use stac::Link;
+use stac_api::{GetSearch, ItemCollection};
+use std::collections::HashMap;
+
+impl<B> Api<B>
+where
+    B: ReadBackend,
+    Error: From<<B as ReadBackend>::Error>,
+{
+    /// Searches for items across collections.
+    ///
+    /// Limits are handled the same way as [Api::items]. Paging links point to
+    /// the `/search` endpoint as GET requests, and each item gets the same
+    /// links as it would from its collection's items endpoint.
+    pub async fn search(&self, mut search: Search<B::Paging>) -> Result<ItemCollection> {
+        if search.search.limit.is_some() {
+            search.search.limit = Some(self.limit(search.search.limit));
+        }
+        let mut query = search.clone();
+        let limit = self.limit(query.search.limit);
+        query.search.limit = Some(limit);
+        let mut page = self.backend.search(query).await?;
+        page.set_counts(limit, self.context);
+        let page = page.into_token_page()?;
+
+        let mut url = self.url_builder.search().clone();
+        let additional_fields = search.search.additional_fields.clone();
+        let mut get_search = GetSearch::try_from(search.search)?;
+        unquote_strings(&additional_fields, &mut get_search.additional_fields);
+        // serde_urlencoded can't serialize sequences, so lists go in as
+        // comma-separated strings like they came in on a GET request.
+        for (key, values) in [
+            ("ids", get_search.ids.take()),
+            ("collections", get_search.collections.take()),
+        ] {
+            if let Some(values) = values {
+                let _ = get_search
+                    .additional_fields
+                    .insert(key.to_string(), values.join(","));
+            }
+        }
+        let query = serde_urlencoded::to_string(&get_search)?;
+        if !query.is_empty() {
+            url.set_query(Some(&query));
+        }
+        let mut item_collection =
+            page.into_item_collection(&url, &Method::GET, Token::encode(&search.paging)?)?;
+        set_links(
+            &mut item_collection.links,
+            vec![Link::root(self.url_builder.root()).title(self.catalog.title.clone())],
+        );
+        item_collection.links = self.decorate_links(std::mem::take(&mut item_collection.links));
+        // Pages usually hold items from only a few collections.
+        let mut shared_links: HashMap<String, Vec<Link>> = HashMap::new();
+        for item in &mut item_collection.items {
+            if let Some(collection_id) = item
+                .get("collection")
+                .and_then(|value| value.as_str())
+                .map(String::from)
+            {
+                if !shared_links.contains_key(&collection_id) {
+                    let links = self.shared_item_links(&collection_id)?;
+                    let _ = shared_links.insert(collection_id.clone(), links);
+                }
+                self.link_item(item, &collection_id, &shared_links[&collection_id])?;
+            } else {
+                self.decorate_item(item);
+            }
+        }
+        Ok(item_collection)
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::super::tests;
+    use crate::{assert_link, memory::Paging, Search, WriteBackend};
+    use stac::{Collection, Item};
+
+    #[tokio::test]
+    async fn search() {
+        let mut api = tests::api();
+        for id in ["collection-a", "collection-b"] {
+            let _ = api
+                .backend
+                .add_collection(Collection::new(id, "a description"))
+                .await
+                .unwrap();
+        }
+        api.backend
+            .add_items(vec![
+                Item::new("item-a").collection("collection-a"),
+                Item::new("item-b").collection("collection-b"),
+                Item::new("item-c").collection("collection-b"),
+            ])
+            .await
+            .unwrap();
+
+        let item_collection = api.search(Search::default()).await.unwrap();
+        assert_eq!(item_collection.items.len(), 3);
+        assert_link!(
+            item_collection,
+            "self",
+            "http://stac-api-backend.test/search",
+            "application/geo+json"
+        );
+        let item: Item = item_collection.items[0].clone().try_into().unwrap();
+        assert_link!(
+            item,
+            "self",
+            "http://stac-api-backend.test/collections/collection-a/items/item-a",
+            "application/geo+json"
+        );
+        let item: Item = item_collection.items[2].clone().try_into().unwrap();
+        assert_link!(
+            item,
+            "collection",
+            "http://stac-api-backend.test/collections/collection-b",
+            "application/json"
+        );
+
+        let mut search: Search<Paging> = Search::default();
+        search.search.collections = Some(vec!["collection-b".to_string()]);
+        let item_collection = api.search(search).await.unwrap();
+        assert_eq!(item_collection.items.len(), 2);
+        assert_eq!(item_collection.items[0]["id"], "item-b");
+        assert_link!(
+            item_collection,
+            "self",
+            "http://stac-api-backend.test/search?collections=collection-b",
+            "application/geo+json"
+        );
+    }
+}
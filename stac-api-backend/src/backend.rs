@@ -1,12 +1,26 @@
-use crate::{Items, Page};
+use crate::{Items, ItemsResult, Metrics, Page, Search};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt};
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{value::RawValue, Map, Value};
 use stac::{Collection, Item};
 use std::fmt::Debug;
 
+/// A backend that can both read and write STAC objects.
+///
+/// This is implemented for every type that implements [ReadBackend] and
+/// [WriteBackend].
+pub trait Backend: ReadBackend + WriteBackend {}
+
+impl<T: ReadBackend + WriteBackend> Backend for T {}
+
 /// A STAC API backend builds each STAC API endpoint.
+///
+/// Read-only backends, e.g. ones that serve a static catalog or proxy another
+/// API, only need to implement this trait.
 #[async_trait]
-pub trait Backend: Send + Sync + Clone + 'static {
+pub trait ReadBackend: Send + Sync + Clone + 'static {
     /// The error type returned by the backend.
     type Error: std::error::Error;
 
@@ -15,22 +29,145 @@ pub trait Backend: Send + Sync + Clone + 'static {
     /// Some might use a token, some might use a skip+take, some might do something else.
     type Paging: Debug + Clone + Serialize + Default + DeserializeOwned + Send + Sync;
 
+    /// Returns the extension query parameters that this backend supports for items, e.g. `sortby`.
+    ///
+    /// The core parameters (`limit`, `bbox`, and `datetime`) must always be
+    /// supported. By default, no extension parameters are supported.
+    fn supported_parameters(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Returns the backend's metrics, e.g. gauges for its connection pool.
+    ///
+    /// By default, there are none. Wrap a backend in
+    /// [Instrumented](crate::Instrumented) to time its methods.
+    fn metrics(&self) -> Metrics {
+        Metrics::default()
+    }
+
     /// Returns all collections in this backend.
     async fn collections(&self) -> Result<Vec<Collection>, Self::Error>;
 
+    /// Returns the id and title of every collection in this backend.
+    ///
+    /// The landing page only needs these for its `child` links. By default,
+    /// this lists every collection with [ReadBackend::collections], so
+    /// backends that can fetch less should.
+    async fn collection_summaries(&self) -> Result<Vec<CollectionSummary>, Self::Error> {
+        let collections = self.collections().await?;
+        Ok(collections
+            .into_iter()
+            .map(CollectionSummary::from)
+            .collect())
+    }
+
     /// Returns a single collection.
     async fn collection(&self, id: &str) -> Result<Option<Collection>, Self::Error>;
 
-    /// Returns items.
+    /// Returns a collection's items.
+    ///
+    /// Collections that exist but have no matching items return an empty
+    /// [ItemsResult::Page], not [ItemsResult::CollectionNotFound].
     async fn items(
         &self,
         id: &str,
         items: Items<Self::Paging>,
-    ) -> Result<Option<Page<Self::Paging>>, Self::Error>;
+    ) -> Result<ItemsResult<Self::Paging>, Self::Error>;
+
+    /// Returns the number of items in a collection that match a query, if
+    /// it can be counted cheaply.
+    ///
+    /// This is used to set `numberMatched` when a page of items doesn't have
+    /// one. The count may be an estimate. By default, returns `None`.
+    async fn matched_count(
+        &self,
+        _id: &str,
+        _items: &Items<Self::Paging>,
+    ) -> Result<Option<u64>, Self::Error> {
+        Ok(None)
+    }
+
+    /// Searches for items across collections.
+    ///
+    /// Unlike [ReadBackend::items], there's no collection that might not
+    /// exist, so a search always returns a page, even if it's empty.
+    async fn search(&self, search: Search<Self::Paging>)
+        -> Result<Page<Self::Paging>, Self::Error>;
 
     /// Returns an item.
     async fn item(&self, collection_id: &str, id: &str) -> Result<Option<Item>, Self::Error>;
 
+    /// Returns when an item was last updated, from its `updated` property.
+    ///
+    /// Servers use this to answer conditional requests without fetching the
+    /// whole item. By default, the item is fetched with [ReadBackend::item],
+    /// so backends that can look up just the timestamp should. Returns `None`
+    /// if the item doesn't exist or doesn't have an RFC 3339 `updated`.
+    async fn item_updated(
+        &self,
+        collection_id: &str,
+        id: &str,
+    ) -> Result<Option<DateTime<Utc>>, Self::Error> {
+        let item = self.item(collection_id, id).await?;
+        Ok(item.and_then(|item| {
+            item.properties
+                .additional_fields
+                .get("updated")
+                .and_then(Value::as_str)
+                .and_then(parse_updated)
+        }))
+    }
+
+    /// Returns when a collection was last updated, from its `updated` field.
+    ///
+    /// Like [ReadBackend::item_updated], this fetches the whole collection
+    /// by default.
+    async fn collection_updated(&self, id: &str) -> Result<Option<DateTime<Utc>>, Self::Error> {
+        let collection = self.collection(id).await?;
+        Ok(collection.and_then(|collection| {
+            collection
+                .additional_fields
+                .get("updated")
+                .and_then(Value::as_str)
+                .and_then(parse_updated)
+        }))
+    }
+
+    /// Returns an item as raw JSON.
+    ///
+    /// Backends that store items as JSON can hand them over without parsing
+    /// them into an [Item] first. By default, serializes the item from
+    /// [ReadBackend::item].
+    async fn raw_item(
+        &self,
+        collection_id: &str,
+        id: &str,
+    ) -> Result<Option<Box<RawValue>>, Self::Error> {
+        let item = self.item(collection_id, id).await?;
+        Ok(item.map(|item| {
+            serde_json::value::to_raw_value(&item).expect("items can always be serialized")
+        }))
+    }
+
+    /// Returns the JSON schemas of the properties that can be used in
+    /// filters, for one collection or (if `collection_id` is `None`) for
+    /// every collection.
+    ///
+    /// The [Api](crate::Api) wraps these into a queryables document. Backends
+    /// without stored queryables can infer them with a
+    /// [Queryables](crate::Queryables) sampler. By default, returns `None`,
+    /// which means the queryables aren't known.
+    async fn queryables(
+        &self,
+        _collection_id: Option<&str>,
+    ) -> Result<Option<Map<String, Value>>, Self::Error> {
+        Ok(None)
+    }
+}
+
+/// A backend that can store STAC objects.
+#[async_trait]
+pub trait WriteBackend: ReadBackend {
     /// Adds a new collection to this backend.
     async fn add_collection(
         &mut self,
@@ -54,4 +191,108 @@ pub trait Backend: Send + Sync + Clone + 'static {
 
     /// Adds a new item to this backend.
     async fn add_item(&mut self, item: Item) -> Result<(), Self::Error>;
+
+    /// Adds new items from a stream, committing them in chunks of `chunk_size`.
+    ///
+    /// Only one chunk is held in memory at a time. A chunk that can't be
+    /// added doesn't stop the ingest: its error is recorded in the returned
+    /// report, as are errors from the stream itself, and the next chunk is
+    /// started. The default implementation calls [WriteBackend::add_items]
+    /// once per chunk.
+    async fn add_items_stream<S>(&mut self, items: S, chunk_size: usize) -> IngestReport
+    where
+        S: Stream<Item = crate::Result<Item>> + Send,
+    {
+        let chunk_size = chunk_size.max(1);
+        let mut items = std::pin::pin!(items);
+        let mut report = IngestReport::default();
+        let mut chunk = Vec::with_capacity(chunk_size);
+        while let Some(result) = items.next().await {
+            match result {
+                Ok(item) => chunk.push(item),
+                Err(err) => report.failures.push(IngestFailure {
+                    chunk: report.chunks,
+                    items: 0,
+                    message: err.to_string(),
+                }),
+            }
+            if chunk.len() >= chunk_size {
+                let chunk = std::mem::replace(&mut chunk, Vec::with_capacity(chunk_size));
+                report.add_chunk(self, chunk).await;
+            }
+        }
+        if !chunk.is_empty() {
+            report.add_chunk(self, chunk).await;
+        }
+        report
+    }
+}
+
+/// A collection's id and title.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollectionSummary {
+    /// The collection id.
+    pub id: String,
+
+    /// The collection title.
+    pub title: Option<String>,
+}
+
+/// A summary of [WriteBackend::add_items_stream].
+#[derive(Debug, Default)]
+pub struct IngestReport {
+    /// The number of items that were added.
+    pub items: usize,
+
+    /// The number of chunks that were committed or failed.
+    pub chunks: usize,
+
+    /// Everything that went wrong, in order.
+    pub failures: Vec<IngestFailure>,
+}
+
+/// A chunk, or an element of the stream, that could not be added.
+#[derive(Debug)]
+pub struct IngestFailure {
+    /// The zero-based index of the chunk.
+    pub chunk: usize,
+
+    /// The number of items that were not added.
+    ///
+    /// This is zero for errors from the stream itself.
+    pub items: usize,
+
+    /// A description of what went wrong.
+    pub message: String,
+}
+
+impl From<Collection> for CollectionSummary {
+    fn from(collection: Collection) -> CollectionSummary {
+        CollectionSummary {
+            id: collection.id,
+            title: collection.title,
+        }
+    }
+}
+
+impl IngestReport {
+    async fn add_chunk<B: WriteBackend>(&mut self, backend: &mut B, items: Vec<Item>) {
+        let count = items.len();
+        match backend.add_items(items).await {
+            Ok(()) => self.items += count,
+            Err(err) => self.failures.push(IngestFailure {
+                chunk: self.chunks,
+                items: count,
+                message: err.to_string(),
+            }),
+        }
+        self.chunks += 1;
+    }
+}
+
+/// Parses an RFC 3339 `updated` timestamp.
+pub(crate) fn parse_updated(updated: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(updated)
+        .ok()
+        .map(|updated| updated.with_timezone(&Utc))
 }
@@ -0,0 +1,858 @@
+//! A small [CQL2](https://docs.ogc.org/is/21-065r2/21-065r2.html) evaluator.
+//!
+//! Supports the comparison, logical, and spatial operators in both the text
+//! and json encodings. This is used by the memory backend, which makes it the
+//! reference implementation of the filter extension for this crate's tests.
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use geo::{
+    Coord, Geometry, LineString, MultiLineString, MultiPoint, MultiPolygon, Point, Polygon, Rect,
+    Relate,
+};
+use serde_json::{Map, Value};
+use stac_api::Filter;
+use std::cmp::Ordering;
+use thiserror::Error;
+
+/// A CQL2 parsing or evaluation error.
+#[derive(Debug, Error)]
+#[error("{0}")]
+pub(crate) struct Error(String);
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// A parsed CQL2 expression.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Expr {
+    Literal(Literal),
+    Property(String),
+    List(Vec<Expr>),
+    Op(String, Vec<Expr>),
+}
+
+/// A literal value, or the value of a property.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Literal {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Timestamp(DateTime<FixedOffset>),
+    Date(NaiveDate),
+    Geometry(Geometry),
+}
+
+impl Expr {
+    /// Parses a filter, in either encoding.
+    ///
+    /// Text filters that look like json are parsed as json, since GET
+    /// requests always carry their filter as a string.
+    pub(crate) fn from_filter(filter: &Filter) -> Result<Expr> {
+        match filter {
+            Filter::Cql2Json(map) => {
+                // Accept a filter that's been wrapped in a `filter` object.
+                if let (Some(filter), None) = (map.get("filter"), map.get("op")) {
+                    Expr::from_json(filter)
+                } else {
+                    Expr::from_json(&Value::Object(map.clone()))
+                }
+            }
+            Filter::Cql2Text(text) if text.trim_start().starts_with('{') => {
+                let value: Value = serde_json::from_str(text)
+                    .map_err(|err| Error(format!("invalid cql2-json: {}", err)))?;
+                Expr::from_json(&value)
+            }
+            Filter::Cql2Text(text) => Expr::from_text(text),
+        }
+    }
+
+    /// Parses a cql2-json expression.
+    pub(crate) fn from_json(value: &Value) -> Result<Expr> {
+        match value {
+            Value::Null => Ok(Expr::Literal(Literal::Null)),
+            Value::Bool(b) => Ok(Expr::Literal(Literal::Bool(*b))),
+            Value::Number(n) => n
+                .as_f64()
+                .map(|n| Expr::Literal(Literal::Number(n)))
+                .ok_or_else(|| Error(format!("invalid number: {}", n))),
+            Value::String(s) => Ok(Expr::Literal(Literal::String(s.clone()))),
+            Value::Array(values) => values
+                .iter()
+                .map(Expr::from_json)
+                .collect::<Result<_>>()
+                .map(Expr::List),
+            Value::Object(object) => {
+                if let Some(op) = object.get("op") {
+                    let op = op
+                        .as_str()
+                        .ok_or_else(|| Error(format!("op is not a string: {}", op)))?;
+                    let args = match object.get("args") {
+                        Some(Value::Array(args)) => {
+                            args.iter().map(Expr::from_json).collect::<Result<_>>()?
+                        }
+                        Some(arg) => vec![Expr::from_json(arg)?],
+                        None => Vec::new(),
+                    };
+                    Ok(Expr::Op(op.to_lowercase(), args))
+                } else if let Some(property) = object.get("property") {
+                    property
+                        .as_str()
+                        .map(|property| Expr::Property(property.to_string()))
+                        .ok_or_else(|| Error(format!("property is not a string: {}", property)))
+                } else if let Some(timestamp) = object.get("timestamp") {
+                    timestamp_literal(timestamp.as_str().unwrap_or_default())
+                } else if let Some(date) = object.get("date") {
+                    date_literal(date.as_str().unwrap_or_default())
+                } else if let Some(Value::Array(bbox)) = object.get("bbox") {
+                    let bbox = bbox
+                        .iter()
+                        .map(|n| {
+                            n.as_f64()
+                                .ok_or_else(|| Error(format!("invalid bbox: {}", n)))
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    bbox_literal(&bbox)
+                } else if object.contains_key("type") {
+                    geometry_from_json(value)
+                        .map(|geometry| Expr::Literal(Literal::Geometry(geometry)))
+                        .ok_or_else(|| Error(format!("invalid geometry: {}", value)))
+                } else {
+                    Err(Error(format!("unsupported cql2-json: {}", value)))
+                }
+            }
+        }
+    }
+
+    /// Parses a cql2-text expression.
+    pub(crate) fn from_text(text: &str) -> Result<Expr> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            position: 0,
+        };
+        let expr = parser.or()?;
+        if let Some(token) = parser.peek() {
+            Err(Error(format!("unexpected {:?} in cql2-text", token)))
+        } else {
+            Ok(expr)
+        }
+    }
+
+    /// Returns true if the item (as json) matches this expression.
+    pub(crate) fn matches(&self, item: &Map<String, Value>) -> Result<bool> {
+        match self {
+            Expr::Op(op, args) => match op.as_str() {
+                "and" => {
+                    for arg in args {
+                        if !arg.matches(item)? {
+                            return Ok(false);
+                        }
+                    }
+                    Ok(true)
+                }
+                "or" => {
+                    for arg in args {
+                        if arg.matches(item)? {
+                            return Ok(true);
+                        }
+                    }
+                    Ok(false)
+                }
+                "not" => Ok(!self.arg(0)?.matches(item)?),
+                "=" | "<>" | "<" | "<=" | ">" | ">=" => {
+                    let ordering = compare(&self.arg(0)?.value(item)?, &self.arg(1)?.value(item)?);
+                    Ok(match (op.as_str(), ordering) {
+                        (_, None) => false,
+                        ("=", Some(ordering)) => ordering == Ordering::Equal,
+                        ("<>", Some(ordering)) => ordering != Ordering::Equal,
+                        ("<", Some(ordering)) => ordering == Ordering::Less,
+                        ("<=", Some(ordering)) => ordering != Ordering::Greater,
+                        (">", Some(ordering)) => ordering == Ordering::Greater,
+                        (_, Some(ordering)) => ordering != Ordering::Less,
+                    })
+                }
+                "like" => match (self.arg(0)?.value(item)?, self.arg(1)?.value(item)?) {
+                    (Literal::String(s), Literal::String(pattern)) => Ok(like(&s, &pattern)),
+                    _ => Ok(false),
+                },
+                "between" => {
+                    let value = self.arg(0)?.value(item)?;
+                    let low = compare(&value, &self.arg(1)?.value(item)?);
+                    let high = compare(&value, &self.arg(2)?.value(item)?);
+                    Ok(matches!(low, Some(Ordering::Greater | Ordering::Equal))
+                        && matches!(high, Some(Ordering::Less | Ordering::Equal)))
+                }
+                "in" => {
+                    let value = self.arg(0)?.value(item)?;
+                    if let Expr::List(list) = self.arg(1)? {
+                        for expr in list {
+                            if compare(&value, &expr.value(item)?) == Some(Ordering::Equal) {
+                                return Ok(true);
+                            }
+                        }
+                        Ok(false)
+                    } else {
+                        Err(Error(
+                            "the second argument to in must be a list".to_string(),
+                        ))
+                    }
+                }
+                "isnull" => Ok(self.arg(0)?.value(item)? == Literal::Null),
+                op if op.starts_with("s_") => {
+                    match (self.arg(0)?.value(item)?, self.arg(1)?.value(item)?) {
+                        (Literal::Geometry(a), Literal::Geometry(b)) => {
+                            let matrix = a.relate(&b);
+                            match op {
+                                "s_intersects" => Ok(matrix.is_intersects()),
+                                "s_disjoint" => Ok(matrix.is_disjoint()),
+                                "s_within" => Ok(matrix.is_within()),
+                                "s_contains" => Ok(matrix.is_contains()),
+                                "s_equals" => Ok(matrix.is_equal_topo()),
+                                "s_touches" => Ok(matrix.is_touches()),
+                                "s_overlaps" => Ok(matrix.is_overlaps()),
+                                "s_crosses" => Ok(matrix.is_crosses()),
+                                _ => Err(Error(format!("unsupported operator: {}", op))),
+                            }
+                        }
+                        _ => Ok(false),
+                    }
+                }
+                _ => Err(Error(format!("unsupported operator: {}", op))),
+            },
+            _ => Ok(self.value(item)? == Literal::Bool(true)),
+        }
+    }
+
+    fn arg(&self, i: usize) -> Result<&Expr> {
+        if let Expr::Op(op, args) = self {
+            args.get(i)
+                .ok_or_else(|| Error(format!("{} is missing argument {}", op, i + 1)))
+        } else {
+            Err(Error(format!("not an operation: {:?}", self)))
+        }
+    }
+
+    fn value(&self, item: &Map<String, Value>) -> Result<Literal> {
+        match self {
+            Expr::Literal(literal) => Ok(literal.clone()),
+            Expr::Property(property) => Ok(property_value(item, property)),
+            Expr::Op(..) => self.matches(item).map(Literal::Bool),
+            Expr::List(_) => Err(Error("a list is not a value".to_string())),
+        }
+    }
+}
+
+fn property_value(item: &Map<String, Value>, property: &str) -> Literal {
+    let property = property.strip_prefix("properties.").unwrap_or(property);
+    let value = match property {
+        "id" | "collection" | "geometry" | "type" => item.get(property),
+        _ => item
+            .get("properties")
+            .and_then(|properties| properties.get(property))
+            .or_else(|| item.get(property)),
+    };
+    match value {
+        None | Some(Value::Null) => Literal::Null,
+        Some(Value::Bool(b)) => Literal::Bool(*b),
+        Some(Value::Number(n)) => n.as_f64().map(Literal::Number).unwrap_or(Literal::Null),
+        Some(Value::String(s)) => Literal::String(s.clone()),
+        Some(value @ Value::Object(_)) => geometry_from_json(value)
+            .map(Literal::Geometry)
+            .unwrap_or(Literal::Null),
+        Some(Value::Array(_)) => Literal::Null,
+    }
+}
+
+fn compare(a: &Literal, b: &Literal) -> Option<Ordering> {
+    match (a, b) {
+        (Literal::Number(a), Literal::Number(b)) => a.partial_cmp(b),
+        (Literal::String(a), Literal::String(b)) => Some(a.cmp(b)),
+        (Literal::Bool(a), Literal::Bool(b)) => Some(a.cmp(b)),
+        (Literal::Timestamp(a), Literal::Timestamp(b)) => Some(a.cmp(b)),
+        (Literal::Date(a), Literal::Date(b)) => Some(a.cmp(b)),
+        (Literal::String(a), Literal::Timestamp(b)) => {
+            DateTime::parse_from_rfc3339(a).ok().map(|a| a.cmp(b))
+        }
+        (Literal::String(a), Literal::Date(b)) => parse_date(a).map(|a| a.cmp(b)),
+        (Literal::Timestamp(_) | Literal::Date(_), Literal::String(_)) => {
+            compare(b, a).map(Ordering::reverse)
+        }
+        _ => None,
+    }
+}
+
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|datetime| datetime.date_naive())
+        .or_else(|_| NaiveDate::parse_from_str(s, "%Y-%m-%d"))
+        .ok()
+}
+
+/// `%` matches any number of characters and `_` matches exactly one.
+///
+/// Only the most recent `%` is ever backtracked to, so matching takes linear
+/// time in the common case and never worse than `s.len() * pattern.len()`.
+fn like(s: &str, pattern: &str) -> bool {
+    let s: Vec<_> = s.chars().collect();
+    let pattern: Vec<_> = pattern.chars().collect();
+    let (mut i, mut j) = (0, 0);
+    // The position after the last `%`, and where in `s` it started matching.
+    let mut star: Option<(usize, usize)> = None;
+    while i < s.len() {
+        match pattern.get(j) {
+            Some('%') => {
+                j += 1;
+                star = Some((j, i));
+            }
+            Some(&c) if c == '_' || c == s[i] => {
+                i += 1;
+                j += 1;
+            }
+            _ => match star {
+                Some((after, start)) => {
+                    j = after;
+                    i = start + 1;
+                    star = Some((after, start + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[j..].iter().all(|&c| c == '%')
+}
+
+fn timestamp_literal(s: &str) -> Result<Expr> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|timestamp| Expr::Literal(Literal::Timestamp(timestamp)))
+        .map_err(|err| Error(format!("invalid timestamp {}: {}", s, err)))
+}
+
+fn date_literal(s: &str) -> Result<Expr> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map(|date| Expr::Literal(Literal::Date(date)))
+        .map_err(|err| Error(format!("invalid date {}: {}", s, err)))
+}
+
+fn bbox_literal(bbox: &[f64]) -> Result<Expr> {
+    let (min, max) = match bbox.len() {
+        4 => ((bbox[0], bbox[1]), (bbox[2], bbox[3])),
+        6 => ((bbox[0], bbox[1]), (bbox[3], bbox[4])),
+        _ => return Err(Error(format!("invalid bbox: {:?}", bbox))),
+    };
+    Ok(Expr::Literal(Literal::Geometry(Geometry::Polygon(
+        Rect::new(min, max).to_polygon(),
+    ))))
+}
+
+fn geometry_from_json(value: &Value) -> Option<Geometry> {
+    let geometry: stac::Geometry = serde_json::from_value(value.clone()).ok()?;
+    geometry.try_into().ok()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Identifier(String),
+    QuotedIdentifier(String),
+    Number(f64),
+    String(String),
+    Operator(String),
+    LeftParen,
+    RightParen,
+    Comma,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                let _ = chars.next();
+            }
+            '(' | ')' | ',' => {
+                let _ = chars.next();
+                tokens.push(match c {
+                    '(' => Token::LeftParen,
+                    ')' => Token::RightParen,
+                    _ => Token::Comma,
+                });
+            }
+            '=' | '<' | '>' => {
+                let _ = chars.next();
+                let mut operator = c.to_string();
+                if let Some(&next) = chars.peek() {
+                    if (c == '<' && (next == '=' || next == '>')) || (c == '>' && next == '=') {
+                        operator.push(next);
+                        let _ = chars.next();
+                    }
+                }
+                tokens.push(Token::Operator(operator));
+            }
+            '\'' | '"' => {
+                let _ = chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        // Quotes are escaped by doubling them.
+                        Some(next) if next == c => {
+                            if chars.peek() == Some(&c) {
+                                s.push(c);
+                                let _ = chars.next();
+                            } else {
+                                break;
+                            }
+                        }
+                        Some(next) => s.push(next),
+                        None => return Err(Error(format!("unterminated string: {}", s))),
+                    }
+                }
+                tokens.push(if c == '\'' {
+                    Token::String(s)
+                } else {
+                    Token::QuotedIdentifier(s)
+                });
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' => {
+                let mut s = String::new();
+                while let Some(&next) = chars.peek() {
+                    if next.is_ascii_digit()
+                        || next == '.'
+                        || next == 'e'
+                        || next == 'E'
+                        || ((next == '-' || next == '+')
+                            && (s.is_empty() || s.ends_with('e') || s.ends_with('E')))
+                    {
+                        s.push(next);
+                        let _ = chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                let n = s
+                    .parse()
+                    .map_err(|_| Error(format!("invalid number: {}", s)))?;
+                tokens.push(Token::Number(n));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut s = String::new();
+                while let Some(&next) = chars.peek() {
+                    if next.is_alphanumeric() || matches!(next, '_' | ':' | '.' | '-') {
+                        s.push(next);
+                        let _ = chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Identifier(s));
+            }
+            _ => return Err(Error(format!("unexpected character: {}", c))),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+/// Coordinates from well-known text, before we know what kind of geometry they belong to.
+enum Coordinates {
+    Position(Vec<f64>),
+    List(Vec<Coordinates>),
+}
+
+impl Parser {
+    fn or(&mut self) -> Result<Expr> {
+        let mut args = vec![self.and()?];
+        while self.keyword("or") {
+            args.push(self.and()?);
+        }
+        Ok(if args.len() == 1 {
+            args.pop().unwrap()
+        } else {
+            Expr::Op("or".to_string(), args)
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut args = vec![self.not()?];
+        while self.keyword("and") {
+            args.push(self.not()?);
+        }
+        Ok(if args.len() == 1 {
+            args.pop().unwrap()
+        } else {
+            Expr::Op("and".to_string(), args)
+        })
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.keyword("not") {
+            Ok(Expr::Op("not".to_string(), vec![self.not()?]))
+        } else {
+            self.predicate()
+        }
+    }
+
+    fn predicate(&mut self) -> Result<Expr> {
+        if self.peek() == Some(&Token::LeftParen) {
+            self.position += 1;
+            let expr = self.or()?;
+            self.expect(Token::RightParen)?;
+            return Ok(expr);
+        }
+        if let Some(Token::Identifier(name)) = self.peek() {
+            let name = name.to_lowercase();
+            if name.starts_with("s_")
+                && self.tokens.get(self.position + 1) == Some(&Token::LeftParen)
+            {
+                self.position += 2;
+                let a = self.operand()?;
+                self.expect(Token::Comma)?;
+                let b = self.operand()?;
+                self.expect(Token::RightParen)?;
+                return Ok(Expr::Op(name, vec![a, b]));
+            }
+        }
+        let lhs = self.operand()?;
+        if let Some(Token::Operator(operator)) = self.peek() {
+            let operator = operator.clone();
+            self.position += 1;
+            let rhs = self.operand()?;
+            return Ok(Expr::Op(operator, vec![lhs, rhs]));
+        }
+        if self.keyword("is") {
+            let not = self.keyword("not");
+            if !self.keyword("null") {
+                return Err(Error("expected NULL after IS".to_string()));
+            }
+            return Ok(negate(Expr::Op("isnull".to_string(), vec![lhs]), not));
+        }
+        let not = self.keyword("not");
+        let expr = if self.keyword("like") {
+            Expr::Op("like".to_string(), vec![lhs, self.operand()?])
+        } else if self.keyword("between") {
+            let low = self.operand()?;
+            if !self.keyword("and") {
+                return Err(Error("expected AND in BETWEEN".to_string()));
+            }
+            Expr::Op("between".to_string(), vec![lhs, low, self.operand()?])
+        } else if self.keyword("in") {
+            self.expect(Token::LeftParen)?;
+            let mut list = vec![self.operand()?];
+            while self.peek() == Some(&Token::Comma) {
+                self.position += 1;
+                list.push(self.operand()?);
+            }
+            self.expect(Token::RightParen)?;
+            Expr::Op("in".to_string(), vec![lhs, Expr::List(list)])
+        } else if not {
+            return Err(Error("expected LIKE, BETWEEN, or IN after NOT".to_string()));
+        } else {
+            return Ok(lhs);
+        };
+        Ok(negate(expr, not))
+    }
+
+    fn operand(&mut self) -> Result<Expr> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| Error("unexpected end of cql2-text".to_string()))?;
+        self.position += 1;
+        match token {
+            Token::Number(n) => Ok(Expr::Literal(Literal::Number(n))),
+            Token::String(s) => Ok(Expr::Literal(Literal::String(s))),
+            Token::QuotedIdentifier(s) => Ok(Expr::Property(s)),
+            Token::Identifier(s) => match s.to_lowercase().as_str() {
+                "true" => Ok(Expr::Literal(Literal::Bool(true))),
+                "false" => Ok(Expr::Literal(Literal::Bool(false))),
+                "null" => Ok(Expr::Literal(Literal::Null)),
+                "timestamp" | "date" => {
+                    self.expect(Token::LeftParen)?;
+                    let value = match self.tokens.get(self.position) {
+                        Some(Token::String(value)) => value.clone(),
+                        _ => return Err(Error(format!("expected a string in {}", s))),
+                    };
+                    self.position += 1;
+                    self.expect(Token::RightParen)?;
+                    if s.eq_ignore_ascii_case("timestamp") {
+                        timestamp_literal(&value)
+                    } else {
+                        date_literal(&value)
+                    }
+                }
+                "bbox" => {
+                    let coordinates = self.coordinates()?;
+                    bbox_literal(&flatten(&coordinates))
+                }
+                "point" | "linestring" | "polygon" | "multipoint" | "multilinestring"
+                | "multipolygon" => {
+                    let coordinates = self.coordinates()?;
+                    geometry_from_wkt(&s.to_lowercase(), &coordinates)
+                        .map(|geometry| Expr::Literal(Literal::Geometry(geometry)))
+                        .ok_or_else(|| Error(format!("invalid {}", s)))
+                }
+                _ => Ok(Expr::Property(s)),
+            },
+            token => Err(Error(format!("unexpected {:?} in cql2-text", token))),
+        }
+    }
+
+    fn coordinates(&mut self) -> Result<Coordinates> {
+        self.expect(Token::LeftParen)?;
+        let mut list = Vec::new();
+        loop {
+            if self.peek() == Some(&Token::LeftParen) {
+                list.push(self.coordinates()?);
+            } else {
+                let mut position = Vec::new();
+                while let Some(&Token::Number(n)) = self.peek() {
+                    position.push(n);
+                    self.position += 1;
+                }
+                list.push(Coordinates::Position(position));
+            }
+            match self.peek() {
+                Some(Token::Comma) => self.position += 1,
+                _ => break,
+            }
+        }
+        self.expect(Token::RightParen)?;
+        Ok(Coordinates::List(list))
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Identifier(s)) if s.eq_ignore_ascii_case(keyword) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<()> {
+        if self.peek() == Some(&token) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(Error(format!(
+                "expected {:?}, found {:?}",
+                token,
+                self.peek()
+            )))
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+}
+
+fn negate(expr: Expr, not: bool) -> Expr {
+    if not {
+        Expr::Op("not".to_string(), vec![expr])
+    } else {
+        expr
+    }
+}
+
+fn flatten(coordinates: &Coordinates) -> Vec<f64> {
+    match coordinates {
+        Coordinates::Position(position) => position.clone(),
+        Coordinates::List(list) => list.iter().flat_map(flatten).collect(),
+    }
+}
+
+fn geometry_from_wkt(kind: &str, coordinates: &Coordinates) -> Option<Geometry> {
+    fn coord(coordinates: &Coordinates) -> Option<Coord> {
+        match coordinates {
+            Coordinates::Position(position) if position.len() >= 2 => Some(Coord {
+                x: position[0],
+                y: position[1],
+            }),
+            // Multipoints can wrap each point in parentheses.
+            Coordinates::List(list) if list.len() == 1 => coord(&list[0]),
+            _ => None,
+        }
+    }
+    fn list(coordinates: &Coordinates) -> Option<&[Coordinates]> {
+        match coordinates {
+            Coordinates::List(list) => Some(list),
+            Coordinates::Position(_) => None,
+        }
+    }
+    fn line_string(coordinates: &Coordinates) -> Option<LineString> {
+        list(coordinates)?
+            .iter()
+            .map(coord)
+            .collect::<Option<Vec<_>>>()
+            .map(LineString::new)
+    }
+    fn polygon(coordinates: &Coordinates) -> Option<Polygon> {
+        let mut rings = list(coordinates)?
+            .iter()
+            .map(line_string)
+            .collect::<Option<Vec<_>>>()?;
+        if rings.is_empty() {
+            return None;
+        }
+        let exterior = rings.remove(0);
+        Some(Polygon::new(exterior, rings))
+    }
+    let geometry = match kind {
+        "point" => Geometry::Point(Point::from(coord(coordinates)?)),
+        "linestring" => Geometry::LineString(line_string(coordinates)?),
+        "polygon" => Geometry::Polygon(polygon(coordinates)?),
+        "multipoint" => Geometry::MultiPoint(MultiPoint::new(
+            list(coordinates)?
+                .iter()
+                .map(|c| coord(c).map(Point::from))
+                .collect::<Option<_>>()?,
+        )),
+        "multilinestring" => Geometry::MultiLineString(MultiLineString::new(
+            list(coordinates)?
+                .iter()
+                .map(line_string)
+                .collect::<Option<_>>()?,
+        )),
+        "multipolygon" => Geometry::MultiPolygon(MultiPolygon::new(
+            list(coordinates)?
+                .iter()
+                .map(polygon)
+                .collect::<Option<_>>()?,
+        )),
+        _ => return None,
+    };
+    Some(geometry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Expr;
+    use serde_json::{json, Map, Value};
+    use stac_api::Filter;
+
+    fn item() -> Map<String, Value> {
+        json!({
+            "type": "Feature",
+            "id": "an-item",
+            "collection": "a-collection",
+            "geometry": {"type": "Point", "coordinates": [-105.1, 40.1]},
+            "properties": {
+                "datetime": "2023-07-11T12:00:00Z",
+                "eo:cloud_cover": 12.5,
+                "platform": "landsat-8",
+                "processed": true
+            }
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+    }
+
+    fn matches(text: &str) -> bool {
+        Expr::from_text(text).unwrap().matches(&item()).unwrap()
+    }
+
+    #[test]
+    fn comparison() {
+        assert!(matches("\"eo:cloud_cover\" < 20"));
+        assert!(!matches("\"eo:cloud_cover\" >= 20"));
+        assert!(matches("id = 'an-item'"));
+        assert!(matches("platform <> 'sentinel-2'"));
+        assert!(matches("datetime > TIMESTAMP('2023-01-01T00:00:00Z')"));
+        assert!(matches("datetime = DATE('2023-07-11')"));
+        assert!(matches("platform LIKE 'landsat%'"));
+        assert!(matches("\"eo:cloud_cover\" BETWEEN 10 AND 15"));
+        assert!(matches("platform IN ('landsat-8', 'landsat-9')"));
+        assert!(matches("missing IS NULL"));
+        assert!(matches("platform IS NOT NULL"));
+        assert!(!matches("missing = 1"));
+        assert!(matches("processed"));
+    }
+
+    #[test]
+    fn logical() {
+        assert!(matches("id = 'an-item' AND collection = 'a-collection'"));
+        assert!(matches(
+            "id = 'another-item' OR collection = 'a-collection'"
+        ));
+        assert!(matches("NOT (id = 'another-item')"));
+        assert!(matches("platform NOT LIKE 'sentinel%'"));
+        assert!(!matches(
+            "(id = 'another-item' OR platform = 'landsat-8') AND \"eo:cloud_cover\" > 50"
+        ));
+    }
+
+    #[test]
+    fn like() {
+        for (s, pattern, expected) in [
+            ("landsat-8", "landsat%", true),
+            ("landsat-8", "%sat%", true),
+            ("landsat-8", "l_ndsat-_", true),
+            ("landsat-8", "%-9", false),
+            ("landsat-8", "landsat", false),
+            ("", "%", true),
+            ("", "_", false),
+            ("abcbc", "%bc", true),
+            ("abc", "a%%c", true),
+        ] {
+            assert_eq!(super::like(s, pattern), expected, "{} LIKE {}", s, pattern);
+        }
+        let long = "a".repeat(100_000);
+        assert!(!super::like(&long, "%a%a%a%a%a%a%a%a%a%a%b"));
+        assert!(super::like(&long, "%a%a%a%a%a%a%a%a%a%a%"));
+    }
+
+    #[test]
+    fn spatial() {
+        assert!(matches(
+            "S_INTERSECTS(geometry, POLYGON((-106 40, -104 40, -104 41, -106 41, -106 40)))"
+        ));
+        assert!(matches("s_within(geometry, BBOX(-106, 40, -104, 41))"));
+        assert!(!matches("S_INTERSECTS(geometry, POINT(0 0))"));
+        assert!(matches("S_DISJOINT(geometry, MULTIPOINT((0 0), (1 1)))"));
+    }
+
+    #[test]
+    fn json() {
+        let filter = Filter::Cql2Json(
+            json!({
+                "op": "and",
+                "args": [
+                    {"op": "=", "args": [{"property": "collection"}, "a-collection"]},
+                    {"op": "s_intersects", "args": [
+                        {"property": "geometry"},
+                        {"type": "Polygon", "coordinates": [[[-106, 40], [-104, 40], [-104, 41], [-106, 41], [-106, 40]]]}
+                    ]},
+                    {"op": "<", "args": [{"property": "eo:cloud_cover"}, 20]}
+                ]
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        );
+        assert!(Expr::from_filter(&filter)
+            .unwrap()
+            .matches(&item())
+            .unwrap());
+        let filter = Filter::Cql2Text(
+            r#"{"op": "=", "args": [{"property": "platform"}, "sentinel-2"]}"#.to_string(),
+        );
+        assert!(!Expr::from_filter(&filter)
+            .unwrap()
+            .matches(&item())
+            .unwrap());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Expr::from_text("id = ").is_err());
+        assert!(Expr::from_text("id = 'unterminated").is_err());
+        assert!(Expr::from_text("id = 'an-item' extra").is_err());
+        assert!(Expr::from_text("foo(bar)").is_err());
+    }
+}
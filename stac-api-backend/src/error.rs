@@ -1,3 +1,4 @@
+use http::StatusCode;
 use thiserror::Error;
 
 /// A crate-specific error type.
@@ -7,6 +8,35 @@ pub enum Error {
     #[error("backend error: {0}")]
     Backend(Box<dyn std::error::Error + Send + Sync>),
 
+    /// The request conflicts with something that's already in the backend,
+    /// e.g. adding a collection that already exists.
+    #[error("conflict: {0}")]
+    Conflict(String),
+
+    /// [base64::DecodeError]
+    #[error(transparent)]
+    Base64Decode(#[from] base64::DecodeError),
+
+    /// The [ApiBuilder](crate::ApiBuilder) configuration is invalid.
+    #[error("invalid api: {0}")]
+    InvalidApi(String),
+
+    /// The backend doesn't have room for the write.
+    #[error("insufficient storage: {0}")]
+    InsufficientStorage(String),
+
+    /// The query is invalid.
+    #[error("invalid query: {0}")]
+    InvalidQuery(String),
+
+    /// Pages can't be linked to with this http method.
+    #[error("method not allowed: {0}")]
+    MethodNotAllowed(http::Method),
+
+    /// The thing that was asked for doesn't exist.
+    #[error("not found: {0}")]
+    NotFound(String),
+
     /// [serde_json::Error]
     #[error(transparent)]
     SerdeJson(#[from] serde_json::Error),
@@ -27,3 +57,29 @@ pub enum Error {
     #[error(transparent)]
     UrlParse(#[from] url::ParseError),
 }
+
+impl Error {
+    /// Returns the http status code that best describes this error.
+    ///
+    /// Errors that aren't the client's fault are internal server errors.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use http::StatusCode;
+    /// use stac_api_backend::Error;
+    ///
+    /// let error = Error::NotFound("no collection with id=an-id".to_string());
+    /// assert_eq!(error.status_code(), StatusCode::NOT_FOUND);
+    /// ```
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Error::Conflict(_) => StatusCode::CONFLICT,
+            Error::Base64Decode(_) | Error::InvalidQuery(_) => StatusCode::BAD_REQUEST,
+            Error::NotFound(_) => StatusCode::NOT_FOUND,
+            Error::MethodNotAllowed(_) => StatusCode::METHOD_NOT_ALLOWED,
+            Error::InsufficientStorage(_) => StatusCode::INSUFFICIENT_STORAGE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
@@ -0,0 +1,453 @@
+use crate::{
+    CollectionSummary, Items, ItemsResult, Metrics, Page, ReadBackend, Search, WriteBackend,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{value::RawValue, Map, Value};
+use stac::{Collection, Item};
+use std::{fmt::Debug, sync::Arc};
+use uuid::Uuid;
+
+/// The version of the CloudEvents spec that [CloudEvent]s follow.
+pub const CLOUD_EVENTS_SPEC_VERSION: &str = "1.0";
+
+/// A change to the catalog.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Event {
+    /// A unique id for this event, so receivers can drop duplicates.
+    pub id: String,
+
+    /// What happened.
+    pub action: Action,
+
+    /// The collection that was changed, or that holds the changed item.
+    #[serde(rename = "collection")]
+    pub collection_id: String,
+
+    /// The item that was changed, if this is an item event.
+    #[serde(rename = "item", skip_serializing_if = "Option::is_none")]
+    pub item_id: Option<String>,
+
+    /// When the change was written.
+    pub timestamp: DateTime<Utc>,
+}
+
+/// The kind of change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// A new collection or item.
+    Created,
+
+    /// A replaced collection or item.
+    ///
+    /// Upserted items are always `updated`, since backends don't say whether
+    /// the item already existed.
+    Updated,
+
+    /// A deleted collection, along with its items.
+    Deleted,
+}
+
+/// A change to the catalog in a [CloudEvents](https://cloudevents.io/) JSON
+/// envelope.
+///
+/// Every transport (webhooks, message buses) sends these, so observers can
+/// share one schema. The `type` is `org.stacspec.<collection|item>.<action>`,
+/// e.g. `org.stacspec.item.created`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CloudEvent {
+    /// Always [CLOUD_EVENTS_SPEC_VERSION].
+    pub specversion: String,
+
+    /// The event's id, unique within its source.
+    pub id: String,
+
+    /// Where the event came from, e.g. the server's root url.
+    pub source: String,
+
+    /// The kind of event, e.g. `org.stacspec.collection.deleted`.
+    #[serde(rename = "type")]
+    pub r#type: String,
+
+    /// The changed object's path, e.g. `collections/naip/items/an-item`.
+    pub subject: String,
+
+    /// When the change was written.
+    pub time: DateTime<Utc>,
+
+    /// Always `application/json`.
+    pub datacontenttype: String,
+
+    /// The ids of the changed collection and item.
+    pub data: Value,
+}
+
+impl Action {
+    /// Returns this action as it's serialized, e.g. `created`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Created => "created",
+            Action::Updated => "updated",
+            Action::Deleted => "deleted",
+        }
+    }
+}
+
+/// Hears about changes to the catalog.
+///
+/// Register listeners on a [Notifying] backend.
+///
+/// # Examples
+///
+/// ```
+/// use async_trait::async_trait;
+/// use stac_api_backend::{Event, EventListener};
+///
+/// #[derive(Debug)]
+/// struct Log;
+///
+/// #[async_trait]
+/// impl EventListener for Log {
+///     async fn notify(&self, events: &[Event]) {
+///         for event in events {
+///             println!("{:?} {}", event.action, event.collection_id);
+///         }
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait EventListener: Debug + Send + Sync {
+    /// Called after every successful write, with one event per changed
+    /// collection or item.
+    ///
+    /// The write has already happened, so listeners should handle (or log)
+    /// their own errors.
+    async fn notify(&self, events: &[Event]);
+}
+
+/// Wraps a backend and tells [EventListener]s about every successful write.
+///
+/// Reads go straight through to the wrapped backend.
+///
+/// # Examples
+///
+/// ```
+/// use stac_api_backend::{MemoryBackend, Notifying};
+///
+/// let backend = Notifying::new(MemoryBackend::new());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Notifying<B> {
+    backend: B,
+    listeners: Vec<Arc<dyn EventListener>>,
+}
+
+impl<B> Notifying<B> {
+    /// Wraps a backend, with no listeners.
+    pub fn new(backend: B) -> Notifying<B> {
+        Notifying {
+            backend,
+            listeners: Vec::new(),
+        }
+    }
+
+    /// Adds a listener.
+    pub fn listener(mut self, listener: impl EventListener + 'static) -> Notifying<B> {
+        self.listeners.push(Arc::new(listener));
+        self
+    }
+
+    /// Returns the wrapped backend.
+    pub fn into_inner(self) -> B {
+        self.backend
+    }
+
+    async fn notify(&self, events: Vec<Event>) {
+        if events.is_empty() {
+            return;
+        }
+        for listener in &self.listeners {
+            listener.notify(&events).await;
+        }
+    }
+}
+
+impl Event {
+    /// Wraps this event in a CloudEvents envelope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::Utc;
+    /// use stac_api_backend::{Action, Event};
+    ///
+    /// let event = Event {
+    ///     id: "an-id".to_string(),
+    ///     action: Action::Created,
+    ///     collection_id: "naip".to_string(),
+    ///     item_id: Some("an-item".to_string()),
+    ///     timestamp: Utc::now(),
+    /// };
+    /// let cloud_event = event.to_cloud_event("http://stac-server.test");
+    /// assert_eq!(cloud_event.r#type, "org.stacspec.item.created");
+    /// assert_eq!(cloud_event.subject, "collections/naip/items/an-item");
+    /// ```
+    pub fn to_cloud_event(&self, source: impl ToString) -> CloudEvent {
+        let mut data = Map::new();
+        let _ = data.insert("collection".to_string(), self.collection_id.clone().into());
+        let (kind, subject) = if let Some(item_id) = &self.item_id {
+            let _ = data.insert("item".to_string(), item_id.clone().into());
+            (
+                "item",
+                format!("collections/{}/items/{}", self.collection_id, item_id),
+            )
+        } else {
+            ("collection", format!("collections/{}", self.collection_id))
+        };
+        CloudEvent {
+            specversion: CLOUD_EVENTS_SPEC_VERSION.to_string(),
+            id: self.id.clone(),
+            source: source.to_string(),
+            r#type: format!("org.stacspec.{}.{}", kind, self.action.as_str()),
+            subject,
+            time: self.timestamp,
+            datacontenttype: "application/json".to_string(),
+            data: Value::Object(data),
+        }
+    }
+
+    fn collection(action: Action, collection_id: impl ToString) -> Event {
+        Event {
+            id: Uuid::new_v4().to_string(),
+            action,
+            collection_id: collection_id.to_string(),
+            item_id: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn item(action: Action, item: &Item) -> Event {
+        Event {
+            id: Uuid::new_v4().to_string(),
+            action,
+            collection_id: item.collection.clone().unwrap_or_default(),
+            item_id: Some(item.id.clone()),
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn items(action: Action, items: &[Item]) -> Vec<Event> {
+        items.iter().map(|item| Event::item(action, item)).collect()
+    }
+}
+
+#[async_trait]
+impl<B: ReadBackend> ReadBackend for Notifying<B> {
+    type Error = B::Error;
+    type Paging = B::Paging;
+
+    fn supported_parameters(&self) -> &'static [&'static str] {
+        self.backend.supported_parameters()
+    }
+
+    fn metrics(&self) -> Metrics {
+        self.backend.metrics()
+    }
+
+    async fn collections(&self) -> Result<Vec<Collection>, B::Error> {
+        self.backend.collections().await
+    }
+
+    async fn collection_summaries(&self) -> Result<Vec<CollectionSummary>, B::Error> {
+        self.backend.collection_summaries().await
+    }
+
+    async fn collection(&self, id: &str) -> Result<Option<Collection>, B::Error> {
+        self.backend.collection(id).await
+    }
+
+    async fn items(
+        &self,
+        id: &str,
+        items: Items<B::Paging>,
+    ) -> Result<ItemsResult<B::Paging>, B::Error> {
+        self.backend.items(id, items).await
+    }
+
+    async fn matched_count(
+        &self,
+        id: &str,
+        items: &Items<B::Paging>,
+    ) -> Result<Option<u64>, B::Error> {
+        self.backend.matched_count(id, items).await
+    }
+
+    async fn search(&self, search: Search<B::Paging>) -> Result<Page<B::Paging>, B::Error> {
+        self.backend.search(search).await
+    }
+
+    async fn item(&self, collection_id: &str, id: &str) -> Result<Option<Item>, B::Error> {
+        self.backend.item(collection_id, id).await
+    }
+
+    async fn item_updated(
+        &self,
+        collection_id: &str,
+        id: &str,
+    ) -> Result<Option<DateTime<Utc>>, B::Error> {
+        self.backend.item_updated(collection_id, id).await
+    }
+
+    async fn collection_updated(&self, id: &str) -> Result<Option<DateTime<Utc>>, B::Error> {
+        self.backend.collection_updated(id).await
+    }
+
+    async fn raw_item(
+        &self,
+        collection_id: &str,
+        id: &str,
+    ) -> Result<Option<Box<RawValue>>, B::Error> {
+        self.backend.raw_item(collection_id, id).await
+    }
+
+    async fn queryables(
+        &self,
+        collection_id: Option<&str>,
+    ) -> Result<Option<Map<String, Value>>, B::Error> {
+        self.backend.queryables(collection_id).await
+    }
+}
+
+#[async_trait]
+impl<B: WriteBackend> WriteBackend for Notifying<B> {
+    async fn add_collection(
+        &mut self,
+        collection: Collection,
+    ) -> Result<Option<Collection>, B::Error> {
+        let id = collection.id.clone();
+        let previous = self.backend.add_collection(collection).await?;
+        let action = if previous.is_some() {
+            Action::Updated
+        } else {
+            Action::Created
+        };
+        self.notify(vec![Event::collection(action, id)]).await;
+        Ok(previous)
+    }
+
+    async fn upsert_collection(
+        &mut self,
+        collection: Collection,
+    ) -> Result<Option<Collection>, B::Error> {
+        let id = collection.id.clone();
+        let previous = self.backend.upsert_collection(collection).await?;
+        let action = if previous.is_some() {
+            Action::Updated
+        } else {
+            Action::Created
+        };
+        self.notify(vec![Event::collection(action, id)]).await;
+        Ok(previous)
+    }
+
+    async fn delete_collection(&mut self, id: &str) -> Result<(), B::Error> {
+        self.backend.delete_collection(id).await?;
+        self.notify(vec![Event::collection(Action::Deleted, id)])
+            .await;
+        Ok(())
+    }
+
+    async fn add_items(&mut self, items: Vec<Item>) -> Result<(), B::Error> {
+        let events = Event::items(Action::Created, &items);
+        self.backend.add_items(items).await?;
+        self.notify(events).await;
+        Ok(())
+    }
+
+    async fn upsert_items(&mut self, items: Vec<Item>) -> Result<(), B::Error> {
+        let events = Event::items(Action::Updated, &items);
+        self.backend.upsert_items(items).await?;
+        self.notify(events).await;
+        Ok(())
+    }
+
+    async fn add_item(&mut self, item: Item) -> Result<(), B::Error> {
+        let event = Event::item(Action::Created, &item);
+        self.backend.add_item(item).await?;
+        self.notify(vec![event]).await;
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::{Action, Event, EventListener, Notifying};
+    use crate::{MemoryBackend, WriteBackend};
+    use async_trait::async_trait;
+    use stac::{Collection, Item};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Default)]
+    struct Recorder(Arc<Mutex<Vec<Event>>>);
+
+    #[async_trait]
+    impl EventListener for Recorder {
+        async fn notify(&self, events: &[Event]) {
+            self.0.lock().unwrap().extend_from_slice(events);
+        }
+    }
+
+    #[tokio::test]
+    async fn notify() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut backend =
+            Notifying::new(MemoryBackend::new()).listener(Recorder(Arc::clone(&events)));
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let _ = backend
+            .upsert_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        backend
+            .add_items(vec![Item::new("item-id").collection("an-id")])
+            .await
+            .unwrap();
+        let _ = backend
+            .add_item(Item::new("no-collection"))
+            .await
+            .unwrap_err();
+        backend.delete_collection("an-id").await.unwrap();
+        let events = events.lock().unwrap();
+        let actions: Vec<_> = events
+            .iter()
+            .map(|event| (event.action, event.item_id.as_deref()))
+            .collect();
+        assert_eq!(
+            actions,
+            [
+                (Action::Created, None),
+                (Action::Updated, None),
+                (Action::Created, Some("item-id")),
+                (Action::Deleted, None),
+            ]
+        );
+        assert!(events.iter().all(|event| event.collection_id == "an-id"));
+    }
+
+    #[test]
+    fn cloud_event() {
+        let event = Event::collection(Action::Deleted, "an-id");
+        let value =
+            serde_json::to_value(event.to_cloud_event("http://stac-api-backend.test")).unwrap();
+        assert_eq!(value["specversion"], "1.0");
+        assert_eq!(value["id"], event.id);
+        assert_eq!(value["source"], "http://stac-api-backend.test");
+        assert_eq!(value["type"], "org.stacspec.collection.deleted");
+        assert_eq!(value["subject"], "collections/an-id");
+        assert_eq!(value["data"], serde_json::json!({"collection": "an-id"}));
+    }
+}
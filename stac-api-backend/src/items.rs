@@ -1,5 +1,8 @@
-use serde::Serialize;
-use std::fmt::Debug;
+use crate::{Error, Result, Token, MAX_LIMIT};
+use chrono::{DateTime, FixedOffset};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fmt::{Debug, Display};
 
 /// A query for items.
 #[derive(Clone, Debug, Default, Serialize)]
@@ -14,6 +17,34 @@ where
     #[serde(flatten)]
     /// The backend-specific paging structure
     pub paging: P,
+
+    /// The GET query string this query came from, without its paging token.
+    ///
+    /// If set, paging links are built from this instead of from `items`, so
+    /// they keep the client's own parameters and nothing else.
+    #[serde(skip)]
+    pub query: Option<String>,
+}
+
+/// How a backend counts the items that match a query, for `numberMatched`.
+///
+/// Exact counts can be slow for large collections, since every matching row
+/// is counted. A backend has a default mode, and a request can pick its own
+/// with the `count` query parameter, e.g. `count=estimated`. Backends that
+/// can't estimate count exactly.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CountMode {
+    /// Count every match, e.g. as pgstac's search context.
+    #[default]
+    Exact,
+
+    /// Use the query planner's estimate, e.g. via pgstac's `estimated_count`.
+    Estimated,
+
+    /// Don't count matches.
+    #[serde(alias = "none")]
+    Off,
 }
 
 /// A get query for items.
@@ -30,3 +61,724 @@ where
     /// The backend-specific paging structure
     pub paging: P,
 }
+
+/// A search across collections.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Search<P>
+where
+    P: Debug + Clone + Serialize + Default,
+{
+    #[serde(flatten)]
+    /// The search.
+    pub search: stac_api::Search,
+
+    #[serde(flatten)]
+    /// The backend-specific paging structure
+    pub paging: P,
+}
+
+/// What an items query is allowed to ask for.
+#[derive(Clone, Debug)]
+pub struct ApiLimits {
+    /// The largest `limit` a client may ask for.
+    ///
+    /// Larger limits are rejected. This is different from
+    /// [Api::max_limit](crate::Api::max_limit), which quietly clamps limits
+    /// that are allowed but larger than the api wants to serve. The
+    /// [Api](crate::Api) also clamps every page to this, whatever its other
+    /// limits are. Defaults to [MAX_LIMIT].
+    pub max_limit: u64,
+
+    /// The extension query parameters that the backend supports, e.g. `sortby`.
+    ///
+    /// See [ReadBackend::supported_parameters](crate::ReadBackend::supported_parameters).
+    pub parameters: Vec<String>,
+
+    /// Should parameters that nothing handles be rejected?
+    ///
+    /// By default they're ignored, which can hide a client's typo. Defaults
+    /// to false.
+    pub strict: bool,
+}
+
+/// The parameters, besides the core ones, that every items query understands.
+const CORE_PARAMETERS: [&str; 2] = ["count", "skip-geometry"];
+
+/// Paging defaults for one collection, overriding the api-wide ones.
+///
+/// Useful for collections whose items are much larger (or smaller) than the
+/// rest, e.g. items with full-resolution footprints.
+#[derive(Clone, Debug, Default)]
+pub struct CollectionDefaults {
+    /// The number of items per page when a request doesn't set a limit.
+    ///
+    /// Falls back to [Api::default_limit](crate::Api::default_limit).
+    pub default_limit: Option<u64>,
+
+    /// The largest number of items per page.
+    ///
+    /// Falls back to [Api::max_limit](crate::Api::max_limit).
+    pub max_limit: Option<u64>,
+
+    /// The sort order when a request doesn't set one.
+    ///
+    /// This is only sent to the backend, so it's not in paging links.
+    pub sortby: Option<Vec<stac_api::Sortby>>,
+}
+
+impl<P> Items<P>
+where
+    P: Debug + Clone + Serialize + Default + DeserializeOwned,
+{
+    /// Creates an items query from a url query string, e.g. `limit=10&token=...`.
+    ///
+    /// The `token` parameter is decoded into the backend's paging structure,
+    /// and every other parameter is parsed as a [stac_api::GetItems]. Anything
+    /// that can't be parsed is an [Error::InvalidQuery].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use stac_api_backend::{Items, Token};
+    /// use std::collections::HashMap;
+    ///
+    /// let token = Token::encode(&HashMap::from([("skip", 10)])).unwrap();
+    /// let query = format!("limit=5&token={}", token.token.unwrap());
+    /// let items: Items<HashMap<String, u64>> = Items::from_get_query(&query).unwrap();
+    /// assert_eq!(items.items.limit, Some(5));
+    /// assert_eq!(items.paging["skip"], 10);
+    /// ```
+    pub fn from_get_query(query: &str) -> Result<Items<P>> {
+        let mut get_items: stac_api::GetItems = serde_urlencoded::from_str(query)
+            .map_err(|err| Error::InvalidQuery(err.to_string()))?;
+        if let Some(limit) = &get_items.limit {
+            if limit.parse::<u64>().is_err() {
+                return Err(invalid_limit(limit));
+            }
+        }
+        if let Some(bbox) = &get_items.bbox {
+            if let Some(n) = bbox.split(',').find(|n| n.trim().parse::<f64>().is_err()) {
+                return Err(invalid_bbox(format!("{:?} is not a number", n)));
+            }
+        }
+        let token = Token {
+            token: get_items.additional_fields.remove("token"),
+        };
+        let items = stac_api::Items::try_from(get_items)
+            .map_err(|err| Error::InvalidQuery(err.to_string()))?;
+        Ok(Items {
+            items,
+            paging: token.decode()?,
+            query: Some(without_token(query)),
+        })
+    }
+
+    /// Creates an items query from a JSON request body.
+    ///
+    /// The body is a [stac_api::Items], plus an optional `token` string that's
+    /// decoded into the backend's paging structure. If `filter-lang` isn't
+    /// set, it's inferred from the filter: a string is `cql2-text` and an
+    /// object is `cql2-json`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use stac_api_backend::Items;
+    ///
+    /// let items: Items<()> = Items::from_post_body(json!({"limit": 5, "bbox": [0, 0, 1, 1]})).unwrap();
+    /// assert_eq!(items.items.bbox.unwrap(), vec![0.0, 0.0, 1.0, 1.0]);
+    /// ```
+    pub fn from_post_body(body: Value) -> Result<Items<P>> {
+        let mut body = match body {
+            Value::Object(body) => body,
+            _ => return Err(Error::InvalidQuery("body must be an object".to_string())),
+        };
+        match body.get("limit") {
+            None | Some(Value::Null) => {}
+            Some(limit) if limit.is_u64() => {}
+            Some(limit) => return Err(invalid_limit(limit)),
+        }
+        match body.get("bbox") {
+            None | Some(Value::Null) => {}
+            Some(Value::Array(bbox)) if bbox.iter().all(Value::is_number) => {}
+            Some(bbox) => return Err(invalid_bbox(format!("{} is not an array of numbers", bbox))),
+        }
+        let token = match body.remove("token") {
+            None | Some(Value::Null) => Token::default(),
+            Some(Value::String(token)) => Token { token: Some(token) },
+            Some(_) => return Err(Error::InvalidQuery("token: must be a string".to_string())),
+        };
+        // Clients send `filter` and `filter-lang` side by side, but
+        // stac_api::Items expects them nested under `filter`.
+        let filter_lang = body.remove("filter-lang");
+        if let Some(filter) = body.remove("filter") {
+            let filter_lang = filter_lang.unwrap_or_else(|| {
+                if filter.is_string() {
+                    "cql2-text".into()
+                } else {
+                    "cql2-json".into()
+                }
+            });
+            let _ = body.insert(
+                "filter".to_string(),
+                serde_json::json!({"filter-lang": filter_lang, "filter": filter}),
+            );
+        }
+        let items = serde_json::from_value(Value::Object(body))
+            .map_err(|err| Error::InvalidQuery(err.to_string()))?;
+        Ok(Items {
+            items,
+            paging: token.decode()?,
+            query: None,
+        })
+    }
+}
+
+impl<P> Items<P>
+where
+    P: Debug + Clone + Serialize + Default,
+{
+    /// Returns true if this query asks for features without their geometries,
+    /// with `skip-geometry=true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use stac_api_backend::Items;
+    ///
+    /// let items: Items<()> = Items::from_get_query("skip-geometry=true").unwrap();
+    /// assert!(items.skip_geometry());
+    /// ```
+    pub fn skip_geometry(&self) -> bool {
+        match self.items.additional_fields.get("skip-geometry") {
+            Some(Value::Bool(skip_geometry)) => *skip_geometry,
+            Some(Value::String(skip_geometry)) => skip_geometry == "true",
+            _ => false,
+        }
+    }
+
+    /// Returns how this query asks for its matches to be counted, with
+    /// `count=exact`, `count=estimated`, or `count=none`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use stac_api_backend::{CountMode, Items};
+    ///
+    /// let items: Items<()> = Items::from_get_query("count=none").unwrap();
+    /// assert_eq!(items.count(), Some(CountMode::Off));
+    /// ```
+    pub fn count(&self) -> Option<CountMode> {
+        count_mode(&self.items.additional_fields)
+    }
+
+    /// Checks this query before it is sent to a backend.
+    ///
+    /// The limit must be between one and the maximum, the bbox must have four
+    /// or six numbers with each minimum no larger than its maximum (longitudes
+    /// may cross the antimeridian), the datetime must be a valid datetime or
+    /// interval (with at most one open end, as `..` or an empty string),
+    /// `skip-geometry` must be a boolean, `count` must be a
+    /// [CountMode], and only supported
+    /// extension parameters may be set. If the limits are
+    /// [strict](ApiLimits::strict), no other parameters may be set either. Every
+    /// problem is described, by parameter, in the returned
+    /// [Error::InvalidQuery].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use stac_api_backend::{ApiLimits, Items};
+    ///
+    /// let mut items: Items<()> = Items::default();
+    /// items.items.bbox = Some(vec![-105.0, 41.0, -104.0]);
+    /// assert!(items.validate(&ApiLimits::default()).is_err());
+    /// ```
+    pub fn validate(&self, limits: &ApiLimits) -> Result<()> {
+        let mut problems = Vec::new();
+        if let Some(limit) = self.items.limit {
+            if limit < 1 {
+                problems.push("limit: must be at least 1".to_string());
+            } else if limit > limits.max_limit {
+                problems.push(format!("limit: must be at most {}", limits.max_limit));
+            }
+        }
+        if let Some(bbox) = &self.items.bbox {
+            if let Some(problem) = validate_bbox(bbox) {
+                problems.push(format!("bbox: {}", problem));
+            }
+        }
+        if let Some(datetime) = &self.items.datetime {
+            if let Err(problem) = parse_datetime(datetime) {
+                problems.push(format!("datetime: {}", problem));
+            }
+        }
+        match self.items.additional_fields.get("skip-geometry") {
+            None | Some(Value::Bool(_)) => {}
+            Some(Value::String(s)) if s == "true" || s == "false" => {}
+            Some(_) => problems.push("skip-geometry: must be true or false".to_string()),
+        }
+        if self.items.additional_fields.contains_key("count")
+            && count_mode(&self.items.additional_fields).is_none()
+        {
+            problems.push("count: must be exact, estimated, or none".to_string());
+        }
+        for (parameter, is_set) in [
+            ("fields", self.items.fields.is_some()),
+            ("sortby", self.items.sortby.is_some()),
+            ("filter-crs", self.items.filter_crs.is_some()),
+            ("filter", self.items.filter.is_some()),
+            ("query", self.items.query.is_some()),
+        ] {
+            if is_set && !limits.parameters.iter().any(|p| p == parameter) {
+                problems.push(format!("{}: not supported", parameter));
+            }
+        }
+        if limits.strict {
+            for parameter in self.items.additional_fields.keys() {
+                // GET queries keep their filter-lang with the other
+                // parameters, rather than in the filter.
+                let parameter = if parameter == "filter-lang" {
+                    "filter"
+                } else {
+                    parameter.as_str()
+                };
+                if !CORE_PARAMETERS.contains(&parameter)
+                    && !limits.parameters.iter().any(|p| p == parameter)
+                {
+                    problems.push(format!("{}: unknown parameter", parameter));
+                }
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidQuery(problems.join("; ")))
+        }
+    }
+}
+
+/// Returns the `count` parameter of a query, if it's set to a valid mode.
+pub(crate) fn count_mode(additional_fields: &Map<String, Value>) -> Option<CountMode> {
+    additional_fields
+        .get("count")
+        .and_then(|count| serde_json::from_value(count.clone()).ok())
+}
+
+/// The start and end of a datetime interval, where `None` is an open end.
+pub(crate) type Interval = (Option<DateTime<FixedOffset>>, Option<DateTime<FixedOffset>>);
+
+/// Parses a `datetime` parameter, either an instant or an interval.
+///
+/// An interval's open end is `..` or an empty string, but not both ends can
+/// be open. Instants are returned as an interval with the same start and
+/// end. Problems are described for a client.
+pub(crate) fn parse_datetime(datetime: &str) -> std::result::Result<Interval, String> {
+    let parse = |s: &str| -> std::result::Result<Option<DateTime<FixedOffset>>, String> {
+        if s.is_empty() || s == ".." {
+            Ok(None)
+        } else {
+            DateTime::parse_from_rfc3339(s)
+                .map(Some)
+                .map_err(|err| format!("{} is not an RFC 3339 datetime ({})", s, err))
+        }
+    };
+    let mut parts = datetime.split('/');
+    let (start, end) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(instant), None, None) => match parse(instant)? {
+            Some(instant) => (Some(instant), Some(instant)),
+            None => return Err("must be a datetime or an interval".to_string()),
+        },
+        (Some(start), Some(end), None) => (parse(start)?, parse(end)?),
+        _ => return Err("an interval has exactly one /".to_string()),
+    };
+    match (start, end) {
+        (None, None) => Err("the start and end can't both be open".to_string()),
+        (Some(start), Some(end)) if start > end => Err("the start is after the end".to_string()),
+        interval => Ok(interval),
+    }
+}
+
+/// Spells a `datetime` interval's open ends as `..`, for backends that don't
+/// understand empty ones.
+pub(crate) fn normalize_datetime(datetime: &str) -> String {
+    match datetime.split_once('/') {
+        Some((start, end)) => {
+            let open = |s: &str| if s.is_empty() { ".." } else { s }.to_string();
+            format!("{}/{}", open(start), open(end))
+        }
+        None => datetime.to_string(),
+    }
+}
+
+/// Removes the paging token from a GET query string, leaving the rest of it
+/// exactly as the client sent it.
+fn without_token(query: &str) -> String {
+    query
+        .split('&')
+        .filter(|pair| {
+            !pair.is_empty()
+                && url::form_urlencoded::parse(pair.as_bytes())
+                    .next()
+                    .is_none_or(|(key, _)| key != "token")
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// The error for a limit that isn't a whole number, e.g. `-1` or `abc`.
+fn invalid_limit(limit: impl Display) -> Error {
+    Error::InvalidQuery(format!("limit: must be a positive integer, got {}", limit))
+}
+
+/// The error for a bbox that can't be parsed, explaining what one looks like.
+fn invalid_bbox(problem: impl Display) -> Error {
+    Error::InvalidQuery(format!("bbox: {}, {}", problem, BBOX_FORMAT))
+}
+
+/// How a bbox is written, for error messages.
+const BBOX_FORMAT: &str =
+    "expected 4 or 6 numbers (west,south,east,north or west,south,min,east,north,max)";
+
+/// Describes what's wrong with a bbox, if anything.
+///
+/// A bbox is four or six finite numbers with each minimum no larger than its
+/// maximum, except for longitudes, where a west that's larger than the east
+/// crosses the antimeridian.
+pub(crate) fn validate_bbox(bbox: &[f64]) -> Option<String> {
+    if bbox.len() != 4 && bbox.len() != 6 {
+        return Some(format!("{}, got {}", BBOX_FORMAT, bbox.len()));
+    }
+    if bbox.iter().any(|n| !n.is_finite()) {
+        return Some("every number must be finite".to_string());
+    }
+    let half = bbox.len() / 2;
+    let (min, max) = bbox.split_at(half);
+    // The first axis is longitude, which can wrap around the antimeridian.
+    for (i, (min, max)) in min.iter().zip(max).enumerate().skip(1) {
+        if min > max {
+            let axis = if i == 1 { "south" } else { "minimum elevation" };
+            return Some(format!(
+                "{} ({}) is larger than its maximum ({})",
+                axis, min, max
+            ));
+        }
+    }
+    None
+}
+
+impl Default for ApiLimits {
+    fn default() -> Self {
+        ApiLimits {
+            max_limit: MAX_LIMIT,
+            parameters: Vec::new(),
+            strict: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ApiLimits, CountMode, Items};
+    use crate::{Error, Token};
+    use serde_json::json;
+    use stac_api::Fields;
+
+    fn validate(f: impl FnOnce(&mut stac_api::Items)) -> Result<(), String> {
+        let mut items: Items<()> = Items::default();
+        f(&mut items.items);
+        items
+            .validate(&ApiLimits::default())
+            .map_err(|err| match err {
+                Error::InvalidQuery(message) => message,
+                _ => panic!("unexpected error: {}", err),
+            })
+    }
+
+    #[test]
+    fn valid() {
+        validate(|_| {}).unwrap();
+        validate(|items| {
+            items.limit = Some(10);
+            items.bbox = Some(vec![170.0, -10.0, -170.0, 10.0]);
+            items.datetime = Some("2023-01-01T00:00:00Z/..".to_string());
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn limit() {
+        assert!(validate(|items| items.limit = Some(0))
+            .unwrap_err()
+            .starts_with("limit"));
+        assert!(validate(|items| items.limit = Some(10_001))
+            .unwrap_err()
+            .starts_with("limit"));
+    }
+
+    #[test]
+    fn bbox() {
+        for bbox in [
+            vec![0.0, 0.0, 1.0],
+            vec![0.0, 1.0, 1.0, 0.0],
+            vec![0.0, 0.0, 10.0, 1.0, 1.0, 0.0],
+            vec![0.0, f64::NAN, 1.0, 1.0],
+        ] {
+            assert!(validate(|items| items.bbox = Some(bbox))
+                .unwrap_err()
+                .starts_with("bbox"));
+        }
+    }
+
+    #[test]
+    fn datetime() {
+        assert!(
+            validate(|items| items.datetime = Some("yesterday".to_string()))
+                .unwrap_err()
+                .starts_with("datetime")
+        );
+        assert!(validate(
+            |items| items.datetime = Some("2023-01-02T00:00:00Z/2023-01-01T00:00:00Z".to_string())
+        )
+        .unwrap_err()
+        .starts_with("datetime"));
+    }
+
+    #[test]
+    fn unparseable_bbox() {
+        let err = Items::<()>::from_get_query("bbox=0,0,a,1").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("bbox: \"a\" is not a number, expected 4 or 6"));
+        for body in [json!({"bbox": "0,0,1,1"}), json!({"bbox": [0, 0, "a", 1]})] {
+            let err = Items::<()>::from_post_body(body).unwrap_err();
+            assert!(err.to_string().contains("bbox: "));
+            assert!(err.to_string().contains("expected 4 or 6"));
+        }
+    }
+
+    #[test]
+    fn open_datetimes() {
+        for datetime in [
+            "2023-01-01T00:00:00Z",
+            "2023-01-01T00:00:00Z/..",
+            "../2023-01-01T00:00:00Z",
+            "2023-01-01T00:00:00Z/",
+            "/2023-01-01T00:00:00Z",
+        ] {
+            assert!(
+                validate(|items| items.datetime = Some(datetime.to_string())).is_ok(),
+                "{}",
+                datetime
+            );
+        }
+        for datetime in ["..", "../..", "/", "", "2023-01-01T00:00:00Z/../.."] {
+            assert!(
+                validate(|items| items.datetime = Some(datetime.to_string()))
+                    .unwrap_err()
+                    .starts_with("datetime"),
+                "{}",
+                datetime
+            );
+        }
+        assert_eq!(
+            super::normalize_datetime("/2023-01-01T00:00:00Z"),
+            "../2023-01-01T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn unsupported() {
+        assert_eq!(
+            validate(|items| items.fields = Some(Fields::default())).unwrap_err(),
+            "fields: not supported"
+        );
+        let mut items: Items<()> = Items::default();
+        items.items.fields = Some(Fields::default());
+        items
+            .validate(&ApiLimits {
+                parameters: vec!["fields".to_string()],
+                ..Default::default()
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn strict() {
+        let items: Items<()> =
+            Items::from_get_query("limt=10&count=none&filter=id%3D%27a%27&filter-lang=cql2-text")
+                .unwrap();
+        let mut limits = ApiLimits {
+            parameters: vec!["filter".to_string()],
+            ..Default::default()
+        };
+        items.validate(&limits).unwrap();
+        limits.strict = true;
+        assert_eq!(
+            items.validate(&limits).unwrap_err().to_string(),
+            "invalid query: limt: unknown parameter"
+        );
+        limits.parameters.push("limt".to_string());
+        items.validate(&limits).unwrap();
+    }
+
+    #[test]
+    fn skip_geometry() {
+        let items: Items<()> = Items::from_get_query("skip-geometry=false").unwrap();
+        assert!(!items.skip_geometry());
+        items.validate(&ApiLimits::default()).unwrap();
+        let items: Items<()> = Items::from_post_body(json!({"skip-geometry": true})).unwrap();
+        assert!(items.skip_geometry());
+        assert_eq!(
+            validate(|items| {
+                let _ = items
+                    .additional_fields
+                    .insert("skip-geometry".to_string(), "yes".into());
+            })
+            .unwrap_err(),
+            "skip-geometry: must be true or false"
+        );
+    }
+
+    #[test]
+    fn count() {
+        for (query, count) in [
+            ("count=exact", CountMode::Exact),
+            ("count=estimated", CountMode::Estimated),
+            ("count=none", CountMode::Off),
+            ("count=off", CountMode::Off),
+        ] {
+            let items: Items<()> = Items::from_get_query(query).unwrap();
+            assert_eq!(items.count(), Some(count));
+            items.validate(&ApiLimits::default()).unwrap();
+        }
+        let items: Items<()> = Items::from_get_query("count=maybe").unwrap();
+        assert_eq!(items.count(), None);
+        assert_eq!(
+            items
+                .validate(&ApiLimits::default())
+                .unwrap_err()
+                .to_string(),
+            "invalid query: count: must be exact, estimated, or none"
+        );
+    }
+
+    #[test]
+    fn every_problem() {
+        let message = validate(|items| {
+            items.limit = Some(0);
+            items.bbox = Some(vec![0.0]);
+        })
+        .unwrap_err();
+        assert!(message.contains("limit"));
+        assert!(message.contains("bbox"));
+    }
+
+    #[test]
+    #[cfg(feature = "memory")]
+    fn from_get_query_skip_take() {
+        use crate::memory::Paging;
+        let token = Token::encode(&Paging {
+            skip: Some(20),
+            take: Some(10),
+        })
+        .unwrap();
+        let query = format!(
+            "limit=10&bbox=0,0,1,1&filter=id%3D%27an-id%27&token={}",
+            token.token.unwrap()
+        );
+        let items: Items<Paging> = Items::from_get_query(&query).unwrap();
+        assert_eq!(items.items.limit, Some(10));
+        assert_eq!(items.items.bbox.unwrap(), vec![0.0, 0.0, 1.0, 1.0]);
+        assert!(items.items.filter.is_some());
+        assert!(items.items.additional_fields.is_empty());
+        assert_eq!(items.paging.skip, Some(20));
+        assert_eq!(items.paging.take, Some(10));
+
+        let items: Items<Paging> = Items::from_get_query("").unwrap();
+        assert!(items.paging.skip.is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "pgstac")]
+    fn from_get_query_pgstac_token() {
+        use crate::pgstac::Paging;
+        let token = Token::encode(&Paging {
+            token: Some("next:collection-a:item-b".to_string()),
+        })
+        .unwrap();
+        let query = format!("token={}", token.token.unwrap());
+        let items: Items<Paging> = Items::from_get_query(&query).unwrap();
+        assert_eq!(items.paging.token.unwrap(), "next:collection-a:item-b");
+    }
+
+    #[test]
+    fn from_get_query_invalid() {
+        for query in ["limit=ten", "bbox=0,0,a,1", "token=bm90IGpzb24"] {
+            assert!(matches!(
+                Items::<()>::from_get_query(query).unwrap_err(),
+                Error::InvalidQuery(_) | Error::Base64Decode(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn invalid_limit() {
+        for query in ["limit=-1", "limit=abc", "limit=1.5"] {
+            let err = Items::<()>::from_get_query(query).unwrap_err();
+            assert!(err
+                .to_string()
+                .contains("limit: must be a positive integer"));
+        }
+        for body in [
+            json!({"limit": -1}),
+            json!({"limit": "abc"}),
+            json!({"limit": 1.5}),
+        ] {
+            let err = Items::<()>::from_post_body(body).unwrap_err();
+            assert!(err
+                .to_string()
+                .contains("limit: must be a positive integer"));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "memory")]
+    fn from_post_body() {
+        use crate::memory::Paging;
+        let token = Token::encode(&Paging {
+            skip: Some(5),
+            take: None,
+        })
+        .unwrap();
+        let items: Items<Paging> = Items::from_post_body(json!({
+            "limit": 5,
+            "filter": {"op": "=", "args": [{"property": "id"}, "an-id"]},
+            "filter-lang": "cql2-json",
+            "token": token.token.unwrap(),
+        }))
+        .unwrap();
+        assert_eq!(items.items.limit, Some(5));
+        assert!(matches!(
+            items.items.filter.unwrap(),
+            stac_api::Filter::Cql2Json(_)
+        ));
+        assert_eq!(items.paging.skip, Some(5));
+
+        let items: Items<Paging> =
+            Items::from_post_body(json!({"filter": "id = 'an-id'"})).unwrap();
+        assert!(matches!(
+            items.items.filter.unwrap(),
+            stac_api::Filter::Cql2Text(_)
+        ));
+        assert!(matches!(
+            Items::<Paging>::from_post_body(json!({"token": 42})).unwrap_err(),
+            Error::InvalidQuery(_)
+        ));
+        assert!(matches!(
+            Items::<Paging>::from_post_body(json!([])).unwrap_err(),
+            Error::InvalidQuery(_)
+        ));
+    }
+}
@@ -43,24 +43,39 @@
 
 mod api;
 mod backend;
+#[cfg(feature = "memory")]
+mod cql2;
 mod error;
+mod events;
 mod items;
 #[cfg(feature = "memory")]
 mod memory;
+mod metrics;
 mod page;
 #[cfg(feature = "pgstac")]
 mod pgstac;
+mod queryables;
+mod raw;
+mod token;
 
 #[cfg(feature = "pgstac")]
-pub use crate::pgstac::PgstacBackend;
+pub use crate::pgstac::{PgstacBackend, PoolConfig};
 #[cfg(feature = "memory")]
-pub use memory::MemoryBackend;
+pub use memory::{Eviction, MemoryBackend};
 pub use {
-    api::{Api, DEFAULT_SERVICE_DESC_MEDIA_TYPE},
-    backend::Backend,
+    api::{
+        Api, ApiBuilder, LinkDecorator, StacExtension, DEFAULT_LIMIT,
+        DEFAULT_SERVICE_DESC_MEDIA_TYPE, MAX_LIMIT,
+    },
+    backend::{Backend, CollectionSummary, IngestFailure, IngestReport, ReadBackend, WriteBackend},
     error::Error,
-    items::{GetItems, Items},
-    page::Page,
+    events::{Action, CloudEvent, Event, EventListener, Notifying, CLOUD_EVENTS_SPEC_VERSION},
+    items::{ApiLimits, CollectionDefaults, CountMode, GetItems, Items, Search},
+    metrics::{Instrumented, MethodMetrics, Metrics, PoolMetrics, LATENCY_BUCKETS},
+    page::{ItemsResult, Page},
+    queryables::Queryables,
+    raw::{RawItem, RawItemCollection},
+    token::Token,
 };
 
 /// A crate-specific result type.
@@ -68,7 +83,7 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 #[cfg(test)]
 mod tests {
-    use {stac_validate as _, tokio as _, tokio_test as _};
+    use {criterion as _, stac_validate as _, tokio as _, tokio_test as _};
 
     #[macro_export]
     macro_rules! assert_link {
@@ -1,21 +1,48 @@
-use crate::{Backend, Items, Page};
+use crate::{
+    cql2::Expr,
+    items::{parse_datetime, validate_bbox},
+    CollectionSummary, Items, ItemsResult, Page, Queryables, ReadBackend, Search, WriteBackend,
+    DEFAULT_LIMIT,
+};
 use async_trait::async_trait;
+use geo::BoundingRect;
+use rstar::{
+    primitives::{GeomWithData, Rectangle},
+    RTree, AABB,
+};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use stac::{Collection, Item, Links};
-use stac_api::ItemCollection;
+use stac_api::{ItemCollection, Sortby};
 use std::{
-    collections::BTreeMap,
-    sync::{Arc, RwLock},
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
 };
 use thiserror::Error;
 
-const DEFAULT_TAKE: usize = 20;
-
 #[derive(Error, Debug)]
 pub enum Error {
+    #[error("over capacity: {0}")]
+    CapacityExceeded(String),
+
     #[error("no collection id={0}")]
     CollectionNotFound(String),
 
+    #[error("invalid filter: {0}")]
+    Cql2(String),
+
+    #[error("invalid bbox: {0}")]
+    InvalidBbox(String),
+
+    #[error("invalid datetime: {0}")]
+    InvalidDatetime(String),
+
+    #[error("item id={1} already exists in collection id={0}")]
+    ItemExists(String, String),
+
     #[error("no collection set on item with id={}", .0.id)]
     NoCollection(Item),
 
@@ -40,8 +67,40 @@ type Result<T> = std::result::Result<T, Error>;
 #[derive(Clone, Debug)]
 pub struct MemoryBackend {
     collections: Arc<RwLock<BTreeMap<String, Collection>>>,
-    items: Arc<RwLock<BTreeMap<String, Vec<Item>>>>,
-    take: usize,
+    items: Arc<RwLock<BTreeMap<String, CollectionItems>>>,
+    preserve_links: bool,
+    max_items: Option<usize>,
+    max_items_per_collection: Option<usize>,
+    eviction: Eviction,
+    clock: Arc<AtomicU64>,
+}
+
+/// What a [MemoryBackend] does when adding items would put it over capacity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Eviction {
+    /// Refuse the whole write and leave the backend as it was.
+    #[default]
+    Reject,
+
+    /// Make room by dropping the items that were least recently added or
+    /// read, including items from the same write.
+    LeastRecentlyUsed,
+}
+
+/// A collection's items, with an R-tree of their bounding boxes.
+///
+/// Items are kept in insertion order so paging is stable, and the tree points
+/// back into the item vector. Queryables are inferred from every item as
+/// it's added, and aren't narrowed when items are evicted. Each item also
+/// has the tick of the backend's clock when it was last added or read.
+#[derive(Debug, Default)]
+struct CollectionItems {
+    items: Vec<Item>,
+    ids: HashMap<String, usize>,
+    index: RTree<GeomWithData<Rectangle<[f64; 2]>, usize>>,
+    queryables: Queryables,
+    accessed: Vec<AtomicU64>,
 }
 
 #[derive(Default, Clone, Debug, Deserialize, Serialize)]
@@ -66,129 +125,445 @@ impl MemoryBackend {
         MemoryBackend {
             collections: Arc::new(RwLock::new(BTreeMap::new())),
             items: Arc::new(RwLock::new(BTreeMap::new())),
-            take: DEFAULT_TAKE,
+            preserve_links: false,
+            max_items: None,
+            max_items_per_collection: None,
+            eviction: Eviction::Reject,
+            clock: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Should collections and items keep their structural links when they're added?
+    ///
+    /// By default every structural link (e.g. `child`, `item`, `next`) is
+    /// dropped when an object is added. If links are preserved, the
+    /// [Api](crate::Api) still replaces the links it generates, like `self`
+    /// and `root`, but leaves the rest alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use stac_api_backend::MemoryBackend;
+    /// let backend = MemoryBackend::new().preserve_links(true);
+    /// ```
+    pub fn preserve_links(mut self, preserve_links: bool) -> MemoryBackend {
+        self.preserve_links = preserve_links;
+        self
+    }
+
+    /// Sets the most items the backend will hold, across every collection.
+    ///
+    /// Writes that would go over are handled by the [Eviction] policy. By
+    /// default there's no limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use stac_api_backend::{Eviction, MemoryBackend};
+    /// let backend = MemoryBackend::new()
+    ///     .max_items(10_000)
+    ///     .eviction(Eviction::LeastRecentlyUsed);
+    /// ```
+    pub fn max_items(mut self, max_items: usize) -> MemoryBackend {
+        self.max_items = Some(max_items);
+        self
+    }
+
+    /// Sets the most items the backend will hold in any one collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use stac_api_backend::MemoryBackend;
+    /// let backend = MemoryBackend::new().max_items_per_collection(1_000);
+    /// ```
+    pub fn max_items_per_collection(mut self, max_items: usize) -> MemoryBackend {
+        self.max_items_per_collection = Some(max_items);
+        self
+    }
+
+    /// Sets what happens when a write would go over capacity.
+    ///
+    /// Defaults to [Eviction::Reject].
+    pub fn eviction(mut self, eviction: Eviction) -> MemoryBackend {
+        self.eviction = eviction;
+        self
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Returns an error if adding these numbers of items, by collection,
+    /// would put the backend over capacity.
+    fn check_capacity(
+        &self,
+        items_map: &BTreeMap<String, CollectionItems>,
+        added: &BTreeMap<String, usize>,
+    ) -> Result<()> {
+        if let Some(max) = self.max_items_per_collection {
+            for (collection, count) in added {
+                let existing = items_map
+                    .get(collection)
+                    .map_or(0, |items| items.items.len());
+                if existing + count > max {
+                    return Err(Error::CapacityExceeded(format!(
+                        "collection id={} would have {} items, and can hold at most {}",
+                        collection,
+                        existing + count,
+                        max
+                    )));
+                }
+            }
+        }
+        if let Some(max) = self.max_items {
+            let total = items_map
+                .values()
+                .map(|items| items.items.len())
+                .chain(added.values().copied())
+                .sum::<usize>();
+            if total > max {
+                return Err(Error::CapacityExceeded(format!(
+                    "the backend would have {} items, and can hold at most {}",
+                    total, max
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds items or, with `replace`, adds or replaces them.
+    ///
+    /// Without `replace`, an item whose id is already in its collection, or
+    /// is in `items` twice, is a conflict.
+    fn write_items(&self, items: Vec<Item>, replace: bool) -> Result<()> {
+        let collections = self.collections.read().unwrap();
+        // Check everything first so a bad item doesn't leave the others half-added.
+        for item in &items {
+            if let Some(collection) = &item.collection {
+                if !collections.contains_key(collection) {
+                    return Err(Error::CollectionNotFound(collection.clone()));
+                }
+            } else {
+                return Err(Error::NoCollection(item.clone()));
+            }
+        }
+        let mut items_map = self.items.write().unwrap();
+        let (items, replacements) = split_existing(&items_map, items, replace)?;
+        let capped = self.max_items.is_some() || self.max_items_per_collection.is_some();
+        if capped && self.eviction == Eviction::Reject {
+            // Replacements don't change any counts.
+            let mut added = BTreeMap::new();
+            for item in &items {
+                *added
+                    .entry(item.collection.clone().unwrap_or_default())
+                    .or_default() += 1;
+            }
+            self.check_capacity(&items_map, &added)?;
+        }
+        for (i, mut item) in replacements {
+            if !self.preserve_links {
+                item.remove_structural_links();
+            }
+            let tick = self.tick();
+            if let Some(collection_items) = item
+                .collection
+                .clone()
+                .and_then(|collection| items_map.get_mut(&collection))
+            {
+                collection_items.replace(i, item, tick);
+            }
+        }
+        for mut item in items {
+            if !self.preserve_links {
+                item.remove_structural_links();
+            }
+            let collection = item.collection.clone().unwrap_or_default();
+            let tick = self.tick();
+            items_map.entry(collection).or_default().push(item, tick);
+        }
+        if capped && self.eviction == Eviction::LeastRecentlyUsed {
+            self.make_room(&mut items_map);
+        }
+        Ok(())
+    }
+
+    /// Evicts the least recently used items until the backend is back under
+    /// its capacities.
+    ///
+    /// This scans every item, so it's only done when a write goes over.
+    fn make_room(&self, items_map: &mut BTreeMap<String, CollectionItems>) {
+        let mut victims: BTreeMap<String, BTreeSet<usize>> = BTreeMap::new();
+        if let Some(max) = self.max_items_per_collection {
+            for (collection, items) in items_map.iter() {
+                let excess = items.items.len().saturating_sub(max);
+                if excess > 0 {
+                    let mut accessed: Vec<_> = items.accessed().collect();
+                    let _ = accessed.select_nth_unstable(excess - 1);
+                    let _ = victims.insert(
+                        collection.clone(),
+                        accessed[..excess].iter().map(|&(_, i)| i).collect(),
+                    );
+                }
+            }
+        }
+        if let Some(max) = self.max_items {
+            let total = items_map
+                .values()
+                .map(|items| items.items.len())
+                .sum::<usize>()
+                - victims.values().map(BTreeSet::len).sum::<usize>();
+            let excess = total.saturating_sub(max);
+            if excess > 0 {
+                let mut accessed: Vec<_> = items_map
+                    .iter()
+                    .flat_map(|(collection, items)| {
+                        let evicted = victims.get(collection);
+                        items
+                            .accessed()
+                            .filter(move |(_, i)| {
+                                !evicted.is_some_and(|evicted| evicted.contains(i))
+                            })
+                            .map(move |(tick, i)| (tick, collection, i))
+                    })
+                    .collect();
+                let _ = accessed.select_nth_unstable(excess - 1);
+                let more: Vec<_> = accessed[..excess]
+                    .iter()
+                    .map(|&(_, collection, i)| (collection.clone(), i))
+                    .collect();
+                for (collection, i) in more {
+                    let _ = victims.entry(collection).or_default().insert(i);
+                }
+            }
+        }
+        for (collection, victims) in victims {
+            if let Some(items) = items_map.get_mut(&collection) {
+                items.evict(&victims);
+            }
         }
     }
 }
 
 #[async_trait]
-impl Backend for MemoryBackend {
+impl ReadBackend for MemoryBackend {
     type Error = Error;
     type Paging = Paging;
 
+    fn supported_parameters(&self) -> &'static [&'static str] {
+        &["filter", "sortby"]
+    }
+
     async fn collections(&self) -> Result<Vec<Collection>> {
         let collections = self.collections.read().unwrap();
         Ok(collections.values().cloned().collect())
     }
 
+    async fn collection_summaries(&self) -> Result<Vec<CollectionSummary>> {
+        let collections = self.collections.read().unwrap();
+        Ok(collections
+            .values()
+            .map(|collection| CollectionSummary {
+                id: collection.id.clone(),
+                title: collection.title.clone(),
+            })
+            .collect())
+    }
+
     async fn collection(&self, id: &str) -> Result<Option<Collection>> {
         let collections = self.collections.read().unwrap();
         Ok(collections.get(id).cloned())
     }
 
-    async fn items(&self, id: &str, query: Items<Paging>) -> Result<Option<Page<Paging>>> {
-        let skip = query.paging.skip.unwrap_or(0);
-        let mut take = query.paging.take.unwrap_or(self.take);
-        if let Some(limit) = query.items.limit {
-            let limit: usize = limit.try_into()?;
-            if limit < take {
-                take = limit;
-            }
+    async fn items(&self, id: &str, query: Items<Paging>) -> Result<ItemsResult<Paging>> {
+        if !self.collections.read().unwrap().contains_key(id) {
+            return Ok(ItemsResult::CollectionNotFound);
         }
-        let items = self.items.read().unwrap();
-        if let Some(items) = items.get(id) {
-            let bbox = query
-                .items
-                .bbox
-                .as_ref()
-                .map(|bbox| stac::geo::bbox(bbox))
-                .transpose()?;
-            let datetime = query
-                .items
-                .datetime
+        let search = Search {
+            search: query.items.into_search(id),
+            paging: query.paging,
+        };
+        self.search(search).await.map(ItemsResult::from)
+    }
+
+    async fn search(&self, search: Search<Paging>) -> Result<Page<Paging>> {
+        let skip = search.paging.skip.unwrap_or(0);
+        // The api sets the limit from its configuration, so the default is
+        // only for queries that don't come through an api.
+        let limit = search.search.limit.map(usize::try_from).transpose()?;
+        let take = match (search.paging.take, limit) {
+            (Some(take), Some(limit)) => take.min(limit),
+            (Some(take), None) => take,
+            (None, Some(limit)) => limit,
+            (None, None) => DEFAULT_LIMIT.try_into()?,
+        };
+        let search = search.search;
+        let bbox = search.bbox.as_deref().map(rects).transpose()?;
+        let intersects = search
+            .intersects
+            .clone()
+            .map(geo::Geometry::try_from)
+            .transpose()?;
+        let datetime = search
+            .datetime
+            .as_ref()
+            .map(|datetime| parse_datetime(datetime))
+            .transpose()
+            .map_err(Error::InvalidDatetime)?;
+        let filter = search.filter.as_ref().map(Expr::from_filter).transpose()?;
+        let envelopes = bbox.clone().or_else(|| {
+            intersects
                 .as_ref()
-                .map(|datetime| stac::datetime::parse(datetime))
-                .transpose()?;
-            let items: Vec<_> = items
-                .iter()
-                .filter(|item| {
-                    bbox.map(|bbox| item.intersects_bbox(bbox).unwrap_or(false))
+                .and_then(|geometry| geometry.bounding_rect())
+                .map(|rect| vec![rect])
+        });
+        let items_map = self.items.read().unwrap();
+        let mut items = Vec::new();
+        for (collection_id, collection_items) in items_map.iter() {
+            if let Some(collections) = &search.collections {
+                if !collections.contains(collection_id) {
+                    continue;
+                }
+            }
+            for (i, item) in collection_items.intersecting(envelopes.as_deref()) {
+                let matches = search
+                    .ids
+                    .as_ref()
+                    .map(|ids| ids.contains(&item.id))
+                    .unwrap_or(true)
+                    && bbox
+                        .as_ref()
+                        .map(|rects| {
+                            rects
+                                .iter()
+                                .any(|rect| item.intersects(rect).unwrap_or(false))
+                        })
                         .unwrap_or(true)
-                        && datetime
-                            .map(|(start, end)| {
-                                item.intersects_datetimes(start, end).unwrap_or(false)
-                            })
-                            .unwrap_or(true)
-                })
-                .collect();
-            let number_matched = items.len();
-            let items = items
-                .into_iter()
-                .cloned()
-                .skip(skip)
-                .take(take)
-                .map(|item| item.try_into().map_err(Error::from))
-                .collect::<Result<_>>()?;
-            let mut item_collection = ItemCollection::new(items)?;
-            item_collection.number_matched = Some(number_matched.try_into()?);
-            let next = if skip + take < number_matched {
+                    && intersects
+                        .as_ref()
+                        .map(|intersects| item.intersects(intersects).unwrap_or(false))
+                        .unwrap_or(true)
+                    && datetime
+                        .map(|(start, end)| item.intersects_datetimes(start, end).unwrap_or(false))
+                        .unwrap_or(true);
+                if matches {
+                    items.push((collection_items, i, item));
+                }
+            }
+        }
+        let items = if let Some(filter) = &filter {
+            let mut matched = Vec::with_capacity(items.len());
+            for (collection_items, i, item) in items {
+                if filter.matches(&stac_api::Item::try_from(item.clone())?)? {
+                    matched.push((collection_items, i, item));
+                }
+            }
+            matched
+        } else {
+            items
+        };
+        let mut items = items;
+        if let Some(sortby) = &search.sortby {
+            items.sort_by(|(_, _, a), (_, _, b)| compare(a, b, sortby));
+        }
+        let number_matched = items.len();
+        let tick = self.tick();
+        let items = items
+            .into_iter()
+            .skip(skip)
+            .take(take)
+            .map(|(collection_items, i, item)| {
+                collection_items.touch(i, tick);
+                item.clone().try_into().map_err(Error::from)
+            })
+            .collect::<Result<_>>()?;
+        let mut item_collection = ItemCollection::new(items)?;
+        item_collection.number_matched = Some(number_matched.try_into()?);
+        let next = if skip + take < number_matched {
+            Some(Paging {
+                skip: Some(skip + take),
+                take: Some(take),
+            })
+        } else {
+            None
+        };
+        let prev = if skip > 0 {
+            if skip >= take {
                 Some(Paging {
-                    skip: Some(skip + take),
+                    skip: Some(skip - take),
                     take: Some(take),
                 })
             } else {
-                None
-            };
-            let prev = if skip > 0 {
-                if skip >= take {
-                    Some(Paging {
-                        skip: Some(skip - take),
-                        take: Some(take),
-                    })
-                } else {
-                    Some(Paging {
-                        skip: None,
-                        take: Some(take),
-                    })
-                }
-            } else {
-                None
+                Some(Paging {
+                    skip: None,
+                    take: Some(take),
+                })
+            }
+        } else {
+            None
+        };
+        // Only link to the ends if there's more than one page.
+        let (first, last) = if next.is_some() || prev.is_some() {
+            let first = Paging {
+                skip: None,
+                take: Some(take),
             };
-            Ok(Some(Page {
-                item_collection,
-                next,
-                prev,
-            }))
+            let last = number_matched
+                .saturating_sub(1)
+                .checked_div(take)
+                .map(|pages| Paging {
+                    skip: Some(pages * take),
+                    take: Some(take),
+                });
+            (Some(first), last)
         } else {
-            let collections = self.collections.read().unwrap();
-            if collections.contains_key(id) {
-                let mut item_collection = ItemCollection::new(vec![])?;
-                item_collection.number_matched = Some(0);
-                Ok(Some(Page {
-                    item_collection,
-                    next: None,
-                    prev: None,
-                }))
-            } else {
-                Ok(None)
-            }
-        }
+            (None, None)
+        };
+        Ok(Page {
+            item_collection,
+            next,
+            prev,
+            first,
+            last,
+            raw_items: None,
+        })
     }
 
     async fn item(&self, collection_id: &str, id: &str) -> Result<Option<Item>> {
         let items = self.items.read().unwrap();
-        if let Some(item) = items
+        if let Some((collection_items, i)) = items
             .get(collection_id)
-            .and_then(|items| items.iter().find(|item| item.id == id))
+            .and_then(|items| items.position(id).map(|i| (items, i)))
         {
-            Ok(Some(item.clone()))
+            collection_items.touch(i, self.tick());
+            Ok(Some(collection_items.items[i].clone()))
         } else {
             Ok(None)
         }
     }
 
+    async fn queryables(&self, collection_id: Option<&str>) -> Result<Option<Map<String, Value>>> {
+        let items = self.items.read().unwrap();
+        let mut queryables = Queryables::new();
+        if let Some(collection_id) = collection_id {
+            if let Some(items) = items.get(collection_id) {
+                queryables.merge(&items.queryables);
+            }
+        } else {
+            for items in items.values() {
+                queryables.merge(&items.queryables);
+            }
+        }
+        Ok(Some(queryables.properties()))
+    }
+}
+
+#[async_trait]
+impl WriteBackend for MemoryBackend {
     async fn add_collection(&mut self, mut collection: Collection) -> Result<Option<Collection>> {
-        collection.remove_structural_links();
+        if !self.preserve_links {
+            collection.remove_structural_links();
+        }
         let mut collections = self.collections.write().unwrap(); // TODO handle poison gracefully
         Ok(collections.insert(collection.id.clone(), collection))
     }
@@ -214,43 +589,263 @@ impl Backend for MemoryBackend {
     }
 
     async fn add_items(&mut self, items: Vec<Item>) -> Result<()> {
-        let collections = self.collections.read().unwrap();
-        let mut items_map = self.items.write().unwrap();
-        for mut item in items {
-            if let Some(collection) = item.collection.clone() {
-                if collections.contains_key(&collection) {
-                    item.remove_structural_links();
-                    items_map.entry(collection.clone()).or_default().push(item);
+        self.write_items(items, false)
+    }
+
+    async fn upsert_items(&mut self, items: Vec<Item>) -> Result<()> {
+        self.write_items(items, true)
+    }
+
+    async fn add_item(&mut self, item: Item) -> Result<()> {
+        self.add_items(vec![item]).await
+    }
+}
+
+impl From<crate::cql2::Error> for Error {
+    fn from(value: crate::cql2::Error) -> Self {
+        Error::Cql2(value.to_string())
+    }
+}
+
+impl CollectionItems {
+    fn push(&mut self, item: Item, tick: u64) {
+        self.queryables.observe(&item);
+        self.insert(item, tick);
+    }
+
+    /// Adds an item to the vector and the tree, without observing its queryables.
+    fn insert(&mut self, item: Item, tick: u64) {
+        if let Some(rectangle) = rectangle(&item) {
+            self.index
+                .insert(GeomWithData::new(rectangle, self.items.len()));
+        }
+        let _ = self.ids.insert(item.id.clone(), self.items.len());
+        self.items.push(item);
+        self.accessed.push(AtomicU64::new(tick));
+    }
+
+    /// Replaces the item at a position with an item that has the same id.
+    fn replace(&mut self, i: usize, item: Item, tick: u64) {
+        self.queryables.observe(&item);
+        if let Some(rectangle) = rectangle(&self.items[i]) {
+            let _ = self.index.remove(&GeomWithData::new(rectangle, i));
+        }
+        if let Some(rectangle) = rectangle(&item) {
+            self.index.insert(GeomWithData::new(rectangle, i));
+        }
+        self.items[i] = item;
+        self.accessed[i] = AtomicU64::new(tick);
+    }
+
+    /// Returns the position of the item with this id.
+    fn position(&self, id: &str) -> Option<usize> {
+        self.ids.get(id).copied()
+    }
+
+    /// Marks an item as read at this tick.
+    fn touch(&self, i: usize, tick: u64) {
+        let _ = self.accessed[i].fetch_max(tick, Ordering::Relaxed);
+    }
+
+    /// Returns each item's last access tick and position.
+    fn accessed(&self) -> impl Iterator<Item = (u64, usize)> + '_ {
+        self.accessed
+            .iter()
+            .enumerate()
+            .map(|(i, accessed)| (accessed.load(Ordering::Relaxed), i))
+    }
+
+    /// Removes the items at these positions and rebuilds the tree.
+    fn evict(&mut self, victims: &BTreeSet<usize>) {
+        let items = std::mem::take(&mut self.items);
+        let accessed = std::mem::take(&mut self.accessed);
+        self.ids.clear();
+        self.index = RTree::new();
+        for (i, (item, accessed)) in items.into_iter().zip(accessed).enumerate() {
+            if !victims.contains(&i) {
+                self.insert(item, accessed.into_inner());
+            }
+        }
+    }
+
+    /// Returns the items, with their positions, whose bounding boxes intersect
+    /// any of the rectangles, in insertion order.
+    ///
+    /// If there are no rectangles, every item is returned. Items without
+    /// geometries never intersect a rectangle.
+    fn intersecting(
+        &self,
+        rects: Option<&[geo::Rect]>,
+    ) -> Box<dyn Iterator<Item = (usize, &Item)> + '_> {
+        if let Some(rects) = rects {
+            let mut indices: Vec<_> = rects
+                .iter()
+                .flat_map(|rect| {
+                    let envelope =
+                        AABB::from_corners(rect.min().x_y().into(), rect.max().x_y().into());
+                    self.index
+                        .locate_in_envelope_intersecting(&envelope)
+                        .map(|rectangle| rectangle.data)
+                })
+                .collect();
+            indices.sort_unstable();
+            indices.dedup();
+            Box::new(indices.into_iter().map(|i| (i, &self.items[i])))
+        } else {
+            Box::new(self.items.iter().enumerate())
+        }
+    }
+}
+
+/// Compares two items by each sortby field in turn.
+///
+/// Items without a field sort after the ones that have it, in either
+/// direction.
+fn compare(a: &Item, b: &Item, sortby: &[Sortby]) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+    for sortby in sortby {
+        let ordering = match (field(a, &sortby.field), field(b, &sortby.field)) {
+            (Some(a), Some(b)) => {
+                let ordering = match (a.as_f64(), b.as_f64()) {
+                    (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+                    _ => a.to_string().cmp(&b.to_string()),
+                };
+                // stac-api doesn't export the direction type, so compare
+                // against a descending sortby instead.
+                if *sortby == Sortby::desc(&sortby.field) {
+                    ordering.reverse()
                 } else {
-                    return Err(Error::CollectionNotFound(collection.clone()));
+                    ordering
                 }
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// Returns an item's value for a sortby field, e.g. `id` or `properties.datetime`.
+fn field(item: &Item, field: &str) -> Option<Value> {
+    match field {
+        "id" => Some(item.id.clone().into()),
+        "collection" => item.collection.clone().map(Value::from),
+        _ => {
+            let property = field.strip_prefix("properties.").unwrap_or(field);
+            if property == "datetime" {
+                item.properties.datetime.clone().map(Value::from)
             } else {
-                return Err(Error::NoCollection(item));
+                item.properties.additional_fields.get(property).cloned()
             }
         }
-        Ok(())
     }
+}
 
-    async fn upsert_items(&mut self, items: Vec<Item>) -> Result<()> {
-        self.add_items(items).await
+/// Converts a validated bbox into two-dimensional rectangles.
+///
+/// A bbox that crosses the antimeridian is split in two, one on each side.
+fn rects(bbox: &[f64]) -> Result<Vec<geo::Rect>> {
+    if let Some(problem) = validate_bbox(bbox) {
+        return Err(Error::InvalidBbox(problem));
+    }
+    let half = bbox.len() / 2;
+    let (west, south, east, north) = (bbox[0], bbox[1], bbox[half], bbox[half + 1]);
+    let rect = |west, east| geo::Rect::new((west, south), (east, north));
+    if west > east {
+        Ok(vec![rect(west, 180.0), rect(-180.0, east)])
+    } else {
+        Ok(vec![rect(west, east)])
     }
+}
 
-    async fn add_item(&mut self, item: Item) -> Result<()> {
-        self.add_items(vec![item]).await
+/// Items that replace a stored item, with the stored item's position.
+type Replacements = Vec<(usize, Item)>;
+
+/// Returns an item's bounding box as a rectangle for the tree, if it has a
+/// geometry.
+fn rectangle(item: &Item) -> Option<Rectangle<[f64; 2]>> {
+    let rect = item
+        .geometry
+        .clone()
+        .and_then(|geometry| geo::Geometry::try_from(geometry).ok())
+        .and_then(|geometry| geometry.bounding_rect())?;
+    Some(Rectangle::from_corners(
+        rect.min().x_y().into(),
+        rect.max().x_y().into(),
+    ))
+}
+
+/// Splits items into new ones and ones that replace a stored item, with the
+/// stored item's position.
+///
+/// Without `replace`, an item whose id is already in its collection, or is
+/// in `items` twice, is a conflict. With it, the last item with each id wins.
+fn split_existing(
+    items_map: &BTreeMap<String, CollectionItems>,
+    items: Vec<Item>,
+    replace: bool,
+) -> Result<(Vec<Item>, Replacements)> {
+    let mut seen = HashMap::new();
+    let mut keep = vec![true; items.len()];
+    for (i, item) in items.iter().enumerate() {
+        let collection = item.collection.as_deref().unwrap_or_default();
+        if let Some(previous) = seen.insert((collection, item.id.as_str()), i) {
+            if !replace {
+                return Err(Error::ItemExists(collection.to_string(), item.id.clone()));
+            }
+            keep[previous] = false;
+        }
+    }
+    let mut new = Vec::new();
+    let mut replacements = Vec::new();
+    for (item, keep) in items.into_iter().zip(keep) {
+        if !keep {
+            continue;
+        }
+        let position = item
+            .collection
+            .as_ref()
+            .and_then(|collection| items_map.get(collection))
+            .and_then(|collection_items| collection_items.position(&item.id));
+        match position {
+            Some(_) if !replace => {
+                return Err(Error::ItemExists(
+                    item.collection.unwrap_or_default(),
+                    item.id,
+                ))
+            }
+            Some(i) => replacements.push((i, item)),
+            None => new.push(item),
+        }
     }
+    Ok((new, replacements))
 }
 
 impl From<Error> for crate::Error {
     fn from(value: Error) -> Self {
-        crate::Error::Backend(Box::new(value))
+        match value {
+            Error::CollectionNotFound(_) => crate::Error::NotFound(value.to_string()),
+            Error::Cql2(_) | Error::InvalidBbox(_) | Error::InvalidDatetime(_) => {
+                crate::Error::InvalidQuery(value.to_string())
+            }
+            Error::CapacityExceeded(_) => crate::Error::InsufficientStorage(value.to_string()),
+            Error::ItemExists(_, _) => crate::Error::Conflict(value.to_string()),
+            _ => crate::Error::Backend(Box::new(value)),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::MemoryBackend;
-    use crate::Backend;
-    use stac::Collection;
+    use super::{Eviction, MemoryBackend};
+    use crate::{Error, Items, ItemsResult, ReadBackend, WriteBackend};
+    use futures_util::stream;
+    use stac::{Collection, Geometry, Item};
+    use stac_api::Sortby;
 
     #[tokio::test]
     async fn add_collection() {
@@ -261,4 +856,397 @@ mod tests {
             .unwrap();
         assert_eq!(backend.collections().await.unwrap().len(), 1);
     }
+
+    #[tokio::test]
+    async fn items_not_found_or_empty() {
+        let mut backend = MemoryBackend::new();
+        assert!(matches!(
+            backend
+                .items("a-collection", Items::default())
+                .await
+                .unwrap(),
+            ItemsResult::CollectionNotFound
+        ));
+        let _ = backend
+            .add_collection(Collection::new("a-collection", "A description"))
+            .await
+            .unwrap();
+        let page = backend
+            .items("a-collection", Items::default())
+            .await
+            .unwrap()
+            .into_page()
+            .unwrap();
+        assert!(page.item_collection.items.is_empty());
+    }
+
+    #[tokio::test]
+    async fn add_existing_item() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("a-collection", "A description"))
+            .await
+            .unwrap();
+        backend
+            .add_item(Item::new("an-item").collection("a-collection"))
+            .await
+            .unwrap();
+        let err: Error = backend
+            .add_item(Item::new("an-item").collection("a-collection"))
+            .await
+            .unwrap_err()
+            .into();
+        assert_eq!(err.status_code(), http::StatusCode::CONFLICT);
+        let err: Error = backend
+            .add_items(vec![
+                Item::new("another-item").collection("a-collection"),
+                Item::new("another-item").collection("a-collection"),
+            ])
+            .await
+            .unwrap_err()
+            .into();
+        assert!(matches!(err, Error::Conflict(_)));
+
+        let mut item = Item::new("an-item").collection("a-collection");
+        let _ = item
+            .properties
+            .additional_fields
+            .insert("foo".to_string(), "bar".into());
+        backend.upsert_items(vec![item]).await.unwrap();
+        let page = backend
+            .items("a-collection", Items::default())
+            .await
+            .unwrap()
+            .into_page()
+            .unwrap();
+        assert_eq!(page.item_collection.items.len(), 1);
+        assert_eq!(page.item_collection.items[0]["properties"]["foo"], "bar");
+
+        let mut item = Item::new("an-item").collection("a-collection");
+        item.geometry = Some(Geometry::point(10.0, 0.0));
+        backend.upsert_items(vec![item]).await.unwrap();
+        let mut item = Item::new("an-item").collection("a-collection");
+        item.geometry = Some(Geometry::point(20.0, 0.0));
+        backend.upsert_items(vec![item]).await.unwrap();
+        for (bbox, expected) in [
+            (vec![9.5, -1.0, 10.5, 1.0], 0),
+            (vec![19.5, -1.0, 20.5, 1.0], 1),
+        ] {
+            let mut query: Items<_> = Items::default();
+            query.items.bbox = Some(bbox);
+            let page = backend
+                .items("a-collection", query)
+                .await
+                .unwrap()
+                .into_page()
+                .unwrap();
+            assert_eq!(page.item_collection.items.len(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn items_invalid_datetime() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("a-collection", "A description"))
+            .await
+            .unwrap();
+        let mut items = Items::default();
+        items.items.datetime = Some("yesterday".to_string());
+        let err = backend.items("a-collection", items).await.unwrap_err();
+        assert!(matches!(Error::from(err), Error::InvalidQuery(_)));
+        let mut items = Items::default();
+        items.items.datetime = Some("2023-01-01T00:00:00Z/".to_string());
+        let _ = backend.items("a-collection", items).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn items_bbox_antimeridian_and_3d() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("a-collection", "A description"))
+            .await
+            .unwrap();
+        let items = [-179.5, 0.0, 179.5]
+            .into_iter()
+            .map(|x| {
+                let mut item = Item::new(format!("item-{}", x)).collection("a-collection");
+                item.geometry = Some(Geometry::point(x, 0.0));
+                item
+            })
+            .collect();
+        backend.add_items(items).await.unwrap();
+        for (bbox, expected) in [
+            (
+                vec![179.0, -1.0, -179.0, 1.0],
+                vec!["item--179.5", "item-179.5"],
+            ),
+            (vec![-1.0, -1.0, -100.0, 1.0, 1.0, 100.0], vec!["item-0"]),
+        ] {
+            let mut query: Items<_> = Items::default();
+            query.items.bbox = Some(bbox);
+            let page = backend
+                .items("a-collection", query)
+                .await
+                .unwrap()
+                .into_page()
+                .unwrap();
+            let ids: Vec<_> = page
+                .item_collection
+                .items
+                .iter()
+                .map(|item| item["id"].as_str().unwrap())
+                .collect();
+            assert_eq!(ids, expected);
+        }
+        let mut query: Items<_> = Items::default();
+        query.items.bbox = Some(vec![0.0, 1.0, 1.0]);
+        let err = backend.items("a-collection", query).await.unwrap_err();
+        assert!(matches!(Error::from(err), Error::InvalidQuery(_)));
+    }
+
+    #[tokio::test]
+    async fn items_sortby() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("a-collection", "A description"))
+            .await
+            .unwrap();
+        let items = [Some(2), None, Some(10), Some(1)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, rank)| {
+                let mut item = Item::new(format!("item-{}", i)).collection("a-collection");
+                if let Some(rank) = rank {
+                    let _ = item
+                        .properties
+                        .additional_fields
+                        .insert("rank".to_string(), rank.into());
+                }
+                item
+            })
+            .collect();
+        backend.add_items(items).await.unwrap();
+
+        for (sortby, expected) in [
+            (
+                Sortby::asc("properties.rank"),
+                ["item-3", "item-0", "item-2"],
+            ),
+            (Sortby::desc("rank"), ["item-2", "item-0", "item-3"]),
+            (Sortby::desc("id"), ["item-3", "item-2", "item-1"]),
+        ] {
+            let mut query: Items<_> = Items::default();
+            query.items.sortby = Some(vec![sortby]);
+            query.items.limit = Some(3);
+            let page = backend
+                .items("a-collection", query)
+                .await
+                .unwrap()
+                .into_page()
+                .unwrap();
+            let ids: Vec<_> = page
+                .item_collection
+                .items
+                .iter()
+                .map(|item| item["id"].as_str().unwrap())
+                .collect();
+            assert_eq!(ids, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn items_bbox() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("a-collection", "A description"))
+            .await
+            .unwrap();
+        let items = (0..100)
+            .map(|i| {
+                let mut item = Item::new(format!("item-{}", i)).collection("a-collection");
+                item.geometry = Some(Geometry::point(f64::from(i), 0.0));
+                item
+            })
+            .chain([Item::new("no-geometry").collection("a-collection")])
+            .collect();
+        backend.add_items(items).await.unwrap();
+
+        let mut query: Items<_> = Items::default();
+        query.items.bbox = Some(vec![9.5, -1.0, 12.5, 1.0]);
+        let page = backend
+            .items("a-collection", query)
+            .await
+            .unwrap()
+            .into_page()
+            .unwrap();
+        let ids: Vec<_> = page
+            .item_collection
+            .items
+            .iter()
+            .map(|item| item["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, ["item-10", "item-11", "item-12"]);
+
+        let page = backend
+            .items("a-collection", Items::default())
+            .await
+            .unwrap()
+            .into_page()
+            .unwrap();
+        assert_eq!(page.item_collection.number_matched, Some(101));
+    }
+
+    #[tokio::test]
+    async fn add_items_stream() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("a-collection", "A description"))
+            .await
+            .unwrap();
+        let items = stream::iter(vec![
+            Ok(Item::new("a").collection("a-collection")),
+            Ok(Item::new("b").collection("a-collection")),
+            Err(Error::InvalidQuery("not an item".to_string())),
+            Ok(Item::new("c").collection("a-collection")),
+            Ok(Item::new("d").collection("another-collection")),
+            Ok(Item::new("e").collection("a-collection")),
+        ]);
+        let report = backend.add_items_stream(items, 2).await;
+        assert_eq!(report.items, 3);
+        assert_eq!(report.chunks, 3);
+        assert_eq!(report.failures.len(), 2);
+        assert_eq!(report.failures[0].chunk, 1);
+        assert_eq!(report.failures[0].items, 0);
+        assert_eq!(report.failures[1].chunk, 1);
+        assert_eq!(report.failures[1].items, 2);
+        let page = backend
+            .items("a-collection", Items::default())
+            .await
+            .unwrap()
+            .into_page()
+            .unwrap();
+        assert_eq!(page.item_collection.items.len(), 3);
+    }
+
+    async fn ids(backend: &MemoryBackend, collection_id: &str) -> Vec<String> {
+        backend
+            .items(collection_id, Items::default())
+            .await
+            .unwrap()
+            .into_page()
+            .unwrap()
+            .item_collection
+            .items
+            .iter()
+            .map(|item| item["id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn reject_over_capacity() {
+        let mut backend = MemoryBackend::new()
+            .max_items(3)
+            .max_items_per_collection(2);
+        for id in ["a-collection", "another-collection"] {
+            let _ = backend
+                .add_collection(Collection::new(id, "A description"))
+                .await
+                .unwrap();
+        }
+        backend
+            .add_items(vec![
+                Item::new("a").collection("a-collection"),
+                Item::new("b").collection("a-collection"),
+            ])
+            .await
+            .unwrap();
+        let error = backend
+            .add_item(Item::new("c").collection("a-collection"))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            Error::from(error).status_code(),
+            http::StatusCode::INSUFFICIENT_STORAGE
+        );
+        let _ = backend
+            .add_items(vec![
+                Item::new("c").collection("another-collection"),
+                Item::new("d").collection("another-collection"),
+            ])
+            .await
+            .unwrap_err();
+        assert!(ids(&backend, "another-collection").await.is_empty());
+        backend
+            .add_item(Item::new("c").collection("another-collection"))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn evict_least_recently_used() {
+        let mut backend = MemoryBackend::new()
+            .max_items_per_collection(2)
+            .eviction(Eviction::LeastRecentlyUsed);
+        let _ = backend
+            .add_collection(Collection::new("a-collection", "A description"))
+            .await
+            .unwrap();
+        let items = ["a", "b"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, id)| {
+                let mut item = Item::new(id).collection("a-collection");
+                item.geometry = Some(Geometry::point(i as f64, 0.0));
+                item
+            })
+            .collect();
+        backend.add_items(items).await.unwrap();
+        let _ = backend.item("a-collection", "a").await.unwrap().unwrap();
+        let mut item = Item::new("c").collection("a-collection");
+        item.geometry = Some(Geometry::point(2.0, 0.0));
+        backend.add_item(item).await.unwrap();
+        assert_eq!(ids(&backend, "a-collection").await, ["a", "c"]);
+
+        // The tree still points at the right items after an eviction.
+        let mut query: Items<_> = Items::default();
+        query.items.bbox = Some(vec![1.5, -1.0, 2.5, 1.0]);
+        let page = backend
+            .items("a-collection", query)
+            .await
+            .unwrap()
+            .into_page()
+            .unwrap();
+        assert_eq!(page.item_collection.items[0]["id"], "c");
+    }
+
+    #[tokio::test]
+    async fn evict_across_collections() {
+        let mut backend = MemoryBackend::new()
+            .max_items(3)
+            .eviction(Eviction::LeastRecentlyUsed);
+        for id in ["a-collection", "another-collection"] {
+            let _ = backend
+                .add_collection(Collection::new(id, "A description"))
+                .await
+                .unwrap();
+        }
+        backend
+            .add_items(vec![
+                Item::new("a").collection("a-collection"),
+                Item::new("b").collection("another-collection"),
+                Item::new("c").collection("a-collection"),
+            ])
+            .await
+            .unwrap();
+        backend
+            .add_items(vec![
+                Item::new("d").collection("another-collection"),
+                Item::new("e").collection("another-collection"),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(ids(&backend, "a-collection").await, ["c"]);
+        assert_eq!(ids(&backend, "another-collection").await, ["d", "e"]);
+    }
 }
@@ -0,0 +1,508 @@
+use crate::{CollectionSummary, Items, ItemsResult, Page, ReadBackend, Search, WriteBackend};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{value::RawValue, Map, Value};
+use stac::{Collection, Item};
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// The upper bounds of the method latency histograms' buckets, in seconds.
+///
+/// These are Prometheus' default buckets. Slower calls are only counted in
+/// the histogram's total.
+pub const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// A snapshot of a backend's metrics.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metrics {
+    /// The backend's connection pool, if it has one.
+    pub pool: Option<PoolMetrics>,
+
+    /// The latencies of each backend method, if the backend is
+    /// [Instrumented].
+    pub methods: Vec<MethodMetrics>,
+}
+
+/// Gauges and counters for a backend's connection pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolMetrics {
+    /// The number of open connections, idle or in use.
+    pub size: u32,
+
+    /// The number of idle connections.
+    pub idle: u32,
+
+    /// The most connections the pool will open.
+    pub max_size: u32,
+
+    /// The number of requests waiting for a connection right now.
+    pub waiters: u64,
+
+    /// The number of requests that timed out waiting for a connection.
+    pub get_timeouts: u64,
+}
+
+/// A latency histogram of one backend method.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MethodMetrics {
+    /// The method's name, e.g. `search`.
+    pub method: &'static str,
+
+    /// The number of calls at or under each of the [LATENCY_BUCKETS].
+    ///
+    /// Like Prometheus' histograms, the counts are cumulative.
+    pub buckets: Vec<u64>,
+
+    /// The number of calls.
+    pub count: u64,
+
+    /// The total time spent in the method.
+    pub sum: Duration,
+}
+
+/// A backend that times every call to the wrapped backend.
+///
+/// The latencies are reported by [ReadBackend::metrics], along with the
+/// wrapped backend's own metrics.
+///
+/// # Examples
+///
+/// ```
+/// use stac_api_backend::{Instrumented, MemoryBackend, ReadBackend};
+///
+/// let backend = Instrumented::new(MemoryBackend::new());
+/// # tokio_test::block_on(async {
+/// let _ = backend.collections().await.unwrap();
+/// let metrics = backend.metrics();
+/// assert_eq!(metrics.methods[0].method, "collections");
+/// assert_eq!(metrics.methods[0].count, 1);
+/// # })
+/// ```
+#[derive(Clone, Debug)]
+pub struct Instrumented<B> {
+    backend: B,
+    latencies: Arc<[Latency; METHODS.len()]>,
+    slow_call_threshold: Option<Duration>,
+}
+
+/// The most characters of a slow call's parameters that are logged.
+const MAX_PARAMS_LEN: usize = 2000;
+
+/// A call that's logged if it takes too long.
+#[derive(Debug)]
+struct Slow {
+    threshold: Duration,
+    method: Method,
+    params: String,
+}
+
+#[derive(Debug, Default)]
+struct Latency {
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Method {
+    Collections,
+    CollectionSummaries,
+    Collection,
+    Items,
+    MatchedCount,
+    Search,
+    Item,
+    ItemUpdated,
+    CollectionUpdated,
+    RawItem,
+    Queryables,
+    AddCollection,
+    UpsertCollection,
+    DeleteCollection,
+    AddItems,
+    UpsertItems,
+    AddItem,
+}
+
+/// Every timed method, in the order they're reported.
+const METHODS: [Method; 17] = [
+    Method::Collections,
+    Method::CollectionSummaries,
+    Method::Collection,
+    Method::Items,
+    Method::MatchedCount,
+    Method::Search,
+    Method::Item,
+    Method::ItemUpdated,
+    Method::CollectionUpdated,
+    Method::RawItem,
+    Method::Queryables,
+    Method::AddCollection,
+    Method::UpsertCollection,
+    Method::DeleteCollection,
+    Method::AddItems,
+    Method::UpsertItems,
+    Method::AddItem,
+];
+
+impl<B> Instrumented<B> {
+    /// Wraps a backend.
+    pub fn new(backend: B) -> Instrumented<B> {
+        Instrumented {
+            backend,
+            latencies: Arc::default(),
+            slow_call_threshold: None,
+        }
+    }
+
+    /// Logs a warning, with the method and its parameters, for every call
+    /// that takes at least this long.
+    ///
+    /// Searches are logged as their JSON, so slow filters can be picked out.
+    /// The warnings are emitted in the caller's [tracing] span, e.g. the
+    /// server's request span.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use stac_api_backend::{Instrumented, MemoryBackend};
+    /// use std::time::Duration;
+    ///
+    /// let backend =
+    ///     Instrumented::new(MemoryBackend::new()).slow_call_threshold(Duration::from_millis(250));
+    /// ```
+    pub fn slow_call_threshold(mut self, threshold: Duration) -> Instrumented<B> {
+        self.slow_call_threshold = Some(threshold);
+        self
+    }
+
+    /// Describes a call, if slow calls are logged.
+    fn slow(&self, method: Method, params: impl FnOnce() -> String) -> Option<Slow> {
+        self.slow_call_threshold.map(|threshold| {
+            let mut params = params();
+            if let Some((i, _)) = params.char_indices().nth(MAX_PARAMS_LEN) {
+                params.truncate(i);
+                params.push_str("...");
+            }
+            Slow {
+                threshold,
+                method,
+                params,
+            }
+        })
+    }
+
+    /// Returns the wrapped backend.
+    pub fn into_inner(self) -> B {
+        self.backend
+    }
+
+    fn method_metrics(&self) -> Vec<MethodMetrics> {
+        METHODS
+            .iter()
+            .map(|&method| {
+                let latency = &self.latencies[method as usize];
+                let buckets = latency
+                    .buckets
+                    .iter()
+                    .scan(0, |total, count| {
+                        *total += count.load(Ordering::Relaxed);
+                        Some(*total)
+                    })
+                    .collect();
+                MethodMetrics {
+                    method: method.name(),
+                    buckets,
+                    count: latency.count.load(Ordering::Relaxed),
+                    sum: Duration::from_micros(latency.sum_micros.load(Ordering::Relaxed)),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Method {
+    fn name(self) -> &'static str {
+        match self {
+            Method::Collections => "collections",
+            Method::CollectionSummaries => "collection_summaries",
+            Method::Collection => "collection",
+            Method::Items => "items",
+            Method::MatchedCount => "matched_count",
+            Method::Search => "search",
+            Method::Item => "item",
+            Method::ItemUpdated => "item_updated",
+            Method::CollectionUpdated => "collection_updated",
+            Method::RawItem => "raw_item",
+            Method::Queryables => "queryables",
+            Method::AddCollection => "add_collection",
+            Method::UpsertCollection => "upsert_collection",
+            Method::DeleteCollection => "delete_collection",
+            Method::AddItems => "add_items",
+            Method::UpsertItems => "upsert_items",
+            Method::AddItem => "add_item",
+        }
+    }
+}
+
+impl Latency {
+    fn observe(&self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        if let Some(i) = LATENCY_BUCKETS.iter().position(|&le| seconds <= le) {
+            let _ = self.buckets[i].fetch_add(1, Ordering::Relaxed);
+        }
+        let _ = self.count.fetch_add(1, Ordering::Relaxed);
+        let _ = self
+            .sum_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+/// Awaits a call, recording how long it took, whether or not it failed, and
+/// logging it if it was slow.
+async fn time<T>(latency: &Latency, slow: Option<Slow>, call: impl Future<Output = T>) -> T {
+    let start = Instant::now();
+    let output = call.await;
+    let elapsed = start.elapsed();
+    latency.observe(elapsed);
+    if let Some(slow) = slow.filter(|slow| elapsed >= slow.threshold) {
+        tracing::warn!(
+            method = slow.method.name(),
+            elapsed_ms = elapsed.as_millis() as u64,
+            params = slow.params,
+            "slow backend call"
+        );
+    }
+    output
+}
+
+/// Describes a query as JSON.
+fn json(value: &impl Serialize) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+#[async_trait]
+impl<B: ReadBackend> ReadBackend for Instrumented<B> {
+    type Error = B::Error;
+    type Paging = B::Paging;
+
+    fn supported_parameters(&self) -> &'static [&'static str] {
+        self.backend.supported_parameters()
+    }
+
+    fn metrics(&self) -> Metrics {
+        let mut metrics = self.backend.metrics();
+        metrics.methods = self.method_metrics();
+        metrics
+    }
+
+    async fn collections(&self) -> Result<Vec<Collection>, B::Error> {
+        let latency = &self.latencies[Method::Collections as usize];
+        let slow = self.slow(Method::Collections, String::new);
+        time(latency, slow, self.backend.collections()).await
+    }
+
+    async fn collection_summaries(&self) -> Result<Vec<CollectionSummary>, B::Error> {
+        let latency = &self.latencies[Method::CollectionSummaries as usize];
+        let slow = self.slow(Method::CollectionSummaries, String::new);
+        time(latency, slow, self.backend.collection_summaries()).await
+    }
+
+    async fn collection(&self, id: &str) -> Result<Option<Collection>, B::Error> {
+        let latency = &self.latencies[Method::Collection as usize];
+        let slow = self.slow(Method::Collection, || format!("id={}", id));
+        time(latency, slow, self.backend.collection(id)).await
+    }
+
+    async fn items(
+        &self,
+        id: &str,
+        items: Items<B::Paging>,
+    ) -> Result<ItemsResult<B::Paging>, B::Error> {
+        let latency = &self.latencies[Method::Items as usize];
+        let slow = self.slow(Method::Items, || {
+            format!("id={} items={}", id, json(&items.items))
+        });
+        time(latency, slow, self.backend.items(id, items)).await
+    }
+
+    async fn matched_count(
+        &self,
+        id: &str,
+        items: &Items<B::Paging>,
+    ) -> Result<Option<u64>, B::Error> {
+        let latency = &self.latencies[Method::MatchedCount as usize];
+        let slow = self.slow(Method::MatchedCount, || {
+            format!("id={} items={}", id, json(&items.items))
+        });
+        time(latency, slow, self.backend.matched_count(id, items)).await
+    }
+
+    async fn search(&self, search: Search<B::Paging>) -> Result<Page<B::Paging>, B::Error> {
+        let latency = &self.latencies[Method::Search as usize];
+        let slow = self.slow(Method::Search, || {
+            format!("search={}", json(&search.search))
+        });
+        time(latency, slow, self.backend.search(search)).await
+    }
+
+    async fn item(&self, collection_id: &str, id: &str) -> Result<Option<Item>, B::Error> {
+        let latency = &self.latencies[Method::Item as usize];
+        let slow = self.slow(Method::Item, || {
+            format!("collection_id={} id={}", collection_id, id)
+        });
+        time(latency, slow, self.backend.item(collection_id, id)).await
+    }
+
+    async fn item_updated(
+        &self,
+        collection_id: &str,
+        id: &str,
+    ) -> Result<Option<DateTime<Utc>>, B::Error> {
+        let latency = &self.latencies[Method::ItemUpdated as usize];
+        let slow = self.slow(Method::ItemUpdated, || {
+            format!("collection_id={} id={}", collection_id, id)
+        });
+        time(latency, slow, self.backend.item_updated(collection_id, id)).await
+    }
+
+    async fn collection_updated(&self, id: &str) -> Result<Option<DateTime<Utc>>, B::Error> {
+        let latency = &self.latencies[Method::CollectionUpdated as usize];
+        let slow = self.slow(Method::CollectionUpdated, || format!("id={}", id));
+        time(latency, slow, self.backend.collection_updated(id)).await
+    }
+
+    async fn raw_item(
+        &self,
+        collection_id: &str,
+        id: &str,
+    ) -> Result<Option<Box<RawValue>>, B::Error> {
+        let latency = &self.latencies[Method::RawItem as usize];
+        let slow = self.slow(Method::RawItem, || {
+            format!("collection_id={} id={}", collection_id, id)
+        });
+        time(latency, slow, self.backend.raw_item(collection_id, id)).await
+    }
+
+    async fn queryables(
+        &self,
+        collection_id: Option<&str>,
+    ) -> Result<Option<Map<String, Value>>, B::Error> {
+        let latency = &self.latencies[Method::Queryables as usize];
+        let slow = self.slow(Method::Queryables, || {
+            format!("collection_id={}", collection_id.unwrap_or_default())
+        });
+        time(latency, slow, self.backend.queryables(collection_id)).await
+    }
+}
+
+#[async_trait]
+impl<B: WriteBackend> WriteBackend for Instrumented<B> {
+    async fn add_collection(
+        &mut self,
+        collection: Collection,
+    ) -> Result<Option<Collection>, B::Error> {
+        let latency = &self.latencies[Method::AddCollection as usize];
+        let slow = self.slow(Method::AddCollection, || format!("id={}", collection.id));
+        time(latency, slow, self.backend.add_collection(collection)).await
+    }
+
+    async fn upsert_collection(
+        &mut self,
+        collection: Collection,
+    ) -> Result<Option<Collection>, B::Error> {
+        let latency = &self.latencies[Method::UpsertCollection as usize];
+        let slow = self.slow(Method::UpsertCollection, || format!("id={}", collection.id));
+        time(latency, slow, self.backend.upsert_collection(collection)).await
+    }
+
+    async fn delete_collection(&mut self, id: &str) -> Result<(), B::Error> {
+        let latency = &self.latencies[Method::DeleteCollection as usize];
+        let slow = self.slow(Method::DeleteCollection, || format!("id={}", id));
+        time(latency, slow, self.backend.delete_collection(id)).await
+    }
+
+    async fn add_items(&mut self, items: Vec<Item>) -> Result<(), B::Error> {
+        let latency = &self.latencies[Method::AddItems as usize];
+        let slow = self.slow(Method::AddItems, || format!("items={}", items.len()));
+        time(latency, slow, self.backend.add_items(items)).await
+    }
+
+    async fn upsert_items(&mut self, items: Vec<Item>) -> Result<(), B::Error> {
+        let latency = &self.latencies[Method::UpsertItems as usize];
+        let slow = self.slow(Method::UpsertItems, || format!("items={}", items.len()));
+        time(latency, slow, self.backend.upsert_items(items)).await
+    }
+
+    async fn add_item(&mut self, item: Item) -> Result<(), B::Error> {
+        let latency = &self.latencies[Method::AddItem as usize];
+        let slow = self.slow(Method::AddItem, || format!("id={}", item.id));
+        time(latency, slow, self.backend.add_item(item)).await
+    }
+}
+
+#[cfg(all(test, feature = "memory"))]
+mod tests {
+    use super::{Instrumented, Method, LATENCY_BUCKETS, MAX_PARAMS_LEN};
+    use crate::{MemoryBackend, ReadBackend, WriteBackend};
+    use stac::Collection;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn records_latencies() {
+        let mut backend = Instrumented::new(MemoryBackend::new());
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let _ = backend.collection("an-id").await.unwrap();
+        let _ = backend.collection("not-an-id").await.unwrap();
+        let metrics = backend.metrics();
+        assert!(metrics.pool.is_none());
+        let collection = metrics
+            .methods
+            .iter()
+            .find(|method| method.method == "collection")
+            .unwrap();
+        assert_eq!(collection.count, 2);
+        assert_eq!(collection.buckets.len(), LATENCY_BUCKETS.len());
+        assert_eq!(*collection.buckets.last().unwrap(), 2);
+        let search = metrics
+            .methods
+            .iter()
+            .find(|method| method.method == "search")
+            .unwrap();
+        assert_eq!(search.count, 0);
+    }
+
+    #[tokio::test]
+    async fn clones_share_latencies() {
+        let backend = Instrumented::new(MemoryBackend::new());
+        let _ = backend.clone().collections().await.unwrap();
+        assert_eq!(backend.metrics().methods[0].count, 1);
+    }
+
+    #[test]
+    fn slow_params() {
+        let backend = Instrumented::new(MemoryBackend::new());
+        assert!(backend
+            .slow(Method::Search, || unreachable!("params aren't described"))
+            .is_none());
+        let backend = backend.slow_call_threshold(Duration::from_millis(10));
+        let slow = backend
+            .slow(Method::Search, || "x".repeat(MAX_PARAMS_LEN + 1))
+            .unwrap();
+        assert_eq!(slow.threshold, Duration::from_millis(10));
+        assert_eq!(slow.params.len(), MAX_PARAMS_LEN + 3);
+        assert!(slow.params.ends_with("..."));
+    }
+}
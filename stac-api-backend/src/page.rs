@@ -1,8 +1,9 @@
-use crate::Result;
+use crate::{Error, RawItemCollection, Result, Token};
 use http::Method;
 use serde::Serialize;
+use serde_json::{value::RawValue, Map, Value};
 use stac::Link;
-use stac_api::ItemCollection;
+use stac_api::{Context, ItemCollection};
 use url::Url;
 
 /// A page of items.
@@ -16,30 +17,206 @@ pub struct Page<P: Serialize> {
 
     /// The paging data for the prev link.
     pub prev: Option<P>,
+
+    /// The paging data for the first link.
+    pub first: Option<P>,
+
+    /// The paging data for the last link.
+    pub last: Option<P>,
+
+    /// The items as raw JSON, for backends that can skip parsing them.
+    ///
+    /// If this is set, the item collection's items should be empty.
+    pub raw_items: Option<Vec<Box<RawValue>>>,
+}
+
+/// The result of asking a backend for a collection's items.
+///
+/// A collection that exists but has no matching items is an empty page, not
+/// [ItemsResult::CollectionNotFound], so servers can tell a `404 Not Found`
+/// from an empty `200 OK`.
+#[derive(Debug)]
+pub enum ItemsResult<P: Serialize> {
+    /// There's no collection with the requested id.
+    CollectionNotFound,
+
+    /// A page of the collection's items, which might be empty.
+    ///
+    /// Boxed, so a missing collection doesn't take up a whole page.
+    Page(Box<Page<P>>),
+}
+
+impl<P: Serialize> ItemsResult<P> {
+    /// Returns the page, or `None` if the collection wasn't found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use stac_api_backend::ItemsResult;
+    ///
+    /// let result: ItemsResult<()> = ItemsResult::CollectionNotFound;
+    /// assert!(result.into_page().is_none());
+    /// ```
+    pub fn into_page(self) -> Option<Page<P>> {
+        match self {
+            ItemsResult::CollectionNotFound => None,
+            ItemsResult::Page(page) => Some(*page),
+        }
+    }
+}
+
+impl<P: Serialize> From<Page<P>> for ItemsResult<P> {
+    fn from(page: Page<P>) -> ItemsResult<P> {
+        ItemsResult::Page(Box::new(page))
+    }
 }
 
 impl<P: Serialize> Page<P> {
+    /// Parses any raw items into the item collection's items.
+    pub fn parse_raw_items(&mut self) -> Result<()> {
+        if let Some(raw_items) = self.raw_items.take() {
+            for raw_item in raw_items {
+                self.item_collection
+                    .items
+                    .push(serde_json::from_str(raw_item.get())?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets every item's geometry to null, for queries with `skip-geometry=true`.
+    pub(crate) fn null_geometries(&mut self) -> Result<()> {
+        for item in &mut self.item_collection.items {
+            let _ = item.insert("geometry".to_string(), Value::Null);
+        }
+        if let Some(raw_items) = &mut self.raw_items {
+            for raw_item in raw_items {
+                *raw_item = crate::raw::null_geometry(raw_item)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets both `numberMatched`/`numberReturned` and the context object,
+    /// from whichever of them the backend filled in.
+    ///
+    /// The context object comes from the deprecated context extension, so it
+    /// can be left off with `context = false`.
+    pub(crate) fn set_counts(&mut self, limit: u64, context: bool) {
+        let returned = match &self.raw_items {
+            Some(raw_items) => raw_items.len(),
+            None => self.item_collection.items.len(),
+        };
+        let returned = u64::try_from(returned).unwrap_or(u64::MAX);
+        let item_collection = &mut self.item_collection;
+        let existing = item_collection.context.take();
+        let matched = item_collection
+            .number_matched
+            .or_else(|| existing.as_ref().and_then(|context| context.matched));
+        item_collection.number_matched = matched;
+        item_collection.number_returned = Some(returned);
+        if context {
+            item_collection.context = Some(Context {
+                returned,
+                limit: Some(limit),
+                matched,
+                additional_fields: existing
+                    .map(|context| context.additional_fields)
+                    .unwrap_or_default(),
+            });
+        }
+    }
+
+    /// Encodes this page's paging data as [Token]s.
+    pub(crate) fn into_token_page(self) -> Result<Page<Token>> {
+        Ok(Page {
+            item_collection: self.item_collection,
+            next: self.next.as_ref().map(Token::encode).transpose()?,
+            prev: self.prev.as_ref().map(Token::encode).transpose()?,
+            first: self.first.as_ref().map(Token::encode).transpose()?,
+            last: self.last.as_ref().map(Token::encode).transpose()?,
+            raw_items: self.raw_items,
+        })
+    }
+
     /// Converts this page into an item collection.
     pub fn into_item_collection(
-        self,
+        mut self,
         url: &Url,
         method: &Method,
         current: P,
     ) -> Result<ItemCollection> {
+        self.parse_raw_items()?;
         let mut item_collection = self.item_collection;
-        add_link(&mut item_collection, &url, "self", current, &method)?;
-        if let Some(next) = self.next {
-            add_link(&mut item_collection, &url, "next", next, &method)?;
-        }
-        if let Some(prev) = self.prev {
-            add_link(&mut item_collection, &url, "prev", prev, &method)?;
-        }
+        add_links(
+            &mut item_collection.links,
+            url,
+            method,
+            current,
+            [
+                ("next", self.next),
+                ("prev", self.prev),
+                ("first", self.first),
+                ("last", self.last),
+            ],
+        )?;
         Ok(item_collection)
     }
+
+    /// Converts this page into an item collection without parsing raw items.
+    ///
+    /// Pages without raw items have their items serialized instead.
+    pub fn into_raw_item_collection(
+        self,
+        url: &Url,
+        method: &Method,
+        current: P,
+    ) -> Result<RawItemCollection> {
+        let mut item_collection = if let Some(raw_items) = self.raw_items {
+            let mut raw_item_collection = RawItemCollection::new(raw_items);
+            raw_item_collection.links = self.item_collection.links;
+            raw_item_collection.number_matched = self.item_collection.number_matched;
+            raw_item_collection.number_returned = self.item_collection.number_returned;
+            raw_item_collection.context = self.item_collection.context;
+            raw_item_collection.additional_fields = self.item_collection.additional_fields;
+            raw_item_collection
+        } else {
+            RawItemCollection::try_from(self.item_collection)?
+        };
+        add_links(
+            &mut item_collection.links,
+            url,
+            method,
+            current,
+            [
+                ("next", self.next),
+                ("prev", self.prev),
+                ("first", self.first),
+                ("last", self.last),
+            ],
+        )?;
+        Ok(item_collection)
+    }
+}
+
+fn add_links<P: Serialize>(
+    links: &mut Vec<Link>,
+    url: &Url,
+    method: &Method,
+    current: P,
+    paging: [(&'static str, Option<P>); 4],
+) -> Result<()> {
+    add_link(links, url, "self", current, method)?;
+    for (rel, paging) in paging {
+        if let Some(paging) = paging {
+            add_link(links, url, rel, paging, method)?;
+        }
+    }
+    Ok(())
 }
 
 fn add_link(
-    item_collection: &mut ItemCollection,
+    links: &mut Vec<Link>,
     url: &Url,
     rel: &'static str,
     query: impl Serialize,
@@ -50,15 +227,38 @@ fn add_link(
             let mut url = url.clone();
             let mut query = serde_urlencoded::to_string(query)?;
             if let Some(existing_query) = url.query() {
-                query = format!("{}&{}", existing_query, query);
+                if query.is_empty() {
+                    query = existing_query.to_string();
+                } else {
+                    query = format!("{}&{}", existing_query, query);
+                }
             }
             if !query.is_empty() {
                 url.set_query(Some(&query));
             }
-            item_collection.links.push(Link::new(url, rel).geojson());
+            links.retain(|link| link.rel != rel);
+            links.push(Link::new(url, rel).geojson());
         }
-        Method::POST => todo!(),
-        _ => unimplemented!(), // TODO make this an error
+        Method::POST => {
+            // The item search spec's POST links carry the paging in a body
+            // that clients merge into the body of the request they just made.
+            let body = match serde_json::to_value(query)? {
+                Value::Object(body) => body,
+                Value::Null => Map::new(),
+                _ => {
+                    return Err(Error::SerdeJson(serde::ser::Error::custom(
+                        "paging must serialize to an object for POST links",
+                    )))
+                }
+            };
+            links.retain(|link| link.rel != rel);
+            let mut link = Link::new(url, rel).geojson();
+            link.method = Some(Method::POST.to_string());
+            link.body = Some(body);
+            link.merge = Some(true);
+            links.push(link);
+        }
+        _ => return Err(Error::MethodNotAllowed(method.clone())),
     }
     Ok(())
 }
@@ -66,17 +266,53 @@ fn add_link(
 #[cfg(test)]
 mod tests {
     use super::Page;
-    use crate::assert_link;
+    use crate::{assert_link, Error, Token};
     use http::Method;
-    use stac_api::ItemCollection;
+    use serde_json::json;
+    use stac::Links;
+    use stac_api::{Context, ItemCollection};
     use url::Url;
 
+    #[test]
+    fn set_counts() {
+        let mut page: Page<()> = Page {
+            item_collection: ItemCollection::new(vec![]).unwrap(),
+            next: None,
+            prev: None,
+            first: None,
+            last: None,
+            raw_items: Some(vec![serde_json::value::to_raw_value(
+                &json!({"type": "Feature", "id": "an-id"}),
+            )
+            .unwrap()]),
+        };
+        page.item_collection.context = Some(Context {
+            returned: 1,
+            limit: None,
+            matched: Some(42),
+            additional_fields: Default::default(),
+        });
+        page.set_counts(10, true);
+        assert_eq!(page.item_collection.number_matched, Some(42));
+        assert_eq!(page.item_collection.number_returned, Some(1));
+        let context = page.item_collection.context.as_ref().unwrap();
+        assert_eq!(context.limit, Some(10));
+        assert_eq!(context.matched, Some(42));
+
+        page.set_counts(10, false);
+        assert_eq!(page.item_collection.number_matched, Some(42));
+        assert!(page.item_collection.context.is_none());
+    }
+
     #[test]
     fn into_item_collection_no_paging() {
         let page: Page<()> = Page {
             item_collection: ItemCollection::new(vec![]).unwrap(),
             next: None,
             prev: None,
+            first: None,
+            last: None,
+            raw_items: None,
         };
         let item_collection = page
             .into_item_collection(
@@ -100,6 +336,9 @@ mod tests {
             item_collection: ItemCollection::new(vec![]).unwrap(),
             next: Some([["skip", "1"], ["take", "1"]]),
             prev: None,
+            first: None,
+            last: None,
+            raw_items: None,
         };
         let item_collection = page
             .into_item_collection(
@@ -129,6 +368,9 @@ mod tests {
             item_collection: ItemCollection::new(vec![]).unwrap(),
             prev: Some([["skip", "1"], ["take", "1"]]),
             next: None,
+            first: None,
+            last: None,
+            raw_items: None,
         };
         let item_collection = page
             .into_item_collection(
@@ -158,6 +400,9 @@ mod tests {
             item_collection: ItemCollection::new(vec![]).unwrap(),
             next: Some([["skip", "1"], ["take", "1"]]),
             prev: None,
+            first: None,
+            last: None,
+            raw_items: None,
         };
         let item_collection = page
             .into_item_collection(
@@ -180,4 +425,117 @@ mod tests {
             "application/geo+json"
         );
     }
+
+    #[test]
+    fn into_item_collection_next_post() {
+        let page = Page {
+            item_collection: ItemCollection::new(vec![]).unwrap(),
+            next: Some(Token {
+                token: Some("next-page".to_string()),
+            }),
+            prev: None,
+            first: None,
+            last: None,
+            raw_items: None,
+        };
+        let item_collection = page
+            .into_item_collection(
+                &Url::parse("http://stac-api-backend.test/search").unwrap(),
+                &Method::POST,
+                Token::default(),
+            )
+            .unwrap();
+        assert_eq!(item_collection.links.len(), 2);
+        assert_link!(
+            item_collection,
+            "next",
+            "http://stac-api-backend.test/search",
+            "application/geo+json"
+        );
+        let next = item_collection.link("next").unwrap();
+        assert_eq!(next.method.as_deref(), Some("POST"));
+        assert_eq!(next.body.as_ref().unwrap()["token"], "next-page");
+        assert_eq!(next.merge, Some(true));
+        let self_link = item_collection.link("self").unwrap();
+        assert!(self_link.body.as_ref().unwrap().is_empty());
+    }
+
+    #[test]
+    fn into_item_collection_other_methods() {
+        let page: Page<()> = Page {
+            item_collection: ItemCollection::new(vec![]).unwrap(),
+            next: None,
+            prev: None,
+            first: None,
+            last: None,
+            raw_items: None,
+        };
+        let err = page
+            .into_item_collection(
+                &Url::parse("http://stac-api-backend.test/search").unwrap(),
+                &Method::DELETE,
+                (),
+            )
+            .unwrap_err();
+        assert!(matches!(err, Error::MethodNotAllowed(_)));
+    }
+
+    #[test]
+    fn into_item_collection_first_last() {
+        let page = Page {
+            item_collection: ItemCollection::new(vec![]).unwrap(),
+            next: None,
+            prev: None,
+            first: Some([["skip", "0"], ["take", "1"]]),
+            last: Some([["skip", "4"], ["take", "1"]]),
+            raw_items: None,
+        };
+        let item_collection = page
+            .into_item_collection(
+                &Url::parse("http://stac-api-backend.test/items").unwrap(),
+                &Method::GET,
+                [["skip", "2"], ["take", "1"]],
+            )
+            .unwrap();
+        assert_eq!(item_collection.links.len(), 3);
+        assert_link!(
+            item_collection,
+            "first",
+            "http://stac-api-backend.test/items?skip=0&take=1",
+            "application/geo+json"
+        );
+        assert_link!(
+            item_collection,
+            "last",
+            "http://stac-api-backend.test/items?skip=4&take=1",
+            "application/geo+json"
+        );
+    }
+
+    #[test]
+    fn raw_items() {
+        let page = || Page {
+            item_collection: ItemCollection::new(vec![]).unwrap(),
+            next: None,
+            prev: None,
+            first: None,
+            last: None,
+            raw_items: Some(vec![serde_json::value::to_raw_value(
+                &json!({"type": "Feature", "id": "an-id"}),
+            )
+            .unwrap()]),
+        };
+        let url = Url::parse("http://stac-api-backend.test/items").unwrap();
+        let item_collection = page()
+            .into_item_collection(&url, &Method::GET, [["skip", "0"]])
+            .unwrap();
+        assert_eq!(item_collection.items[0]["id"], "an-id");
+        let raw_item_collection = page()
+            .into_raw_item_collection(&url, &Method::GET, [["skip", "0"]])
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(raw_item_collection).unwrap(),
+            serde_json::to_value(item_collection).unwrap()
+        );
+    }
 }
@@ -1,20 +1,146 @@
 //! STAC API backend for pgstac.
 
-use crate::{Backend, Items, Page};
+use crate::{
+    backend::parse_updated,
+    items::{count_mode, normalize_datetime, parse_datetime},
+    CollectionSummary, CountMode, Items, ItemsResult, Metrics, Page, PoolMetrics, ReadBackend,
+    Search, WriteBackend,
+};
 use async_trait::async_trait;
-use bb8::Pool;
+use bb8::{ManageConnection, Pool, PooledConnection};
 use bb8_postgres::PostgresConnectionManager;
+use chrono::{DateTime, Utc};
 use pgstac::Client;
 use serde::{Deserialize, Serialize};
+use serde_json::{value::RawValue, Map, Value};
 use stac::{Collection, Item};
-use stac_api::ItemCollection;
+use stac_api::{Context, ItemCollection};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 use thiserror::Error;
-use tokio_postgres::tls::NoTls;
+use tokio_postgres::{error::SqlState, tls::NoTls, types::ToSql, Row, Statement};
 
 /// The pgstac backend.
 #[derive(Clone, Debug)]
 pub struct PgstacBackend {
-    pool: Pool<PostgresConnectionManager<NoTls>>, // TODO allow tls
+    pool: Pool<Manager>,
+    max_size: u32,
+    waiters: Arc<AtomicU64>,
+    count: CountMode,
+    batch_size: usize,
+}
+
+/// The default number of items sent to pgstac in each statement.
+const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// Manages pooled connections that keep their prepared statements.
+#[derive(Clone, Debug)]
+struct Manager {
+    manager: PostgresConnectionManager<NoTls>, // TODO allow tls
+
+    /// `SET` statements run on every new connection.
+    settings: Option<String>,
+}
+
+/// A pooled connection.
+///
+/// The read queries are run on every request, so each is prepared once per
+/// connection and its statement reused, instead of being re-parsed and
+/// re-planned every time.
+#[derive(Debug)]
+struct Connection {
+    client: tokio_postgres::Client,
+    statements: HashMap<&'static str, Statement>,
+}
+
+/// Counts a request as waiting for a connection until it's dropped, even if
+/// the request is cancelled.
+#[derive(Debug)]
+struct Waiting<'a>(&'a AtomicU64);
+
+const COLLECTIONS: &str = "SELECT pgstac.all_collections()";
+const COLLECTION_SUMMARIES: &str =
+    "SELECT id, content->>'title' FROM pgstac.collections ORDER BY id";
+const COLLECTION: &str = "SELECT pgstac.get_collection($1)";
+const COLLECTION_EXISTS: &str = "SELECT EXISTS (SELECT 1 FROM pgstac.collections WHERE id = $1)";
+const ITEM: &str = "SELECT pgstac.get_item($1, $2)";
+const RAW_ITEM: &str = "SELECT pgstac.get_item($1, $2)::text";
+const ITEM_UPDATED: &str = "SELECT (SELECT content->'properties'->>'updated' \
+    FROM pgstac.items WHERE collection = $1 AND id = $2)";
+const COLLECTION_UPDATED: &str =
+    "SELECT (SELECT content->>'updated' FROM pgstac.collections WHERE id = $1)";
+const SEARCH: &str = "SELECT pgstac.search($1)::text";
+const ESTIMATED_COUNT: &str = "SELECT pgstac.estimated_count(pgstac.stac_search_to_where($1))";
+const CREATE_ITEMS: &str = "SELECT pgstac.create_items($1)";
+const UPSERT_ITEMS: &str = "SELECT pgstac.upsert_items($1)";
+const QUERYABLES: &str = "SELECT pgstac.get_queryables($1::text[])::text";
+
+/// Connection pool settings for the pgstac backend.
+///
+/// # Examples
+///
+/// ```
+/// use stac_api_backend::PoolConfig;
+///
+/// let pool = PoolConfig {
+///     min_idle: Some(4),
+///     statement_timeout: Some(30_000),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub struct PoolConfig {
+    /// The most connections the pool will open. Defaults to ten.
+    #[serde(default)]
+    pub max_size: Option<u32>,
+
+    /// The number of idle connections the pool keeps open.
+    ///
+    /// These are all opened, and checked, before
+    /// [PgstacBackend::connect_with] returns, so the first requests don't
+    /// have to wait for new connections.
+    #[serde(default)]
+    pub min_idle: Option<u32>,
+
+    /// How long a statement can run before Postgres cancels it, in
+    /// milliseconds.
+    ///
+    /// Set on every pooled connection, so a pathological search fails
+    /// instead of holding on to its connection. If not set, the database's
+    /// `statement_timeout` is used.
+    #[serde(default)]
+    pub statement_timeout: Option<u64>,
+
+    /// The memory each sort or hash in a query can use before spilling to
+    /// disk, in kilobytes.
+    ///
+    /// Set on every pooled connection. If not set, the database's `work_mem`
+    /// is used.
+    #[serde(default)]
+    pub work_mem: Option<u64>,
+}
+
+impl PoolConfig {
+    /// Returns the `SET` statements for these settings, if there are any.
+    fn settings(&self) -> Option<String> {
+        let settings: Vec<String> = [
+            ("statement_timeout", self.statement_timeout),
+            ("work_mem", self.work_mem),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| format!("SET {} = {}", name, value)))
+        .collect();
+        if settings.is_empty() {
+            None
+        } else {
+            Some(settings.join("; "))
+        }
+    }
 }
 
 /// Crate-specific error enum.
@@ -24,14 +150,42 @@ pub enum Error {
     #[error(transparent)]
     Bb8TokioPostgresRun(#[from] bb8::RunError<tokio_postgres::Error>),
 
+    /// A batch of items could not be written, so none were.
+    #[error(
+        "could not write items {start}..{end}, so no items were written: {}",
+        source.as_db_error().map(ToString::to_string).unwrap_or_else(|| source.to_string())
+    )]
+    Batch {
+        /// The index of the first item in the batch.
+        start: usize,
+
+        /// The index after the last item in the batch.
+        end: usize,
+
+        /// The database error.
+        source: Box<tokio_postgres::Error>,
+    },
+
     /// [pgstac::Error]
     #[error(transparent)]
     Pgstac(#[from] pgstac::Error),
 
+    /// The search's datetime is invalid.
+    #[error("invalid datetime: {0}")]
+    InvalidDatetime(String),
+
+    /// The pool settings are invalid.
+    #[error("invalid pool config: {0}")]
+    PoolConfig(String),
+
     /// [stac_api::Error]
     #[error(transparent)]
     StacApi(#[from] stac_api::Error),
 
+    /// [serde_json::Error]
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+
     /// [tokio_postgres::Error]
     #[error(transparent)]
     TokioPostgres(#[from] tokio_postgres::Error),
@@ -39,6 +193,9 @@ pub enum Error {
 
 type Result<T> = std::result::Result<T, Error>;
 
+/// bb8's default pool size.
+const DEFAULT_MAX_SIZE: u32 = 10;
+
 /// Paging structure.
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
 pub struct Paging {
@@ -47,106 +204,520 @@ pub struct Paging {
     pub token: Option<String>,
 }
 
+/// A page of pgstac search results, with the features left as raw JSON.
+#[derive(Debug, Deserialize)]
+struct RawPage<'a> {
+    #[serde(borrow)]
+    features: Vec<&'a RawValue>,
+    next: Option<String>,
+    prev: Option<String>,
+    context: Context,
+}
+
 impl PgstacBackend {
     /// Creates a new pgstac backend.
+    ///
+    /// One connection is opened, and checked, before this returns.
     pub async fn connect(config: &str) -> Result<PgstacBackend> {
-        let manager = PostgresConnectionManager::new_from_stringlike(config, NoTls)?;
-        let pool = Pool::builder().build(manager).await?;
-        Ok(PgstacBackend { pool })
+        PgstacBackend::connect_with(config, PoolConfig::default()).await
+    }
+
+    /// Creates a new pgstac backend with connection pool settings.
+    ///
+    /// The pool is warmed up before this returns: `min_idle` connections (or
+    /// one, if that's not set) are opened and each runs a trivial query, so
+    /// a bad config or unreachable database is an error at startup instead
+    /// of on the first request.
+    pub async fn connect_with(config: &str, pool_config: PoolConfig) -> Result<PgstacBackend> {
+        let max_size = pool_config.max_size.unwrap_or(DEFAULT_MAX_SIZE);
+        let min_idle = pool_config.min_idle.unwrap_or_default();
+        // bb8 panics on these instead of returning an error.
+        if max_size == 0 {
+            return Err(Error::PoolConfig(
+                "max_size must be greater than zero".to_string(),
+            ));
+        }
+        if min_idle > max_size {
+            return Err(Error::PoolConfig(format!(
+                "min_idle ({}) must be no larger than max_size ({})",
+                min_idle, max_size
+            )));
+        }
+        let builder = Pool::builder()
+            .max_size(max_size)
+            .min_idle(pool_config.min_idle);
+        let manager = Manager {
+            manager: PostgresConnectionManager::new_from_stringlike(config, NoTls)?,
+            settings: pool_config.settings(),
+        };
+        // The pool retries failed connections until it times out, so connect
+        // once directly to fail fast with the database's own error.
+        let connection = manager.connect().await?;
+        let pool = builder.build_unchecked(manager);
+        // The pool is empty, so there's room.
+        let _ = pool.add(connection);
+        let backend = PgstacBackend {
+            pool,
+            max_size,
+            waiters: Arc::default(),
+            count: CountMode::default(),
+            batch_size: DEFAULT_BATCH_SIZE,
+        };
+        backend.warm_up(min_idle.max(1)).await?;
+        Ok(backend)
+    }
+
+    /// Checks out this many connections at once and runs a query on each.
+    async fn warm_up(&self, connections: u32) -> Result<()> {
+        let connections =
+            futures_util::future::try_join_all((0..connections).map(|_| self.pool.get())).await?;
+        for connection in &connections {
+            let _ = connection.client.simple_query("SELECT 1").await?;
+        }
+        Ok(())
+    }
+
+    /// Checks out a pooled connection, counting the requests that are
+    /// waiting for one.
+    async fn connection(&self) -> Result<PooledConnection<'_, Manager>> {
+        let _waiting = Waiting::new(&self.waiters);
+        Ok(self.pool.get().await?)
+    }
+
+    /// Sets how matching items are counted.
+    pub fn count(mut self, count: CountMode) -> PgstacBackend {
+        self.count = count;
+        self
+    }
+
+    /// Sets the largest number of items sent to pgstac in one statement.
+    ///
+    /// Adding or upserting more items than this sends them in batches,
+    /// all in one transaction. Defaults to 1000, and sizes of zero are
+    /// treated as one.
+    pub fn batch_size(mut self, batch_size: usize) -> PgstacBackend {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Returns how to count a query's matches: the query's `count`
+    /// parameter, or the backend's mode.
+    fn count_mode(&self, search: &stac_api::Search) -> CountMode {
+        count_mode(&search.additional_fields).unwrap_or(self.count)
+    }
+
+    /// Builds pgstac's search argument, with the paging token and whether to count.
+    ///
+    /// The `count` parameter is taken out, so it doesn't change pgstac's hash
+    /// of the search.
+    fn search_value(&self, search: Search<Paging>) -> Result<Value> {
+        let Search { mut search, paging } = search;
+        let count = self.count_mode(&search);
+        let _ = search.additional_fields.remove("count");
+        check_datetime(&mut search)?;
+        if let Some(token) = paging.token {
+            let _ = search
+                .additional_fields
+                .insert("token".to_string(), token.into());
+        }
+        let context = match count {
+            CountMode::Exact => "on",
+            CountMode::Estimated | CountMode::Off => "off",
+        };
+        let _ = search.additional_fields.insert(
+            "conf".to_string(),
+            serde_json::json!({ "context": context }),
+        );
+        serde_json::to_value(search).map_err(Error::from)
+    }
+
+    /// Writes items in batches with a pgstac function, in one transaction.
+    ///
+    /// If a batch fails, nothing is written, and the error says which items
+    /// were in the failed batch.
+    async fn write_items(&self, query: &'static str, items: &[Item]) -> Result<()> {
+        let mut connection = self.connection().await?;
+        let statement = connection.statement(query).await?;
+        let transaction = connection.client.transaction().await?;
+        for (i, batch) in items.chunks(self.batch_size).enumerate() {
+            let start = i * self.batch_size;
+            let value = serde_json::to_value(batch)?;
+            let _ = transaction
+                .execute(&statement, &[&value])
+                .await
+                .map_err(|source| Error::Batch {
+                    start,
+                    end: start + batch.len(),
+                    source: Box::new(source),
+                })?;
+        }
+        transaction.commit().await?;
+        Ok(())
     }
 }
 
+impl<'a> Waiting<'a> {
+    fn new(waiters: &'a AtomicU64) -> Waiting<'a> {
+        let _ = waiters.fetch_add(1, Ordering::Relaxed);
+        Waiting(waiters)
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        let _ = self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Returns an items query as a search for [ESTIMATED_COUNT], without the
+/// `count` parameter.
+fn where_search(items: &Items<Paging>, id: &str) -> Result<stac_api::Search> {
+    let mut search = items.items.clone().into_search(id);
+    let _ = search.additional_fields.remove("count");
+    check_datetime(&mut search)?;
+    Ok(search)
+}
+
+/// Checks a search's datetime, instead of letting the database reject it,
+/// and spells its open ends the way pgstac expects.
+fn check_datetime(search: &mut stac_api::Search) -> Result<()> {
+    if let Some(datetime) = search.datetime.as_mut() {
+        let _ = parse_datetime(datetime).map_err(Error::InvalidDatetime)?;
+        *datetime = normalize_datetime(datetime);
+    }
+    Ok(())
+}
+
+/// Reads a page from a row of [SEARCH].
+///
+/// The page is asked for as text so the items can be passed through without
+/// being parsed into maps.
+fn page(row: Row) -> Result<Page<Paging>> {
+    let text: String = row.try_get(0)?;
+    let page: RawPage<'_> = serde_json::from_str(&text)?;
+    let next = page.next.map(|next| Paging {
+        token: Some(format!("next:{}", next)),
+    });
+    let prev = page.prev.map(|prev| Paging {
+        token: Some(format!("prev:{}", prev)),
+    });
+    let mut item_collection = ItemCollection::new(Vec::new())?;
+    item_collection.number_matched = page.context.matched;
+    item_collection.number_returned = Some(page.context.returned);
+    item_collection.context = Some(page.context);
+    Ok(Page {
+        item_collection,
+        next,
+        prev,
+        first: None,
+        last: None,
+        raw_items: Some(page.features.into_iter().map(ToOwned::to_owned).collect()),
+    })
+}
+
 #[async_trait]
-impl Backend for PgstacBackend {
+impl ReadBackend for PgstacBackend {
     type Error = Error;
     type Paging = Paging;
 
+    fn supported_parameters(&self) -> &'static [&'static str] {
+        &["fields", "sortby", "filter-crs", "filter", "query"]
+    }
+
+    fn metrics(&self) -> Metrics {
+        let state = self.pool.state();
+        Metrics {
+            pool: Some(PoolMetrics {
+                size: state.connections,
+                idle: state.idle_connections,
+                max_size: self.max_size,
+                waiters: self.waiters.load(Ordering::Relaxed),
+                get_timeouts: state.statistics.get_timed_out,
+            }),
+            methods: Vec::new(),
+        }
+    }
+
     async fn collections(&self) -> Result<Vec<Collection>> {
-        let client = self.pool.get().await?;
-        let client = Client::new(&*client);
-        client.collections().await.map_err(Error::from)
+        let mut connection = self.connection().await?;
+        let row = connection.query_one(COLLECTIONS, &[]).await?;
+        let collections: Option<Value> = row.try_get(0)?;
+        collections
+            .map(serde_json::from_value)
+            .transpose()
+            .map(Option::unwrap_or_default)
+            .map_err(Error::from)
+    }
+
+    async fn collection_summaries(&self) -> Result<Vec<CollectionSummary>> {
+        let mut connection = self.connection().await?;
+        let rows = connection.query(COLLECTION_SUMMARIES, &[]).await?;
+        rows.into_iter()
+            .map(|row| {
+                Ok(CollectionSummary {
+                    id: row.try_get(0)?,
+                    title: row.try_get(1)?,
+                })
+            })
+            .collect()
     }
 
     async fn collection(&self, id: &str) -> Result<Option<Collection>> {
-        let client = self.pool.get().await?;
-        let client = Client::new(&*client);
-        client.collection(id).await.map_err(Error::from)
+        let mut connection = self.connection().await?;
+        let row = connection.query_one(COLLECTION, &[&id]).await?;
+        let collection: Option<Value> = row.try_get(0)?;
+        collection
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(Error::from)
     }
 
-    async fn items(&self, id: &str, query: Items<Paging>) -> Result<Option<Page<Paging>>> {
-        let client = self.pool.get().await?;
-        let client = Client::new(&*client);
-        let mut search = query.items.into_search(id);
-        if let Some(token) = query.paging.token {
-            let _ = search
-                .additional_fields
-                .insert("token".to_string(), token.into());
-        }
-        let page = client.search(search).await?;
-        if page.features.is_empty() {
-            // TODO should we error if there's no collection?
-            Ok(None)
+    async fn items(&self, id: &str, query: Items<Paging>) -> Result<ItemsResult<Paging>> {
+        let count = if query.count().unwrap_or(self.count) == CountMode::Estimated {
+            Some(serde_json::to_value(where_search(&query, id)?)?)
         } else {
-            let next = page.next_token().map(|token| Paging { token: Some(token) });
-            let prev = page.prev_token().map(|token| Paging { token: Some(token) });
-            let mut item_collection = ItemCollection::new(page.features)?;
-            item_collection.context = Some(page.context);
-            Ok(Some(Page {
-                item_collection,
-                next,
-                prev,
-            }))
+            None
+        };
+        let search = self.search_value(Search {
+            search: query.items.into_search(id),
+            paging: query.paging,
+        })?;
+        let mut connection = self.connection().await?;
+        let exists = connection.statement(COLLECTION_EXISTS).await?;
+        let statement = connection.statement(SEARCH).await?;
+        let estimated_count = if count.is_some() {
+            Some(connection.statement(ESTIMATED_COUNT).await?)
+        } else {
+            None
+        };
+        // The queries are all sent before any response is awaited, so
+        // tokio-postgres pipelines them into one round trip.
+        let client = &connection.client;
+        let exists_params: [&(dyn ToSql + Sync); 1] = [&id];
+        let search_params: [&(dyn ToSql + Sync); 1] = [&search];
+        let (exists, row, count) = futures_util::try_join!(
+            client.query_one(&exists, &exists_params),
+            client.query_one(&statement, &search_params),
+            async {
+                match (&estimated_count, &count) {
+                    (Some(statement), Some(count)) => {
+                        client.query_one(statement, &[count]).await.map(Some)
+                    }
+                    _ => Ok(None),
+                }
+            },
+        )?;
+        if !exists.try_get::<_, bool>(0)? {
+            return Ok(ItemsResult::CollectionNotFound);
+        }
+        let mut page = page(row)?;
+        if let Some(row) = count {
+            let count: Option<i64> = row.try_get(0)?;
+            page.item_collection.number_matched = count.and_then(|count| u64::try_from(count).ok());
         }
+        Ok(page.into())
+    }
+
+    async fn matched_count(&self, id: &str, items: &Items<Paging>) -> Result<Option<u64>> {
+        if items.count().unwrap_or(self.count) != CountMode::Estimated {
+            return Ok(None);
+        }
+        let search = serde_json::to_value(where_search(items, id)?)?;
+        let mut connection = self.connection().await?;
+        let row = connection.query_one(ESTIMATED_COUNT, &[&search]).await?;
+        let count: Option<i64> = row.try_get(0)?;
+        Ok(count.and_then(|count| u64::try_from(count).ok()))
+    }
+
+    async fn search(&self, search: Search<Paging>) -> Result<Page<Paging>> {
+        let search = self.search_value(search)?;
+        let mut connection = self.connection().await?;
+        let row = connection.query_one(SEARCH, &[&search]).await?;
+        page(row)
     }
 
     async fn item(&self, collection_id: &str, id: &str) -> Result<Option<Item>> {
-        let client = self.pool.get().await?;
-        let client = Client::new(&*client);
-        client.item(id, collection_id).await.map_err(Error::from)
+        let mut connection = self.connection().await?;
+        let row = connection.query_one(ITEM, &[&id, &collection_id]).await?;
+        let item: Option<Value> = row.try_get(0)?;
+        item.map(serde_json::from_value)
+            .transpose()
+            .map_err(Error::from)
+    }
+
+    async fn item_updated(&self, collection_id: &str, id: &str) -> Result<Option<DateTime<Utc>>> {
+        // Just the timestamp, straight from the items table, instead of the
+        // whole item from pgstac.get_item.
+        let mut connection = self.connection().await?;
+        let row = connection
+            .query_one(ITEM_UPDATED, &[&collection_id, &id])
+            .await?;
+        let updated: Option<String> = row.try_get(0)?;
+        Ok(updated.as_deref().and_then(parse_updated))
+    }
+
+    async fn collection_updated(&self, id: &str) -> Result<Option<DateTime<Utc>>> {
+        let mut connection = self.connection().await?;
+        let row = connection.query_one(COLLECTION_UPDATED, &[&id]).await?;
+        let updated: Option<String> = row.try_get(0)?;
+        Ok(updated.as_deref().and_then(parse_updated))
     }
 
+    async fn raw_item(&self, collection_id: &str, id: &str) -> Result<Option<Box<RawValue>>> {
+        // As text, the item goes straight into a raw value without being
+        // parsed into a map and then an item.
+        let mut connection = self.connection().await?;
+        let row = connection
+            .query_one(RAW_ITEM, &[&id, &collection_id])
+            .await?;
+        let text: Option<String> = row.try_get(0)?;
+        text.map(RawValue::from_string)
+            .transpose()
+            .map_err(Error::from)
+    }
+
+    async fn queryables(&self, collection_id: Option<&str>) -> Result<Option<Map<String, Value>>> {
+        let mut connection = self.connection().await?;
+        let collection_ids = collection_id.map(|collection_id| vec![collection_id.to_string()]);
+        let row = connection.query_one(QUERYABLES, &[&collection_ids]).await?;
+        let text: Option<String> = row.try_get(0)?;
+        let queryables: Option<Value> = text.map(|text| serde_json::from_str(&text)).transpose()?;
+        Ok(
+            queryables.and_then(|mut queryables| match queryables["properties"].take() {
+                Value::Object(properties) => Some(properties),
+                _ => None,
+            }),
+        )
+    }
+}
+
+#[async_trait]
+impl WriteBackend for PgstacBackend {
     async fn add_collection(&mut self, collection: Collection) -> Result<Option<Collection>> {
-        let client = self.pool.get().await?;
-        let client = Client::new(&*client);
+        let connection = self.connection().await?;
+        let client = Client::new(&connection.client);
         client.add_collection(collection).await?;
         Ok(None) // TODO check and retrieve the previous collection
     }
 
     async fn upsert_collection(&mut self, collection: Collection) -> Result<Option<Collection>> {
-        let client = self.pool.get().await?;
-        let client = Client::new(&*client);
+        let connection = self.connection().await?;
+        let client = Client::new(&connection.client);
         client.upsert_collection(collection).await?;
         Ok(None) // TODO check and retrieve the previous collection
     }
 
     async fn delete_collection(&mut self, id: &str) -> Result<()> {
-        let client = self.pool.get().await?;
-        let client = Client::new(&*client);
+        let connection = self.connection().await?;
+        let client = Client::new(&connection.client);
         client.delete_collection(id).await?;
         Ok(())
     }
 
     async fn add_items(&mut self, items: Vec<Item>) -> Result<()> {
-        let client = self.pool.get().await?;
-        let client = Client::new(&*client);
-        client.add_items(&items).await.map_err(Error::from)
+        self.write_items(CREATE_ITEMS, &items).await
     }
 
     async fn upsert_items(&mut self, items: Vec<Item>) -> Result<()> {
-        let client = self.pool.get().await?;
-        let client = Client::new(&*client);
-        client.upsert_items(&items).await.map_err(Error::from)
+        self.write_items(UPSERT_ITEMS, &items).await
     }
 
     async fn add_item(&mut self, item: Item) -> Result<()> {
-        let client = self.pool.get().await?;
-        let client = Client::new(&*client);
+        let connection = self.connection().await?;
+        let client = Client::new(&connection.client);
         client.add_item(item).await.map_err(Error::from)
     }
 }
 
+impl Connection {
+    /// Runs a query that returns one row.
+    async fn query_one(
+        &mut self,
+        query: &'static str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> std::result::Result<Row, tokio_postgres::Error> {
+        let statement = self.statement(query).await?;
+        self.client.query_one(&statement, params).await
+    }
+
+    /// Runs a query that returns any number of rows.
+    async fn query(
+        &mut self,
+        query: &'static str,
+        params: &[&(dyn ToSql + Sync)],
+    ) -> std::result::Result<Vec<Row>, tokio_postgres::Error> {
+        let statement = self.statement(query).await?;
+        self.client.query(&statement, params).await
+    }
+
+    /// Returns a query's cached statement, preparing it first if this
+    /// connection hasn't run it yet.
+    async fn statement(
+        &mut self,
+        query: &'static str,
+    ) -> std::result::Result<Statement, tokio_postgres::Error> {
+        if let Some(statement) = self.statements.get(query) {
+            return Ok(statement.clone());
+        }
+        let statement = self.client.prepare(query).await?;
+        let _ = self.statements.insert(query, statement.clone());
+        Ok(statement)
+    }
+}
+
+#[async_trait]
+impl ManageConnection for Manager {
+    type Connection = Connection;
+    type Error = tokio_postgres::Error;
+
+    async fn connect(&self) -> std::result::Result<Connection, tokio_postgres::Error> {
+        let client = self.manager.connect().await?;
+        if let Some(settings) = &self.settings {
+            client.batch_execute(settings).await?;
+        }
+        Ok(Connection {
+            client,
+            statements: HashMap::new(),
+        })
+    }
+
+    async fn is_valid(
+        &self,
+        connection: &mut Connection,
+    ) -> std::result::Result<(), tokio_postgres::Error> {
+        self.manager.is_valid(&mut connection.client).await
+    }
+
+    fn has_broken(&self, connection: &mut Connection) -> bool {
+        self.manager.has_broken(&mut connection.client)
+    }
+}
+
+impl Error {
+    fn sql_state(&self) -> Option<&SqlState> {
+        let error = match self {
+            Error::Bb8TokioPostgresRun(bb8::RunError::User(error))
+            | Error::Pgstac(pgstac::Error::TokioPostgres(error))
+            | Error::TokioPostgres(error) => error,
+            Error::Batch { source, .. } => source,
+            _ => return None,
+        };
+        error.code()
+    }
+}
+
 impl From<Error> for crate::Error {
     fn from(value: Error) -> Self {
-        crate::Error::Backend(Box::new(value))
+        if let Error::InvalidDatetime(_) = value {
+            return crate::Error::InvalidQuery(value.to_string());
+        }
+        match value.sql_state() {
+            Some(&SqlState::UNIQUE_VIOLATION) => crate::Error::Conflict(value.to_string()),
+            // Class 22 is "data exception", e.g. an invalid datetime or token.
+            Some(state) if state.code().starts_with("22") => {
+                crate::Error::InvalidQuery(value.to_string())
+            }
+            _ => crate::Error::Backend(Box::new(value)),
+        }
     }
 }
@@ -0,0 +1,162 @@
+use serde_json::{json, Map, Value};
+use stac::Item;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Infers queryables from the properties of the items it has seen.
+///
+/// Backends without stored queryables can pass every item they ingest to
+/// [Queryables::observe], and then serve [Queryables::properties] from
+/// [ReadBackend::queryables](crate::ReadBackend::queryables). Each property
+/// gets the JSON types of the values that were seen for it, and strings
+/// that were always RFC 3339 datetimes get the `date-time` format.
+///
+/// # Examples
+///
+/// ```
+/// use stac::Item;
+/// use stac_api_backend::Queryables;
+///
+/// let mut item = Item::new("an-id");
+/// let _ = item.properties.additional_fields.insert("eo:cloud_cover".to_string(), 4.2.into());
+/// let mut queryables = Queryables::new();
+/// queryables.observe(&item);
+/// assert_eq!(queryables.properties()["eo:cloud_cover"]["type"], "number");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Queryables {
+    properties: BTreeMap<String, Observed>,
+}
+
+/// What's been seen of one property.
+#[derive(Clone, Debug, Default)]
+struct Observed {
+    types: BTreeSet<&'static str>,
+    strings: u64,
+    datetimes: u64,
+}
+
+impl Queryables {
+    /// Creates a sampler that hasn't seen any items.
+    pub fn new() -> Queryables {
+        Queryables::default()
+    }
+
+    /// Records the types of an item's properties.
+    pub fn observe(&mut self, item: &Item) {
+        let properties = match serde_json::to_value(&item.properties) {
+            Ok(Value::Object(properties)) => properties,
+            _ => return,
+        };
+        for (key, value) in properties {
+            let observed = self.properties.entry(key).or_default();
+            let type_ = match &value {
+                // Nulls say nothing about what a property holds.
+                Value::Null => continue,
+                Value::Bool(_) => "boolean",
+                Value::Number(number) if number.is_f64() => "number",
+                Value::Number(_) => "integer",
+                Value::String(s) => {
+                    observed.strings += 1;
+                    if chrono::DateTime::parse_from_rfc3339(s).is_ok() {
+                        observed.datetimes += 1;
+                    }
+                    "string"
+                }
+                Value::Array(_) => "array",
+                Value::Object(_) => "object",
+            };
+            let _ = observed.types.insert(type_);
+        }
+    }
+
+    /// Adds everything another sampler has seen to this one.
+    pub fn merge(&mut self, other: &Queryables) {
+        for (key, other) in &other.properties {
+            let observed = self.properties.entry(key.clone()).or_default();
+            observed.types.extend(&other.types);
+            observed.strings += other.strings;
+            observed.datetimes += other.datetimes;
+        }
+    }
+
+    /// Returns the JSON schemas of the core queryables and every property seen so far.
+    pub fn properties(&self) -> Map<String, Value> {
+        let mut properties = Map::new();
+        let _ = properties.insert(
+            "id".to_string(),
+            json!({"title": "Item ID", "type": "string"}),
+        );
+        let _ = properties.insert(
+            "collection".to_string(),
+            json!({"title": "Collection ID", "type": "string"}),
+        );
+        let _ = properties.insert(
+            "geometry".to_string(),
+            json!({"title": "Geometry", "$ref": "https://geojson.org/schema/Geometry.json"}),
+        );
+        for (key, observed) in &self.properties {
+            let _ = properties.insert(key.clone(), observed.schema());
+        }
+        properties
+    }
+}
+
+impl Observed {
+    fn schema(&self) -> Value {
+        let mut types = self.types.clone();
+        if types.contains("number") {
+            // Integers are numbers too.
+            let _ = types.remove("integer");
+        }
+        let mut schema = Map::new();
+        if types.len() == 1 {
+            let _ = schema.insert("type".to_string(), types.first().copied().into());
+        } else if !types.is_empty() {
+            let _ = schema.insert("type".to_string(), types.into_iter().collect());
+        }
+        if self.strings > 0 && self.strings == self.datetimes {
+            let _ = schema.insert("format".to_string(), "date-time".into());
+        }
+        Value::Object(schema)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Queryables;
+    use stac::Item;
+
+    #[test]
+    fn observe() {
+        let mut a = Item::new("a");
+        let _ = a
+            .properties
+            .additional_fields
+            .insert("gsd".to_string(), 10.into());
+        let _ = a
+            .properties
+            .additional_fields
+            .insert("platform".to_string(), "landsat-8".into());
+        let mut b = Item::new("b");
+        let _ = b
+            .properties
+            .additional_fields
+            .insert("gsd".to_string(), 0.5.into());
+        let _ = b
+            .properties
+            .additional_fields
+            .insert("platform".to_string(), serde_json::Value::Null);
+        let mut queryables = Queryables::new();
+        queryables.observe(&a);
+        let mut other = Queryables::new();
+        other.observe(&b);
+        queryables.merge(&other);
+        let properties = queryables.properties();
+        assert_eq!(properties["gsd"]["type"], "number");
+        assert_eq!(properties["platform"]["type"], "string");
+        assert!(properties["platform"].get("format").is_none());
+        assert_eq!(properties["datetime"]["type"], "string");
+        assert_eq!(properties["datetime"]["format"], "date-time");
+        assert_eq!(properties["id"]["type"], "string");
+    }
+}
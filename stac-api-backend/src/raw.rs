@@ -0,0 +1,337 @@
+use crate::{api::is_replaced, backend::parse_updated, Result};
+use chrono::{DateTime, Utc};
+use schemars::{gen::SchemaGenerator, schema::Schema, JsonSchema};
+use serde::{
+    de::{MapAccess, Visitor},
+    ser::{Error as _, SerializeMap},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::{value::RawValue, Map, Value};
+use stac::Link;
+use stac_api::{Context, ItemCollection};
+use std::fmt;
+
+/// An item collection whose items are passed through as raw JSON.
+///
+/// This serializes to the same JSON as an [ItemCollection], but the items are
+/// never parsed into maps, so backends that store items as JSON can hand
+/// their bytes straight to the response.
+#[derive(Debug, Serialize)]
+pub struct RawItemCollection {
+    r#type: &'static str,
+
+    /// The items.
+    #[serde(rename = "features")]
+    pub items: Vec<RawItem>,
+
+    /// Links related to this item collection.
+    pub links: Vec<Link>,
+
+    /// The number of items that meet the selection parameters, possibly estimated.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "numberMatched")]
+    pub number_matched: Option<u64>,
+
+    /// The number of items in the features array.
+    #[serde(skip_serializing_if = "Option::is_none", rename = "numberReturned")]
+    pub number_returned: Option<u64>,
+
+    /// The search-related metadata.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<Context>,
+
+    /// Additional fields.
+    #[serde(flatten)]
+    pub additional_fields: Map<String, Value>,
+}
+
+/// A raw JSON item, plus the links to add to it when it is serialized.
+///
+/// Only the top level of the item is scanned during serialization, and only
+/// the `links` array is rewritten.
+#[derive(Debug)]
+pub struct RawItem {
+    /// The item's JSON.
+    pub raw: Box<RawValue>,
+
+    /// Links set on the item, replacing any of its own links with the same rel.
+    pub links: Vec<Link>,
+}
+
+impl RawItemCollection {
+    /// Creates a new raw item collection from raw items.
+    pub fn new(items: Vec<Box<RawValue>>) -> RawItemCollection {
+        RawItemCollection {
+            r#type: "FeatureCollection",
+            items: items.into_iter().map(RawItem::new).collect(),
+            links: Vec::new(),
+            number_matched: None,
+            number_returned: None,
+            context: None,
+            additional_fields: Map::new(),
+        }
+    }
+}
+
+impl TryFrom<ItemCollection> for RawItemCollection {
+    type Error = crate::Error;
+
+    fn try_from(item_collection: ItemCollection) -> Result<RawItemCollection> {
+        let items = item_collection
+            .items
+            .iter()
+            .map(serde_json::value::to_raw_value)
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let mut raw_item_collection = RawItemCollection::new(items);
+        raw_item_collection.links = item_collection.links;
+        raw_item_collection.number_matched = item_collection.number_matched;
+        raw_item_collection.number_returned = item_collection.number_returned;
+        raw_item_collection.context = item_collection.context;
+        raw_item_collection.additional_fields = item_collection.additional_fields;
+        Ok(raw_item_collection)
+    }
+}
+
+impl JsonSchema for RawItemCollection {
+    fn schema_name() -> String {
+        ItemCollection::schema_name()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        ItemCollection::json_schema(gen)
+    }
+}
+
+impl RawItem {
+    /// Creates a new raw item with no extra links.
+    pub fn new(raw: Box<RawValue>) -> RawItem {
+        RawItem {
+            raw,
+            links: Vec::new(),
+        }
+    }
+
+    /// Returns this item's id, if it has one.
+    ///
+    /// Every other field is skipped without being parsed.
+    pub fn id(&self) -> Result<Option<String>> {
+        #[derive(Deserialize)]
+        struct Id {
+            id: Option<String>,
+        }
+        let id: Id = serde_json::from_str(self.raw.get())?;
+        Ok(id.id)
+    }
+
+    /// Returns when this item was last updated, from its `updated` property.
+    ///
+    /// Like [RawItem::id], nothing else is parsed. Timestamps that aren't
+    /// RFC 3339 are ignored.
+    pub fn updated(&self) -> Result<Option<DateTime<Utc>>> {
+        #[derive(Deserialize)]
+        struct Updated<'a> {
+            #[serde(borrow)]
+            properties: Option<Properties<'a>>,
+        }
+        #[derive(Deserialize)]
+        struct Properties<'a> {
+            updated: Option<&'a str>,
+        }
+        let updated: Updated<'_> = serde_json::from_str(self.raw.get())?;
+        Ok(updated
+            .properties
+            .and_then(|properties| properties.updated)
+            .and_then(parse_updated))
+    }
+}
+
+impl Serialize for RawItem {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.links.is_empty() {
+            return self.raw.serialize(serializer);
+        }
+        let fields: Fields<'_> = serde_json::from_str(self.raw.get()).map_err(S::Error::custom)?;
+        let mut map = serializer.serialize_map(Some(fields.0.len()))?;
+        let mut has_links = false;
+        for (key, value) in fields.0 {
+            if key == "links" {
+                has_links = true;
+                let existing: Vec<&RawValue> =
+                    serde_json::from_str(value.get()).map_err(S::Error::custom)?;
+                let mut links = Vec::with_capacity(existing.len() + self.links.len());
+                for link in existing {
+                    let rel_href: RelHref =
+                        serde_json::from_str(link.get()).map_err(S::Error::custom)?;
+                    if !is_replaced(&self.links, &rel_href.rel, &rel_href.href) {
+                        links.push(RawLink::Raw(link));
+                    }
+                }
+                links.extend(self.links.iter().map(RawLink::Link));
+                map.serialize_entry(&key, &links)?;
+            } else {
+                map.serialize_entry(&key, value)?;
+            }
+        }
+        if !has_links {
+            map.serialize_entry("links", &self.links)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(Deserialize)]
+struct RelHref {
+    #[serde(default)]
+    rel: String,
+    #[serde(default)]
+    href: String,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum RawLink<'a> {
+    Raw(&'a RawValue),
+    Link(&'a Link),
+}
+
+/// Returns a raw item with a null geometry, leaving everything else unparsed.
+pub(crate) fn null_geometry(raw: &RawValue) -> Result<Box<RawValue>> {
+    let fields: Fields<'_> = serde_json::from_str(raw.get())?;
+    let mut json = String::with_capacity(raw.get().len() + 16);
+    json.push('{');
+    let mut has_geometry = false;
+    for (key, value) in &fields.0 {
+        if json.len() > 1 {
+            json.push(',');
+        }
+        json.push_str(&serde_json::to_string(key)?);
+        json.push(':');
+        if key == "geometry" {
+            has_geometry = true;
+            json.push_str("null");
+        } else {
+            json.push_str(value.get());
+        }
+    }
+    if !has_geometry {
+        // GeoJSON features always have a geometry, even if it's null.
+        if json.len() > 1 {
+            json.push(',');
+        }
+        json.push_str("\"geometry\":null");
+    }
+    json.push('}');
+    RawValue::from_string(json).map_err(Into::into)
+}
+
+/// The top-level fields of a JSON object, in order, with unparsed values.
+struct Fields<'a>(Vec<(String, &'a RawValue)>);
+
+impl<'de: 'a, 'a> Deserialize<'de> for Fields<'a> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Fields<'a>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct FieldsVisitor;
+
+        impl<'de> Visitor<'de> for FieldsVisitor {
+            type Value = Fields<'de>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a JSON object")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> std::result::Result<Fields<'de>, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut fields = Vec::with_capacity(map.size_hint().unwrap_or_default());
+                while let Some(field) = map.next_entry()? {
+                    fields.push(field);
+                }
+                Ok(Fields(fields))
+            }
+        }
+
+        deserializer.deserialize_map(FieldsVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RawItem, RawItemCollection};
+    use serde_json::{json, value::RawValue};
+    use stac::Link;
+    use stac_api::ItemCollection;
+
+    fn raw(value: serde_json::Value) -> Box<RawValue> {
+        serde_json::value::to_raw_value(&value).unwrap()
+    }
+
+    #[test]
+    fn item_links() {
+        let mut item = RawItem::new(raw(json!({
+            "type": "Feature",
+            "id": "an-id",
+            "links": [
+                {"href": "./a.json", "rel": "related"},
+                {"href": "./an-id.json", "rel": "self"}
+            ],
+            "properties": {"datetime": null}
+        })));
+        assert_eq!(item.id().unwrap().unwrap(), "an-id");
+        item.links.push(Link::new(
+            "http://stac-api-backend.test/items/an-id",
+            "self",
+        ));
+        let value = serde_json::to_value(&item).unwrap();
+        let links = value["links"].as_array().unwrap();
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0]["rel"], "related");
+        assert_eq!(links[1]["href"], "http://stac-api-backend.test/items/an-id");
+        assert!(value["properties"]["datetime"].is_null());
+    }
+
+    #[test]
+    fn item_without_links() {
+        let mut item = RawItem::new(raw(json!({"type": "Feature", "id": "an-id"})));
+        item.links.push(Link::new(
+            "http://stac-api-backend.test/items/an-id",
+            "self",
+        ));
+        let value = serde_json::to_value(&item).unwrap();
+        assert_eq!(value["links"][0]["rel"], "self");
+    }
+
+    #[test]
+    fn null_geometry() {
+        let item = raw(json!({
+            "type": "Feature",
+            "id": "an-id",
+            "geometry": {"type": "Point", "coordinates": [-105.1, 41.0]},
+            "properties": {}
+        }));
+        let value: serde_json::Value =
+            serde_json::from_str(super::null_geometry(&item).unwrap().get()).unwrap();
+        assert_eq!(value["id"], "an-id");
+        assert!(value["geometry"].is_null());
+        let item = raw(json!({"type": "Feature", "id": "an-id"}));
+        let value: serde_json::Value =
+            serde_json::from_str(super::null_geometry(&item).unwrap().get()).unwrap();
+        assert!(value.as_object().unwrap()["geometry"].is_null());
+    }
+
+    #[test]
+    fn same_json_as_item_collection() {
+        let item_collection = ItemCollection::new(vec![json!({"type": "Feature", "id": "an-id"})
+            .as_object()
+            .unwrap()
+            .clone()])
+        .unwrap();
+        let expected = serde_json::to_value(&item_collection).unwrap();
+        let raw_item_collection = RawItemCollection::try_from(item_collection).unwrap();
+        assert_eq!(serde_json::to_value(raw_item_collection).unwrap(), expected);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{Error, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+/// A backend's paging structure, encoded as one opaque `token` query parameter.
+///
+/// Backends page in different ways (skip and take, pgstac's own tokens, ...),
+/// so the paging structure is serialized to json and base64-encoded. Clients
+/// only ever see `token=...`, whatever the backend.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Token {
+    /// The encoded paging structure, or `None` for the first page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl Token {
+    /// Encodes a paging structure.
+    ///
+    /// If the paging structure is empty (or every field is `null`), the token
+    /// is `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use stac_api_backend::Token;
+    /// use std::collections::HashMap;
+    ///
+    /// let token = Token::encode(&HashMap::from([("skip", 10)])).unwrap();
+    /// let paging: HashMap<String, u64> = token.decode().unwrap();
+    /// assert_eq!(paging["skip"], 10);
+    /// assert_eq!(Token::encode(&()).unwrap(), Token::default());
+    /// ```
+    pub fn encode<P: Serialize>(paging: &P) -> Result<Token> {
+        let value = match serde_json::to_value(paging)? {
+            Value::Object(mut object) => {
+                object.retain(|_, value| !value.is_null());
+                if object.is_empty() {
+                    return Ok(Token::default());
+                }
+                Value::Object(object)
+            }
+            Value::Null => return Ok(Token::default()),
+            value => value,
+        };
+        Ok(Token {
+            token: Some(URL_SAFE_NO_PAD.encode(serde_json::to_vec(&value)?)),
+        })
+    }
+
+    /// Decodes this token into a paging structure.
+    ///
+    /// An empty token decodes to the default paging structure, i.e. the first
+    /// page. A token that can't be decoded is an [Error::InvalidQuery].
+    pub fn decode<P: DeserializeOwned + Default>(&self) -> Result<P> {
+        if let Some(token) = &self.token {
+            let bytes = URL_SAFE_NO_PAD.decode(token)?;
+            serde_json::from_slice(&bytes)
+                .map_err(|err| Error::InvalidQuery(format!("invalid token: {}", err)))
+        } else {
+            Ok(P::default())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Token;
+    use crate::Error;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
+    struct Paging {
+        skip: Option<usize>,
+        take: Option<usize>,
+    }
+
+    #[test]
+    fn roundtrip() {
+        let paging = Paging {
+            skip: Some(2),
+            take: Some(1),
+        };
+        let token = Token::encode(&paging).unwrap();
+        assert!(token.token.is_some());
+        assert_eq!(token.decode::<Paging>().unwrap(), paging);
+    }
+
+    #[test]
+    fn empty() {
+        let token = Token::encode(&Paging::default()).unwrap();
+        assert_eq!(token, Token::default());
+        assert_eq!(token.decode::<Paging>().unwrap(), Paging::default());
+    }
+
+    #[test]
+    fn invalid() {
+        let token = Token {
+            token: Some("not a token!".to_string()),
+        };
+        assert!(token.decode::<Paging>().is_err());
+        let token = Token {
+            token: Some("bm90IGpzb24".to_string()),
+        };
+        assert!(matches!(
+            token.decode::<Paging>().unwrap_err(),
+            Error::InvalidQuery(_)
+        ));
+    }
+}
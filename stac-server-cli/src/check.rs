@@ -0,0 +1,324 @@
+use crate::Result;
+use reqwest::{header::CONTENT_TYPE, Client};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter},
+};
+use url::Url;
+
+/// The maximum number of item pages to follow when checking paging.
+const MAX_PAGES: usize = 3;
+
+/// The results of checking a running server.
+#[derive(Debug, Default, Serialize)]
+pub struct CheckReport {
+    /// Every check that was run, in order.
+    pub checks: Vec<Check>,
+}
+
+/// A single check against a running server.
+#[derive(Debug, Serialize)]
+pub struct Check {
+    /// What was checked.
+    pub name: String,
+
+    /// A description of the failure, or `None` if the check passed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+}
+
+struct Checker {
+    client: Client,
+    report: CheckReport,
+}
+
+/// Smoke-tests a running STAC API server.
+///
+/// Checks that the landing page's links resolve with the media types they
+/// advertise, that the advertised conformance classes have the routes to back
+/// them up, and that item paging links work. Failed checks are recorded in the
+/// report; only failing to build the http client returns an error.
+pub async fn check(url: &str) -> Result<CheckReport> {
+    let mut checker = Checker {
+        client: Client::builder().build()?,
+        report: CheckReport::default(),
+    };
+    checker.run(url).await;
+    Ok(checker.report)
+}
+
+impl Checker {
+    async fn run(&mut self, url: &str) {
+        let root = if let Some(root) = self.get_json("landing page", url, "application/json").await
+        {
+            root
+        } else {
+            return;
+        };
+        let links = links(&root);
+        for link in &links {
+            let rel = string(link, "rel").unwrap_or_default();
+            let href = if let Some(href) = string(link, "href") {
+                href
+            } else {
+                self.fail(format!("landing page {} link", rel), "link has no href");
+                continue;
+            };
+            let media_type = string(link, "type").unwrap_or_default();
+            let name = format!("landing page {} link", rel);
+            let _ = self.get(&name, &href, &media_type).await;
+        }
+
+        let conforms_to = if let Some(conforms_to) = root.get("conformsTo") {
+            Some(conforms_to.clone())
+        } else if let Some(href) = link_href(&links, "conformance") {
+            self.get_json("conformance", &href, "application/json")
+                .await
+                .and_then(|conformance| conformance.get("conformsTo").cloned())
+        } else {
+            None
+        };
+        let conforms_to: Vec<String> = if let Some(Value::Array(conforms_to)) = conforms_to {
+            conforms_to
+                .into_iter()
+                .filter_map(|value| value.as_str().map(String::from))
+                .collect()
+        } else {
+            self.fail("conformance classes", "no conformsTo array");
+            return;
+        };
+        self.pass("conformance classes");
+
+        let conforms = |class: &str| conforms_to.iter().any(|c| c.contains(class));
+        if conforms("item-search") {
+            if link_href(&links, "search").is_some() {
+                self.pass("item search has a search link");
+            } else {
+                self.fail(
+                    "item search has a search link",
+                    "the landing page has no search link",
+                );
+            }
+        }
+        if conforms("ogcapi-features") {
+            if let Some(href) = link_href(&links, "data") {
+                self.pass("features has a data link");
+                self.check_collections(&href).await;
+            } else {
+                self.fail(
+                    "features has a data link",
+                    "the landing page has no data link",
+                );
+            }
+        }
+    }
+
+    async fn check_collections(&mut self, href: &str) {
+        let collections = if let Some(collections) =
+            self.get_json("collections", href, "application/json").await
+        {
+            collections
+        } else {
+            return;
+        };
+        let collection = match collections.get("collections") {
+            Some(Value::Array(collections)) => {
+                self.pass("collections has a collections array");
+                collections.first().cloned()
+            }
+            _ => {
+                self.fail(
+                    "collections has a collections array",
+                    "no collections array",
+                );
+                return;
+            }
+        };
+        let collection = if let Some(collection) = collection {
+            collection
+        } else {
+            return;
+        };
+        let id = string(&collection, "id").unwrap_or_default();
+        if let Some(href) = link_href(&links(&collection), "items") {
+            self.check_items(&id, &href).await;
+        } else {
+            self.fail(
+                format!("collection {} has an items link", id),
+                "no items link",
+            );
+        }
+    }
+
+    async fn check_items(&mut self, collection_id: &str, href: &str) {
+        let mut href = href.to_string();
+        let mut ids = HashSet::new();
+        for page in 1..=MAX_PAGES {
+            let name = format!("collection {} items page {}", collection_id, page);
+            let items =
+                if let Some(items) = self.get_json(&name, &href, "application/geo+json").await {
+                    items
+                } else {
+                    return;
+                };
+            let features = if let Some(Value::Array(features)) = items.get("features") {
+                features
+            } else {
+                self.fail(
+                    format!("{} has a features array", name),
+                    "no features array",
+                );
+                return;
+            };
+            for feature in features {
+                if let Some(id) = string(feature, "id") {
+                    if !ids.insert(id.clone()) {
+                        self.fail(
+                            format!("{} has new items", name),
+                            format!("item {} was on an earlier page", id),
+                        );
+                        return;
+                    }
+                }
+            }
+            if let Some(next) = link_href(&links(&items), "next") {
+                href = next;
+            } else {
+                return;
+            }
+        }
+    }
+
+    async fn get_json(&mut self, name: &str, href: &str, media_type: &str) -> Option<Value> {
+        let text = self.get(name, href, media_type).await?;
+        match serde_json::from_str(&text) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.fail(format!("{} is json", name), err);
+                None
+            }
+        }
+    }
+
+    /// Gets an href, checking the status and media type, and returns the body.
+    async fn get(&mut self, name: &str, href: &str, media_type: &str) -> Option<String> {
+        let name = format!("{} ({})", name, href);
+        if let Err(err) = Url::parse(href) {
+            self.fail(name, err);
+            return None;
+        }
+        let response = match self.client.get(href).send().await {
+            Ok(response) => response,
+            Err(err) => {
+                self.fail(name, err);
+                return None;
+            }
+        };
+        if !response.status().is_success() {
+            self.fail(name, format!("status {}", response.status()));
+            return None;
+        }
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        if !media_type.is_empty() && essence(&content_type) != essence(media_type) {
+            self.fail(
+                name,
+                format!("expected {}, got {}", media_type, content_type),
+            );
+            return None;
+        }
+        match response.text().await {
+            Ok(text) => {
+                self.pass(name);
+                Some(text)
+            }
+            Err(err) => {
+                self.fail(name, err);
+                None
+            }
+        }
+    }
+
+    fn pass(&mut self, name: impl ToString) {
+        self.report.checks.push(Check {
+            name: name.to_string(),
+            failure: None,
+        });
+    }
+
+    fn fail(&mut self, name: impl ToString, failure: impl ToString) {
+        self.report.checks.push(Check {
+            name: name.to_string(),
+            failure: Some(failure.to_string()),
+        });
+    }
+}
+
+impl CheckReport {
+    /// Returns the checks that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|check| check.failure.is_some())
+    }
+}
+
+fn links(value: &Value) -> Vec<Value> {
+    value
+        .get("links")
+        .and_then(|links| links.as_array())
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn link_href(links: &[Value], rel: &str) -> Option<String> {
+    links
+        .iter()
+        .find(|link| link.get("rel").and_then(|r| r.as_str()) == Some(rel))
+        .and_then(|link| string(link, "href"))
+}
+
+fn string(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(|value| value.as_str())
+        .map(String::from)
+}
+
+/// The media type without its parameters, e.g. `application/json` for `application/json; charset=utf-8`.
+fn essence(media_type: &str) -> String {
+    media_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+impl Display for CheckReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for check in &self.checks {
+            writeln!(f, "{}", check)?;
+        }
+        write!(
+            f,
+            "Ran {} check(s), {} failure(s)",
+            self.checks.len(),
+            self.failures().count()
+        )
+    }
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(failure) = &self.failure {
+            write!(f, "FAIL {}: {}", self.name, failure)
+        } else {
+            write!(f, "ok   {}", self.name)
+        }
+    }
+}
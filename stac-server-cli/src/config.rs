@@ -0,0 +1,369 @@
+use crate::{Error, Result};
+use serde::Deserialize;
+use stac_api_backend::{CountMode, Eviction, MemoryBackend, PoolConfig};
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+    str::FromStr,
+};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, BufReader},
+};
+use toml::Table;
+
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    pub server: stac_server::Config,
+
+    // TODO document how to pick a backend with a config file
+    #[serde(default = "BackendConfig::default")]
+    pub backend: BackendConfig,
+
+    #[serde(default)]
+    pub load: LoadConfig,
+
+    #[serde(default)]
+    pub memory: MemoryConfig,
+
+    /// Publish catalog changes to NATS.
+    #[serde(default)]
+    pub nats: Option<NatsConfig>,
+}
+
+/// Configuration for publishing catalog changes to NATS.
+///
+/// Requires the `nats` feature. Each change is published, as a CloudEvent, to
+/// `<subject>.collections.<action>` or `<subject>.items.<action>`, e.g.
+/// `stac.items.created`.
+///
+/// ```toml
+/// [nats]
+/// url = "nats://localhost:4222"
+/// subject = "stac"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub struct NatsConfig {
+    /// The NATS server url.
+    pub url: String,
+
+    /// The prefix of every subject. Defaults to `stac`.
+    #[serde(default = "default_subject")]
+    pub subject: String,
+}
+
+/// Capacity limits for the memory backend.
+///
+/// By default the memory backend holds as many items as it's given. A demo
+/// server fed by a pipeline should set a cap, e.g.:
+///
+/// ```toml
+/// [memory]
+/// max_items = 100000
+/// max_items_per_collection = 10000
+/// eviction = "least_recently_used"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct MemoryConfig {
+    /// The most items across every collection.
+    pub max_items: Option<usize>,
+
+    /// The most items in any one collection.
+    pub max_items_per_collection: Option<usize>,
+
+    /// What to do with writes that would go over: `reject` them (the
+    /// default), or evict the `least_recently_used` items.
+    #[serde(default)]
+    pub eviction: Eviction,
+}
+
+impl MemoryConfig {
+    /// Builds a memory backend with these limits.
+    pub fn backend(&self) -> MemoryBackend {
+        let mut backend = MemoryBackend::new().eviction(self.eviction);
+        if let Some(max_items) = self.max_items {
+            backend = backend.max_items(max_items);
+        }
+        if let Some(max_items) = self.max_items_per_collection {
+            backend = backend.max_items_per_collection(max_items);
+        }
+        backend
+    }
+}
+
+/// Configuration for loading hrefs.
+#[derive(Debug, Default, Deserialize)]
+pub struct LoadConfig {
+    /// Headers sent with every request for a remote href, e.g. `Authorization`.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+
+    /// Validate every collection and item against the STAC json-schemas,
+    /// including its extensions' schemas, before writing it to the backend.
+    #[serde(default)]
+    pub validate: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub enum BackendConfig {
+    Memory,
+    Pgstac(PgstacConfig),
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PgstacConfig {
+    pub config: String,
+
+    /// How to count matching items by default: `exact`, `estimated`, or
+    /// `none`.
+    ///
+    /// Requests can pick their own with the `count` query parameter, e.g.
+    /// `?count=estimated`.
+    #[serde(default)]
+    pub count: CountMode,
+
+    /// Connection pool settings, e.g. `min_idle` connections to open at
+    /// startup, or a `statement_timeout` for every query.
+    #[serde(default)]
+    pub pool: PoolConfig,
+
+    /// The largest number of items sent to pgstac in one statement.
+    ///
+    /// Bigger writes are split into batches, all in one transaction.
+    #[serde(default)]
+    pub batch_size: Option<usize>,
+}
+
+impl Config {
+    /// Reads a TOML configuration file, or standard input if the path is `-`.
+    ///
+    /// A configuration file can list other files to layer underneath it with
+    /// `include = ["base.toml", "prod.toml"]`. Included paths are relative to
+    /// the including file, later includes override earlier ones, and the
+    /// including file overrides them all.
+    pub async fn from_toml(path: impl AsRef<Path>) -> Result<Config> {
+        Config::from_tomls([path]).await
+    }
+
+    /// Reads and layers several TOML configuration files, with later files
+    /// overriding earlier ones.
+    ///
+    /// Tables are merged key-by-key, so an override file only needs the values
+    /// that differ from the files before it.
+    pub async fn from_tomls<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Result<Config> {
+        let mut table = Table::new();
+        for path in paths {
+            merge(
+                &mut table,
+                read_table(path.as_ref().to_path_buf(), &mut Vec::new()).await?,
+            );
+        }
+        toml::Value::Table(table).try_into().map_err(Error::from)
+    }
+}
+
+fn read_table<'a>(
+    path: PathBuf,
+    stack: &'a mut Vec<PathBuf>,
+) -> Pin<Box<dyn Future<Output = Result<Table>> + Send + 'a>> {
+    Box::pin(async move {
+        let mut string = String::new();
+        let (path, directory) = if path == Path::new("-") {
+            let _ = tokio::io::stdin().read_to_string(&mut string).await?;
+            (path, PathBuf::new())
+        } else {
+            // Canonical paths, so a file can't sneak back in through a
+            // different relative path.
+            let path = tokio::fs::canonicalize(&path).await?;
+            if stack.contains(&path) {
+                return Err(Error::ConfigInclude(format!(
+                    "{} is included recursively",
+                    path.display()
+                )));
+            }
+            let mut reader = File::open(&path).await.map(BufReader::new)?;
+            let _ = reader.read_to_string(&mut string).await?;
+            let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            (path, directory)
+        };
+        let mut table: Table = toml::from_str(&string)?;
+        let includes = match table.remove("include") {
+            Some(toml::Value::Array(includes)) => includes,
+            Some(_) => {
+                return Err(Error::ConfigInclude(format!(
+                    "include in {} must be an array of paths",
+                    path.display()
+                )))
+            }
+            None => return Ok(table),
+        };
+        stack.push(path.clone());
+        let mut layered = Table::new();
+        for include in includes {
+            let include = include.as_str().ok_or_else(|| {
+                Error::ConfigInclude(format!(
+                    "include in {} must be an array of paths",
+                    path.display()
+                ))
+            })?;
+            merge(
+                &mut layered,
+                read_table(directory.join(include), stack).await?,
+            );
+        }
+        let _ = stack.pop();
+        merge(&mut layered, table);
+        Ok(layered)
+    })
+}
+
+/// Merges `overrides` into `base`, recursing into tables.
+fn merge(base: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overrides)) => {
+                merge(base, overrides)
+            }
+            (_, value) => {
+                let _ = base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn default_subject() -> String {
+    "stac".to_string()
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let s = include_str!("config.toml");
+        s.parse().unwrap()
+    }
+}
+
+impl FromStr for Config {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Config> {
+        toml::from_str(&s).map_err(Error::from)
+    }
+}
+
+impl BackendConfig {
+    pub fn set_pgstac_config(&mut self, config: impl ToString) {
+        *self = BackendConfig::Pgstac(PgstacConfig {
+            config: config.to_string(),
+            count: CountMode::default(),
+            pool: PoolConfig::default(),
+            batch_size: None,
+        })
+    }
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        BackendConfig::Memory
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge, read_table, Table};
+    use crate::Error;
+    use std::path::{Path, PathBuf};
+
+    /// Writes config files into a fresh directory named after the test.
+    fn write(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("{}-{}", name, std::process::id()));
+        if directory.exists() {
+            std::fs::remove_dir_all(&directory).unwrap();
+        }
+        for (path, contents) in files {
+            let path = directory.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        directory
+    }
+
+    async fn read(path: &Path) -> crate::Result<Table> {
+        read_table(path.to_path_buf(), &mut Vec::new()).await
+    }
+
+    #[test]
+    fn merge_tables() {
+        let mut base: Table = toml::from_str("a = 1\n[t]\nb = 1\nc = 1\n").unwrap();
+        let overrides: Table = toml::from_str("a = 2\n[t]\nc = 2\nd = 2\n").unwrap();
+        merge(&mut base, overrides);
+        let expected: Table = toml::from_str("a = 2\n[t]\nb = 1\nc = 2\nd = 2\n").unwrap();
+        assert_eq!(base, expected);
+    }
+
+    #[tokio::test]
+    async fn later_includes_override_earlier_ones() {
+        let directory = write(
+            "later-includes",
+            &[
+                (
+                    "config.toml",
+                    "include = [\"first.toml\", \"conf.d/second.toml\"]\n",
+                ),
+                ("first.toml", "a = 1\nb = 1\n"),
+                ("conf.d/second.toml", "b = 2\n"),
+            ],
+        );
+        let table = read(&directory.join("config.toml")).await.unwrap();
+        assert_eq!(table["a"].as_integer(), Some(1));
+        assert_eq!(table["b"].as_integer(), Some(2));
+        assert!(!table.contains_key("include"));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn including_file_wins() {
+        let directory = write(
+            "including-file-wins",
+            &[
+                ("config.toml", "include = [\"base.toml\"]\n[t]\na = 2\n"),
+                ("base.toml", "[t]\na = 1\nb = 1\n"),
+            ],
+        );
+        let table = read(&directory.join("config.toml")).await.unwrap();
+        assert_eq!(table["t"]["a"].as_integer(), Some(2));
+        assert_eq!(table["t"]["b"].as_integer(), Some(1));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn include_must_be_an_array() {
+        let directory = write(
+            "include-not-an-array",
+            &[
+                ("config.toml", "include = \"base.toml\"\n"),
+                ("base.toml", ""),
+            ],
+        );
+        let err = read(&directory.join("config.toml")).await.unwrap_err();
+        assert!(matches!(err, Error::ConfigInclude(_)), "{:?}", err);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn recursive_include() {
+        let directory = write(
+            "recursive-include",
+            &[
+                ("config.toml", "include = [\"conf.d/other.toml\"]\n"),
+                (
+                    "conf.d/other.toml",
+                    "include = [\"../conf.d/../config.toml\"]\n",
+                ),
+            ],
+        );
+        let err = read(&directory.join("config.toml")).await.unwrap_err();
+        assert!(matches!(err, Error::ConfigInclude(_)), "{:?}", err);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+use stac::Value;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("item {item_id} references a collection that does not exist: {collection_id}")]
+    CollectionNotFound {
+        item_id: String,
+        collection_id: String,
+    },
+
+    #[error("invalid config include: {0}")]
+    ConfigInclude(String),
+
+    #[cfg(feature = "geoparquet")]
+    #[error(transparent)]
+    Arrow(#[from] arrow_schema::ArrowError),
+
+    #[cfg(feature = "geoparquet")]
+    #[error("unsupported geoparquet geometry column type: {0}")]
+    GeoparquetGeometry(String),
+
+    #[cfg(feature = "geoparquet")]
+    #[error(transparent)]
+    Geozero(#[from] geozero::error::GeozeroError),
+
+    #[error("item {id} is invalid: {}", validation_message(.source))]
+    InvalidItem {
+        id: String,
+        source: stac_validate::Error,
+    },
+
+    #[error("invalid header {0}")]
+    Header(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("cannot load a {}", .0.type_name())]
+    Load(Value),
+
+    #[error("this stac-server was built without the {0} feature")]
+    MissingFeature(&'static str),
+
+    #[cfg(feature = "nats")]
+    #[error(transparent)]
+    NatsConnect(#[from] async_nats::ConnectError),
+
+    #[error("item {0} does not have a collection")]
+    NoCollection(String),
+
+    #[error("{0} problem(s) found")]
+    Invalid(usize),
+
+    #[error(transparent)]
+    Join(#[from] tokio::task::JoinError),
+
+    #[error("{0} href(s) could not be loaded")]
+    LoadFailures(usize),
+
+    #[error("the load did not finish within {0} second(s)")]
+    LoadTimeout(u64),
+
+    #[error("invalid load test: {0}")]
+    LoadTest(String),
+
+    #[error("invalid log level {0}")]
+    LogLevel(String),
+
+    #[error(transparent)]
+    ObjectStore(#[from] object_store::Error),
+
+    #[cfg(feature = "geoparquet")]
+    #[error(transparent)]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    #[error("{0}")]
+    PgstacVersion(String),
+
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+
+    #[error("invalid --since datetime: {0}")]
+    Since(chrono::ParseError),
+
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Stac(#[from] stac::Error),
+
+    #[error(transparent)]
+    StacApi(#[from] stac_api::Error),
+
+    #[error(transparent)]
+    StacApiBackend(#[from] stac_api_backend::Error),
+
+    #[error(transparent)]
+    StacAsync(#[from] stac_async::Error),
+
+    #[error("{}", validation_message(.0))]
+    StacValidate(#[from] stac_validate::Error),
+
+    #[error(transparent)]
+    StacServer(#[from] stac_server::Error),
+
+    #[error("invalid use of standard input: {0}")]
+    Stdin(String),
+
+    #[error("{0} request(s) failed")]
+    RequestFailures(usize),
+
+    #[error(transparent)]
+    TokioPostgres(#[from] tokio_postgres::Error),
+
+    #[error(transparent)]
+    TomlDe(#[from] toml::de::Error),
+
+    #[error(transparent)]
+    UrlParse(#[from] url::ParseError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+fn validation_message(err: &stac_validate::Error) -> String {
+    if let stac_validate::Error::Validation(errors) = err {
+        errors
+            .iter()
+            .map(|err| format!("{}: {}", err.instance_path, err))
+            .collect::<Vec<_>>()
+            .join("; ")
+    } else {
+        err.to_string()
+    }
+}
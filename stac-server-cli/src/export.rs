@@ -0,0 +1,161 @@
+use crate::Result;
+use async_trait::async_trait;
+use object_store::{buffered::BufWriter, path::Path, ObjectStore};
+use serde::Serialize;
+use stac::{Catalog, Item, Link, Links};
+use stac_api_backend::{Items, ItemsResult, ReadBackend};
+use stac_server::ExportStore;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use url::Url;
+
+/// Uploads finished export jobs to an object store, e.g. `s3://bucket/exports`.
+///
+/// Credentials are read from the environment, the same way as for
+/// [export]. Destinations that aren't urls are left alone, since local
+/// exports are served straight from the jobs directory.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ObjectStoreExport;
+
+#[async_trait]
+impl ExportStore for ObjectStoreExport {
+    async fn put(
+        &self,
+        path: &std::path::Path,
+        destination: &str,
+        file_name: &str,
+    ) -> stac_server::Result<Option<String>> {
+        match Url::parse(destination) {
+            // Single-letter schemes are Windows drive letters.
+            Ok(url) if url.scheme().len() > 1 => upload(path, &url, file_name)
+                .await
+                .map(Some)
+                .map_err(|err| stac_server::Error::Export(Box::new(err))),
+            _ => Ok(None),
+        }
+    }
+}
+
+async fn upload(path: &std::path::Path, url: &Url, file_name: &str) -> Result<String> {
+    let options = std::env::vars().map(|(key, value)| (key.to_ascii_lowercase(), value));
+    let (store, prefix) = object_store::parse_url_opts(url, options)?;
+    let mut writer = BufWriter::new(Arc::from(store), prefix.child(file_name));
+    let mut file = tokio::fs::File::open(path).await?;
+    let _ = tokio::io::copy(&mut file, &mut writer).await?;
+    writer.shutdown().await?;
+    Ok(format!(
+        "{}/{}",
+        url.as_str().trim_end_matches('/'),
+        file_name
+    ))
+}
+
+/// Writes the backend's collections and items as a static STAC catalog.
+///
+/// The destination can be a local directory or an object store url, e.g.
+/// `s3://bucket/prefix`. Object store credentials are read from the
+/// environment. The layout is:
+///
+/// ```text
+/// catalog.json
+/// <collection-id>/collection.json
+/// <collection-id>/<item-id>/<item-id>.json
+/// ```
+///
+/// All structural links are rewritten to be relative, so the catalog can be
+/// moved around or fed back into [load_hrefs](crate::load_hrefs).
+pub async fn export<B>(backend: &B, mut catalog: Catalog, destination: &str) -> Result<()>
+where
+    B: ReadBackend,
+    stac_api_backend::Error: From<B::Error>,
+{
+    let url = destination_url(destination)?;
+    let options = std::env::vars().map(|(key, value)| (key.to_ascii_lowercase(), value));
+    let (store, prefix) = object_store::parse_url_opts(&url, options)?;
+
+    catalog.remove_structural_links();
+    catalog.links.push(Link::root("./catalog.json").json());
+    for mut collection in backend
+        .collections()
+        .await
+        .map_err(stac_api_backend::Error::from)?
+    {
+        let collection_id = collection.id.clone();
+        let collection_prefix = prefix.child(collection_id.as_str());
+        collection.remove_structural_links();
+        collection.links.extend([
+            Link::root("../catalog.json").json(),
+            Link::parent("../catalog.json").json(),
+        ]);
+        let mut paging = B::Paging::default();
+        loop {
+            let query = Items {
+                items: Default::default(),
+                paging,
+                query: None,
+            };
+            let mut page = if let ItemsResult::Page(page) = backend
+                .items(&collection_id, query)
+                .await
+                .map_err(stac_api_backend::Error::from)?
+            {
+                *page
+            } else {
+                break;
+            };
+            page.parse_raw_items()?;
+            for item in page.item_collection.items {
+                let mut item = Item::try_from(item)?;
+                item.remove_structural_links();
+                item.links.extend([
+                    Link::root("../../catalog.json").json(),
+                    Link::parent("../collection.json").json(),
+                    Link::collection("../collection.json").json(),
+                ]);
+                let file_name = format!("{}.json", item.id);
+                collection
+                    .links
+                    .push(Link::item(format!("./{}/{}", item.id, file_name)).geojson());
+                let path = collection_prefix
+                    .child(item.id.as_str())
+                    .child(file_name.as_str());
+                put(&*store, &path, &item).await?;
+            }
+            if let Some(next) = page.next {
+                paging = next;
+            } else {
+                break;
+            }
+        }
+        catalog.links.push(
+            Link::child(format!("./{}/collection.json", collection_id))
+                .json()
+                .title(collection.title.clone()),
+        );
+        put(
+            &*store,
+            &collection_prefix.child("collection.json"),
+            &collection,
+        )
+        .await?;
+    }
+    put(&*store, &prefix.child("catalog.json"), &catalog).await
+}
+
+fn destination_url(destination: &str) -> Result<Url> {
+    match Url::parse(destination) {
+        // Single-letter schemes are Windows drive letters.
+        Ok(url) if url.scheme().len() > 1 => Ok(url),
+        _ => {
+            let path = std::path::absolute(destination)?;
+            std::fs::create_dir_all(&path)?;
+            Ok(Url::from_directory_path(&path).expect("absolute paths should convert to urls"))
+        }
+    }
+}
+
+async fn put(store: &dyn ObjectStore, path: &Path, value: &impl Serialize) -> Result<()> {
+    let bytes = serde_json::to_vec_pretty(value)?;
+    let _ = store.put(path, bytes.into()).await?;
+    Ok(())
+}
@@ -0,0 +1,290 @@
+use crate::Result;
+use arrow_array::{cast::AsArray, Array, BinaryArray, RecordBatch};
+use arrow_json::{reader::infer_json_schema_from_seekable, ReaderBuilder};
+use arrow_schema::{DataType, Field, Schema};
+use geozero::{geojson::GeoJson, wkb::Wkb, CoordDimensions, ToJson, ToWkb};
+use parquet::{
+    arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ArrowWriter},
+    file::properties::WriterProperties,
+    format::KeyValue,
+};
+use serde_json::{value::RawValue, Map, Value};
+use stac::Item;
+use stac_server::{ExportFormat, ExportWriter};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Seek, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::sync::mpsc::Sender;
+
+/// The number of rows in each record batch of an exported file.
+const EXPORT_BATCH_SIZE: usize = 10_000;
+
+/// The geoparquet file metadata for a single WKB geometry column.
+const GEO_METADATA: &str = r#"{"version":"1.0.0","primary_column":"geometry","columns":{"geometry":{"encoding":"WKB","geometry_types":[]}}}"#;
+
+/// Top-level item attributes in the stac-geoparquet layout. Every other column
+/// is an item property.
+const TOP_LEVEL_KEYS: [&str; 9] = [
+    "type",
+    "stac_version",
+    "stac_extensions",
+    "id",
+    "geometry",
+    "bbox",
+    "links",
+    "assets",
+    "collection",
+];
+
+/// Reads a stac-geoparquet file in record batches of `batch_size` rows, sending
+/// each batch's items down the channel.
+///
+/// This is blocking, so it should be run on a blocking thread. Errors
+/// converting an individual batch are sent down the channel; errors opening
+/// or reading the file are returned.
+pub(crate) fn read(href: &str, batch_size: usize, sender: Sender<Result<Vec<Item>>>) -> Result<()> {
+    let file = File::open(href)?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)?
+        .with_batch_size(batch_size)
+        .build()?;
+    for result in reader {
+        let record_batch = result?;
+        if sender.blocking_send(items(record_batch)).is_err() {
+            // The receiver hung up, so there's no one left to read the items.
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Exports items as stac-geoparquet, for export jobs.
+///
+/// Item properties become columns, geometries are stored as WKB, and
+/// bboxes are stored as structs, the same layout that
+/// [load_hrefs](crate::load_hrefs) reads. Rows are staged in a
+/// newline-delimited file next to the export so the schema can be inferred
+/// from every item before the parquet file is written.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GeoparquetExport;
+
+struct GeoparquetWriter {
+    path: PathBuf,
+    rows_path: PathBuf,
+    rows: BufWriter<File>,
+}
+
+impl ExportFormat for GeoparquetExport {
+    fn name(&self) -> &str {
+        "geoparquet"
+    }
+
+    fn media_type(&self) -> &str {
+        "application/vnd.apache.parquet"
+    }
+
+    fn extension(&self) -> &str {
+        "parquet"
+    }
+
+    fn writer(&self, path: &Path) -> stac_server::Result<Box<dyn ExportWriter>> {
+        let rows_path = path.with_extension("rows.ndjson");
+        let rows = File::create(&rows_path).map_err(export_error)?;
+        Ok(Box::new(GeoparquetWriter {
+            path: path.to_path_buf(),
+            rows_path,
+            rows: BufWriter::new(rows),
+        }))
+    }
+}
+
+impl ExportWriter for GeoparquetWriter {
+    fn write(&mut self, item: &RawValue) -> stac_server::Result<()> {
+        let item: Map<String, Value> = serde_json::from_str(item.get()).map_err(export_error)?;
+        serde_json::to_writer(&mut self.rows, &row(item)).map_err(export_error)?;
+        self.rows.write_all(b"\n").map_err(export_error)
+    }
+
+    fn finish(self: Box<Self>) -> stac_server::Result<()> {
+        let GeoparquetWriter {
+            path,
+            rows_path,
+            rows,
+        } = *self;
+        let _ = rows
+            .into_inner()
+            .map_err(|err| export_error(err.into_error()))?;
+        write(&rows_path, &path).map_err(export_error)?;
+        std::fs::remove_file(rows_path).map_err(export_error)
+    }
+}
+
+fn export_error(err: impl Into<crate::Error>) -> stac_server::Error {
+    stac_server::Error::Export(Box::new(err.into()))
+}
+
+/// Flattens an item into a stac-geoparquet row, with its geometry still as GeoJSON.
+fn row(mut item: Map<String, Value>) -> Map<String, Value> {
+    let mut row = Map::new();
+    if let Some(Value::Object(properties)) = item.remove("properties") {
+        row.extend(properties);
+    }
+    for (key, value) in item {
+        if key == "bbox" {
+            let _ = row.insert(key, bbox_struct(value));
+        } else {
+            let _ = row.insert(key, value);
+        }
+    }
+    // Parquet can't store structs without fields, and empty objects come
+    // back as defaults when read.
+    row.retain(|_, value| !matches!(value, Value::Object(object) if object.is_empty()));
+    row
+}
+
+/// The inverse of [bbox].
+fn bbox_struct(value: Value) -> Value {
+    let keys: &[&str] = match value.as_array().map(Vec::len) {
+        Some(4) => &["xmin", "ymin", "xmax", "ymax"],
+        Some(6) => &["xmin", "ymin", "zmin", "xmax", "ymax", "zmax"],
+        _ => return value,
+    };
+    if let Value::Array(values) = value {
+        Value::Object(keys.iter().map(|key| key.to_string()).zip(values).collect())
+    } else {
+        value
+    }
+}
+
+/// Writes staged rows to a geoparquet file.
+fn write(rows_path: &Path, path: &Path) -> Result<()> {
+    let mut reader = BufReader::new(File::open(rows_path)?);
+    let (schema, _) = infer_json_schema_from_seekable(&mut reader, None)?;
+    reader.rewind()?;
+    let mut fields: Vec<_> = schema
+        .fields()
+        .iter()
+        .filter(|field| field.name() != "geometry")
+        .cloned()
+        .collect();
+    let mut decoder = ReaderBuilder::new(Arc::new(Schema::new(fields.clone())))
+        .with_batch_size(EXPORT_BATCH_SIZE)
+        .build_decoder()?;
+    fields.push(Arc::new(Field::new("geometry", DataType::Binary, true)));
+    let schema = Arc::new(Schema::new(fields));
+    let properties = WriterProperties::builder()
+        .set_key_value_metadata(Some(vec![KeyValue::new(
+            "geo".to_string(),
+            GEO_METADATA.to_string(),
+        )]))
+        .build();
+    let mut writer =
+        ArrowWriter::try_new(File::create(path)?, Arc::clone(&schema), Some(properties))?;
+    let mut lines = reader.lines();
+    loop {
+        let mut rows = Vec::with_capacity(EXPORT_BATCH_SIZE);
+        let mut geometries = Vec::with_capacity(EXPORT_BATCH_SIZE);
+        for line in lines.by_ref().take(EXPORT_BATCH_SIZE) {
+            let mut row: Map<String, Value> = serde_json::from_str(&line?)?;
+            let geometry = match row.remove("geometry") {
+                Some(Value::Null) | None => None,
+                Some(geometry) => {
+                    Some(GeoJson(&geometry.to_string()).to_wkb(CoordDimensions::xy())?)
+                }
+            };
+            geometries.push(geometry);
+            rows.push(row);
+        }
+        if rows.is_empty() {
+            break;
+        }
+        decoder.serialize(&rows)?;
+        let mut columns = match decoder.flush()? {
+            Some(record_batch) => record_batch.columns().to_vec(),
+            None => Vec::new(),
+        };
+        columns.push(Arc::new(BinaryArray::from_iter(geometries)));
+        writer.write(&RecordBatch::try_new(Arc::clone(&schema), columns)?)?;
+    }
+    let _ = writer.close()?;
+    Ok(())
+}
+
+fn items(mut record_batch: RecordBatch) -> Result<Vec<Item>> {
+    let mut geometries = vec![None; record_batch.num_rows()];
+    if let Ok(index) = record_batch.schema().index_of("geometry") {
+        let column = record_batch.remove_column(index);
+        for (i, geometry) in geometries.iter_mut().enumerate() {
+            if column.is_null(i) {
+                continue;
+            }
+            let wkb = if let Some(array) = column.as_binary_opt::<i32>() {
+                array.value(i)
+            } else if let Some(array) = column.as_binary_opt::<i64>() {
+                array.value(i)
+            } else {
+                return Err(crate::Error::GeoparquetGeometry(
+                    column.data_type().to_string(),
+                ));
+            };
+            *geometry = Some(serde_json::from_str::<Value>(&Wkb(wkb).to_json()?)?);
+        }
+    }
+    let rows: Vec<Map<String, Value>> = if record_batch.num_rows() == 0 {
+        Vec::new()
+    } else {
+        let mut writer = arrow_json::ArrayWriter::new(Vec::new());
+        writer.write(&record_batch)?;
+        writer.finish()?;
+        serde_json::from_slice(&writer.into_inner())?
+    };
+    rows.into_iter()
+        .zip(geometries)
+        .map(|(row, geometry)| item(row, geometry))
+        .collect()
+}
+
+fn item(row: Map<String, Value>, geometry: Option<Value>) -> Result<Item> {
+    let mut item = Map::new();
+    let mut properties = Map::new();
+    for (key, value) in row {
+        if key == "bbox" {
+            let _ = item.insert(key, bbox(value));
+        } else if TOP_LEVEL_KEYS.contains(&key.as_str()) {
+            let _ = item.insert(key, value);
+        } else {
+            let _ = properties.insert(key, value);
+        }
+    }
+    let _ = item.insert("geometry".to_string(), geometry.unwrap_or(Value::Null));
+    let _ = item.insert("properties".to_string(), Value::Object(properties));
+    for (key, default) in [
+        ("type", Value::from("Feature")),
+        ("stac_version", Value::from(stac::STAC_VERSION)),
+        ("links", Value::Array(Vec::new())),
+        ("assets", Value::Object(Map::new())),
+    ] {
+        let _ = item.entry(key).or_insert(default);
+    }
+    serde_json::from_value(Value::Object(item)).map_err(Into::into)
+}
+
+/// stac-geoparquet stores bboxes as structs, but STAC wants an array.
+fn bbox(value: Value) -> Value {
+    if let Value::Object(mut object) = value {
+        let keys: &[&str] = if object.contains_key("zmin") {
+            &["xmin", "ymin", "zmin", "xmax", "ymax", "zmax"]
+        } else {
+            &["xmin", "ymin", "xmax", "ymax"]
+        };
+        Value::Array(
+            keys.iter()
+                .map(|key| object.remove(*key).unwrap_or(Value::Null))
+                .collect(),
+        )
+    } else {
+        value
+    }
+}
@@ -0,0 +1,166 @@
+use crate::{LoadReport, Loader, Result};
+use chrono::{DateTime, FixedOffset};
+use futures_util::StreamExt;
+use stac::Item;
+use stac_api::{Collections, Search, UrlBuilder};
+use stac_api_backend::Backend;
+use stac_async::ApiClient;
+use std::time::Instant;
+
+/// Options for harvesting from a remote STAC API.
+#[derive(Clone, Debug, Default)]
+pub struct Harvest {
+    /// Only harvest these collections.
+    ///
+    /// If `None`, every collection is harvested.
+    pub collections: Option<Vec<String>>,
+
+    /// Only harvest items updated after this time.
+    ///
+    /// An item's `updated` property is used, falling back to `created`. Items
+    /// with neither are always harvested.
+    pub since: Option<DateTime<FixedOffset>>,
+}
+
+impl Loader {
+    /// Harvests collections and items from a remote STAC API into the backend.
+    ///
+    /// Collections are read from the remote `/collections` endpoint and items
+    /// are paged through via `/search` (or the collection's items endpoint, if
+    /// the API doesn't support search), one collection at a time. Collections
+    /// and items are written according to the loader's mode, so harvesting
+    /// with [LoadMode::Upsert](crate::LoadMode::Upsert) and `since` set to the
+    /// last harvest time keeps a mirror up to date. Failures for individual
+    /// collections are recorded in the returned report.
+    pub async fn harvest<B>(
+        &self,
+        backend: &mut B,
+        url: &str,
+        harvest: &Harvest,
+    ) -> Result<LoadReport>
+    where
+        B: Backend,
+        stac_api_backend::Error: From<B::Error>,
+    {
+        let start = Instant::now();
+        let mut report = LoadReport {
+            hrefs: 1,
+            ..Default::default()
+        };
+        let url_builder = UrlBuilder::new(url)?;
+        let collections: Collections = reqwest::get(url_builder.collections().clone())
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let api_client = ApiClient::new(url)?;
+        for collection in collections.collections {
+            if let Some(ids) = &harvest.collections {
+                if !ids.contains(&collection.id) {
+                    continue;
+                }
+            }
+            let collection_id = collection.id.clone();
+            let href = url_builder.collection(&collection_id)?.to_string();
+            match self.write_collection(backend, collection).await {
+                Ok(true) => {
+                    report.collections += 1;
+                    self.print(format_args!("Harvested collection {}", collection_id));
+                }
+                Ok(false) => report.skipped += 1,
+                Err(err) => {
+                    self.fail(&mut report, href, err);
+                    continue;
+                }
+            }
+            if let Err(err) = self
+                .harvest_items(backend, &api_client, &collection_id, harvest, &mut report)
+                .await
+            {
+                self.fail(&mut report, href, err);
+            }
+        }
+        report.duration = start.elapsed();
+        Ok(report)
+    }
+
+    async fn harvest_items<B>(
+        &self,
+        backend: &mut B,
+        api_client: &ApiClient,
+        collection_id: &str,
+        harvest: &Harvest,
+        report: &mut LoadReport,
+    ) -> Result<()>
+    where
+        B: Backend,
+        stac_api_backend::Error: From<B::Error>,
+    {
+        let search = Search {
+            collections: Some(vec![collection_id.to_string()]),
+            ..Default::default()
+        };
+        // Not every API supports item search, so fall back to the collection's items.
+        let mut stream = match api_client.search(search).await {
+            Ok(stream) => stream.boxed(),
+            Err(stac_async::Error::NotFound(_)) => {
+                api_client.items(collection_id, None).await?.boxed()
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let mut items = Vec::with_capacity(self.batch_size);
+        while let Some(result) = stream.next().await {
+            let item = Item::try_from(result?)?;
+            if is_updated_since(&item, harvest.since) {
+                items.push(item);
+            } else {
+                report.skipped += 1;
+            }
+            if items.len() >= self.batch_size.max(1) {
+                self.write_harvested(backend, collection_id, std::mem::take(&mut items), report)
+                    .await?;
+            }
+        }
+        self.write_harvested(backend, collection_id, items, report)
+            .await
+    }
+
+    async fn write_harvested<B>(
+        &self,
+        backend: &mut B,
+        collection_id: &str,
+        items: Vec<Item>,
+        report: &mut LoadReport,
+    ) -> Result<()>
+    where
+        B: Backend,
+        stac_api_backend::Error: From<B::Error>,
+    {
+        let count = items.len();
+        let written = self.write_items(backend, items).await?;
+        report.items += written;
+        report.skipped += count - written;
+        if written > 0 {
+            self.print(format_args!(
+                "Harvested {} item(s) from {}",
+                written, collection_id
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn is_updated_since(item: &Item, since: Option<DateTime<FixedOffset>>) -> bool {
+    let since = if let Some(since) = since {
+        since
+    } else {
+        return true;
+    };
+    item.properties
+        .updated
+        .as_ref()
+        .or(item.properties.created.as_ref())
+        .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+        .map(|timestamp| timestamp > since)
+        .unwrap_or(true)
+}
@@ -0,0 +1,833 @@
+use crate::{Error, Result};
+use futures_util::{StreamExt, TryStreamExt};
+use reqwest::{header::HeaderMap, Client};
+use serde::{Serialize, Serializer};
+use stac::{Collection, Href, Item, Value};
+use stac_api_backend::Backend;
+use stac_validate::Validate;
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{
+    fs::File,
+    io::{AsyncBufRead, AsyncBufReadExt, BufReader},
+    sync::Semaphore,
+    task::JoinSet,
+};
+use tokio_util::io::StreamReader;
+
+/// The default number of newline-delimited or geoparquet items added to the
+/// backend at a time.
+pub const DEFAULT_BATCH_SIZE: usize = 1000;
+
+/// The default maximum number of hrefs read at the same time.
+pub const DEFAULT_CONCURRENCY: usize = 64;
+
+/// The default maximum number of item files written to the backend at the
+/// same time.
+pub const DEFAULT_WRITE_CONCURRENCY: usize = 4;
+
+/// Loads STAC values from hrefs into a backend.
+#[derive(Clone, Debug)]
+pub struct Loader {
+    /// Should progress be printed to standard error as hrefs are loaded?
+    pub progress: bool,
+
+    /// Should hrefs be read and validated without writing anything to the backend?
+    ///
+    /// Each value is validated against the STAC json-schemas, and each item's
+    /// collection must either be read in the same load or already exist in
+    /// the backend.
+    pub dry_run: bool,
+
+    /// Should values be validated before they're written to the backend?
+    ///
+    /// Each value is validated against the STAC json-schemas, including its
+    /// extensions' schemas. Invalid values are load failures and aren't
+    /// written, so junk doesn't get into the backend. Dry runs always validate.
+    pub validate: bool,
+
+    /// The number of newline-delimited or geoparquet items to add to the
+    /// backend at a time.
+    pub batch_size: usize,
+
+    /// The maximum number of hrefs to read at the same time.
+    ///
+    /// This limits the number of open files and HTTP connections.
+    pub concurrency: usize,
+
+    /// The maximum number of items and item collections written to the
+    /// backend at the same time.
+    ///
+    /// They're written after every collection, so their order doesn't
+    /// matter. Newline-delimited and geoparquet items are still written one
+    /// batch at a time.
+    pub write_concurrency: usize,
+
+    /// How collections and items that already exist in the backend are handled.
+    pub mode: LoadMode,
+
+    /// Headers sent with every request for a remote href, e.g. `Authorization`.
+    pub headers: HeaderMap,
+}
+
+/// How a load handles collections and items that already exist in the backend.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum LoadMode {
+    /// Add collections and items, failing if they already exist.
+    #[default]
+    Insert,
+
+    /// Add collections and items, replacing any that already exist.
+    Upsert,
+
+    /// Add collections and items, skipping any that already exist.
+    SkipExisting,
+}
+
+/// A summary of a load.
+#[derive(Debug, Default, Serialize)]
+pub struct LoadReport {
+    /// The number of hrefs that were read.
+    pub hrefs: usize,
+
+    /// The number of collections that were upserted, or that would have been
+    /// upserted during a dry run.
+    pub collections: usize,
+
+    /// The number of items that were added, or that would have been added
+    /// during a dry run.
+    pub items: usize,
+
+    /// The number of collections and items that were skipped because they
+    /// already exist in the backend.
+    pub skipped: usize,
+
+    /// Hrefs that could not be read or loaded.
+    pub failures: Vec<LoadFailure>,
+
+    /// How long the load took.
+    #[serde(serialize_with = "serialize_seconds")]
+    pub duration: Duration,
+}
+
+/// An href that could not be read or loaded.
+#[derive(Debug, Serialize)]
+pub struct LoadFailure {
+    /// The href.
+    pub href: String,
+
+    /// A description of what went wrong.
+    pub message: String,
+}
+
+/// Loads hrefs into a backend without printing any progress.
+pub async fn load_hrefs<B>(backend: &mut B, hrefs: Vec<String>) -> Result<LoadReport>
+where
+    B: Backend,
+    stac_api_backend::Error: From<B::Error>,
+{
+    Loader::default().load(backend, hrefs).await
+}
+
+impl Loader {
+    /// Reads every href and loads its collections and items into the backend.
+    ///
+    /// Hrefs ending in `.ndjson` or `.jsonl` are streamed as newline-delimited
+    /// items, and (with the `geoparquet` feature) local files ending in
+    /// `.parquet` or `.geoparquet` are read as stac-geoparquet, in record
+    /// batches. Both are loaded after all other hrefs, so their collections
+    /// can come from the same load. Failures to read or load an individual href
+    /// are recorded in the returned report instead of stopping the load.
+    pub async fn load<B>(&self, backend: &mut B, hrefs: Vec<String>) -> Result<LoadReport>
+    where
+        B: Backend,
+        stac_api_backend::Error: From<B::Error>,
+    {
+        // TODO this could probably be its own method on a backend?
+
+        let start = Instant::now();
+        let mut report = LoadReport::default();
+        let client = self.client()?;
+        let (ndjson_hrefs, hrefs): (Vec<_>, Vec<_>) =
+            hrefs.into_iter().partition(|href| is_ndjson(href));
+        #[cfg(feature = "geoparquet")]
+        let (geoparquet_hrefs, hrefs): (Vec<_>, Vec<_>) =
+            hrefs.into_iter().partition(|href| is_geoparquet(href));
+        let mut join_set: JoinSet<(String, Result<Value>)> = JoinSet::new();
+        let semaphore = Arc::new(Semaphore::new(self.concurrency.max(1)));
+        for href in hrefs {
+            let validate = self.dry_run || self.validate;
+            let semaphore = semaphore.clone();
+            let client = client.clone();
+            join_set.spawn(async move {
+                let _permit = semaphore
+                    .acquire_owned()
+                    .await
+                    .expect("the semaphore is never closed");
+                let result = read(&client, href.clone(), validate).await;
+                (href, result)
+            });
+        }
+        let mut collection_ids = HashSet::new();
+        let mut item_vectors = Vec::new();
+        while let Some(result) = join_set.join_next().await {
+            let (href, result) = result?;
+            let value = match result {
+                Ok(value) => value,
+                Err(err) => {
+                    self.fail(&mut report, href, err);
+                    continue;
+                }
+            };
+            report.hrefs += 1;
+            match value {
+                Value::Catalog(_) => self.fail(&mut report, href, Error::Load(value)),
+                Value::Collection(collection) if self.dry_run => {
+                    report.collections += 1;
+                    self.print(format_args!("Validated collection from {}", href));
+                    let _ = collection_ids.insert(collection.id);
+                }
+                Value::Collection(collection) => {
+                    match self.write_collection(backend, collection).await {
+                        Ok(true) => {
+                            report.collections += 1;
+                            self.print(format_args!("Loaded collection from {}", href));
+                        }
+                        Ok(false) => {
+                            report.skipped += 1;
+                            self.print(format_args!("Skipped existing collection from {}", href));
+                        }
+                        Err(err) => self.fail(&mut report, href, err),
+                    }
+                }
+                Value::Item(item) => item_vectors.push((href, vec![item])),
+                Value::ItemCollection(item_collection) => {
+                    item_vectors.push((href, item_collection.items))
+                }
+            }
+        }
+        if self.dry_run {
+            for (href, items) in item_vectors {
+                self.add_items(backend, &mut collection_ids, href, items, &mut report)
+                    .await;
+            }
+        } else {
+            let mut writes = futures_util::stream::iter(item_vectors)
+                .map(|(href, items)| {
+                    let mut backend = backend.clone();
+                    async move {
+                        let count = items.len();
+                        let result = self.write_items(&mut backend, items).await;
+                        (href, count, result)
+                    }
+                })
+                .buffer_unordered(self.write_concurrency.max(1));
+            while let Some((href, count, result)) = writes.next().await {
+                self.record_write(&mut report, href, count, result);
+            }
+        }
+        for href in ndjson_hrefs {
+            match open(&client, &href).await {
+                Ok(reader) => {
+                    report.hrefs += 1;
+                    self.load_lines(backend, &mut collection_ids, &href, reader, &mut report)
+                        .await;
+                }
+                Err(err) => self.fail(&mut report, href, err),
+            }
+        }
+        #[cfg(feature = "geoparquet")]
+        for href in geoparquet_hrefs {
+            self.load_geoparquet(backend, &mut collection_ids, &href, &mut report)
+                .await;
+        }
+        report.duration = start.elapsed();
+        Ok(report)
+    }
+
+    /// Loads hrefs or newline-delimited items from a reader, e.g. standard input.
+    ///
+    /// If the first non-whitespace character is `{`, the reader is loaded as
+    /// newline-delimited items. Otherwise, each non-empty line is an href to
+    /// load.
+    pub async fn load_reader<B, R>(&self, backend: &mut B, mut reader: R) -> Result<LoadReport>
+    where
+        B: Backend,
+        stac_api_backend::Error: From<B::Error>,
+        R: AsyncBufRead + Unpin + Send,
+    {
+        let is_ndjson = loop {
+            let buf = reader.fill_buf().await?;
+            if buf.is_empty() {
+                return Ok(LoadReport::default());
+            }
+            let whitespace = buf.iter().take_while(|b| b.is_ascii_whitespace()).count();
+            if whitespace < buf.len() {
+                let is_ndjson = buf[whitespace] == b'{';
+                reader.consume(whitespace);
+                break is_ndjson;
+            }
+            reader.consume(whitespace);
+        };
+        if is_ndjson {
+            self.load_ndjson(backend, reader).await
+        } else {
+            let mut hrefs = Vec::new();
+            let mut lines = reader.lines();
+            while let Some(line) = lines.next_line().await? {
+                let href = line.trim();
+                if !href.is_empty() {
+                    hrefs.push(href.to_string());
+                }
+            }
+            self.load(backend, hrefs).await
+        }
+    }
+
+    /// Loads newline-delimited items, e.g. from standard input.
+    ///
+    /// Each non-empty line must be a single STAC item. Items are added to the
+    /// backend in batches of [Loader::batch_size].
+    pub async fn load_ndjson<B, R>(&self, backend: &mut B, reader: R) -> Result<LoadReport>
+    where
+        B: Backend,
+        stac_api_backend::Error: From<B::Error>,
+        R: AsyncBufRead + Unpin + Send,
+    {
+        let start = Instant::now();
+        let mut report = LoadReport {
+            hrefs: 1,
+            ..Default::default()
+        };
+        self.load_lines(backend, &mut HashSet::new(), "-", reader, &mut report)
+            .await;
+        report.duration = start.elapsed();
+        Ok(report)
+    }
+
+    async fn load_lines<B, R>(
+        &self,
+        backend: &mut B,
+        collection_ids: &mut HashSet<String>,
+        href: &str,
+        reader: R,
+        report: &mut LoadReport,
+    ) where
+        B: Backend,
+        stac_api_backend::Error: From<B::Error>,
+        R: AsyncBufRead + Unpin + Send,
+    {
+        if !self.dry_run && self.mode == LoadMode::Insert {
+            self.stream_lines(backend, href, reader, report).await;
+            return;
+        }
+        let mut lines = reader.lines();
+        let mut line_number = 0;
+        let mut items = Vec::with_capacity(self.batch_size);
+        loop {
+            let line = match lines.next_line().await {
+                Ok(Some(line)) => line,
+                Ok(None) => break,
+                Err(err) => {
+                    self.fail(report, href.to_string(), err);
+                    break;
+                }
+            };
+            line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Item>(&line) {
+                Ok(item) => items.push(item),
+                Err(err) => self.fail(report, format!("{}:{}", href, line_number), err),
+            }
+            if items.len() >= self.batch_size {
+                let items = std::mem::replace(&mut items, Vec::with_capacity(self.batch_size));
+                self.add_batch(backend, collection_ids, href, items, report)
+                    .await;
+            }
+        }
+        if !items.is_empty() {
+            self.add_batch(backend, collection_ids, href, items, report)
+                .await;
+        }
+    }
+
+    /// Streams newline-delimited items straight into the backend.
+    ///
+    /// Lines that aren't items, or that aren't valid items when
+    /// [Loader::validate] is set, are recorded as failures with their line
+    /// number, and never reach the backend.
+    async fn stream_lines<B, R>(
+        &self,
+        backend: &mut B,
+        href: &str,
+        reader: R,
+        report: &mut LoadReport,
+    ) where
+        B: Backend,
+        stac_api_backend::Error: From<B::Error>,
+        R: AsyncBufRead + Unpin + Send,
+    {
+        let items = futures_util::stream::unfold(
+            (reader.lines(), 0, &mut *report),
+            |(mut lines, mut line_number, report)| async move {
+                loop {
+                    let line = match lines.next_line().await {
+                        Ok(Some(line)) => line,
+                        Ok(None) => return None,
+                        Err(err) => {
+                            self.fail(report, href.to_string(), err);
+                            return None;
+                        }
+                    };
+                    line_number += 1;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let item = match serde_json::from_str::<Item>(&line) {
+                        Ok(item) => item,
+                        Err(err) => {
+                            self.fail(report, format!("{}:{}", href, line_number), err);
+                            continue;
+                        }
+                    };
+                    let item = if self.validate {
+                        match tokio::task::spawn_blocking(move || {
+                            validate_items(std::slice::from_ref(&item)).map(|()| item)
+                        })
+                        .await
+                        .map_err(Error::from)
+                        .and_then(|result| result)
+                        {
+                            Ok(item) => item,
+                            Err(err) => {
+                                self.fail(report, format!("{}:{}", href, line_number), err);
+                                continue;
+                            }
+                        }
+                    } else {
+                        item
+                    };
+                    return Some((Ok(item), (lines, line_number, report)));
+                }
+            },
+        );
+        let ingest = backend.add_items_stream(items, self.batch_size).await;
+        report.items += ingest.items;
+        self.print(format_args!(
+            "Loaded {} item(s) from {} in {} chunk(s)",
+            ingest.items, href, ingest.chunks
+        ));
+        for failure in ingest.failures {
+            let href = format!("{} (chunk {})", href, failure.chunk);
+            self.print(format_args!("Failed to load {}: {}", href, failure.message));
+            report.failures.push(LoadFailure {
+                href,
+                message: failure.message,
+            });
+        }
+    }
+
+    #[cfg(feature = "geoparquet")]
+    async fn load_geoparquet<B>(
+        &self,
+        backend: &mut B,
+        collection_ids: &mut HashSet<String>,
+        href: &str,
+        report: &mut LoadReport,
+    ) where
+        B: Backend,
+        stac_api_backend::Error: From<B::Error>,
+    {
+        // Only one batch is held in the channel at a time, so the reader
+        // can't get too far ahead of the backend.
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(1);
+        let path = href.to_string();
+        let batch_size = self.batch_size;
+        let handle =
+            tokio::task::spawn_blocking(move || crate::geoparquet::read(&path, batch_size, sender));
+        report.hrefs += 1;
+        while let Some(result) = receiver.recv().await {
+            match result {
+                Ok(items) => {
+                    self.add_batch(backend, collection_ids, href, items, report)
+                        .await
+                }
+                Err(err) => self.fail(report, href.to_string(), err),
+            }
+        }
+        if let Err(err) = handle.await.map_err(Error::from).and_then(|result| result) {
+            self.fail(report, href.to_string(), err);
+        }
+    }
+
+    async fn add_batch<B>(
+        &self,
+        backend: &mut B,
+        collection_ids: &mut HashSet<String>,
+        href: &str,
+        items: Vec<Item>,
+        report: &mut LoadReport,
+    ) where
+        B: Backend,
+        stac_api_backend::Error: From<B::Error>,
+    {
+        let items = if self.dry_run || self.validate {
+            match tokio::task::spawn_blocking(move || validate_items(&items).map(|()| items))
+                .await
+                .map_err(Error::from)
+                .and_then(|result| result)
+            {
+                Ok(items) => items,
+                Err(err) => {
+                    self.fail(report, href.to_string(), err);
+                    return;
+                }
+            }
+        } else {
+            items
+        };
+        self.add_items(backend, collection_ids, href.to_string(), items, report)
+            .await
+    }
+
+    async fn add_items<B>(
+        &self,
+        backend: &mut B,
+        collection_ids: &mut HashSet<String>,
+        href: String,
+        items: Vec<Item>,
+        report: &mut LoadReport,
+    ) where
+        B: Backend,
+        stac_api_backend::Error: From<B::Error>,
+    {
+        let count = items.len();
+        if self.dry_run {
+            match check_collections(&*backend, collection_ids, &items).await {
+                Ok(()) => {
+                    report.items += count;
+                    self.print(format_args!("Validated {} item(s) from {}", count, href));
+                }
+                Err(err) => self.fail(report, href, err),
+            }
+        } else {
+            let result = self.write_items(backend, items).await;
+            self.record_write(report, href, count, result);
+        }
+    }
+
+    fn record_write(
+        &self,
+        report: &mut LoadReport,
+        href: String,
+        count: usize,
+        result: Result<usize>,
+    ) {
+        match result {
+            Ok(written) => {
+                report.items += written;
+                report.skipped += count - written;
+                self.print(format_args!(
+                    "Loaded {} item(s) from {}, skipped {}",
+                    written,
+                    href,
+                    count - written
+                ));
+            }
+            Err(err) => self.fail(report, href, err),
+        }
+    }
+
+    /// Writes a collection according to the mode, returning false if it was skipped.
+    pub(crate) async fn write_collection<B>(
+        &self,
+        backend: &mut B,
+        collection: Collection,
+    ) -> Result<bool>
+    where
+        B: Backend,
+        stac_api_backend::Error: From<B::Error>,
+    {
+        match self.mode {
+            LoadMode::Insert => {
+                let _ = backend
+                    .add_collection(collection)
+                    .await
+                    .map_err(stac_api_backend::Error::from)?;
+            }
+            LoadMode::Upsert => {
+                let _ = backend
+                    .upsert_collection(collection)
+                    .await
+                    .map_err(stac_api_backend::Error::from)?;
+            }
+            LoadMode::SkipExisting => {
+                if backend
+                    .collection(&collection.id)
+                    .await
+                    .map_err(stac_api_backend::Error::from)?
+                    .is_some()
+                {
+                    return Ok(false);
+                }
+                let _ = backend
+                    .add_collection(collection)
+                    .await
+                    .map_err(stac_api_backend::Error::from)?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Writes items according to the mode, returning the number that were written.
+    pub(crate) async fn write_items<B>(&self, backend: &mut B, items: Vec<Item>) -> Result<usize>
+    where
+        B: Backend,
+        stac_api_backend::Error: From<B::Error>,
+    {
+        let items = if self.mode == LoadMode::SkipExisting {
+            let mut new_items = Vec::with_capacity(items.len());
+            for item in items {
+                let exists = if let Some(collection_id) = &item.collection {
+                    backend
+                        .item(collection_id, &item.id)
+                        .await
+                        .map_err(stac_api_backend::Error::from)?
+                        .is_some()
+                } else {
+                    false
+                };
+                if !exists {
+                    new_items.push(item);
+                }
+            }
+            new_items
+        } else {
+            items
+        };
+        let count = items.len();
+        if count == 0 {
+            return Ok(0);
+        }
+        if self.mode == LoadMode::Upsert {
+            backend.upsert_items(items).await
+        } else {
+            backend.add_items(items).await
+        }
+        .map_err(stac_api_backend::Error::from)?;
+        Ok(count)
+    }
+
+    fn client(&self) -> Result<Client> {
+        Client::builder()
+            .default_headers(self.headers.clone())
+            .build()
+            .map_err(Error::from)
+    }
+
+    pub(crate) fn print(&self, args: std::fmt::Arguments<'_>) {
+        if self.progress {
+            eprintln!("{}", args);
+        }
+    }
+
+    pub(crate) fn fail(&self, report: &mut LoadReport, href: String, err: impl Into<Error>) {
+        let message = err.into().to_string();
+        self.print(format_args!("Failed to load {}: {}", href, message));
+        report.failures.push(LoadFailure { href, message });
+    }
+}
+
+impl Default for Loader {
+    fn default() -> Self {
+        Loader {
+            progress: false,
+            dry_run: false,
+            validate: false,
+            batch_size: DEFAULT_BATCH_SIZE,
+            concurrency: DEFAULT_CONCURRENCY,
+            write_concurrency: DEFAULT_WRITE_CONCURRENCY,
+            mode: LoadMode::default(),
+            headers: HeaderMap::new(),
+        }
+    }
+}
+
+fn is_ndjson(href: &str) -> bool {
+    let path = href.split(['?', '#']).next().unwrap_or(href);
+    path.ends_with(".ndjson") || path.ends_with(".jsonl")
+}
+
+#[cfg(feature = "geoparquet")]
+fn is_geoparquet(href: &str) -> bool {
+    href.ends_with(".parquet") || href.ends_with(".geoparquet")
+}
+
+fn is_remote(href: &str) -> bool {
+    href.starts_with("http://") || href.starts_with("https://")
+}
+
+async fn open(client: &Client, href: &str) -> Result<Box<dyn AsyncBufRead + Unpin + Send>> {
+    if is_remote(href) {
+        let response = client.get(href).send().await?.error_for_status()?;
+        let stream = response.bytes_stream().map_err(std::io::Error::other);
+        Ok(Box::new(StreamReader::new(stream)))
+    } else {
+        let file = File::open(href).await?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+async fn read(client: &Client, href: String, validate: bool) -> Result<Value> {
+    let value: Value = if is_remote(&href) {
+        let mut value: Value = client
+            .get(&href)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        value.set_href(href);
+        value
+    } else {
+        stac_async::read(href).await?
+    };
+    if validate {
+        tokio::task::spawn_blocking(move || validate_value(&value).map(|()| value)).await?
+    } else {
+        Ok(value)
+    }
+}
+
+fn validate_value(value: &Value) -> Result<()> {
+    if let Value::ItemCollection(item_collection) = value {
+        validate_items(&item_collection.items)
+    } else {
+        value.validate().map_err(Error::from)
+    }
+}
+
+fn validate_items(items: &[Item]) -> Result<()> {
+    // Items are validated one-by-one so problems can be tied back to an item id.
+    for item in items {
+        item.validate().map_err(|err| Error::InvalidItem {
+            id: item.id.clone(),
+            source: err,
+        })?;
+    }
+    Ok(())
+}
+
+async fn check_collections<B>(
+    backend: &B,
+    collection_ids: &mut HashSet<String>,
+    items: &[Item],
+) -> Result<()>
+where
+    B: Backend,
+    stac_api_backend::Error: From<B::Error>,
+{
+    for item in items {
+        let collection_id = item
+            .collection
+            .as_ref()
+            .ok_or_else(|| Error::NoCollection(item.id.clone()))?;
+        if collection_ids.contains(collection_id) {
+            continue;
+        }
+        if backend
+            .collection(collection_id)
+            .await
+            .map_err(stac_api_backend::Error::from)?
+            .is_some()
+        {
+            let _ = collection_ids.insert(collection_id.clone());
+        } else {
+            return Err(Error::CollectionNotFound {
+                item_id: item.id.clone(),
+                collection_id: collection_id.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+impl LoadReport {
+    /// Returns the number of items loaded per second.
+    pub fn items_per_second(&self) -> f64 {
+        let seconds = self.duration.as_secs_f64();
+        if seconds > 0. {
+            self.items as f64 / seconds
+        } else {
+            0.
+        }
+    }
+}
+
+impl Display for LoadReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Loaded {} collection(s) and {} item(s) from {} href(s) in {:.2}s ({:.1} items/s), {} skipped, {} failure(s)",
+            self.collections,
+            self.items,
+            self.hrefs,
+            self.duration.as_secs_f64(),
+            self.items_per_second(),
+            self.skipped,
+            self.failures.len()
+        )
+    }
+}
+
+fn serialize_seconds<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Loader;
+    use stac::{Collection, Item, Link};
+    use stac_api_backend::{MemoryBackend, ReadBackend, WriteBackend};
+
+    #[tokio::test]
+    async fn ndjson_validate() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let item = |id: &str| {
+            let mut item = Item::new(id).collection("an-id");
+            item.links
+                .push(Link::new("./collection.json", "collection"));
+            item
+        };
+        let valid = serde_json::to_string(&item("valid")).unwrap();
+        let mut invalid = serde_json::to_value(item("invalid")).unwrap();
+        invalid["properties"]["datetime"] = serde_json::Value::Null;
+        let ndjson = format!("{}\n{}\n", valid, invalid);
+        let loader = Loader {
+            validate: true,
+            ..Default::default()
+        };
+        let report = loader
+            .load_ndjson(&mut backend, ndjson.as_bytes())
+            .await
+            .unwrap();
+        assert_eq!(report.items, 1);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].href, "-:2");
+        assert!(backend.item("an-id", "valid").await.unwrap().is_some());
+        assert!(backend.item("an-id", "invalid").await.unwrap().is_none());
+    }
+}
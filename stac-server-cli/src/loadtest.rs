@@ -0,0 +1,364 @@
+use crate::{Error, Result};
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    fmt::{Display, Formatter},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// The upper bounds of the latency histogram's buckets, in milliseconds.
+///
+/// Slower requests go in a last, unbounded bucket.
+const BUCKETS: [u64; 12] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000];
+
+/// The most collections whose items are requested by the default mix.
+const MAX_COLLECTIONS: usize = 10;
+
+/// How a load test drives a running server.
+#[derive(Clone, Debug)]
+pub struct LoadTest {
+    /// The number of requests in flight at once.
+    pub concurrency: usize,
+
+    /// The number of requests to send, unless `duration` is set.
+    pub requests: usize,
+
+    /// Send requests for this long instead of a fixed number of them.
+    pub duration: Option<Duration>,
+
+    /// The paths to request, relative to the server's root url, each with
+    /// how many times it's requested per round.
+    ///
+    /// Requests go out in the same order on every run, so results are
+    /// comparable. If empty, the landing page, the collections, and up to
+    /// ten collections' items are requested, with more weight on the items.
+    pub mix: Vec<(u32, String)>,
+}
+
+/// The results of a load test.
+#[derive(Debug, Serialize)]
+pub struct LoadTestReport {
+    /// How long the load test took, in seconds.
+    pub seconds: f64,
+
+    /// Latencies of every request.
+    pub total: Latencies,
+
+    /// Latencies of each path in the mix.
+    pub paths: Vec<Latencies>,
+}
+
+/// Latencies of a set of requests, in milliseconds.
+#[derive(Debug, Serialize)]
+pub struct Latencies {
+    /// The requested path, or `*` for every request.
+    pub path: String,
+
+    /// The number of requests.
+    pub requests: usize,
+
+    /// The number of requests that didn't get a successful response.
+    pub failures: usize,
+
+    /// Requests per second over the whole load test.
+    pub requests_per_second: f64,
+
+    /// The fastest request.
+    pub min: f64,
+
+    /// The mean request.
+    pub mean: f64,
+
+    /// The median request.
+    pub p50: f64,
+
+    /// The 90th percentile request.
+    pub p90: f64,
+
+    /// The 99th percentile request.
+    pub p99: f64,
+
+    /// The slowest request.
+    pub max: f64,
+
+    /// The number of requests in each latency bucket.
+    pub histogram: Vec<Bucket>,
+}
+
+/// A latency histogram bucket.
+#[derive(Debug, Serialize)]
+pub struct Bucket {
+    /// The bucket's upper bound in milliseconds, or `None` for the last
+    /// bucket.
+    pub le: Option<u64>,
+
+    /// The number of requests in this bucket.
+    pub count: usize,
+}
+
+#[derive(Debug)]
+struct Sample {
+    path: usize,
+    latency: Duration,
+    ok: bool,
+}
+
+impl LoadTest {
+    /// Drives the server at `url` and reports request latencies.
+    ///
+    /// Failed requests are counted in the report. Only failing to build the
+    /// http client or to discover the default mix returns an error.
+    pub async fn run(&self, url: &str) -> Result<LoadTestReport> {
+        let client = Client::builder().build()?;
+        let root = url.trim_end_matches('/');
+        let mix = if self.mix.is_empty() {
+            default_mix(&client, root).await?
+        } else {
+            self.mix.clone()
+        };
+        let schedule: Arc<Vec<usize>> = Arc::new(
+            mix.iter()
+                .enumerate()
+                .flat_map(|(i, (weight, _))| std::iter::repeat_n(i, *weight as usize))
+                .collect(),
+        );
+        if schedule.is_empty() {
+            return Err(Error::LoadTest("the request mix has no weight".to_string()));
+        }
+        let urls: Arc<Vec<String>> = Arc::new(
+            mix.iter()
+                .map(|(_, path)| format!("{}/{}", root, path.trim_start_matches('/')))
+                .collect(),
+        );
+        let next = Arc::new(AtomicUsize::new(0));
+        let start = Instant::now();
+        let deadline = self.duration.map(|duration| start + duration);
+        let requests = self.requests;
+        let workers = (0..self.concurrency.max(1)).map(|_| {
+            let client = client.clone();
+            let schedule = Arc::clone(&schedule);
+            let urls = Arc::clone(&urls);
+            let next = Arc::clone(&next);
+            tokio::spawn(async move {
+                let mut samples = Vec::new();
+                loop {
+                    let n = next.fetch_add(1, Ordering::Relaxed);
+                    let done = match deadline {
+                        Some(deadline) => Instant::now() >= deadline,
+                        None => n >= requests,
+                    };
+                    if done {
+                        break;
+                    }
+                    let path = schedule[n % schedule.len()];
+                    let started = Instant::now();
+                    let ok = match client.get(&urls[path]).send().await {
+                        Ok(response) => {
+                            response.status().is_success() && response.bytes().await.is_ok()
+                        }
+                        Err(_) => false,
+                    };
+                    samples.push(Sample {
+                        path,
+                        latency: started.elapsed(),
+                        ok,
+                    });
+                }
+                samples
+            })
+        });
+        let samples: Vec<Sample> = futures_util::future::try_join_all(workers)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+        let seconds = start.elapsed().as_secs_f64();
+        let paths = mix
+            .iter()
+            .enumerate()
+            .map(|(i, (_, path))| {
+                let samples: Vec<&Sample> =
+                    samples.iter().filter(|sample| sample.path == i).collect();
+                Latencies::new(path.clone(), &samples, seconds)
+            })
+            .collect();
+        Ok(LoadTestReport {
+            seconds,
+            total: Latencies::new(
+                "*".to_string(),
+                &samples.iter().collect::<Vec<_>>(),
+                seconds,
+            ),
+            paths,
+        })
+    }
+}
+
+impl Default for LoadTest {
+    fn default() -> Self {
+        LoadTest {
+            concurrency: 10,
+            requests: 1000,
+            duration: None,
+            mix: Vec::new(),
+        }
+    }
+}
+
+/// Parses one entry of a request mix, e.g. `4:/collections/naip/items`.
+///
+/// Entries without a weight have a weight of one.
+pub fn parse_mix(entry: &str) -> Result<(u32, String)> {
+    match entry.split_once(':') {
+        // Paths can have colons too, e.g. in a datetime query.
+        Some((weight, path)) if weight.chars().all(|c| c.is_ascii_digit()) => {
+            match weight.parse() {
+                Ok(weight) if weight > 0 => Ok((weight, path.to_string())),
+                _ => Err(Error::LoadTest(format!(
+                    "{} (the weight must be a positive integer)",
+                    entry
+                ))),
+            }
+        }
+        _ => Ok((1, entry.to_string())),
+    }
+}
+
+/// The landing page, the collections, and each collection's items.
+async fn default_mix(client: &Client, root: &str) -> Result<Vec<(u32, String)>> {
+    let collections: Value = client
+        .get(format!("{}/collections", root))
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let mut mix = vec![(1, "/".to_string()), (1, "/collections".to_string())];
+    let ids = collections
+        .get("collections")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|collection| collection.get("id").and_then(Value::as_str))
+        .take(MAX_COLLECTIONS);
+    for id in ids {
+        mix.push((4, format!("/collections/{}/items", id)));
+    }
+    Ok(mix)
+}
+
+impl Latencies {
+    fn new(path: String, samples: &[&Sample], seconds: f64) -> Latencies {
+        let mut millis: Vec<f64> = samples
+            .iter()
+            .map(|sample| sample.latency.as_secs_f64() * 1000.0)
+            .collect();
+        millis.sort_by(f64::total_cmp);
+        let percentile = |p: f64| {
+            let rank = (p * millis.len() as f64).ceil() as usize;
+            millis
+                .get(rank.saturating_sub(1))
+                .copied()
+                .unwrap_or_default()
+        };
+        let mut histogram: Vec<Bucket> = BUCKETS
+            .iter()
+            .map(|&le| Bucket {
+                le: Some(le),
+                count: 0,
+            })
+            .chain(std::iter::once(Bucket { le: None, count: 0 }))
+            .collect();
+        for &ms in &millis {
+            let i = BUCKETS
+                .iter()
+                .position(|&le| ms <= le as f64)
+                .unwrap_or(BUCKETS.len());
+            histogram[i].count += 1;
+        }
+        Latencies {
+            path,
+            requests: samples.len(),
+            failures: samples.iter().filter(|sample| !sample.ok).count(),
+            requests_per_second: if seconds > 0.0 {
+                samples.len() as f64 / seconds
+            } else {
+                0.0
+            },
+            min: millis.first().copied().unwrap_or_default(),
+            mean: if millis.is_empty() {
+                0.0
+            } else {
+                millis.iter().sum::<f64>() / millis.len() as f64
+            },
+            p50: percentile(0.5),
+            p90: percentile(0.9),
+            p99: percentile(0.99),
+            max: millis.last().copied().unwrap_or_default(),
+            histogram,
+        }
+    }
+}
+
+impl Display for LoadTestReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<40} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}",
+            "path", "requests", "failures", "req/s", "p50 ms", "p90 ms", "p99 ms", "max ms"
+        )?;
+        for latencies in self.paths.iter().chain(std::iter::once(&self.total)) {
+            writeln!(f, "{}", latencies)?;
+        }
+        writeln!(f)?;
+        let most = self
+            .total
+            .histogram
+            .iter()
+            .map(|bucket| bucket.count)
+            .max()
+            .unwrap_or_default()
+            .max(1);
+        for bucket in &self.total.histogram {
+            let bound = match bucket.le {
+                Some(le) => format!("<= {} ms", le),
+                None => format!("> {} ms", BUCKETS[BUCKETS.len() - 1]),
+            };
+            writeln!(
+                f,
+                "{:>12} {:<40} {}",
+                bound,
+                "#".repeat(bucket.count * 40 / most),
+                bucket.count
+            )?;
+        }
+        writeln!(f)?;
+        write!(
+            f,
+            "Sent {} request(s) in {:.2}s, {} failure(s)",
+            self.total.requests, self.seconds, self.total.failures
+        )
+    }
+}
+
+impl Display for Latencies {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:<40} {:>8} {:>8} {:>8.1} {:>8.1} {:>8.1} {:>8.1} {:>8.1}",
+            self.path,
+            self.requests,
+            self.failures,
+            self.requests_per_second,
+            self.p50,
+            self.p90,
+            self.p99,
+            self.max
+        )
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{NatsConfig, Result};
+use async_trait::async_trait;
+use stac_api_backend::{Event, EventListener};
+
+/// Publishes catalog changes to NATS subjects.
+///
+/// Each [Event] is published as a structured
+/// [CloudEvent](stac_api_backend::CloudEvent) to
+/// `<subject>.collections.<action>` or `<subject>.items.<action>`. Publishing
+/// happens after the write succeeded, so failures are logged and don't fail
+/// the write.
+#[derive(Debug)]
+pub struct NatsPublisher {
+    client: async_nats::Client,
+    subject: String,
+    source: String,
+}
+
+impl NatsPublisher {
+    /// Connects to the configured NATS server.
+    ///
+    /// The source identifies this server in every event, e.g. its root url.
+    pub async fn connect(config: &NatsConfig, source: impl ToString) -> Result<NatsPublisher> {
+        let client = async_nats::connect(&config.url).await?;
+        Ok(NatsPublisher {
+            client,
+            subject: config.subject.clone(),
+            source: source.to_string(),
+        })
+    }
+
+    fn subject(&self, event: &Event) -> String {
+        let kind = if event.item_id.is_some() {
+            "items"
+        } else {
+            "collections"
+        };
+        format!("{}.{}.{}", self.subject, kind, event.action.as_str())
+    }
+}
+
+#[async_trait]
+impl EventListener for NatsPublisher {
+    async fn notify(&self, events: &[Event]) {
+        for event in events {
+            let subject = self.subject(event);
+            let payload = match serde_json::to_vec(&event.to_cloud_event(&self.source)) {
+                Ok(payload) => payload,
+                Err(err) => {
+                    tracing::error!("could not serialize event for {}: {}", subject, err);
+                    continue;
+                }
+            };
+            if let Err(err) = self.client.publish(subject.clone(), payload.into()).await {
+                tracing::error!("could not publish to {}: {}", subject, err);
+            }
+        }
+        if let Err(err) = self.client.flush().await {
+            tracing::error!("could not flush nats events: {}", err);
+        }
+    }
+}
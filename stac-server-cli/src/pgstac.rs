@@ -0,0 +1,169 @@
+use crate::{Error, Result};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+};
+use tokio_postgres::Client;
+
+/// The pgstac version this server is tested against.
+pub const PGSTAC_VERSION: &str = "0.8.1";
+
+/// A summary of a pgstac migration.
+#[derive(Debug, Serialize)]
+pub struct Migration {
+    /// The pgstac version before the migration, or `None` if pgstac wasn't installed.
+    pub from: Option<String>,
+
+    /// The pgstac version after the migration.
+    pub to: String,
+
+    /// The migration files that were applied, in order.
+    pub applied: Vec<PathBuf>,
+}
+
+/// Returns the installed pgstac version, or `None` if pgstac isn't installed.
+pub async fn pgstac_version(client: &Client) -> Result<Option<String>> {
+    let row = client
+        .query_one(
+            "SELECT to_regprocedure('pgstac.get_version()') IS NOT NULL",
+            &[],
+        )
+        .await?;
+    if row.get(0) {
+        let row = client.query_one("SELECT pgstac.get_version()", &[]).await?;
+        Ok(Some(row.get(0)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Installs or upgrades pgstac to the `to` version.
+///
+/// `migrations` is a directory laid out like pypgstac's migrations, with full
+/// installs named `pgstac.<version>.sql` and upgrades named
+/// `pgstac.<from>-<to>.sql`. If pgstac isn't installed, the full install is
+/// applied. Otherwise, the shortest chain of upgrades from the installed
+/// version is applied. Everything runs in one transaction, so a failed
+/// migration leaves the database as it was.
+pub async fn migrate(client: &mut Client, migrations: &Path, to: &str) -> Result<Migration> {
+    let from = pgstac_version(client).await?;
+    let files = MigrationFiles::read(migrations)?;
+    let applied = match &from {
+        Some(from) if version(from)? > version(to)? => {
+            return Err(Error::PgstacVersion(format!(
+                "installed pgstac version {} is newer than {}",
+                from, to
+            )))
+        }
+        Some(from) => files.upgrades(from, to)?,
+        None => vec![files.install(to)?],
+    };
+    let transaction = client.transaction().await?;
+    for path in &applied {
+        let sql = tokio::fs::read_to_string(path).await?;
+        transaction.batch_execute(&sql).await?;
+    }
+    transaction.commit().await?;
+    Ok(Migration {
+        from,
+        to: to.to_string(),
+        applied,
+    })
+}
+
+#[derive(Debug, Default)]
+struct MigrationFiles {
+    installs: HashMap<String, PathBuf>,
+    upgrades: HashMap<String, Vec<(String, PathBuf)>>,
+}
+
+impl MigrationFiles {
+    fn read(directory: &Path) -> Result<MigrationFiles> {
+        let mut files = MigrationFiles::default();
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            let versions = if let Some(versions) = path
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .and_then(|file_name| file_name.strip_prefix("pgstac."))
+                .and_then(|file_name| file_name.strip_suffix(".sql"))
+            {
+                versions.to_string()
+            } else {
+                continue;
+            };
+            if let Some((from, to)) = versions.split_once('-') {
+                files
+                    .upgrades
+                    .entry(from.to_string())
+                    .or_default()
+                    .push((to.to_string(), path));
+            } else {
+                let _ = files.installs.insert(versions, path);
+            }
+        }
+        Ok(files)
+    }
+
+    fn install(&self, to: &str) -> Result<PathBuf> {
+        self.installs
+            .get(to)
+            .cloned()
+            .ok_or_else(|| Error::PgstacVersion(format!("no migration installs pgstac {}", to)))
+    }
+
+    fn upgrades(&self, from: &str, to: &str) -> Result<Vec<PathBuf>> {
+        // Breadth-first, so we take the fewest steps.
+        let mut previous: HashMap<&str, (&str, &PathBuf)> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(version) = queue.pop_front() {
+            if version == to {
+                let mut paths = Vec::new();
+                let mut version = to;
+                while let Some(&(before, path)) = previous.get(version) {
+                    paths.push(path.clone());
+                    version = before;
+                }
+                paths.reverse();
+                return Ok(paths);
+            }
+            for (next, path) in self.upgrades.get(version).into_iter().flatten() {
+                if next != from && !previous.contains_key(next.as_str()) {
+                    let _ = previous.insert(next, (version, path));
+                    queue.push_back(next);
+                }
+            }
+        }
+        Err(Error::PgstacVersion(format!(
+            "no migrations upgrade pgstac {} to {}",
+            from, to
+        )))
+    }
+}
+
+fn version(s: &str) -> Result<Vec<u64>> {
+    s.split('.')
+        .map(|part| part.parse::<u64>())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| Error::PgstacVersion(format!("invalid pgstac version: {}", s)))
+}
+
+impl Display for Migration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.from {
+            Some(from) if self.applied.is_empty() => {
+                write!(f, "pgstac {} is already installed", from)
+            }
+            Some(from) => write!(
+                f,
+                "Upgraded pgstac from {} to {} ({} migration(s))",
+                from,
+                self.to,
+                self.applied.len()
+            ),
+            None => write!(f, "Installed pgstac {}", self.to),
+        }
+    }
+}
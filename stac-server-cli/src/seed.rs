@@ -0,0 +1,143 @@
+use crate::{Result, DEFAULT_BATCH_SIZE};
+use chrono::{DateTime, Utc};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::json;
+use stac::{Asset, Collection, Item};
+use stac_api_backend::Backend;
+
+/// Generates synthetic collections and items into a backend.
+#[derive(Clone, Debug)]
+pub struct Seeder {
+    /// The number of collections to generate.
+    pub collections: usize,
+
+    /// The number of items to generate in each collection.
+    pub items: usize,
+
+    /// Should each item get a couple of (fake) assets?
+    pub assets: bool,
+
+    /// The random number generator seed, so runs can be reproduced.
+    ///
+    /// If `None`, a random seed is used.
+    pub seed: Option<u64>,
+
+    /// The number of items to add to the backend at a time.
+    pub batch_size: usize,
+}
+
+impl Seeder {
+    /// Generates the collections and items and adds them to the backend.
+    ///
+    /// Collections are named `seed-0`, `seed-1`, etc, and items are named
+    /// `seed-0-0`, `seed-0-1`, etc. Each item gets a random bbox and a random
+    /// datetime between 2000 and 2024, and each collection's extent covers its
+    /// items.
+    pub async fn seed<B>(&self, backend: &mut B) -> Result<()>
+    where
+        B: Backend,
+        stac_api_backend::Error: From<B::Error>,
+    {
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+        for i in 0..self.collections {
+            let collection_id = format!("seed-{}", i);
+            let items: Vec<Item> = (0..self.items)
+                .map(|j| self.item(&mut rng, &collection_id, j))
+                .collect();
+            let collection = collection(&collection_id, &items);
+            backend
+                .upsert_collection(collection)
+                .await
+                .map_err(stac_api_backend::Error::from)?;
+            let mut items = items.into_iter().peekable();
+            while items.peek().is_some() {
+                let batch: Vec<_> = items.by_ref().take(self.batch_size.max(1)).collect();
+                backend
+                    .add_items(batch)
+                    .await
+                    .map_err(stac_api_backend::Error::from)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn item(&self, rng: &mut StdRng, collection_id: &str, i: usize) -> Item {
+        let mut item = Item::new(format!("{}-{}", collection_id, i)).collection(collection_id);
+        let width = rng.random_range(0.01..5.0);
+        let height = rng.random_range(0.01..5.0);
+        let xmin = rng.random_range(-180.0..180.0 - width);
+        let ymin = rng.random_range(-90.0..90.0 - height);
+        let (xmax, ymax) = (xmin + width, ymin + height);
+        item.geometry = Some(
+            serde_json::from_value(json!({
+                "type": "Polygon",
+                "coordinates": [[
+                    [xmin, ymin],
+                    [xmax, ymin],
+                    [xmax, ymax],
+                    [xmin, ymax],
+                    [xmin, ymin],
+                ]],
+            }))
+            .expect("a polygon should deserialize into a geometry"),
+        );
+        item.bbox = Some(vec![xmin, ymin, xmax, ymax]);
+        let timestamp = rng.random_range(946_684_800..1_704_067_200); // 2000 through 2023
+        item.properties.datetime = Some(
+            DateTime::<Utc>::from_timestamp(timestamp, 0)
+                .expect("timestamp should be in range")
+                .to_rfc3339(),
+        );
+        if self.assets {
+            let href = format!("https://example.com/{}/{}", collection_id, item.id);
+            let mut data = Asset::new(format!("{}/data.tif", href));
+            data.r#type = Some("image/tiff; application=geotiff".to_string());
+            data.roles = Some(vec!["data".to_string()]);
+            let mut thumbnail = Asset::new(format!("{}/thumbnail.png", href));
+            thumbnail.r#type = Some("image/png".to_string());
+            thumbnail.roles = Some(vec!["thumbnail".to_string()]);
+            let _ = item.assets.insert("data".to_string(), data);
+            let _ = item.assets.insert("thumbnail".to_string(), thumbnail);
+        }
+        item
+    }
+}
+
+impl Default for Seeder {
+    fn default() -> Seeder {
+        Seeder {
+            collections: 1,
+            items: 100,
+            assets: false,
+            seed: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+}
+
+fn collection(id: &str, items: &[Item]) -> Collection {
+    let mut collection = Collection::new(id, format!("Synthetic collection {}", id));
+    collection.title = Some(format!("Seed collection {}", id));
+    if items.is_empty() {
+        return collection;
+    }
+    let mut bbox = vec![f64::MAX, f64::MAX, f64::MIN, f64::MIN];
+    for item_bbox in items.iter().filter_map(|item| item.bbox.as_ref()) {
+        bbox[0] = bbox[0].min(item_bbox[0]);
+        bbox[1] = bbox[1].min(item_bbox[1]);
+        bbox[2] = bbox[2].max(item_bbox[2]);
+        bbox[3] = bbox[3].max(item_bbox[3]);
+    }
+    collection.extent.spatial.bbox = vec![bbox];
+    // RFC 3339 strings with the same offset sort chronologically.
+    let datetimes = || {
+        items
+            .iter()
+            .filter_map(|item| item.properties.datetime.clone())
+    };
+    collection.extent.temporal.interval = vec![[datetimes().min(), datetimes().max()]];
+    collection
+}
@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use object_store::{
+    aws::AmazonS3Builder, azure::MicrosoftAzureBuilder, gcp::GoogleCloudStorageBuilder,
+    signer::Signer, ObjectStoreScheme,
+};
+use stac_server::AssetSigner;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use url::Url;
+
+/// Presigns S3, Google Cloud Storage, and Azure asset hrefs.
+///
+/// Credentials are read from the environment, the same way as for
+/// [export](crate::export), e.g. `AWS_ACCESS_KEY_ID` or
+/// `GOOGLE_SERVICE_ACCOUNT`. Other hrefs are left alone.
+#[derive(Debug, Default)]
+pub struct ObjectStoreSigner {
+    signers: Mutex<HashMap<String, Arc<dyn Signer>>>,
+}
+
+#[async_trait]
+impl AssetSigner for ObjectStoreSigner {
+    async fn sign(&self, href: &str, expires_in: Duration) -> stac_server::Result<Option<String>> {
+        let url = match Url::parse(href) {
+            Ok(url) => url,
+            Err(_) => return Ok(None),
+        };
+        let (scheme, path) = match ObjectStoreScheme::parse(&url) {
+            Ok((scheme, path)) => (scheme, path),
+            Err(_) => return Ok(None),
+        };
+        let signer = match self
+            .signer(&url, scheme)
+            .map_err(|err| stac_server::Error::AssetSigner(Box::new(err)))?
+        {
+            Some(signer) => signer,
+            None => return Ok(None),
+        };
+        let signed_url = signer
+            .signed_url(http::Method::GET, &path, expires_in)
+            .await
+            .map_err(|err| stac_server::Error::AssetSigner(Box::new(err)))?;
+        Ok(Some(signed_url.to_string()))
+    }
+}
+
+impl ObjectStoreSigner {
+    /// Returns the signer for this url's bucket, creating it if needed.
+    fn signer(
+        &self,
+        url: &Url,
+        scheme: ObjectStoreScheme,
+    ) -> object_store::Result<Option<Arc<dyn Signer>>> {
+        let bucket = format!(
+            "{}://{}/{}",
+            url.scheme(),
+            url.host_str().unwrap_or_default(),
+            url.path_segments()
+                .and_then(|mut segments| segments.next())
+                .unwrap_or_default()
+        );
+        if let Some(signer) = self.signers.lock().unwrap().get(&bucket) {
+            return Ok(Some(Arc::clone(signer)));
+        }
+        let signer: Arc<dyn Signer> = match scheme {
+            ObjectStoreScheme::AmazonS3 => {
+                Arc::new(AmazonS3Builder::from_env().with_url(url.as_str()).build()?)
+            }
+            ObjectStoreScheme::GoogleCloudStorage => Arc::new(
+                GoogleCloudStorageBuilder::from_env()
+                    .with_url(url.as_str())
+                    .build()?,
+            ),
+            ObjectStoreScheme::MicrosoftAzure => Arc::new(
+                MicrosoftAzureBuilder::from_env()
+                    .with_url(url.as_str())
+                    .build()?,
+            ),
+            _ => return Ok(None),
+        };
+        let _ = self
+            .signers
+            .lock()
+            .unwrap()
+            .insert(bucket, Arc::clone(&signer));
+        Ok(Some(signer))
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{Error, Result};
+use serde::Serialize;
+use stac::{Collection, Item};
+use stac_api_backend::{Items, ItemsResult, ReadBackend};
+use stac_validate::{Validate, Validator};
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter},
+};
+
+/// A summary of a backend's validation.
+#[derive(Debug, Default, Serialize)]
+pub struct ValidationReport {
+    /// The number of collections that were checked.
+    pub collections: usize,
+
+    /// The number of items that were checked.
+    pub items: usize,
+
+    /// Everything that's wrong with the backend's contents.
+    pub problems: Vec<ValidationProblem>,
+}
+
+/// Something wrong with a collection or item in a backend.
+#[derive(Debug, Serialize)]
+pub struct ValidationProblem {
+    /// The id of the collection, or of the item's collection.
+    pub collection: String,
+
+    /// The id of the item, if the problem is with an item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item: Option<String>,
+
+    /// A description of what's wrong.
+    pub message: String,
+}
+
+/// Checks every collection and item in a backend.
+///
+/// Each collection and item is validated against the STAC json-schemas, and
+/// each item's `collection` attribute must name the collection it is stored
+/// in. Problems are collected into the returned report instead of stopping
+/// the validation.
+pub async fn validate<B>(backend: &B) -> Result<ValidationReport>
+where
+    B: ReadBackend,
+    stac_api_backend::Error: From<B::Error>,
+{
+    let mut report = ValidationReport::default();
+    let mut validator = Validator::new();
+    let collections = backend
+        .collections()
+        .await
+        .map_err(stac_api_backend::Error::from)?;
+    let collection_ids: HashSet<String> = collections
+        .iter()
+        .map(|collection| collection.id.clone())
+        .collect();
+    for collection in collections {
+        report.collections += 1;
+        let collection_id = collection.id.clone();
+        let (result, returned) = validate_collection(collection, validator).await?;
+        validator = returned;
+        if let Err(err) = result {
+            report.problem(&collection_id, None, err);
+        }
+        let mut paging = B::Paging::default();
+        loop {
+            let query = Items {
+                items: Default::default(),
+                paging,
+                query: None,
+            };
+            let mut page = if let ItemsResult::Page(page) = backend
+                .items(&collection_id, query)
+                .await
+                .map_err(stac_api_backend::Error::from)?
+            {
+                *page
+            } else {
+                break;
+            };
+            page.parse_raw_items()?;
+            let mut items = Vec::with_capacity(page.item_collection.items.len());
+            for item in page.item_collection.items {
+                report.items += 1;
+                let id = item.get("id").and_then(|id| id.as_str()).map(String::from);
+                match Item::try_from(item) {
+                    Ok(item) => items.push(item),
+                    Err(err) => report.problem(&collection_id, id, err),
+                }
+            }
+            for item in &items {
+                match &item.collection {
+                    Some(id) if *id == collection_id => {}
+                    Some(id) if collection_ids.contains(id) => report.problem(
+                        &collection_id,
+                        Some(item.id.clone()),
+                        format!("item belongs to collection {}", id),
+                    ),
+                    Some(id) => report.problem(
+                        &collection_id,
+                        Some(item.id.clone()),
+                        format!("collection {} does not exist", id),
+                    ),
+                    None => report.problem(
+                        &collection_id,
+                        Some(item.id.clone()),
+                        Error::NoCollection(item.id.clone()),
+                    ),
+                }
+            }
+            let (results, returned) = validate_items(items, validator).await?;
+            validator = returned;
+            for (id, result) in results {
+                if let Err(err) = result {
+                    report.problem(&collection_id, Some(id), err);
+                }
+            }
+            if let Some(next) = page.next {
+                paging = next;
+            } else {
+                break;
+            }
+        }
+    }
+    Ok(report)
+}
+
+// Validation fetches schemas with a blocking client, so it runs on a blocking
+// thread. The validator is handed back so its schema cache can be reused.
+
+async fn validate_collection(
+    collection: Collection,
+    mut validator: Validator,
+) -> Result<(Result<()>, Validator)> {
+    tokio::task::spawn_blocking(move || {
+        let result = collection
+            .validate_with_validator(&mut validator)
+            .map_err(Error::from);
+        (result, validator)
+    })
+    .await
+    .map_err(Error::from)
+}
+
+async fn validate_items(
+    items: Vec<Item>,
+    mut validator: Validator,
+) -> Result<(Vec<(String, Result<()>)>, Validator)> {
+    tokio::task::spawn_blocking(move || {
+        let results = items
+            .into_iter()
+            .map(|item| {
+                let result = item
+                    .validate_with_validator(&mut validator)
+                    .map_err(Error::from);
+                (item.id, result)
+            })
+            .collect();
+        (results, validator)
+    })
+    .await
+    .map_err(Error::from)
+}
+
+impl ValidationReport {
+    /// Returns true if no problems were found.
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    fn problem(&mut self, collection: &str, item: Option<String>, message: impl ToString) {
+        self.problems.push(ValidationProblem {
+            collection: collection.to_string(),
+            item,
+            message: message.to_string(),
+        });
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for problem in &self.problems {
+            writeln!(f, "{}", problem)?;
+        }
+        write!(
+            f,
+            "Checked {} collection(s) and {} item(s), {} problem(s)",
+            self.collections,
+            self.items,
+            self.problems.len()
+        )
+    }
+}
+
+impl Display for ValidationProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(item) = &self.item {
+            write!(f, "{}/{}: {}", self.collection, item, self.message)
+        } else {
+            write!(f, "{}: {}", self.collection, self.message)
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use crate::{router::error_response, AssetMode, AssetsConfig, Result};
+use async_trait::async_trait;
+use axum::{
+    body::{BoxBody, StreamBody},
+    extract::{Path, State},
+    http::{
+        header::{
+            ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG, LAST_MODIFIED,
+            LOCATION, RANGE,
+        },
+        HeaderMap, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    Extension,
+};
+use serde_json::Value;
+use stac_api::UrlBuilder;
+use stac_api_backend::{Api, LinkDecorator, ReadBackend};
+use std::{fmt::Debug, sync::Arc, time::Duration};
+use url::Url;
+
+/// Response headers that are passed through when proxying an asset.
+const PROXIED_HEADERS: [axum::http::HeaderName; 6] = [
+    ACCEPT_RANGES,
+    CONTENT_LENGTH,
+    CONTENT_RANGE,
+    CONTENT_TYPE,
+    ETAG,
+    LAST_MODIFIED,
+];
+
+/// Signs asset hrefs, e.g. by presigning object store urls, so private assets
+/// can be served through the asset endpoint.
+///
+/// Register a signer with [RouterBuilder::asset_signer](crate::RouterBuilder::asset_signer).
+///
+/// # Examples
+///
+/// ```
+/// use async_trait::async_trait;
+/// use stac_server::AssetSigner;
+/// use std::time::Duration;
+///
+/// #[derive(Debug)]
+/// struct Token(String);
+///
+/// #[async_trait]
+/// impl AssetSigner for Token {
+///     async fn sign(&self, href: &str, _: Duration) -> stac_server::Result<Option<String>> {
+///         Ok(Some(format!("{}?token={}", href, self.0)))
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait AssetSigner: Debug + Send + Sync {
+    /// Returns a signed url for this href, valid for `expires_in`, or `None`
+    /// if this signer doesn't handle the href.
+    async fn sign(&self, href: &str, expires_in: Duration) -> Result<Option<String>>;
+}
+
+/// Everything the asset endpoint needs besides the [Api].
+#[derive(Clone, Debug)]
+pub(crate) struct Assets {
+    pub(crate) config: AssetsConfig,
+    pub(crate) signer: Option<Arc<dyn AssetSigner>>,
+    pub(crate) client: reqwest::Client,
+}
+
+/// Points item asset hrefs at the asset endpoint.
+#[derive(Debug)]
+pub(crate) struct AssetHrefs(pub(crate) UrlBuilder);
+
+impl LinkDecorator for AssetHrefs {
+    fn item(&self, item: &mut stac_api::Item) {
+        let collection_id = item.get("collection").and_then(Value::as_str);
+        let item_id = item.get("id").and_then(Value::as_str);
+        let url = match (collection_id, item_id) {
+            (Some(collection_id), Some(item_id)) => self.0.item(collection_id, item_id).ok(),
+            _ => None,
+        };
+        let (mut url, assets) = match (url, item.get_mut("assets")) {
+            (Some(url), Some(Value::Object(assets))) => (url, assets),
+            _ => return,
+        };
+        for (key, asset) in assets.iter_mut() {
+            if let Ok(mut segments) = url.path_segments_mut() {
+                let _ = segments.push("assets").push(key);
+            }
+            if let Some(asset) = asset.as_object_mut() {
+                let _ = asset.insert("href".to_string(), url.to_string().into());
+            }
+            if let Ok(mut segments) = url.path_segments_mut() {
+                let _ = segments.pop().pop();
+            }
+        }
+    }
+}
+
+/// Redirects to, or proxies, an item's asset.
+pub(crate) async fn asset<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+    Extension(assets): Extension<Assets>,
+    Path((collection_id, item_id, key)): Path<(String, String, String)>,
+    headers: HeaderMap,
+) -> std::result::Result<Response, (StatusCode, String)>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    // The backend's item, so hrefs aren't rewritten to point back here.
+    let item = api
+        .backend
+        .item(&collection_id, &item_id)
+        .await
+        .map_err(|err| error_response(err.into()))?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!(
+                    "no item with id={} in collection={}",
+                    item_id, collection_id
+                ),
+            )
+        })?;
+    let href = item
+        .assets
+        .get(&key)
+        .map(|asset| asset.href.as_str())
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("no asset with key={} on item={}", key, item_id),
+            )
+        })?;
+    // Relative hrefs are relative to the item, so resolve them against its
+    // self href, or its url here if it doesn't have one.
+    let base = match item.links.iter().find(|link| link.rel == "self") {
+        Some(link) => Url::parse(&link.href),
+        None => api.url_builder.item(&collection_id, &item_id),
+    };
+    let href = base
+        .and_then(|base| base.join(href))
+        .map_err(|err| error_response(err.into()))?
+        .to_string();
+    let href = sign(
+        assets.signer.as_ref(),
+        href,
+        Duration::from_secs(assets.config.expires_in),
+    )
+    .await?;
+    match assets.config.mode {
+        AssetMode::Redirect => {
+            Ok((StatusCode::TEMPORARY_REDIRECT, [(LOCATION, href)]).into_response())
+        }
+        AssetMode::Proxy => proxy(&assets.client, &href, &headers).await,
+    }
+}
+
+/// Signs an href if there's a signer that handles it.
+pub(crate) async fn sign(
+    signer: Option<&Arc<dyn AssetSigner>>,
+    href: String,
+    expires_in: Duration,
+) -> std::result::Result<String, (StatusCode, String)> {
+    if let Some(signer) = signer {
+        let signed = signer.sign(&href, expires_in).await.map_err(|err| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("internal server error: {}", err),
+            )
+        })?;
+        Ok(signed.unwrap_or(href))
+    } else {
+        Ok(href)
+    }
+}
+
+async fn proxy(
+    client: &reqwest::Client,
+    href: &str,
+    headers: &HeaderMap,
+) -> std::result::Result<Response, (StatusCode, String)> {
+    let url = Url::parse(href).map_err(|err| error_response(err.into()))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err((
+            StatusCode::BAD_GATEWAY,
+            format!("could not fetch asset: can't proxy {} urls", url.scheme()),
+        ));
+    }
+    let mut request = client.get(url);
+    if let Some(range) = headers.get(RANGE) {
+        request = request.header(RANGE, range);
+    }
+    let response = request.send().await.map_err(|err| {
+        (
+            StatusCode::BAD_GATEWAY,
+            format!("could not fetch asset: {}", err),
+        )
+    })?;
+    let status = response.status();
+    let mut proxied = HeaderMap::new();
+    for name in PROXIED_HEADERS {
+        if let Some(value) = response.headers().get(&name) {
+            let _ = proxied.insert(name, value.clone());
+        }
+    }
+    let body = BoxBody::new(StreamBody::new(response.bytes_stream()));
+    Ok((status, proxied, body).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AssetHrefs;
+    use serde_json::json;
+    use stac_api::UrlBuilder;
+    use stac_api_backend::LinkDecorator;
+
+    #[test]
+    fn asset_hrefs() {
+        let mut item = json!({
+            "type": "Feature",
+            "id": "an-item",
+            "collection": "a-collection",
+            "assets": {
+                "data": {"href": "s3://bucket/data.tif"},
+                "a key": {"href": "s3://bucket/other.tif"},
+            },
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+        AssetHrefs(UrlBuilder::new("http://stac-server.test").unwrap()).item(&mut item);
+        assert_eq!(
+            item["assets"]["data"]["href"],
+            "http://stac-server.test/collections/a-collection/items/an-item/assets/data"
+        );
+        assert_eq!(
+            item["assets"]["a key"]["href"],
+            "http://stac-server.test/collections/a-collection/items/an-item/assets/a%20key"
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use axum::{
+    http::{header::CONTENT_TYPE, HeaderMap},
+    response::Redirect,
+    routing::get,
+    Router,
+};
+use std::path::Path;
+use tower_http::services::{ServeDir, ServeFile};
+
+/// Serves a [STAC Browser](https://github.com/radiantearth/stac-browser)
+/// build at `/browser`, pointed at this API.
+///
+/// The build's `config.js` is replaced with one that sets the catalog url.
+/// STAC Browser should be built with `--pathPrefix=/browser/`, and it uses
+/// hash routing so every page is served by `index.html`.
+pub(crate) fn router(dist: &Path, root_url: &str) -> Router {
+    let config = format!(
+        "window.STAC_BROWSER_CONFIG = {};\n",
+        serde_json::json!({
+            "catalogUrl": format!("{}/", root_url.trim_end_matches('/')),
+            "historyMode": "hash",
+            "pathPrefix": "/browser/",
+        })
+    );
+    let serve_dir = ServeDir::new(dist).fallback(ServeFile::new(dist.join("index.html")));
+    Router::new()
+        .route(
+            "/browser",
+            get(|| async { Redirect::permanent("/browser/") }),
+        )
+        .route(
+            "/browser/config.js",
+            get(|| async move {
+                let mut headers = HeaderMap::new();
+                let _ = headers.insert(CONTENT_TYPE, "application/javascript".parse().unwrap());
+                (headers, config)
+            }),
+        )
+        .nest_service("/browser/", serve_dir)
+}
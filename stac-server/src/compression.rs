@@ -0,0 +1,135 @@
+use crate::Result;
+use axum::http::{header::ACCEPT_ENCODING, HeaderMap, HeaderValue};
+use flate2::{write::GzEncoder, Compression};
+use std::io::Write;
+
+/// The brotli quality of precompressed documents.
+///
+/// They're only compressed once, so this is the slowest, smallest setting.
+const BROTLI_QUALITY: u32 = 11;
+
+/// The brotli window size, as a power of two.
+const BROTLI_WINDOW: u32 = 22;
+
+/// A content coding that a body can be precompressed with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    /// Every precompressed encoding, in order of preference.
+    pub(crate) const ALL: [Encoding; 2] = [Encoding::Brotli, Encoding::Gzip];
+
+    /// The encoding's `Content-Encoding` token.
+    pub(crate) fn token(self) -> &'static str {
+        match self {
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+        }
+    }
+
+    /// The encoding's `Content-Encoding` header value.
+    pub(crate) fn header_value(self) -> HeaderValue {
+        HeaderValue::from_static(self.token())
+    }
+
+    /// Compresses a body.
+    pub(crate) fn compress(self, body: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Encoding::Brotli => {
+                let mut writer =
+                    brotli::CompressorWriter::new(Vec::new(), 4096, BROTLI_QUALITY, BROTLI_WINDOW);
+                writer.write_all(body)?;
+                Ok(writer.into_inner())
+            }
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+                encoder.write_all(body)?;
+                encoder.finish().map_err(Into::into)
+            }
+        }
+    }
+}
+
+/// Returns the client's preferred encoding of the ones that are available,
+/// from the request's `Accept-Encoding`.
+///
+/// Encodings with a `q` of zero are refused, and ties go to the earlier
+/// available encoding. Without an `Accept-Encoding` the body isn't encoded.
+pub(crate) fn negotiate(headers: &HeaderMap, available: &[Encoding]) -> Option<Encoding> {
+    let mut weights: Vec<(&str, f32)> = Vec::new();
+    for value in headers.get_all(ACCEPT_ENCODING) {
+        let Ok(value) = value.to_str() else {
+            continue;
+        };
+        for coding in value.split(',') {
+            let mut parts = coding.split(';').map(str::trim);
+            let token = parts.next().unwrap_or_default();
+            let q = parts
+                .find_map(|param| param.strip_prefix("q="))
+                .and_then(|q| q.parse().ok())
+                .unwrap_or(1.0);
+            if !token.is_empty() {
+                weights.push((token, q));
+            }
+        }
+    }
+    let weight = |encoding: Encoding| {
+        weights
+            .iter()
+            .find(|(token, _)| token.eq_ignore_ascii_case(encoding.token()))
+            .or_else(|| weights.iter().find(|(token, _)| *token == "*"))
+            .map(|&(_, q)| q)
+    };
+    available
+        .iter()
+        .filter_map(|&encoding| weight(encoding).map(|q| (encoding, q)))
+        .filter(|&(_, q)| q > 0.0)
+        .fold(
+            None,
+            |best: Option<(Encoding, f32)>, (encoding, q)| match best {
+                Some((_, best_q)) if best_q >= q => best,
+                _ => Some((encoding, q)),
+            },
+        )
+        .map(|(encoding, _)| encoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Encoding;
+    use axum::http::{header::ACCEPT_ENCODING, HeaderMap};
+
+    fn negotiate(accept_encoding: &str) -> Option<Encoding> {
+        let mut headers = HeaderMap::new();
+        let _ = headers.insert(ACCEPT_ENCODING, accept_encoding.parse().unwrap());
+        super::negotiate(&headers, &Encoding::ALL)
+    }
+
+    #[test]
+    fn negotiate_encoding() {
+        assert_eq!(super::negotiate(&HeaderMap::new(), &Encoding::ALL), None);
+        assert_eq!(negotiate("gzip, deflate, br"), Some(Encoding::Brotli));
+        assert_eq!(negotiate("gzip"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("br;q=0.5, gzip"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("*, br;q=0"), Some(Encoding::Gzip));
+        assert_eq!(negotiate("identity"), None);
+        let mut headers = HeaderMap::new();
+        let _ = headers.insert(ACCEPT_ENCODING, "br".parse().unwrap());
+        assert_eq!(
+            super::negotiate(&headers, &[Encoding::Gzip]),
+            None,
+            "only available encodings are negotiated"
+        );
+    }
+
+    #[test]
+    fn compress() {
+        let body = b"{\"conformsTo\": []}".repeat(100);
+        for encoding in Encoding::ALL {
+            assert!(encoding.compress(&body).unwrap().len() < body.len());
+        }
+    }
+}
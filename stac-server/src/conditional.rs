@@ -0,0 +1,158 @@
+use axum::http::{
+    header::{ACCEPT, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    HeaderMap, HeaderValue,
+};
+use chrono::{DateTime, Utc};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+/// `Last-Modified` and `ETag` validators for a collection or item, from its
+/// `updated` timestamp.
+///
+/// The same timestamp is served in every format, so the ETag is weak and also
+/// hashes the request's `Accept` header and query string.
+#[derive(Debug)]
+pub(crate) struct Validators {
+    last_modified: HeaderValue,
+    etag: HeaderValue,
+}
+
+impl Validators {
+    /// Builds the validators for a request.
+    pub(crate) fn new(
+        updated: DateTime<Utc>,
+        headers: &HeaderMap,
+        query: Option<&str>,
+    ) -> Validators {
+        let mut hasher = Sha256::new();
+        hasher.update(updated.to_rfc3339().as_bytes());
+        for accept in headers.get_all(ACCEPT) {
+            hasher.update(b"\n");
+            hasher.update(accept.as_bytes());
+        }
+        hasher.update(b"\n");
+        hasher.update(query.unwrap_or_default().as_bytes());
+        let hex: String = hasher.finalize()[..8]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        Validators {
+            last_modified: HeaderValue::from_str(
+                &updated.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+            )
+            .expect("http dates are valid header values"),
+            etag: HeaderValue::from_str(&format!("W/\"{}\"", hex))
+                .expect("hex etags are valid header values"),
+        }
+    }
+
+    /// Returns true if the request's copy is current.
+    ///
+    /// As [RFC 9110](https://www.rfc-editor.org/rfc/rfc9110#section-13.2.2)
+    /// says, `If-Modified-Since` is only checked if there's no
+    /// `If-None-Match`.
+    pub(crate) fn not_modified(&self, headers: &HeaderMap) -> bool {
+        if headers.contains_key(IF_NONE_MATCH) {
+            return etag_matches(headers, &self.etag);
+        }
+        let last_modified = self
+            .last_modified
+            .to_str()
+            .ok()
+            .and_then(|value| DateTime::parse_from_rfc2822(value).ok());
+        headers
+            .get(IF_MODIFIED_SINCE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+            .zip(last_modified)
+            .is_some_and(|(since, last_modified)| last_modified <= since)
+    }
+
+    /// Returns the `Last-Modified` and `ETag` headers.
+    pub(crate) fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let _ = headers.insert(LAST_MODIFIED, self.last_modified.clone());
+        let _ = headers.insert(ETAG, self.etag.clone());
+        headers
+    }
+}
+
+/// Returns true if a request has `If-None-Match` or `If-Modified-Since`.
+pub(crate) fn is_conditional(headers: &HeaderMap) -> bool {
+    headers.contains_key(IF_NONE_MATCH) || headers.contains_key(IF_MODIFIED_SINCE)
+}
+
+/// Returns true if the request's `If-None-Match` has this ETag, using the
+/// weak comparison.
+pub(crate) fn etag_matches(headers: &HeaderMap, etag: &HeaderValue) -> bool {
+    let etag = etag.to_str().unwrap_or_default();
+    let etag = etag.trim_start_matches("W/");
+    headers
+        .get_all(IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Parses the RFC 3339 `updated` field of a collection.
+pub(crate) fn updated(fields: &Map<String, Value>) -> Option<DateTime<Utc>> {
+    fields
+        .get("updated")
+        .and_then(Value::as_str)
+        .and_then(|updated| DateTime::parse_from_rfc3339(updated).ok())
+        .map(|updated| updated.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Validators;
+    use axum::http::{
+        header::{ACCEPT, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+        HeaderMap,
+    };
+    use chrono::{DateTime, Utc};
+
+    fn updated() -> DateTime<Utc> {
+        "2023-10-07T12:34:56.789Z".parse().unwrap()
+    }
+
+    #[test]
+    fn if_modified_since() {
+        let validators = Validators::new(updated(), &HeaderMap::new(), None);
+        let headers = validators.headers();
+        assert_eq!(headers[LAST_MODIFIED], "Sat, 07 Oct 2023 12:34:56 GMT");
+        let mut request = HeaderMap::new();
+        let _ = request.insert(IF_MODIFIED_SINCE, headers[LAST_MODIFIED].clone());
+        assert!(validators.not_modified(&request));
+        let _ = request.insert(
+            IF_MODIFIED_SINCE,
+            "Sat, 07 Oct 2023 12:34:55 GMT".parse().unwrap(),
+        );
+        assert!(!validators.not_modified(&request));
+    }
+
+    #[test]
+    fn if_none_match() {
+        let validators = Validators::new(updated(), &HeaderMap::new(), Some("f=json"));
+        let etag = validators.headers()[ETAG].clone();
+        assert!(etag.to_str().unwrap().starts_with("W/\""));
+        let mut request = HeaderMap::new();
+        let _ = request.insert(IF_NONE_MATCH, etag.clone());
+        assert!(validators.not_modified(&request));
+
+        // If-None-Match wins over If-Modified-Since.
+        let _ = request.insert(IF_NONE_MATCH, "\"something-else\"".parse().unwrap());
+        let _ = request.insert(
+            IF_MODIFIED_SINCE,
+            "Sat, 07 Oct 2023 12:34:56 GMT".parse().unwrap(),
+        );
+        assert!(!validators.not_modified(&request));
+
+        let mut html = HeaderMap::new();
+        let _ = html.insert(ACCEPT, "text/html".parse().unwrap());
+        let other = Validators::new(updated(), &html, Some("f=json"));
+        assert_ne!(other.headers()[ETAG], etag);
+    }
+}
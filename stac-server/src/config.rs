@@ -1,5 +1,7 @@
 use serde::Deserialize;
 use stac::Catalog;
+use stac_api_backend::{CollectionDefaults, DEFAULT_LIMIT};
+use std::{collections::HashMap, path::PathBuf};
 
 /// Server configuration.
 #[derive(Clone, Debug, Deserialize)]
@@ -14,6 +16,472 @@ pub struct Config {
 
     /// The catalog that will serve as the landing page.
     pub catalog: Catalog,
+
+    /// The number of items per page when a request doesn't set a limit.
+    #[serde(default = "default_limit")]
+    pub default_limit: u64,
+
+    /// The largest number of items per page.
+    ///
+    /// Requests for more items are clamped to this limit.
+    #[serde(default)]
+    pub max_limit: Option<u64>,
+
+    /// The largest number of items per page, whatever else is configured.
+    ///
+    /// Requests for more items are rejected, and every other limit
+    /// (including per-collection ones) is clamped to this. Defaults to
+    /// 10,000.
+    #[serde(default)]
+    pub limit_ceiling: Option<u64>,
+
+    /// Should item pages include the deprecated context object, as well as
+    /// `numberMatched` and `numberReturned`?
+    ///
+    /// Defaults to true.
+    #[serde(default = "default_context")]
+    pub context: bool,
+
+    /// Should items requests with unknown query parameters be rejected?
+    ///
+    /// Some OGC API compliance suites expect a `400 Bad Request` for a
+    /// parameter that the server doesn't understand. By default, unknown
+    /// parameters are ignored.
+    #[serde(default)]
+    pub strict_parameters: bool,
+
+    /// How many seconds a listing of the backend's collections is reused by
+    /// the root and collections endpoints.
+    ///
+    /// Useful for large catalogs, at the cost of collection changes taking
+    /// this long to show up. By default, collections are listed on every
+    /// request.
+    #[serde(default)]
+    pub collections_ttl: Option<u64>,
+
+    /// The number of decimal places to round coordinates in returned
+    /// geometries to.
+    ///
+    /// Requests can set their own with the `precision` query parameter. If
+    /// neither is set, geometries are returned as stored.
+    #[serde(default)]
+    pub precision: Option<u32>,
+
+    /// A [STAC Browser](https://github.com/radiantearth/stac-browser) build
+    /// directory to serve at `/browser`.
+    ///
+    /// Build STAC Browser with `--pathPrefix=/browser/`. The catalog url is
+    /// set to this server's root when the browser is served.
+    #[serde(default)]
+    pub browser: Option<PathBuf>,
+
+    /// Should this server serve `/sitemap.xml`?
+    ///
+    /// The sitemap lists the html pages of every collection and item, so
+    /// search engines can index a public catalog. Defaults to false.
+    #[serde(default)]
+    pub sitemap: bool,
+
+    /// How many seconds the sitemap index is reused.
+    ///
+    /// The index is cached because building it pages through every item in
+    /// the backend. Defaults to an hour.
+    #[serde(default = "default_sitemap_ttl")]
+    pub sitemap_ttl: u64,
+
+    /// Should this server serve collections as an OGC API - Records catalog
+    /// at `/records`?
+    ///
+    /// See [Records](crate::Records). Defaults to false.
+    #[serde(default)]
+    pub records: bool,
+
+    /// Should this server serve Prometheus metrics at `/metrics`?
+    ///
+    /// The metrics are the backend's [Metrics](stac_api_backend::Metrics):
+    /// its connection pool's gauges and, if the backend is
+    /// [Instrumented](stac_api_backend::Instrumented), each method's
+    /// latencies. Defaults to false.
+    #[serde(default)]
+    pub metrics: bool,
+
+    /// Serve assets at `/collections/{collectionId}/items/{itemId}/assets/{key}`.
+    ///
+    /// If not set, there's no asset endpoint.
+    #[serde(default)]
+    pub assets: Option<AssetsConfig>,
+
+    /// Serve item thumbnails at `/collections/{collectionId}/items/{itemId}/thumbnail`.
+    ///
+    /// If not set, there's no thumbnail endpoint.
+    #[serde(default)]
+    pub thumbnails: Option<ThumbnailsConfig>,
+
+    /// Run search exports as background jobs at `/jobs`.
+    ///
+    /// If not set, there are no job endpoints.
+    #[serde(default)]
+    pub jobs: Option<JobsConfig>,
+
+    /// POST catalog changes to these webhooks.
+    ///
+    /// See [Webhooks](crate::Webhooks). If not set, there are no webhooks.
+    #[serde(default)]
+    pub webhooks: Option<WebhooksConfig>,
+
+    /// Serve CPU profiles at `/admin/pprof/profile`.
+    ///
+    /// Requires the `profiling` feature. If not set, there's no profiling
+    /// endpoint.
+    #[serde(default)]
+    pub profiling: Option<ProfilingConfig>,
+
+    /// Log a warning for every request, and every backend call, that's slow.
+    ///
+    /// If not set, nothing is logged for being slow.
+    #[serde(default)]
+    pub slow_log: Option<SlowLogConfig>,
+
+    /// Paging overrides for specific collections, by collection id.
+    #[serde(default)]
+    pub collections: HashMap<String, CollectionConfig>,
+
+    /// Connection settings for the HTTP server.
+    #[serde(default)]
+    pub http: HttpConfig,
+}
+
+/// Connection settings for the HTTP server.
+///
+/// The defaults suit most deployments. Servers with many concurrent
+/// connections, e.g. behind a load balancer that multiplexes clients over
+/// HTTP/2, might need a longer backlog or more streams per connection.
+///
+/// # Examples
+///
+/// ```toml
+/// [server.http]
+/// http2 = true
+/// max_concurrent_streams = 500
+/// backlog = 4096
+/// nodelay = true
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub struct HttpConfig {
+    /// Should HTTP/1 connections be kept open between requests?
+    ///
+    /// Defaults to true.
+    #[serde(default = "default_keep_alive")]
+    pub keep_alive: bool,
+
+    /// Should the server accept HTTP/2 (without TLS) as well as HTTP/1?
+    ///
+    /// Defaults to false.
+    #[serde(default)]
+    pub http2: bool,
+
+    /// The most concurrent streams a client can open on one HTTP/2 connection.
+    ///
+    /// If not set, hyper's default is used.
+    #[serde(default)]
+    pub max_concurrent_streams: Option<u32>,
+
+    /// The most connections that can wait to be accepted.
+    ///
+    /// Defaults to 1024.
+    #[serde(default = "default_backlog")]
+    pub backlog: u32,
+
+    /// Should `TCP_NODELAY` be set on accepted connections?
+    ///
+    /// This sends small responses right away instead of batching them with
+    /// Nagle's algorithm. Defaults to false.
+    #[serde(default)]
+    pub nodelay: bool,
+}
+
+/// Paging overrides for one collection.
+///
+/// # Examples
+///
+/// ```toml
+/// [server.collections.naip]
+/// default_limit = 5
+/// max_limit = 50
+/// sortby = "-properties.datetime,id"
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct CollectionConfig {
+    /// The number of items per page when a request doesn't set a limit.
+    #[serde(default)]
+    pub default_limit: Option<u64>,
+
+    /// The largest number of items per page.
+    #[serde(default)]
+    pub max_limit: Option<u64>,
+
+    /// The sort order when a request doesn't set one, in the same format as
+    /// the `sortby` query parameter.
+    #[serde(default)]
+    pub sortby: Option<String>,
+}
+
+/// Configuration for the asset endpoint.
+///
+/// Asset hrefs are signed by the router's
+/// [AssetSigner](crate::AssetSigner), if there is one.
+///
+/// # Examples
+///
+/// ```toml
+/// [server.assets]
+/// mode = "proxy"
+/// expires_in = 600
+/// rewrite_hrefs = true
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub struct AssetsConfig {
+    /// How assets are served.
+    #[serde(default)]
+    pub mode: AssetMode,
+
+    /// How long signed urls are valid for, in seconds.
+    #[serde(default = "default_expires_in")]
+    pub expires_in: u64,
+
+    /// Should item asset hrefs point to the asset endpoint?
+    ///
+    /// This hides the storage location from clients, but items then have to
+    /// be parsed on every request.
+    #[serde(default)]
+    pub rewrite_hrefs: bool,
+}
+
+/// How the asset endpoint serves assets.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetMode {
+    /// Redirect to the (signed) asset href.
+    #[default]
+    Redirect,
+
+    /// Stream the asset through the server.
+    Proxy,
+}
+
+/// Configuration for the thumbnail endpoint.
+///
+/// An item's thumbnail is its asset with the `thumbnail` role (or key). If
+/// an item has no thumbnail and `titiler` is set, a preview is rendered from
+/// its cloud-optimized GeoTIFF instead. Thumbnail hrefs are signed by the
+/// router's [AssetSigner](crate::AssetSigner), if there is one.
+///
+/// # Examples
+///
+/// ```toml
+/// [server.thumbnails]
+/// titiler = "https://titiler.xyz"
+/// max_size = 512
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub struct ThumbnailsConfig {
+    /// The root url of a [titiler](https://developmentseed.org/titiler/)
+    /// server that renders previews of cloud-optimized GeoTIFFs.
+    #[serde(default)]
+    pub titiler: Option<String>,
+
+    /// The largest width or height of a rendered preview, in pixels.
+    #[serde(default = "default_max_size")]
+    pub max_size: u32,
+
+    /// The number of thumbnails to keep in memory.
+    #[serde(default = "default_cache_size")]
+    pub cache_size: usize,
+}
+
+/// Configuration for export jobs.
+///
+/// `POST /jobs/export` with a search body starts a job that writes every
+/// matching item to a file in `directory`. Jobs are kept in memory, so they
+/// don't survive a restart.
+///
+/// # Examples
+///
+/// ```toml
+/// [server.jobs]
+/// directory = "/var/lib/stac-server/exports"
+/// destination = "s3://bucket/exports"
+/// max_items = 10000000
+/// max_concurrent = 4
+/// retention = 86400
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub struct JobsConfig {
+    /// The local directory that exports are written to.
+    pub directory: PathBuf,
+
+    /// Where finished exports are uploaded, e.g. `s3://bucket/exports`.
+    ///
+    /// Uploads are handled by the router's
+    /// [ExportStore](crate::ExportStore), and the download link redirects to
+    /// the uploaded file, signed by the router's
+    /// [AssetSigner](crate::AssetSigner) if there is one. If not set,
+    /// exports are served from `directory`.
+    #[serde(default)]
+    pub destination: Option<String>,
+
+    /// The most items one job will export.
+    ///
+    /// Jobs stop once they've written this many items. If not set, every
+    /// matching item is exported.
+    #[serde(default)]
+    pub max_items: Option<u64>,
+
+    /// The most jobs that run at once.
+    ///
+    /// New jobs get a `429 Too Many Requests` while this many are accepted or
+    /// running. If not set, there's no limit.
+    #[serde(default)]
+    pub max_concurrent: Option<usize>,
+
+    /// How long finished jobs are kept, in seconds.
+    ///
+    /// Older jobs are forgotten and their exports are deleted from
+    /// `directory`. Defaults to one day.
+    #[serde(default = "default_retention")]
+    pub retention: u64,
+}
+
+/// Configuration for webhooks.
+///
+/// # Examples
+///
+/// ```toml
+/// [server.webhooks]
+/// urls = ["https://example.com/stac-hook"]
+/// secret = "a shared secret"
+/// retries = 5
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub struct WebhooksConfig {
+    /// The urls that every change is POSTed to.
+    pub urls: Vec<String>,
+
+    /// The key used to sign each delivery.
+    ///
+    /// If not set, deliveries aren't signed.
+    #[serde(default)]
+    pub secret: Option<String>,
+
+    /// How many times a failed delivery is retried before it's logged as a
+    /// dead letter.
+    ///
+    /// Defaults to 3.
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+}
+
+/// Configuration for the CPU profiling endpoint.
+///
+/// `GET /admin/pprof/profile?seconds=30` samples every thread for that long
+/// and responds with a pprof protobuf, e.g. for `go tool pprof`. Add
+/// `format=flamegraph` for an SVG flamegraph instead.
+///
+/// # Examples
+///
+/// ```toml
+/// [server.profiling]
+/// token = "a secret"
+/// max_seconds = 120
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProfilingConfig {
+    /// The bearer token that profile requests must send in their
+    /// `Authorization` header.
+    ///
+    /// If not set, anyone who can reach the server can profile it.
+    #[serde(default)]
+    pub token: Option<String>,
+
+    /// The longest profile, in seconds.
+    ///
+    /// Defaults to 60.
+    #[serde(default = "default_max_seconds")]
+    pub max_seconds: u64,
+}
+
+/// Configuration for the slow request log.
+///
+/// Requests that take at least `request_ms` are logged as warnings with
+/// their method, route, query (with credentials redacted), status, and how
+/// long they took. `backend_ms` is applied by whoever builds the backend,
+/// e.g. with
+/// [Instrumented::slow_call_threshold](stac_api_backend::Instrumented::slow_call_threshold),
+/// and logs each slow backend call's method and parameters, so a slow
+/// request's time can be broken down.
+///
+/// # Examples
+///
+/// ```toml
+/// [server.slow_log]
+/// request_ms = 1000
+/// backend_ms = 500
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SlowLogConfig {
+    /// The shortest request, in milliseconds, that's logged.
+    ///
+    /// If not set, requests aren't logged.
+    #[serde(default)]
+    pub request_ms: Option<u64>,
+
+    /// The shortest backend call, in milliseconds, that's logged.
+    ///
+    /// If not set, backend calls aren't logged.
+    #[serde(default)]
+    pub backend_ms: Option<u64>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            keep_alive: default_keep_alive(),
+            http2: false,
+            max_concurrent_streams: None,
+            backlog: default_backlog(),
+            nodelay: false,
+        }
+    }
+}
+
+impl Default for ThumbnailsConfig {
+    fn default() -> Self {
+        ThumbnailsConfig {
+            titiler: None,
+            max_size: default_max_size(),
+            cache_size: default_cache_size(),
+        }
+    }
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        WebhooksConfig {
+            urls: Vec::new(),
+            secret: None,
+            retries: default_retries(),
+        }
+    }
+}
+
+impl Default for AssetsConfig {
+    fn default() -> Self {
+        AssetsConfig {
+            mode: AssetMode::default(),
+            expires_in: default_expires_in(),
+            rewrite_hrefs: false,
+        }
+    }
 }
 
 impl Config {
@@ -42,6 +510,98 @@ impl Default for Config {
                 "stac-server-rs",
                 "The default STAC API server from stac-server-rs",
             ),
+            default_limit: DEFAULT_LIMIT,
+            max_limit: None,
+            limit_ceiling: None,
+            context: true,
+            strict_parameters: false,
+            collections_ttl: None,
+            precision: None,
+            browser: None,
+            sitemap: false,
+            sitemap_ttl: default_sitemap_ttl(),
+            records: false,
+            metrics: false,
+            assets: None,
+            thumbnails: None,
+            jobs: None,
+            webhooks: None,
+            profiling: None,
+            slow_log: None,
+            collections: HashMap::new(),
+            http: HttpConfig::default(),
         }
     }
 }
+
+impl CollectionConfig {
+    /// Returns the api's defaults for this collection.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use stac_server::CollectionConfig;
+    ///
+    /// let config = CollectionConfig {
+    ///     sortby: Some("-properties.datetime,id".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(config.collection_defaults().sortby.unwrap().len(), 2);
+    /// ```
+    pub fn collection_defaults(&self) -> CollectionDefaults {
+        CollectionDefaults {
+            default_limit: self.default_limit,
+            max_limit: self.max_limit,
+            sortby: self.sortby.as_ref().map(|sortby| {
+                sortby
+                    .split(',')
+                    .map(|s| s.parse().unwrap_or_else(|never| match never {}))
+                    .collect()
+            }),
+        }
+    }
+}
+
+fn default_limit() -> u64 {
+    DEFAULT_LIMIT
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+fn default_max_seconds() -> u64 {
+    60
+}
+
+fn default_context() -> bool {
+    true
+}
+
+fn default_expires_in() -> u64 {
+    3600
+}
+
+fn default_max_size() -> u32 {
+    256
+}
+
+fn default_cache_size() -> usize {
+    1000
+}
+
+fn default_sitemap_ttl() -> u64 {
+    60 * 60
+}
+
+fn default_retention() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_keep_alive() -> bool {
+    true
+}
+
+fn default_backlog() -> u32 {
+    1024
+}
@@ -3,6 +3,19 @@ use thiserror::Error;
 /// Crate-specific error enum.
 #[derive(Debug, Error)]
 pub enum Error {
+    /// An [AssetSigner](crate::AssetSigner) could not sign an href.
+    #[error("could not sign asset href: {0}")]
+    AssetSigner(Box<dyn std::error::Error + Send + Sync>),
+
+    /// An export job could not write or upload its items.
+    #[error("could not export items: {0}")]
+    Export(Box<dyn std::error::Error + Send + Sync>),
+
+    /// The config asks for something this crate was built without, e.g.
+    /// profiling without the `profiling` feature.
+    #[error("stac-server was built without the {0} feature")]
+    MissingFeature(&'static str),
+
     /// [std::net::AddrParseError]
     #[error(transparent)]
     AddrParse(#[from] std::net::AddrParseError),
@@ -11,13 +24,17 @@ pub enum Error {
     #[error(transparent)]
     Hyper(#[from] hyper::Error),
 
+    /// [axum::http::header::InvalidHeaderValue]
+    #[error(transparent)]
+    InvalidHeaderValue(#[from] axum::http::header::InvalidHeaderValue),
+
     /// [std::io::Error]
     #[error(transparent)]
     Io(#[from] std::io::Error),
 
-    /// [serde_qs::Error]
+    /// [serde_json::Error]
     #[error(transparent)]
-    SerdeQs(#[from] serde_qs::Error),
+    SerdeJson(#[from] serde_json::Error),
 
     /// [stac_api::Error]
     #[error(transparent)]
@@ -0,0 +1,50 @@
+use aide::axum::ApiRouter;
+use stac::Link;
+use stac_api::UrlBuilder;
+use stac_api_backend::{Api, ReadBackend, StacExtension};
+use std::{
+    fmt::{Debug, Formatter},
+    sync::Arc,
+};
+
+/// A [StacExtension] that serves its own routes, e.g. `/aggregate`.
+///
+/// Register these with [api_with_extensions](crate::api_with_extensions).
+pub trait ExtensionRoutes<B: ReadBackend>: StacExtension {
+    /// Adds this extension's routes to the router.
+    ///
+    /// The routes are added after the core routes, and share the [Api] as
+    /// state. It is behind an [Arc] so handlers don't clone it per request.
+    fn routes(&self, router: ApiRouter<Arc<Api<B>>>) -> ApiRouter<Arc<Api<B>>>;
+}
+
+/// Lets the [Api] see a routed extension as a plain [StacExtension].
+pub(crate) struct Registered<B: ReadBackend>(pub(crate) Arc<dyn ExtensionRoutes<B>>);
+
+impl<B: ReadBackend> Debug for Registered<B> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Registered").field(&self.0).finish()
+    }
+}
+
+impl<B: ReadBackend> StacExtension for Registered<B> {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn conformance_classes(&self) -> Vec<String> {
+        self.0.conformance_classes()
+    }
+
+    fn parameters(&self) -> Vec<String> {
+        self.0.parameters()
+    }
+
+    fn items(&self, items: &mut stac_api::Items) -> stac_api_backend::Result<()> {
+        self.0.items(items)
+    }
+
+    fn landing_links(&self, url_builder: &UrlBuilder) -> stac_api_backend::Result<Vec<Link>> {
+        self.0.landing_links(url_builder)
+    }
+}
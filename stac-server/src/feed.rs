@@ -0,0 +1,153 @@
+use crate::html::escape;
+use serde_json::Value;
+use stac::Collection;
+use std::fmt::Write;
+
+/// Used when neither the feed nor an entry has any timestamps.
+const EPOCH: &str = "1970-01-01T00:00:00Z";
+
+/// Item properties that say when an item was last changed, in order of preference.
+const UPDATED_PROPERTIES: [&str; 3] = ["updated", "created", "datetime"];
+
+/// Writes an [Atom](https://www.rfc-editor.org/rfc/rfc4287) feed of items,
+/// most recently updated first.
+///
+/// Each entry links to its item's `self` link.
+pub(crate) fn atom(
+    collection: &Collection,
+    mut items: Vec<stac_api::Item>,
+    feed_url: &str,
+) -> String {
+    items.sort_by(|a, b| updated(b).cmp(&updated(a)));
+    let feed_updated = items.first().and_then(updated).unwrap_or(EPOCH);
+    let collection_url = collection
+        .links
+        .iter()
+        .find(|link| link.rel == "self")
+        .map(|link| link.href.as_str());
+
+    let mut feed = String::new();
+    feed.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    feed.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    writeln!(feed, "  <id>{}</id>", escape(feed_url)).expect("writing to a string is infallible");
+    writeln!(
+        feed,
+        "  <title>{}</title>",
+        escape(collection.title.as_deref().unwrap_or(&collection.id))
+    )
+    .expect("writing to a string is infallible");
+    writeln!(feed, "  <updated>{}</updated>", escape(feed_updated))
+        .expect("writing to a string is infallible");
+    writeln!(
+        feed,
+        "  <link rel=\"self\" type=\"application/atom+xml\" href=\"{}\"/>",
+        escape(feed_url)
+    )
+    .expect("writing to a string is infallible");
+    if let Some(collection_url) = collection_url {
+        writeln!(
+            feed,
+            "  <link rel=\"alternate\" type=\"application/json\" href=\"{}\"/>",
+            escape(collection_url)
+        )
+        .expect("writing to a string is infallible");
+    }
+    for item in &items {
+        entry(&mut feed, item, feed_updated);
+    }
+    feed.push_str("</feed>\n");
+    feed
+}
+
+fn entry(feed: &mut String, item: &stac_api::Item, feed_updated: &str) {
+    let id = item.get("id").and_then(Value::as_str).unwrap_or_default();
+    let href = item
+        .get("links")
+        .and_then(Value::as_array)
+        .and_then(|links| {
+            links
+                .iter()
+                .find(|link| link.get("rel").and_then(Value::as_str) == Some("self"))
+        })
+        .and_then(|link| link.get("href"))
+        .and_then(Value::as_str);
+    let properties = item.get("properties");
+    let title = properties
+        .and_then(|properties| properties.get("title"))
+        .and_then(Value::as_str)
+        .unwrap_or(id);
+    feed.push_str("  <entry>\n");
+    writeln!(feed, "    <id>{}</id>", escape(href.unwrap_or(id)))
+        .expect("writing to a string is infallible");
+    writeln!(feed, "    <title>{}</title>", escape(title))
+        .expect("writing to a string is infallible");
+    writeln!(
+        feed,
+        "    <updated>{}</updated>",
+        escape(updated(item).unwrap_or(feed_updated))
+    )
+    .expect("writing to a string is infallible");
+    if let Some(href) = href {
+        writeln!(
+            feed,
+            "    <link rel=\"alternate\" type=\"application/geo+json\" href=\"{}\"/>",
+            escape(href)
+        )
+        .expect("writing to a string is infallible");
+    }
+    if let Some(description) = properties
+        .and_then(|properties| properties.get("description"))
+        .and_then(Value::as_str)
+    {
+        writeln!(feed, "    <summary>{}</summary>", escape(description))
+            .expect("writing to a string is infallible");
+    }
+    feed.push_str("  </entry>\n");
+}
+
+fn updated(item: &stac_api::Item) -> Option<&str> {
+    let properties = item.get("properties")?;
+    UPDATED_PROPERTIES
+        .iter()
+        .find_map(|key| properties.get(*key).and_then(Value::as_str))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use stac::{Collection, Link};
+
+    fn item(id: &str, updated: &str) -> stac_api::Item {
+        json!({
+            "type": "Feature",
+            "id": id,
+            "properties": {"updated": updated, "datetime": null},
+            "links": [{"rel": "self", "href": format!("http://stac-server.test/collections/an-id/items/{}", id)}],
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+    }
+
+    #[test]
+    fn atom() {
+        let mut collection = Collection::new("an-id", "a description");
+        collection.title = Some("A & B".to_string());
+        collection
+            .links
+            .push(Link::self_("http://stac-server.test/collections/an-id"));
+        let feed = super::atom(
+            &collection,
+            vec![
+                item("old", "2023-01-01T00:00:00Z"),
+                item("new", "2023-06-01T00:00:00Z"),
+            ],
+            "http://stac-server.test/collections/an-id/feed.xml",
+        );
+        assert!(feed.contains("<title>A &amp; B</title>"));
+        assert!(feed.contains("<updated>2023-06-01T00:00:00Z</updated>\n  <link"));
+        let new = feed.find("items/new</id>").unwrap();
+        let old = feed.find("items/old</id>").unwrap();
+        assert!(new < old);
+    }
+}
@@ -0,0 +1,280 @@
+use crate::{Output, Renderer, Result};
+use serde_json::Value;
+use stac::{Collection, Item, Link};
+use std::fmt::Write;
+
+/// Renders single items and collections as HTML pages, with a map.
+///
+/// An item's page lists its properties and assets, and a collection's page
+/// lists its description and extents. Both use
+/// [Leaflet](https://leafletjs.com/) to show the item's geometry or the
+/// collection's spatial extent on a map.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HtmlRenderer;
+
+impl Renderer for HtmlRenderer {
+    fn media_type(&self, output: Output<'_>) -> Option<&str> {
+        match output {
+            Output::Item(_) | Output::Collection(_) => Some("text/html"),
+            _ => None,
+        }
+    }
+
+    fn render(&self, output: Output<'_>) -> Result<Vec<u8>> {
+        match output {
+            Output::Item(item) => Ok(item_page(item)?.into_bytes()),
+            Output::Collection(collection) => Ok(collection_page(collection)?.into_bytes()),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    fn format(&self) -> Option<&str> {
+        Some("html")
+    }
+}
+
+fn item_page(item: &Item) -> Result<String> {
+    let mut properties = String::new();
+    if let Value::Object(object) = serde_json::to_value(&item.properties)? {
+        for (key, value) in object {
+            let value = match value {
+                Value::String(s) => s,
+                value => value.to_string(),
+            };
+            writeln!(
+                properties,
+                "<tr><th>{}</th><td>{}</td></tr>",
+                escape(&key),
+                escape(&value)
+            )
+            .expect("writing to a string is infallible");
+        }
+    }
+
+    let mut keys: Vec<_> = item.assets.keys().collect();
+    keys.sort();
+    let mut assets = String::new();
+    for key in keys {
+        let asset = &item.assets[key];
+        writeln!(
+            assets,
+            "<li><a href=\"{}\">{}</a>{}{}</li>",
+            escape(&asset.href),
+            escape(asset.title.as_deref().unwrap_or(key)),
+            asset
+                .r#type
+                .as_deref()
+                .map(|t| format!(" <code>{}</code>", escape(t)))
+                .unwrap_or_default(),
+            asset
+                .roles
+                .as_ref()
+                .map(|roles| format!(" ({})", escape(&roles.join(", "))))
+                .unwrap_or_default(),
+        )
+        .expect("writing to a string is infallible");
+    }
+
+    let mut links = String::new();
+    for link in &item.links {
+        writeln!(links, "{}", link_item(link)).expect("writing to a string is infallible");
+    }
+
+    let preview = item
+        .links
+        .iter()
+        .find(|link| link.rel == "preview")
+        .map(|link| {
+            format!(
+                "    <img src=\"{}\" alt=\"Thumbnail\"/>\n",
+                escape(&link.href)
+            )
+        })
+        .unwrap_or_default();
+    let title = escape(&item.id);
+    Ok(page(
+        &title,
+        &format!(
+            "{preview}    <h2>Properties</h2>
+    <table>
+{properties}    </table>
+    <h2>Assets</h2>
+    <ul>
+{assets}    </ul>
+    <h2>Links</h2>
+    <ul>
+{links}    </ul>
+"
+        ),
+        &serde_json::to_string(&item.geometry)?,
+        None,
+    ))
+}
+
+fn collection_page(collection: &Collection) -> Result<String> {
+    let mut bboxes = String::new();
+    for bbox in &collection.extent.spatial.bbox {
+        writeln!(
+            bboxes,
+            "<li>{}</li>",
+            escape(
+                &bbox
+                    .iter()
+                    .map(f64::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        )
+        .expect("writing to a string is infallible");
+    }
+    let mut intervals = String::new();
+    for interval in &collection.extent.temporal.interval {
+        writeln!(
+            intervals,
+            "<li>{} to {}</li>",
+            escape(interval[0].as_deref().unwrap_or("..")),
+            escape(interval[1].as_deref().unwrap_or(".."))
+        )
+        .expect("writing to a string is infallible");
+    }
+    let mut links = String::new();
+    for link in &collection.links {
+        writeln!(links, "{}", link_item(link)).expect("writing to a string is infallible");
+    }
+
+    let title = escape(collection.title.as_deref().unwrap_or(&collection.id));
+    Ok(page(
+        &title,
+        &format!(
+            "    <p>{description}</p>
+    <h2>Spatial extent</h2>
+    <ul>
+{bboxes}    </ul>
+    <h2>Temporal extent</h2>
+    <ul>
+{intervals}    </ul>
+    <h2>Links</h2>
+    <ul>
+{links}    </ul>
+",
+            description = escape(&collection.description),
+        ),
+        &serde_json::to_string(&crate::records::extent_geometry(collection))?,
+        Some(&serde_json::to_string(&crate::jsonld::dataset(collection))?),
+    ))
+}
+
+/// Writes a page with a heading, a map of the geometry, and then the body.
+///
+/// JSON-LD, if any, goes in the head.
+fn page(title: &str, body: &str, geometry: &str, json_ld: Option<&str>) -> String {
+    // These go in script tags, so they can't close the tag early.
+    let geometry = geometry.replace("</", "<\\/");
+    let json_ld = json_ld
+        .map(|json_ld| {
+            format!(
+                "    <script type=\"application/ld+json\">{}</script>\n",
+                json_ld.replace("</", "<\\/")
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        "<!DOCTYPE html>
+<html>
+  <head>
+    <title>{title}</title>
+    <meta charset=\"utf-8\"/>
+    <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+    <link rel=\"stylesheet\" href=\"https://unpkg.com/leaflet@1.9.4/dist/leaflet.css\"/>
+    <script src=\"https://unpkg.com/leaflet@1.9.4/dist/leaflet.js\"></script>
+{json_ld}    <style>
+      body {{ font-family: sans-serif; margin: 1em 2em; }}
+      #map {{ height: 400px; }}
+      th {{ text-align: left; padding-right: 1em; }}
+    </style>
+  </head>
+  <body>
+    <h1>{title}</h1>
+    <div id=\"map\"></div>
+{body}    <script>
+      var map = L.map('map').setView([0, 0], 1);
+      L.tileLayer('https://tile.openstreetmap.org/{{z}}/{{x}}/{{y}}.png', {{
+        attribution: '&copy; OpenStreetMap contributors'
+      }}).addTo(map);
+      var geometry = {geometry};
+      if (geometry) {{
+        map.fitBounds(L.geoJSON(geometry).addTo(map).getBounds());
+      }}
+    </script>
+  </body>
+</html>
+"
+    )
+}
+
+fn link_item(link: &Link) -> String {
+    format!(
+        "<li>{}: <a href=\"{}\">{}</a></li>",
+        escape(&link.rel),
+        escape(&link.href),
+        escape(link.title.as_deref().unwrap_or(&link.href))
+    )
+}
+
+/// Escapes text for HTML (or XML) content and attribute values.
+pub(crate) fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HtmlRenderer;
+    use crate::{Output, Renderer};
+    use stac::{Asset, Collection, Item};
+
+    #[test]
+    fn item_page() {
+        let mut item = Item::new("<an-id>");
+        let _ = item
+            .properties
+            .additional_fields
+            .insert("platform".to_string(), "sentinel-2a".into());
+        let mut asset = Asset::new("http://stac-server.test/data.tif");
+        asset.r#type = Some("image/tiff; application=geotiff".to_string());
+        let _ = item.assets.insert("data".to_string(), asset);
+        let html = String::from_utf8(HtmlRenderer.render(Output::Item(&item)).unwrap()).unwrap();
+        assert!(html.contains("<title>&lt;an-id&gt;</title>"));
+        assert!(html.contains("<tr><th>platform</th><td>sentinel-2a</td></tr>"));
+        assert!(html.contains("<a href=\"http://stac-server.test/data.tif\">data</a>"));
+        assert!(html.contains("var geometry = null;"));
+        assert!(!html.contains("application/ld+json"));
+        assert!(!html.contains("<img"));
+    }
+
+    #[test]
+    fn collection_page() {
+        let mut collection = Collection::new("an-id", "a <description>");
+        collection.extent.spatial.bbox = vec![vec![-105.0, 40.0, -104.0, 41.0]];
+        let html = String::from_utf8(
+            HtmlRenderer
+                .render(Output::Collection(&collection))
+                .unwrap(),
+        )
+        .unwrap();
+        assert!(html.contains("<title>an-id</title>"));
+        assert!(html.contains("<p>a &lt;description&gt;</p>"));
+        assert!(html.contains("\"type\":\"Polygon\""));
+        assert!(html.contains("<script type=\"application/ld+json\">{\"@context\""));
+    }
+}
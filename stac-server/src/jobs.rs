@@ -0,0 +1,549 @@
+use crate::{assets::sign, router::error_response, AssetSigner, Error, JobsConfig, Result};
+use async_trait::async_trait;
+use axum::{
+    body::StreamBody,
+    extract::{Path, State},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE, LOCATION},
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::{json, value::RawValue, Value};
+use stac_api_backend::{Api, ReadBackend, Search};
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio_util::io::ReaderStream;
+
+/// The number of items requested from the backend at a time.
+const PAGE_SIZE: u64 = 1000;
+
+/// Signed download urls are followed right away, so they don't need to last long.
+const SIGNED_URL_LIFETIME: Duration = Duration::from_secs(60);
+
+/// A file format for export jobs.
+///
+/// Newline-delimited JSON is always available. Register other formats with
+/// [RouterBuilder::export_format](crate::RouterBuilder::export_format), and
+/// request them with the `format` field of the job's search body.
+pub trait ExportFormat: Debug + Send + Sync {
+    /// The format's name, e.g. `ndjson`.
+    fn name(&self) -> &str;
+
+    /// The media type of the exported file.
+    fn media_type(&self) -> &str;
+
+    /// The exported file's extension, without the dot.
+    fn extension(&self) -> &str;
+
+    /// Creates a writer for a new export at `path`.
+    fn writer(&self, path: &std::path::Path) -> Result<Box<dyn ExportWriter>>;
+}
+
+/// Writes the items of one export.
+///
+/// Writers are run on a blocking thread, so they can use blocking io.
+pub trait ExportWriter: Send {
+    /// Writes one item, as raw JSON.
+    fn write(&mut self, item: &RawValue) -> Result<()>;
+
+    /// Finishes the export, after every item has been written.
+    fn finish(self: Box<Self>) -> Result<()>;
+}
+
+/// Uploads finished exports, e.g. to an object store.
+///
+/// Register a store with
+/// [RouterBuilder::export_store](crate::RouterBuilder::export_store), and
+/// set the upload location with [JobsConfig::destination].
+#[async_trait]
+pub trait ExportStore: Debug + Send + Sync {
+    /// Uploads the export at `path` to `destination` as `file_name`, returning
+    /// its href, or `None` if this store doesn't handle the destination.
+    async fn put(
+        &self,
+        path: &std::path::Path,
+        destination: &str,
+        file_name: &str,
+    ) -> Result<Option<String>>;
+}
+
+/// Exports items as newline-delimited JSON.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NdjsonExport;
+
+struct NdjsonWriter(BufWriter<File>);
+
+impl ExportFormat for NdjsonExport {
+    fn name(&self) -> &str {
+        "ndjson"
+    }
+
+    fn media_type(&self) -> &str {
+        "application/x-ndjson"
+    }
+
+    fn extension(&self) -> &str {
+        "ndjson"
+    }
+
+    fn writer(&self, path: &std::path::Path) -> Result<Box<dyn ExportWriter>> {
+        Ok(Box::new(NdjsonWriter(BufWriter::new(File::create(path)?))))
+    }
+}
+
+impl ExportWriter for NdjsonWriter {
+    fn write(&mut self, item: &RawValue) -> Result<()> {
+        self.0.write_all(item.get().as_bytes())?;
+        self.0.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> Result<()> {
+        self.0.flush().map_err(Error::from)
+    }
+}
+
+/// Everything the job endpoints need besides the [Api].
+#[derive(Clone, Debug)]
+pub(crate) struct Jobs {
+    pub(crate) config: JobsConfig,
+    pub(crate) formats: Vec<Arc<dyn ExportFormat>>,
+    pub(crate) store: Option<Arc<dyn ExportStore>>,
+    pub(crate) signer: Option<Arc<dyn AssetSigner>>,
+    pub(crate) jobs: Arc<Mutex<HashMap<String, Job>>>,
+}
+
+/// One export job.
+#[derive(Clone, Debug)]
+pub(crate) struct Job {
+    id: String,
+    status: Status,
+    format: Arc<dyn ExportFormat>,
+    created: String,
+    finished: Option<DateTime<Utc>>,
+    items_written: u64,
+    message: Option<String>,
+
+    /// Where the export ended up, if it was uploaded.
+    href: Option<String>,
+}
+
+/// A job's status, as in OGC API - Processes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Accepted,
+    Running,
+    Successful,
+    Failed,
+}
+
+/// Starts an export job for the search in the request body.
+///
+/// The body's `format` field picks the export format, and defaults to `ndjson`.
+pub(crate) async fn create<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+    Extension(jobs): Extension<Jobs>,
+    Json(mut body): Json<Value>,
+) -> std::result::Result<Response, (StatusCode, String)>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    let format_name = match body.as_object_mut().and_then(|body| body.remove("format")) {
+        Some(Value::String(format)) => format,
+        Some(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "format must be a string".to_string(),
+            ))
+        }
+        None => "ndjson".to_string(),
+    };
+    let format = jobs
+        .formats
+        .iter()
+        .find(|format| format.name() == format_name)
+        .cloned()
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "unsupported export format: {} (supported formats: {})",
+                    format_name,
+                    jobs.formats
+                        .iter()
+                        .map(|format| format.name())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            )
+        })?;
+    let mut search: stac_api::Search = serde_json::from_value(body)
+        .map_err(|err| (StatusCode::BAD_REQUEST, format!("invalid search: {}", err)))?;
+    search.limit = Some(api.limit(Some(PAGE_SIZE)));
+
+    jobs.prune().await;
+    let id = format!("{:032x}", rand::random::<u128>());
+    let job = Job {
+        id: id.clone(),
+        status: Status::Accepted,
+        format,
+        created: now(),
+        finished: None,
+        items_written: 0,
+        message: None,
+        href: None,
+    };
+    jobs.start(job.clone())?;
+    let _job = tokio::spawn(run(
+        api.backend.clone(),
+        Search {
+            search,
+            paging: B::Paging::default(),
+        },
+        jobs,
+        id,
+    ));
+
+    let url = job_url(&api, &job.id).map_err(|err| error_response(err.into()))?;
+    let status = job.status_info(&url);
+    Ok((StatusCode::CREATED, [(LOCATION, url)], Json(status)).into_response())
+}
+
+/// Returns a job's status.
+pub(crate) async fn status<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+    Extension(jobs): Extension<Jobs>,
+    Path(job_id): Path<String>,
+) -> std::result::Result<Json<Value>, (StatusCode, String)>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    jobs.prune().await;
+    let job = jobs.job(&job_id)?;
+    let url = job_url(&api, &job.id).map_err(|err| error_response(err.into()))?;
+    Ok(Json(job.status_info(&url)))
+}
+
+/// Downloads a finished job's export.
+pub(crate) async fn download(
+    Extension(jobs): Extension<Jobs>,
+    Path(job_id): Path<String>,
+) -> std::result::Result<Response, (StatusCode, String)> {
+    jobs.prune().await;
+    let job = jobs.job(&job_id)?;
+    if job.status != Status::Successful {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("job={} has not finished successfully", job_id),
+        ));
+    }
+    if let Some(href) = job.href {
+        let href = sign(jobs.signer.as_ref(), href, SIGNED_URL_LIFETIME).await?;
+        return Ok((StatusCode::TEMPORARY_REDIRECT, [(LOCATION, href)]).into_response());
+    }
+    let file = tokio::fs::File::open(jobs.path(&job))
+        .await
+        .map_err(|err| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("internal server error: {}", err),
+            )
+        })?;
+    let mut headers = HeaderMap::new();
+    if let Ok(media_type) = HeaderValue::from_str(job.format.media_type()) {
+        let _ = headers.insert(CONTENT_TYPE, media_type);
+    }
+    if let Ok(disposition) =
+        HeaderValue::from_str(&format!("attachment; filename=\"{}\"", job.file_name()))
+    {
+        let _ = headers.insert(CONTENT_DISPOSITION, disposition);
+    }
+    Ok((headers, StreamBody::new(ReaderStream::new(file))).into_response())
+}
+
+/// Runs a job to completion, recording how it went.
+async fn run<B: ReadBackend>(backend: B, search: Search<B::Paging>, jobs: Jobs, id: String)
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    jobs.update(&id, |job| job.status = Status::Running);
+    let result = export(backend, search, &jobs, &id).await;
+    jobs.update(&id, |job| {
+        job.finished = Some(Utc::now());
+        match result {
+            Ok(href) => {
+                job.status = Status::Successful;
+                job.href = href;
+            }
+            Err(err) => {
+                tracing::warn!("export job {} failed: {}", job.id, err);
+                job.status = Status::Failed;
+                job.message = Some(err.to_string());
+            }
+        }
+    });
+}
+
+/// Writes every item that matches the search, then uploads the file if
+/// there's a destination.
+async fn export<B: ReadBackend>(
+    backend: B,
+    mut search: Search<B::Paging>,
+    jobs: &Jobs,
+    id: &str,
+) -> Result<Option<String>>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    let job = jobs
+        .job(id)
+        .map_err(|(_, message)| Error::Export(message.into()))?;
+    let path = jobs.path(&job);
+    let mut writer = job.format.writer(&path)?;
+    let mut items_written = 0;
+    loop {
+        let mut page = backend
+            .search(search.clone())
+            .await
+            .map_err(stac_api_backend::Error::from)?;
+        let mut items = match page.raw_items.take() {
+            Some(raw_items) => raw_items,
+            None => page
+                .item_collection
+                .items
+                .iter()
+                .map(serde_json::value::to_raw_value)
+                .collect::<std::result::Result<_, _>>()?,
+        };
+        if let Some(max_items) = jobs.config.max_items {
+            items.truncate(max_items.saturating_sub(items_written) as usize);
+        }
+        items_written += items.len() as u64;
+        writer = tokio::task::spawn_blocking(move || {
+            for item in items {
+                writer.write(&item)?;
+            }
+            Ok::<_, Error>(writer)
+        })
+        .await
+        .map_err(|err| Error::Export(Box::new(err)))??;
+        jobs.update(id, |job| job.items_written = items_written);
+        match page.next {
+            Some(next) if jobs.config.max_items != Some(items_written) => search.paging = next,
+            _ => break,
+        }
+    }
+    tokio::task::spawn_blocking(move || writer.finish())
+        .await
+        .map_err(|err| Error::Export(Box::new(err)))??;
+
+    let destination = match &jobs.config.destination {
+        Some(destination) => destination,
+        None => return Ok(None),
+    };
+    let store = jobs
+        .store
+        .as_ref()
+        .ok_or_else(|| Error::Export("there's no export store for the destination".into()))?;
+    let href = store
+        .put(&path, destination, &job.file_name())
+        .await?
+        .ok_or_else(|| {
+            Error::Export(format!("the export store doesn't handle {}", destination).into())
+        })?;
+    tokio::fs::remove_file(&path).await?;
+    Ok(Some(href))
+}
+
+impl Jobs {
+    /// Adds a new job, unless there are already too many unfinished jobs.
+    fn start(&self, job: Job) -> std::result::Result<(), (StatusCode, String)> {
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(max_concurrent) = self.config.max_concurrent {
+            let unfinished = jobs
+                .values()
+                .filter(|job| matches!(job.status, Status::Accepted | Status::Running))
+                .count();
+            if unfinished >= max_concurrent {
+                return Err((
+                    StatusCode::TOO_MANY_REQUESTS,
+                    format!(
+                        "there are already {} export jobs running, try again later",
+                        unfinished
+                    ),
+                ));
+            }
+        }
+        let _ = jobs.insert(job.id.clone(), job);
+        Ok(())
+    }
+
+    /// Forgets jobs that finished longer ago than the retention period, and
+    /// deletes their exports.
+    async fn prune(&self) {
+        let retention = chrono::Duration::seconds(self.config.retention as i64);
+        let expired: Vec<Job> = {
+            let mut jobs = self.jobs.lock().unwrap();
+            let now = Utc::now();
+            let ids: Vec<String> = jobs
+                .values()
+                .filter(|job| {
+                    job.finished
+                        .is_some_and(|finished| now - finished >= retention)
+                })
+                .map(|job| job.id.clone())
+                .collect();
+            ids.iter().filter_map(|id| jobs.remove(id)).collect()
+        };
+        for job in expired {
+            let path = self.path(&job);
+            if let Err(err) = tokio::fs::remove_file(&path).await {
+                if err.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!("could not delete export {}: {}", path.display(), err);
+                }
+            }
+        }
+    }
+
+    fn job(&self, id: &str) -> std::result::Result<Job, (StatusCode, String)> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| (StatusCode::NOT_FOUND, format!("no job with id={}", id)))
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut Job)) {
+        if let Some(job) = self.jobs.lock().unwrap().get_mut(id) {
+            f(job);
+        }
+    }
+
+    /// The local path of a job's export.
+    fn path(&self, job: &Job) -> PathBuf {
+        self.config.directory.join(job.file_name())
+    }
+}
+
+impl Job {
+    fn file_name(&self) -> String {
+        format!("{}.{}", self.id, self.format.extension())
+    }
+
+    /// Describes the job, with a download link once it's finished.
+    fn status_info(&self, url: &str) -> Value {
+        let mut links = vec![json!({"rel": "self", "type": "application/json", "href": url})];
+        if self.status == Status::Successful {
+            links.push(json!({
+                "rel": "enclosure",
+                "type": self.format.media_type(),
+                "href": format!("{}/download", url),
+            }));
+        }
+        json!({
+            "jobID": self.id,
+            "status": self.status,
+            "format": self.format.name(),
+            "created": self.created,
+            "finished": self
+                .finished
+                .map(|finished| finished.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            "itemsWritten": self.items_written,
+            "message": self.message,
+            "links": links,
+        })
+    }
+}
+
+fn job_url<B: ReadBackend>(api: &Api<B>, id: &str) -> std::result::Result<String, url::ParseError> {
+    Ok(api
+        .url_builder
+        .root()
+        .join(&format!("jobs/{}", id))?
+        .to_string())
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExportFormat, Job, Jobs, NdjsonExport, Status};
+    use crate::JobsConfig;
+    use axum::http::StatusCode;
+    use serde_json::value::to_raw_value;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    fn job(id: &str, status: Status) -> Job {
+        Job {
+            id: id.to_string(),
+            status,
+            format: Arc::new(NdjsonExport),
+            created: super::now(),
+            finished: None,
+            items_written: 0,
+            message: None,
+            href: None,
+        }
+    }
+
+    #[test]
+    fn max_concurrent() {
+        let jobs = Jobs {
+            config: JobsConfig {
+                directory: std::env::temp_dir(),
+                destination: None,
+                max_items: None,
+                max_concurrent: Some(2),
+                retention: 3600,
+            },
+            formats: vec![Arc::new(NdjsonExport)],
+            store: None,
+            signer: None,
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+        };
+        jobs.start(job("a", Status::Running)).unwrap();
+        jobs.start(job("b", Status::Successful)).unwrap();
+        jobs.start(job("c", Status::Accepted)).unwrap();
+        let (status, _) = jobs.start(job("d", Status::Accepted)).unwrap_err();
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        jobs.update("a", |job| job.status = Status::Failed);
+        jobs.start(job("d", Status::Accepted)).unwrap();
+    }
+
+    #[test]
+    fn ndjson() {
+        let directory = std::env::temp_dir().join(format!("ndjson-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("export.ndjson");
+        let mut writer = NdjsonExport.writer(&path).unwrap();
+        for id in ["a", "b"] {
+            writer
+                .write(&to_raw_value(&serde_json::json!({"id": id})).unwrap())
+                .unwrap();
+        }
+        writer.finish().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\"id\":\"a\"}\n{\"id\":\"b\"}\n"
+        );
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{Output, Renderer, Result};
+use serde_json::{json, Map, Value};
+use stac::{Collection, Link};
+use stac_api::Root;
+
+/// Renders the landing page and collections as [schema.org](https://schema.org/)
+/// JSON-LD, so search engines can discover the datasets.
+///
+/// The landing page becomes a [DataCatalog](https://schema.org/DataCatalog)
+/// and each collection becomes a [Dataset](https://schema.org/Dataset). The
+/// same JSON-LD is embedded in html collection pages.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonLdRenderer;
+
+impl Renderer for JsonLdRenderer {
+    fn media_type(&self, output: Output<'_>) -> Option<&str> {
+        match output {
+            Output::Root(_) | Output::Collection(_) => Some("application/ld+json"),
+            _ => None,
+        }
+    }
+
+    fn render(&self, output: Output<'_>) -> Result<Vec<u8>> {
+        match output {
+            Output::Root(root) => Ok(serde_json::to_vec(&data_catalog(root))?),
+            Output::Collection(collection) => Ok(serde_json::to_vec(&dataset(collection))?),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    fn format(&self) -> Option<&str> {
+        Some("jsonld")
+    }
+}
+
+/// Returns a schema.org `DataCatalog` for a landing page.
+pub(crate) fn data_catalog(root: &Root) -> Value {
+    let catalog = &root.catalog;
+    let mut data_catalog = Map::new();
+    let _ = data_catalog.insert("@context".to_string(), "https://schema.org/".into());
+    let _ = data_catalog.insert("@type".to_string(), "DataCatalog".into());
+    let _ = data_catalog.insert(
+        "name".to_string(),
+        catalog.title.as_deref().unwrap_or(&catalog.id).into(),
+    );
+    let _ = data_catalog.insert(
+        "description".to_string(),
+        catalog.description.clone().into(),
+    );
+    if let Some(url) = href(&catalog.links, "self") {
+        let _ = data_catalog.insert("url".to_string(), url.into());
+    }
+    let datasets: Vec<_> = catalog
+        .links
+        .iter()
+        .filter(|link| link.rel == "child")
+        .map(|link| {
+            json!({
+                "@type": "Dataset",
+                "name": link.title.as_deref().unwrap_or(&link.href),
+                "url": link.href,
+            })
+        })
+        .collect();
+    if !datasets.is_empty() {
+        let _ = data_catalog.insert("dataset".to_string(), datasets.into());
+    }
+    Value::Object(data_catalog)
+}
+
+/// Returns a schema.org `Dataset` for a collection.
+pub(crate) fn dataset(collection: &Collection) -> Value {
+    let mut dataset = Map::new();
+    let _ = dataset.insert("@context".to_string(), "https://schema.org/".into());
+    let _ = dataset.insert("@type".to_string(), "Dataset".into());
+    let _ = dataset.insert(
+        "name".to_string(),
+        collection.title.as_deref().unwrap_or(&collection.id).into(),
+    );
+    let _ = dataset.insert(
+        "description".to_string(),
+        collection.description.clone().into(),
+    );
+    let _ = dataset.insert("identifier".to_string(), collection.id.clone().into());
+    if let Some(url) = href(&collection.links, "self") {
+        let _ = dataset.insert("url".to_string(), url.into());
+    }
+    if let Some(keywords) = &collection.keywords {
+        let _ = dataset.insert("keywords".to_string(), keywords.clone().into());
+    }
+    // `proprietary` and `various` aren't licenses, but a license link might be.
+    let license = href(&collection.links, "license").or_else(|| {
+        Some(collection.license.as_str())
+            .filter(|license| !["proprietary", "various"].contains(license))
+    });
+    if let Some(license) = license {
+        let _ = dataset.insert("license".to_string(), license.into());
+    }
+    if let Some(providers) = &collection.providers {
+        let creators: Vec<_> = providers
+            .iter()
+            .map(|provider| {
+                let mut organization = json!({"@type": "Organization", "name": provider.name});
+                if let Some(url) = &provider.url {
+                    organization["url"] = url.clone().into();
+                }
+                organization
+            })
+            .collect();
+        let _ = dataset.insert("creator".to_string(), creators.into());
+    }
+    if let Some([xmin, ymin, xmax, ymax]) | Some([xmin, ymin, _, xmax, ymax, _]) =
+        collection.extent.spatial.bbox.first().map(Vec::as_slice)
+    {
+        // schema.org boxes are "south west north east".
+        let _ = dataset.insert(
+            "spatialCoverage".to_string(),
+            json!({
+                "@type": "Place",
+                "geo": {
+                    "@type": "GeoShape",
+                    "box": format!("{} {} {} {}", ymin, xmin, ymax, xmax),
+                },
+            }),
+        );
+    }
+    if let Some([start, end]) = collection.extent.temporal.interval.first() {
+        if start.is_some() || end.is_some() {
+            let _ = dataset.insert(
+                "temporalCoverage".to_string(),
+                format!(
+                    "{}/{}",
+                    start.as_deref().unwrap_or(".."),
+                    end.as_deref().unwrap_or("..")
+                )
+                .into(),
+            );
+        }
+    }
+    if let Some(items) = href(&collection.links, "items") {
+        let _ = dataset.insert(
+            "distribution".to_string(),
+            json!([{
+                "@type": "DataDownload",
+                "contentUrl": items,
+                "encodingFormat": "application/geo+json",
+            }]),
+        );
+    }
+    Value::Object(dataset)
+}
+
+fn href<'a>(links: &'a [Link], rel: &str) -> Option<&'a str> {
+    links
+        .iter()
+        .find(|link| link.rel == rel)
+        .map(|link| link.href.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use stac::{Catalog, Collection, Link, Provider};
+    use stac_api::{Conformance, Root};
+
+    #[test]
+    fn dataset() {
+        let mut collection = Collection::new("an-id", "a description");
+        collection.title = Some("A title".to_string());
+        collection.license = "CC-BY-4.0".to_string();
+        collection.keywords = Some(vec!["imagery".to_string()]);
+        collection.providers = Some(vec![Provider::new("A provider")]);
+        collection.extent.spatial.bbox = vec![vec![-105.0, 40.0, -104.0, 41.0]];
+        collection.extent.temporal.interval =
+            vec![[Some("2023-01-01T00:00:00Z".to_string()), None]];
+        collection
+            .links
+            .push(Link::self_("http://stac-server.test/collections/an-id"));
+        let dataset = super::dataset(&collection);
+        assert_eq!(dataset["@type"], "Dataset");
+        assert_eq!(dataset["name"], "A title");
+        assert_eq!(dataset["url"], "http://stac-server.test/collections/an-id");
+        assert_eq!(dataset["license"], "CC-BY-4.0");
+        assert_eq!(dataset["creator"][0]["name"], "A provider");
+        assert_eq!(dataset["spatialCoverage"]["geo"]["box"], "40 -105 41 -104");
+        assert_eq!(dataset["temporalCoverage"], "2023-01-01T00:00:00Z/..");
+    }
+
+    #[test]
+    fn data_catalog() {
+        let mut catalog = Catalog::new("an-id", "a description");
+        catalog.links.push(
+            Link::child("http://stac-server.test/collections/a").title(Some("A".to_string())),
+        );
+        let root = Root {
+            catalog,
+            conformance: Conformance {
+                conforms_to: Vec::new(),
+            },
+        };
+        let data_catalog = super::data_catalog(&root);
+        assert_eq!(data_catalog["@type"], "DataCatalog");
+        assert_eq!(data_catalog["name"], "an-id");
+        assert_eq!(data_catalog["dataset"][0]["name"], "A");
+    }
+}
@@ -30,11 +30,46 @@
     unused_results
 )]
 
+mod assets;
+mod browser;
+mod compression;
+mod conditional;
 mod config;
 mod error;
+mod extension;
+mod feed;
+mod html;
+mod jobs;
+mod jsonld;
+mod metrics;
+mod paths;
+mod precision;
+#[cfg(feature = "profiling")]
+mod profiling;
+mod records;
+mod render;
 mod router;
+mod sitemap;
+mod slow;
+mod thumbnail;
+mod webhooks;
 
-pub use {config::Config, error::Error, router::api};
+pub use {
+    assets::AssetSigner,
+    config::{
+        AssetMode, AssetsConfig, CollectionConfig, Config, HttpConfig, JobsConfig, ProfilingConfig,
+        SlowLogConfig, ThumbnailsConfig, WebhooksConfig,
+    },
+    error::Error,
+    extension::ExtensionRoutes,
+    html::HtmlRenderer,
+    jobs::{ExportFormat, ExportStore, ExportWriter, NdjsonExport},
+    jsonld::JsonLdRenderer,
+    records::Records,
+    render::{GeoJsonSeqRenderer, ItemChunks, JsonRenderer, NdjsonRenderer, Output, Renderer},
+    router::{api, api_with_extensions, RouterBuilder},
+    webhooks::{Webhooks, SIGNATURE_HEADER},
+};
 
 /// Crate-specific result type.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -54,15 +89,10 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// ```
 pub async fn serve<B>(backend: B, config: Config) -> Result<()>
 where
-    B: stac_api_backend::Backend,
-    stac_api_backend::Error: From<<B as stac_api_backend::Backend>::Error>,
+    B: stac_api_backend::ReadBackend,
+    stac_api_backend::Error: From<<B as stac_api_backend::ReadBackend>::Error>,
 {
-    let addr = config.addr.parse::<std::net::SocketAddr>()?;
-    let api = api(backend, config)?;
-    axum::Server::bind(&addr)
-        .serve(api.into_make_service())
-        .await
-        .map_err(Error::from)
+    RouterBuilder::new(backend, config).serve().await
 }
 
 // Needed for integration tests.
@@ -0,0 +1,150 @@
+use stac_api_backend::{Metrics, LATENCY_BUCKETS};
+use std::fmt::Write;
+
+/// The media type of the Prometheus text exposition format.
+pub(crate) const METRICS_MEDIA_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Writes a backend's metrics in the Prometheus text exposition format.
+///
+/// Methods that haven't been called are left out.
+pub(crate) fn render(metrics: &Metrics) -> String {
+    let mut text = String::new();
+    if let Some(pool) = &metrics.pool {
+        let gauges = [
+            (
+                "stac_backend_pool_connections",
+                "Open connections in the backend's pool, idle or in use.",
+                u64::from(pool.size),
+            ),
+            (
+                "stac_backend_pool_idle_connections",
+                "Idle connections in the backend's pool.",
+                u64::from(pool.idle),
+            ),
+            (
+                "stac_backend_pool_max_connections",
+                "The most connections the backend's pool will open.",
+                u64::from(pool.max_size),
+            ),
+            (
+                "stac_backend_pool_waiters",
+                "Requests waiting for a connection from the backend's pool.",
+                pool.waiters,
+            ),
+        ];
+        for (name, help, value) in gauges {
+            write_header(&mut text, name, help, "gauge");
+            writeln!(text, "{} {}", name, value).expect("writing to a string is infallible");
+        }
+        let name = "stac_backend_pool_get_timeouts_total";
+        write_header(
+            &mut text,
+            name,
+            "Requests that timed out waiting for a connection from the backend's pool.",
+            "counter",
+        );
+        writeln!(text, "{} {}", name, pool.get_timeouts)
+            .expect("writing to a string is infallible");
+    }
+    let methods: Vec<_> = metrics
+        .methods
+        .iter()
+        .filter(|method| method.count > 0)
+        .collect();
+    if !methods.is_empty() {
+        let name = "stac_backend_request_duration_seconds";
+        write_header(
+            &mut text,
+            name,
+            "Latencies of calls to the backend, by method.",
+            "histogram",
+        );
+        for method in methods {
+            for (le, count) in LATENCY_BUCKETS.iter().zip(&method.buckets) {
+                writeln!(
+                    text,
+                    "{}_bucket{{method=\"{}\",le=\"{}\"}} {}",
+                    name, method.method, le, count
+                )
+                .expect("writing to a string is infallible");
+            }
+            writeln!(
+                text,
+                "{}_bucket{{method=\"{}\",le=\"+Inf\"}} {}",
+                name, method.method, method.count
+            )
+            .expect("writing to a string is infallible");
+            writeln!(
+                text,
+                "{}_sum{{method=\"{}\"}} {}",
+                name,
+                method.method,
+                method.sum.as_secs_f64()
+            )
+            .expect("writing to a string is infallible");
+            writeln!(
+                text,
+                "{}_count{{method=\"{}\"}} {}",
+                name, method.method, method.count
+            )
+            .expect("writing to a string is infallible");
+        }
+    }
+    text
+}
+
+fn write_header(text: &mut String, name: &str, help: &str, kind: &str) {
+    writeln!(text, "# HELP {} {}", name, help).expect("writing to a string is infallible");
+    writeln!(text, "# TYPE {} {}", name, kind).expect("writing to a string is infallible");
+}
+
+#[cfg(test)]
+mod tests {
+    use stac_api_backend::{MethodMetrics, Metrics, PoolMetrics, LATENCY_BUCKETS};
+    use std::time::Duration;
+
+    #[test]
+    fn render() {
+        let metrics = Metrics {
+            pool: Some(PoolMetrics {
+                size: 4,
+                idle: 1,
+                max_size: 10,
+                waiters: 2,
+                get_timeouts: 3,
+            }),
+            methods: vec![
+                MethodMetrics {
+                    method: "search",
+                    buckets: (1..=LATENCY_BUCKETS.len() as u64).collect(),
+                    count: 12,
+                    sum: Duration::from_millis(1500),
+                },
+                MethodMetrics {
+                    method: "item",
+                    buckets: vec![0; LATENCY_BUCKETS.len()],
+                    count: 0,
+                    sum: Duration::ZERO,
+                },
+            ],
+        };
+        let text = super::render(&metrics);
+        assert!(
+            text.contains("# TYPE stac_backend_pool_waiters gauge\nstac_backend_pool_waiters 2\n")
+        );
+        assert!(text.contains("stac_backend_pool_get_timeouts_total 3\n"));
+        assert!(text.contains(
+            "stac_backend_request_duration_seconds_bucket{method=\"search\",le=\"0.005\"} 1\n"
+        ));
+        assert!(text.contains(
+            "stac_backend_request_duration_seconds_bucket{method=\"search\",le=\"+Inf\"} 12\n"
+        ));
+        assert!(text.contains("stac_backend_request_duration_seconds_sum{method=\"search\"} 1.5\n"));
+        assert!(!text.contains("method=\"item\""));
+    }
+
+    #[test]
+    fn render_empty() {
+        assert_eq!(super::render(&Metrics::default()), "");
+    }
+}
@@ -0,0 +1,94 @@
+use axum::{
+    http::{StatusCode, Uri},
+    response::{IntoResponse, Redirect, Response},
+};
+use std::borrow::Cow;
+
+/// Responds to a request that no route matched.
+///
+/// If the path only missed a route because of a trailing slash or repeated
+/// slashes, e.g. `/collections/` or `/collections//an-id`, this redirects to
+/// the normalized path under the root url, keeping the query. The redirect
+/// is a `308 Permanent Redirect`, so the method and body are kept too.
+/// Anything else is a `404 Not Found`.
+///
+/// Paths are otherwise case-sensitive, because collection and item ids are.
+pub(crate) async fn fallback(root_url: &str, uri: Uri) -> Response {
+    let path = uri.path();
+    match normalize(path) {
+        Cow::Owned(normalized) if normalized != path => {
+            let mut location = format!("{}{}", root_url.trim_end_matches('/'), normalized);
+            if let Some(query) = uri.query() {
+                location.push('?');
+                location.push_str(query);
+            }
+            Redirect::permanent(&location).into_response()
+        }
+        _ => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// Collapses repeated slashes and removes a trailing slash, except from the
+/// root.
+fn normalize(path: &str) -> Cow<'_, str> {
+    if !path.contains("//") && (path == "/" || !path.ends_with('/')) {
+        return Cow::Borrowed(path);
+    }
+    let mut normalized = String::with_capacity(path.len());
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        normalized.push('/');
+        normalized.push_str(segment);
+    }
+    if normalized.is_empty() {
+        normalized.push('/');
+    }
+    Cow::Owned(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Config;
+    use axum::{
+        body::Body,
+        http::{header::LOCATION, Request, StatusCode},
+    };
+    use stac_api_backend::MemoryBackend;
+    use tower::ServiceExt;
+
+    #[test]
+    fn normalize() {
+        for (path, expected) in [
+            ("/", "/"),
+            ("//", "/"),
+            ("/collections", "/collections"),
+            ("/collections/", "/collections"),
+            ("/collections//an-id///items/", "/collections/an-id/items"),
+        ] {
+            assert_eq!(super::normalize(path), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn fallback() {
+        let config = Config {
+            addr: "stac-server-rs.test/stac/v1".to_string(),
+            ..Default::default()
+        };
+        let api = crate::api(MemoryBackend::new(), config).unwrap();
+        let request = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = api
+            .clone()
+            .oneshot(request("/collections/?limit=1"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            response.headers()[LOCATION],
+            "http://stac-server-rs.test/stac/v1/collections?limit=1"
+        );
+        let response = api.clone().oneshot(request("/not-a-route")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = api.oneshot(request("/collections")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
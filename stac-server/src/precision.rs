@@ -0,0 +1,155 @@
+use aide::OperationInput;
+use async_trait::async_trait;
+use axum::{
+    extract::FromRequestParts,
+    http::{request::Parts, StatusCode},
+};
+use serde_json::{Number, Value};
+use stac_api_backend::{RawItem, RawItemCollection};
+
+/// The number of decimal places to round coordinates in returned geometries to.
+///
+/// The `precision` query parameter overrides the server's default, which is
+/// layered onto the router as an extension.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Precision(pub(crate) Option<u32>);
+
+impl Precision {
+    /// Rounds the geometry of every item in a page.
+    pub(crate) fn round_items(
+        self,
+        items: &mut RawItemCollection,
+    ) -> Result<(), (StatusCode, String)> {
+        for item in &mut items.items {
+            self.round_raw_item(item)?;
+        }
+        Ok(())
+    }
+
+    /// Rounds the geometry of an item that's still raw JSON.
+    pub(crate) fn round_raw_item(self, item: &mut RawItem) -> Result<(), (StatusCode, String)> {
+        if self.0.is_none() {
+            return Ok(());
+        }
+        let mut value: Value = serde_json::from_str(item.raw.get()).map_err(internal)?;
+        if let Some(geometry) = value.get_mut("geometry") {
+            self.round_geometry(geometry);
+        }
+        item.raw = serde_json::value::to_raw_value(&value).map_err(internal)?;
+        Ok(())
+    }
+
+    /// Rounds the coordinates of a GeoJSON geometry, including the members of
+    /// geometry collections.
+    fn round_geometry(self, geometry: &mut Value) {
+        if let Some(coordinates) = geometry.get_mut("coordinates") {
+            self.round_coordinates(coordinates);
+        }
+        if let Some(Value::Array(geometries)) = geometry.get_mut("geometries") {
+            for geometry in geometries {
+                self.round_geometry(geometry);
+            }
+        }
+    }
+
+    fn round_coordinates(self, coordinates: &mut Value) {
+        match coordinates {
+            Value::Array(values) => {
+                for value in values {
+                    self.round_coordinates(value);
+                }
+            }
+            // Integers are already as precise as they're going to get.
+            Value::Number(number) if number.is_f64() => {
+                if let Some(rounded) = number.as_f64().and_then(|n| self.round(n)) {
+                    *number = rounded;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn round(self, n: f64) -> Option<Number> {
+        let factor = 10f64.powi(i32::try_from(self.0?).ok()?);
+        if !factor.is_finite() {
+            // More decimal places than a float has.
+            return None;
+        }
+        Number::from_f64((n * factor).round() / factor)
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Precision {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _: &S,
+    ) -> Result<Precision, (StatusCode, String)> {
+        let default = parts
+            .extensions
+            .get::<Precision>()
+            .copied()
+            .unwrap_or_default();
+        let query = parts.uri.query().unwrap_or_default();
+        match url::form_urlencoded::parse(query.as_bytes()).find(|(key, _)| key == "precision") {
+            Some((_, value)) => value.parse().map(|n| Precision(Some(n))).map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "invalid precision={}, expected a non-negative integer",
+                        value
+                    ),
+                )
+            }),
+            None => Ok(default),
+        }
+    }
+}
+
+impl OperationInput for Precision {}
+
+fn internal(err: serde_json::Error) -> (StatusCode, String) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("internal server error: {}", err),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Precision;
+    use serde_json::json;
+
+    #[test]
+    fn round_geometry() {
+        let mut geometry = json!({
+            "type": "GeometryCollection",
+            "geometries": [
+                {"type": "Point", "coordinates": [-105.123456, 40.987654]},
+                {"type": "LineString", "coordinates": [[1.55, 2.0], [3, 4.04]]},
+            ]
+        });
+        Precision(Some(1)).round_geometry(&mut geometry);
+        assert_eq!(
+            geometry,
+            json!({
+                "type": "GeometryCollection",
+                "geometries": [
+                    {"type": "Point", "coordinates": [-105.1, 41.0]},
+                    {"type": "LineString", "coordinates": [[1.6, 2.0], [3, 4.0]]},
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn no_precision() {
+        let mut geometry = json!({"type": "Point", "coordinates": [-105.123456, 40.987654]});
+        Precision(None).round_geometry(&mut geometry);
+        assert_eq!(geometry["coordinates"][0], -105.123456);
+        Precision(Some(400)).round_geometry(&mut geometry);
+        assert_eq!(geometry["coordinates"][0], -105.123456);
+    }
+}
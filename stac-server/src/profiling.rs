@@ -0,0 +1,181 @@
+use crate::ProfilingConfig;
+use axum::{
+    extract::Query,
+    http::{
+        header::{AUTHORIZATION, CONTENT_DISPOSITION, CONTENT_TYPE},
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    Extension,
+};
+use pprof::{protos::Message, ProfilerGuardBuilder};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::{fmt::Display, time::Duration};
+
+/// How long a profile runs if the request doesn't say.
+const DEFAULT_SECONDS: u64 = 30;
+
+/// How many times a second every thread is sampled if the request doesn't say.
+const DEFAULT_FREQUENCY: i32 = 100;
+
+/// Libraries whose frames can't be unwound safely from a signal handler.
+const BLOCKLIST: [&str; 4] = ["libc", "libgcc", "pthread", "vdso"];
+
+/// The query of a profile request.
+#[derive(Debug, Deserialize, JsonSchema)]
+pub(crate) struct ProfileQuery {
+    seconds: Option<u64>,
+    frequency: Option<i32>,
+    #[serde(default)]
+    format: ProfileFormat,
+}
+
+/// The format of a profile response.
+#[derive(Clone, Copy, Debug, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum ProfileFormat {
+    #[default]
+    Pprof,
+    Flamegraph,
+}
+
+/// Profiles the server's cpu for a while, then responds with the samples.
+///
+/// Only one profile runs at a time; other requests get `409 Conflict` until
+/// it's done.
+pub(crate) async fn profile(
+    Extension(config): Extension<ProfilingConfig>,
+    Query(query): Query<ProfileQuery>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(token) = &config.token {
+        let authorized = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|bearer| bearer == token);
+        if !authorized {
+            return (StatusCode::UNAUTHORIZED, "a bearer token is required").into_response();
+        }
+    }
+    let seconds = query.seconds.unwrap_or(DEFAULT_SECONDS);
+    if seconds == 0 || seconds > config.max_seconds {
+        return (
+            StatusCode::BAD_REQUEST,
+            format!("seconds must be between 1 and {}", config.max_seconds),
+        )
+            .into_response();
+    }
+    let frequency = query.frequency.unwrap_or(DEFAULT_FREQUENCY);
+    if frequency <= 0 {
+        return (StatusCode::BAD_REQUEST, "frequency must be positive").into_response();
+    }
+    let format = query.format;
+    // The profiler's guard isn't Send, so the whole profile runs on a
+    // blocking thread.
+    let result = tokio::task::spawn_blocking(move || -> pprof::Result<Vec<u8>> {
+        let guard = ProfilerGuardBuilder::default()
+            .frequency(frequency)
+            .blocklist(&BLOCKLIST)
+            .build()?;
+        std::thread::sleep(Duration::from_secs(seconds));
+        let report = guard.report().build()?;
+        match format {
+            ProfileFormat::Pprof => Ok(report.pprof()?.encode_to_vec()),
+            ProfileFormat::Flamegraph => {
+                let mut body = Vec::new();
+                report.flamegraph(&mut body)?;
+                Ok(body)
+            }
+        }
+    })
+    .await;
+    let body = match result {
+        Ok(Ok(body)) => body,
+        Ok(Err(pprof::Error::Running)) => {
+            return (StatusCode::CONFLICT, "a profile is already running").into_response()
+        }
+        Ok(Err(err)) => return internal(err),
+        Err(err) => return internal(err),
+    };
+    let mut headers = HeaderMap::new();
+    match format {
+        ProfileFormat::Pprof => {
+            let _ = headers.insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/octet-stream"),
+            );
+            let _ = headers.insert(
+                CONTENT_DISPOSITION,
+                HeaderValue::from_static("attachment; filename=\"profile.pb\""),
+            );
+        }
+        ProfileFormat::Flamegraph => {
+            let _ = headers.insert(CONTENT_TYPE, HeaderValue::from_static("image/svg+xml"));
+        }
+    }
+    (headers, body).into_response()
+}
+
+fn internal(err: impl Display) -> Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("internal server error: {}", err),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Config, ProfilingConfig};
+    use axum::{
+        body::Body,
+        http::{header::AUTHORIZATION, Request, StatusCode},
+    };
+    use stac_api_backend::MemoryBackend;
+    use tower::ServiceExt;
+
+    fn config() -> Config {
+        Config {
+            profiling: Some(ProfilingConfig {
+                token: Some("a-token".to_string()),
+                max_seconds: 2,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn profile() {
+        let api = crate::api(MemoryBackend::new(), config()).unwrap();
+        let request = |uri: &str, token: &str| {
+            Request::builder()
+                .uri(uri)
+                .header(AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = api
+            .clone()
+            .oneshot(request("/admin/pprof/profile?seconds=1", "not-the-token"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = api
+            .clone()
+            .oneshot(request("/admin/pprof/profile?seconds=3", "a-token"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = api
+            .oneshot(request(
+                "/admin/pprof/profile?seconds=1&format=flamegraph",
+                "a-token",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "image/svg+xml");
+    }
+}
@@ -0,0 +1,259 @@
+use crate::{router::error_response, ExtensionRoutes};
+use aide::axum::{routing::get, ApiRouter};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::{json, Map, Value};
+use stac::{Collection, Link};
+use stac_api::UrlBuilder;
+use stac_api_backend::{Api, ReadBackend, StacExtension};
+use std::sync::Arc;
+
+const CONFORMANCE_CLASSES: [&str; 3] = [
+    "http://www.opengis.net/spec/ogcapi-records-1/1.0/conf/record-core",
+    "http://www.opengis.net/spec/ogcapi-records-1/1.0/conf/record-collection",
+    "http://www.opengis.net/spec/ogcapi-records-1/1.0/conf/json",
+];
+
+/// The link relation for a catalog of records.
+const OGC_CATALOG_REL: &str = "http://www.opengis.net/def/rel/ogc/1.0/ogc-catalog";
+
+/// Serves collection metadata as an [OGC API -
+/// Records](https://ogcapi.ogc.org/records/) catalog, for harvesters that
+/// don't speak STAC.
+///
+/// Every collection becomes a record in a single catalog at `/records`:
+///
+/// - `/records` describes the catalog
+/// - `/records/items` lists every record
+/// - `/records/items/{recordId}` is one collection's record
+///
+/// Enable it with [Config::records](crate::Config::records), or register it
+/// with [RouterBuilder::extension](crate::RouterBuilder::extension).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Records;
+
+impl StacExtension for Records {
+    fn name(&self) -> &str {
+        "records"
+    }
+
+    fn conformance_classes(&self) -> Vec<String> {
+        CONFORMANCE_CLASSES.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn landing_links(&self, url_builder: &UrlBuilder) -> stac_api_backend::Result<Vec<Link>> {
+        Ok(vec![Link::new(
+            url_builder.root().join("records")?,
+            OGC_CATALOG_REL,
+        )
+        .r#type("application/json".to_string())
+        .title("Records".to_string())])
+    }
+}
+
+impl<B: ReadBackend + 'static> ExtensionRoutes<B> for Records
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    fn routes(&self, router: ApiRouter<Arc<Api<B>>>) -> ApiRouter<Arc<Api<B>>> {
+        router
+            .api_route("/records", get(catalog))
+            .api_route("/records/items", get(records))
+            .api_route("/records/items/:record_id", get(record))
+    }
+}
+
+async fn catalog<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+) -> Result<Json<Value>, (StatusCode, String)>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    let url = catalog_url(&api.url_builder).map_err(|err| error_response(err.into()))?;
+    Ok(Json(json!({
+        "id": "records",
+        "type": "Collection",
+        "itemType": "record",
+        "title": api.catalog.title.as_deref().unwrap_or(&api.catalog.id),
+        "description": api.catalog.description,
+        "links": [
+            {"rel": "self", "type": "application/json", "href": url},
+            {"rel": "root", "type": "application/json", "href": api.url_builder.root().as_str()},
+            {"rel": "items", "type": "application/geo+json", "href": format!("{}/items", url)},
+        ],
+    })))
+}
+
+async fn records<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+) -> Result<Json<Value>, (StatusCode, String)>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    // Collections aren't paged, so neither are records.
+    let collections = api.collections().await.map_err(error_response)?;
+    let url = catalog_url(&api.url_builder).map_err(|err| error_response(err.into()))?;
+    let features = collections
+        .collections
+        .iter()
+        .map(|collection| record_from_collection(collection, &url))
+        .collect::<Vec<_>>();
+    Ok(Json(json!({
+        "type": "FeatureCollection",
+        "numberMatched": features.len(),
+        "numberReturned": features.len(),
+        "features": features,
+        "links": [
+            {"rel": "self", "type": "application/geo+json", "href": format!("{}/items", url)},
+            {"rel": "collection", "type": "application/json", "href": url},
+        ],
+    })))
+}
+
+async fn record<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+    Path(record_id): Path<String>,
+) -> Result<Json<Value>, (StatusCode, String)>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    let url = catalog_url(&api.url_builder).map_err(|err| error_response(err.into()))?;
+    if let Some(collection) = api.collection(&record_id).await.map_err(error_response)? {
+        Ok(Json(record_from_collection(&collection, &url)))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            format!("no record with id={}", record_id),
+        ))
+    }
+}
+
+/// Describes a collection as a record in the records catalog at `catalog_url`.
+fn record_from_collection(collection: &Collection, catalog_url: &str) -> Value {
+    let mut properties = Map::new();
+    let _ = properties.insert("type".to_string(), "dataset".into());
+    let _ = properties.insert(
+        "title".to_string(),
+        collection.title.as_deref().unwrap_or(&collection.id).into(),
+    );
+    let _ = properties.insert(
+        "description".to_string(),
+        collection.description.clone().into(),
+    );
+    if let Some(keywords) = &collection.keywords {
+        let _ = properties.insert("keywords".to_string(), keywords.clone().into());
+    }
+    let _ = properties.insert("license".to_string(), collection.license.clone().into());
+    if let Some(providers) = &collection.providers {
+        let contacts: Vec<_> = providers
+            .iter()
+            .map(|provider| {
+                let mut contact = json!({"name": provider.name});
+                if let Some(roles) = &provider.roles {
+                    contact["roles"] = roles.clone().into();
+                }
+                if let Some(url) = &provider.url {
+                    contact["links"] = json!([{"href": url}]);
+                }
+                contact
+            })
+            .collect();
+        let _ = properties.insert("contacts".to_string(), contacts.into());
+    }
+
+    let mut links = vec![
+        json!({
+            "rel": "self",
+            "type": "application/geo+json",
+            "href": format!("{}/items/{}", catalog_url, collection.id),
+        }),
+        json!({"rel": "collection", "type": "application/json", "href": catalog_url}),
+    ];
+    if let Some(link) = collection.links.iter().find(|link| link.rel == "self") {
+        links.push(json!({
+            "rel": "alternate",
+            "type": "application/json",
+            "title": "STAC Collection",
+            "href": link.href,
+        }));
+    }
+    if let Some(link) = collection.links.iter().find(|link| link.rel == "items") {
+        links.push(json!({
+            "rel": "items",
+            "type": "application/geo+json",
+            "href": link.href,
+        }));
+    }
+
+    let time = collection
+        .extent
+        .temporal
+        .interval
+        .first()
+        .filter(|[start, end]| start.is_some() || end.is_some())
+        .map(|[start, end]| {
+            json!({"interval": [
+                start.as_deref().unwrap_or(".."),
+                end.as_deref().unwrap_or(".."),
+            ]})
+        })
+        .unwrap_or(Value::Null);
+
+    json!({
+        "id": collection.id,
+        "type": "Feature",
+        "conformsTo": ["http://www.opengis.net/spec/ogcapi-records-1/1.0/req/record-core"],
+        "geometry": extent_geometry(collection),
+        "time": time,
+        "properties": properties,
+        "links": links,
+    })
+}
+
+/// Returns the collection's overall spatial extent as a GeoJSON polygon, or null.
+pub(crate) fn extent_geometry(collection: &Collection) -> Value {
+    match collection.extent.spatial.bbox.first().map(Vec::as_slice) {
+        Some([xmin, ymin, xmax, ymax]) | Some([xmin, ymin, _, xmax, ymax, _]) => json!({
+            "type": "Polygon",
+            "coordinates": [[[xmin, ymin], [xmax, ymin], [xmax, ymax], [xmin, ymax], [xmin, ymin]]],
+        }),
+        _ => Value::Null,
+    }
+}
+
+fn catalog_url(url_builder: &UrlBuilder) -> Result<String, url::ParseError> {
+    Ok(url_builder.root().join("records")?.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use stac::{Collection, Link, Provider};
+
+    #[test]
+    fn record_from_collection() {
+        let mut collection = Collection::new("an-id", "a description");
+        collection.extent.spatial.bbox = vec![vec![-105.0, 40.0, -104.0, 41.0]];
+        collection.providers = Some(vec![Provider::new("A provider")]);
+        collection
+            .links
+            .push(Link::self_("http://stac-server.test/collections/an-id"));
+        let record = super::record_from_collection(&collection, "http://stac-server.test/records");
+        assert_eq!(record["id"], "an-id");
+        assert_eq!(record["properties"]["type"], "dataset");
+        assert_eq!(record["properties"]["title"], "an-id");
+        assert_eq!(record["properties"]["contacts"][0]["name"], "A provider");
+        assert_eq!(record["geometry"]["type"], "Polygon");
+        assert_eq!(record["time"], serde_json::Value::Null);
+        assert_eq!(
+            record["links"][0]["href"],
+            "http://stac-server.test/records/items/an-id"
+        );
+        assert_eq!(
+            record["links"][2]["href"],
+            "http://stac-server.test/collections/an-id"
+        );
+    }
+}
@@ -0,0 +1,1106 @@
+use crate::{
+    compression::{self, Encoding},
+    conditional::etag_matches,
+    HtmlRenderer, JsonLdRenderer, Result,
+};
+use aide::{
+    gen::GenContext,
+    openapi::{Operation, Response as OpenApiResponse},
+    OperationInput, OperationOutput,
+};
+use async_trait::async_trait;
+use axum::{
+    body::{Bytes, StreamBody},
+    extract::FromRequestParts,
+    http::{
+        header::{ACCEPT, CONTENT_ENCODING, CONTENT_TYPE, ETAG, LINK, VARY},
+        request::Parts,
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    Json,
+};
+use schemars::JsonSchema;
+use sha2::{Digest, Sha256};
+use stac::{Collection, Item, Link};
+use stac_api::{Collections, Conformance, Context, Root};
+use stac_api_backend::{RawItem, RawItemCollection};
+use std::{fmt::Formatter, marker::PhantomData, sync::Arc};
+use url::Url;
+
+/// The output of an endpoint, before it's rendered into a response body.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum Output<'a> {
+    /// The landing page.
+    Root(&'a Root),
+
+    /// The conformance classes.
+    Conformance(&'a Conformance),
+
+    /// All collections.
+    Collections(&'a Collections),
+
+    /// A single collection.
+    Collection(&'a Collection),
+
+    /// A page of items.
+    Items(&'a RawItemCollection),
+
+    /// A single item.
+    Item(&'a Item),
+}
+
+/// The chunks of a streamed page of items.
+pub type ItemChunks = Box<dyn Iterator<Item = Result<Vec<u8>>> + Send>;
+
+/// Renders endpoint outputs into a response format, e.g. GeoJSON or HTML.
+///
+/// Register renderers with [RouterBuilder::renderer](crate::RouterBuilder::renderer).
+/// Each request is rendered by the renderer whose [format](Renderer::format)
+/// matches the `f` query parameter, or else the first renderer whose media
+/// type matches the request's `Accept` header, falling back to JSON.
+/// Responses link to every other format as `alternate` links.
+///
+/// # Examples
+///
+/// ```
+/// use stac_server::{Output, Renderer};
+///
+/// #[derive(Debug)]
+/// struct Text;
+///
+/// impl Renderer for Text {
+///     fn media_type(&self, output: Output<'_>) -> Option<&str> {
+///         match output {
+///             Output::Conformance(_) => Some("text/plain"),
+///             _ => None,
+///         }
+///     }
+///
+///     fn render(&self, output: Output<'_>) -> stac_server::Result<Vec<u8>> {
+///         match output {
+///             Output::Conformance(conformance) => Ok(conformance.conforms_to.join("\n").into_bytes()),
+///             _ => unreachable!(),
+///         }
+///     }
+/// }
+/// ```
+pub trait Renderer: std::fmt::Debug + Send + Sync {
+    /// Returns the media type this renderer produces for this output, or
+    /// `None` if it can't render it.
+    fn media_type(&self, output: Output<'_>) -> Option<&str>;
+
+    /// Renders the output.
+    ///
+    /// Only called if [Renderer::media_type] returned a media type for this output.
+    fn render(&self, output: Output<'_>) -> Result<Vec<u8>>;
+
+    /// Renders a page of items one chunk at a time, so the response can start
+    /// before the whole page is rendered.
+    ///
+    /// Only called for pages of items that [Renderer::media_type] returned a
+    /// media type for. The chunks are rendered as they're sent. By default,
+    /// returns `None`, and the page is rendered with [Renderer::render].
+    fn render_items(&self, items: Arc<RawItemCollection>) -> Option<ItemChunks> {
+        let _ = items;
+        None
+    }
+
+    /// Renders a single item straight from its raw JSON.
+    ///
+    /// Only called for items that [Renderer::media_type] returned a media
+    /// type for. By default, returns `None`, and the item is parsed and
+    /// rendered with [Renderer::render].
+    fn render_raw_item(&self, item: &RawItem) -> Option<Result<Vec<u8>>> {
+        let _ = item;
+        None
+    }
+
+    /// A short name that picks this renderer with the `f` query parameter, e.g. `ndjson`.
+    ///
+    /// By default, a renderer can only be picked by its media type.
+    fn format(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// The default renderer, which writes JSON (or GeoJSON, for items).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonRenderer;
+
+/// Writes pages of items as [GeoJSON text sequences](https://www.rfc-editor.org/rfc/rfc8142).
+///
+/// Each feature is on its own line, prefixed with a record separator.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GeoJsonSeqRenderer;
+
+/// Writes pages of items as newline-delimited JSON, one feature per line.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NdjsonRenderer;
+
+/// An ordered list of renderers.
+#[derive(Clone, Debug)]
+pub(crate) struct Renderers(Vec<Arc<dyn Renderer>>);
+
+/// Picks a request's response format from its `f` query parameter or its
+/// `Accept` header.
+///
+/// Every endpoint that renders an [Output] extracts one of these, so new
+/// formats work everywhere they can render something.
+#[derive(Debug)]
+pub(crate) struct Negotiate {
+    renderers: Renderers,
+    accept: Vec<String>,
+    format: Option<String>,
+}
+
+/// A rendered response body.
+///
+/// The type parameter is only used for the OpenAPI document, which describes
+/// the JSON form of the output.
+#[derive(Debug)]
+pub(crate) struct Rendered<T> {
+    status: StatusCode,
+    content_type: HeaderValue,
+    headers: HeaderMap,
+    body: Body,
+    output: PhantomData<T>,
+}
+
+/// A response body that's serialized once and shared by every response,
+/// e.g. the OpenAPI document.
+///
+/// The body is also compressed once, and each compressed variant is served
+/// as-is to clients that accept it.
+#[derive(Clone, Debug)]
+pub(crate) struct Document {
+    content_type: HeaderValue,
+    body: Bytes,
+    etag: HeaderValue,
+    encoded: Vec<Encoded>,
+}
+
+/// A precompressed variant of a [Document], with its own strong ETag.
+#[derive(Clone, Debug)]
+struct Encoded {
+    encoding: Encoding,
+    body: Bytes,
+    etag: HeaderValue,
+}
+
+/// An output that never changes at runtime, rendered once by every renderer
+/// that can render it.
+///
+/// The documents are in the same order as the [Renderers] they came from.
+#[derive(Clone, Debug)]
+pub(crate) struct Prerendered(Arc<Vec<Option<Document>>>);
+
+/// A renderer, and the media type it renders an output as.
+type Selected<'a> = (&'a Arc<dyn Renderer>, &'a str);
+
+/// A body that's either rendered all at once, shared, or streamed.
+enum Body {
+    Bytes(Vec<u8>),
+    Shared(Bytes),
+    Chunks(ItemChunks),
+}
+
+impl std::fmt::Debug for Body {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Body::Bytes(bytes) => f.debug_tuple("Bytes").field(&bytes.len()).finish(),
+            Body::Shared(bytes) => f.debug_tuple("Shared").field(&bytes.len()).finish(),
+            Body::Chunks(_) => f.debug_tuple("Chunks").finish(),
+        }
+    }
+}
+
+impl Renderer for JsonRenderer {
+    fn media_type(&self, output: Output<'_>) -> Option<&str> {
+        match output {
+            Output::Items(_) | Output::Item(_) => Some("application/geo+json"),
+            _ => Some("application/json"),
+        }
+    }
+
+    fn render(&self, output: Output<'_>) -> Result<Vec<u8>> {
+        let body = match output {
+            Output::Root(root) => serde_json::to_vec(root),
+            Output::Conformance(conformance) => serde_json::to_vec(conformance),
+            Output::Collections(collections) => serde_json::to_vec(collections),
+            Output::Collection(collection) => serde_json::to_vec(collection),
+            Output::Items(items) => serde_json::to_vec(items),
+            Output::Item(item) => serde_json::to_vec(item),
+        }?;
+        Ok(body)
+    }
+
+    fn render_items(&self, items: Arc<RawItemCollection>) -> Option<ItemChunks> {
+        // Everything but the features is rendered up front, and split around
+        // the empty features array.
+        let mut empty = RawItemCollection::new(Vec::new());
+        empty.links = items.links.clone();
+        empty.number_matched = items.number_matched;
+        empty.number_returned = items.number_returned;
+        empty.context = items.context.as_ref().map(|context| Context {
+            returned: context.returned,
+            limit: context.limit,
+            matched: context.matched,
+            additional_fields: context.additional_fields.clone(),
+        });
+        empty.additional_fields = items.additional_fields.clone();
+        let mut header = match serde_json::to_vec(&empty) {
+            Ok(header) => header,
+            Err(err) => return Some(Box::new(std::iter::once(Err(err.into())))),
+        };
+        let features = b"\"features\":[";
+        let split = header
+            .windows(features.len())
+            .position(|window| window == features)?
+            + features.len();
+        let footer = header.split_off(split);
+        let chunks = (0..items.items.len()).map(move |i| {
+            let mut chunk = if i == 0 { Vec::new() } else { vec![b','] };
+            serde_json::to_writer(&mut chunk, &items.items[i])?;
+            Ok(chunk)
+        });
+        Some(Box::new(
+            std::iter::once(Ok(header))
+                .chain(chunks)
+                .chain(std::iter::once(Ok(footer))),
+        ))
+    }
+
+    fn render_raw_item(&self, item: &RawItem) -> Option<Result<Vec<u8>>> {
+        Some(serde_json::to_vec(item).map_err(Into::into))
+    }
+
+    fn format(&self) -> Option<&str> {
+        Some("json")
+    }
+}
+
+impl Renderer for GeoJsonSeqRenderer {
+    fn media_type(&self, output: Output<'_>) -> Option<&str> {
+        match output {
+            Output::Items(_) => Some("application/geo+json-seq"),
+            _ => None,
+        }
+    }
+
+    fn render(&self, output: Output<'_>) -> Result<Vec<u8>> {
+        feature_lines(output, Some(RECORD_SEPARATOR))
+    }
+
+    fn render_items(&self, items: Arc<RawItemCollection>) -> Option<ItemChunks> {
+        Some(feature_line_chunks(items, Some(RECORD_SEPARATOR)))
+    }
+
+    fn format(&self) -> Option<&str> {
+        Some("geojsonseq")
+    }
+}
+
+impl Renderer for NdjsonRenderer {
+    fn media_type(&self, output: Output<'_>) -> Option<&str> {
+        match output {
+            Output::Items(_) => Some("application/x-ndjson"),
+            _ => None,
+        }
+    }
+
+    fn render(&self, output: Output<'_>) -> Result<Vec<u8>> {
+        feature_lines(output, None)
+    }
+
+    fn render_items(&self, items: Arc<RawItemCollection>) -> Option<ItemChunks> {
+        Some(feature_line_chunks(items, None))
+    }
+
+    fn format(&self) -> Option<&str> {
+        Some("ndjson")
+    }
+}
+
+const RECORD_SEPARATOR: u8 = 0x1e;
+
+/// Link relations that are copied from a page of items to `Link` headers,
+/// since line-based formats have nowhere else to put them.
+const PAGING_RELS: [&str; 4] = ["next", "prev", "first", "last"];
+
+fn feature_lines(output: Output<'_>, prefix: Option<u8>) -> Result<Vec<u8>> {
+    let items = match output {
+        Output::Items(items) => items,
+        _ => return Ok(Vec::new()),
+    };
+    let mut body = Vec::new();
+    for item in &items.items {
+        body.extend(feature_line(item, prefix)?);
+    }
+    Ok(body)
+}
+
+fn feature_line_chunks(items: Arc<RawItemCollection>, prefix: Option<u8>) -> ItemChunks {
+    Box::new((0..items.items.len()).map(move |i| feature_line(&items.items[i], prefix)))
+}
+
+fn feature_line(item: &RawItem, prefix: Option<u8>) -> Result<Vec<u8>> {
+    let mut line = Vec::new();
+    if let Some(prefix) = prefix {
+        line.push(prefix);
+    }
+    let mut feature = serde_json::to_vec(item)?;
+    if feature.contains(&b'\n') {
+        // Raw items are passed through as-is, so they might be pretty-printed.
+        let value: serde_json::Value = serde_json::from_slice(&feature)?;
+        feature = serde_json::to_vec(&value)?;
+    }
+    line.extend(feature);
+    line.push(b'\n');
+    Ok(line)
+}
+
+impl Renderers {
+    pub(crate) fn push(&mut self, renderer: Arc<dyn Renderer>) {
+        self.0.push(renderer);
+    }
+
+    /// Picks the renderer for a format from the `f` query parameter, or
+    /// else the first one that matches the `Accept` media ranges, or else JSON
+    /// if there aren't any.
+    ///
+    /// Returns `None` if no renderer for this output has the requested format
+    /// or an accepted media type.
+    fn select(
+        &self,
+        accept: &[String],
+        format: Option<&str>,
+        output: Output<'_>,
+    ) -> Option<(&Arc<dyn Renderer>, &str)> {
+        let renderers = || {
+            self.0.iter().filter_map(|renderer| {
+                renderer
+                    .media_type(output)
+                    .map(|media_type| (renderer, media_type))
+            })
+        };
+        if let Some(format) = format {
+            return renderers().find(|(renderer, _)| {
+                renderer
+                    .format()
+                    .map(|f| f.eq_ignore_ascii_case(format))
+                    .unwrap_or_default()
+            });
+        }
+        if accept.is_empty() {
+            return renderers().next();
+        }
+        accept.iter().find_map(|media_range| {
+            renderers().find(|(_, media_type)| matches(media_range, media_type))
+        })
+    }
+
+    /// Renders an output that never changes with every renderer that can.
+    pub(crate) fn prerender(&self, output: Output<'_>) -> Result<Prerendered> {
+        let documents = self
+            .0
+            .iter()
+            .map(|renderer| {
+                renderer
+                    .media_type(output)
+                    .map(|media_type| Document::new(media_type, renderer.render(output)?))
+                    .transpose()
+            })
+            .collect::<Result<_>>()?;
+        Ok(Prerendered(Arc::new(documents)))
+    }
+
+    /// The media types this output can be rendered as, for error messages.
+    fn media_types(&self, output: Output<'_>) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter_map(|renderer| renderer.media_type(output))
+            .collect()
+    }
+
+    /// The formats that can render this output, for error messages.
+    fn formats(&self, output: Output<'_>) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter(|renderer| renderer.media_type(output).is_some())
+            .filter_map(|renderer| renderer.format())
+            .collect()
+    }
+}
+
+impl Negotiate {
+    /// Reads the format from a request's headers and query string.
+    pub(crate) fn new(renderers: Renderers, headers: &HeaderMap, query: Option<&str>) -> Negotiate {
+        let accept = accept(headers);
+        let format = url::form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+            .find(|(key, _)| key == "f")
+            .map(|(_, value)| value.into_owned());
+        Negotiate {
+            renderers,
+            accept,
+            format,
+        }
+    }
+
+    /// Returns `alternate` links to this output in every other format it can
+    /// be rendered in.
+    ///
+    /// The links are built from the output's `self` link, with the `f` query
+    /// parameter set to each format.
+    pub(crate) fn alternates(&self, output: Output<'_>) -> Vec<Link> {
+        let selected = self
+            .renderers
+            .select(&self.accept, self.format.as_deref(), output)
+            .map(|(renderer, _)| renderer);
+        let url = match self_href(output).and_then(|href| Url::parse(href).ok()) {
+            Some(url) => url,
+            None => return Vec::new(),
+        };
+        let mut links = Vec::new();
+        for renderer in &self.renderers.0 {
+            if selected.is_some_and(|selected| Arc::ptr_eq(selected, renderer)) {
+                continue;
+            }
+            if let (Some(format), Some(media_type)) =
+                (renderer.format(), renderer.media_type(output))
+            {
+                let mut url = url.clone();
+                let query: Vec<(String, String)> = url
+                    .query_pairs()
+                    .filter(|(key, _)| key != "f")
+                    .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                    .collect();
+                let _ = url
+                    .query_pairs_mut()
+                    .clear()
+                    .extend_pairs(query)
+                    .append_pair("f", format);
+                links.push(Link::new(url, "alternate").r#type(media_type.to_string()));
+            }
+        }
+        links
+    }
+
+    /// Renders the output in the negotiated format.
+    ///
+    /// Pages of items also get their paging links as `Link` headers.
+    pub(crate) fn render<T>(
+        &self,
+        output: Output<'_>,
+    ) -> std::result::Result<Rendered<T>, (StatusCode, String)> {
+        let (renderer, media_type) = self.select(output)?;
+        let render = || -> Result<Rendered<T>> {
+            let body = Body::Bytes(renderer.render(output)?);
+            Rendered::new(media_type, output, body)
+        };
+        render().map_err(internal)
+    }
+
+    /// Renders a page of items in the negotiated format, streaming it if the
+    /// renderer can.
+    pub(crate) fn render_items<T>(
+        &self,
+        items: RawItemCollection,
+    ) -> std::result::Result<Rendered<T>, (StatusCode, String)> {
+        let (renderer, media_type) = self.select(Output::Items(&items))?;
+        let render = || -> Result<Rendered<T>> {
+            let items = Arc::new(items);
+            let body = match renderer.render_items(Arc::clone(&items)) {
+                Some(chunks) => Body::Chunks(chunks),
+                None => Body::Bytes(renderer.render(Output::Items(&items))?),
+            };
+            Rendered::new(media_type, Output::Items(&items), body)
+        };
+        render().map_err(internal)
+    }
+
+    /// Renders a raw item in the negotiated format, with its `alternate` links.
+    ///
+    /// The item is only parsed if the renderer can't write it raw, e.g. for HTML.
+    pub(crate) fn render_raw_item<T>(
+        &self,
+        mut item: RawItem,
+    ) -> std::result::Result<Rendered<T>, (StatusCode, String)> {
+        // Picking a renderer and building the alternate links only need the
+        // item's links.
+        let mut links = Item::new(String::new());
+        links.links = item.links.clone();
+        let (renderer, media_type) = self.select(Output::Item(&links))?;
+        item.links.extend(self.alternates(Output::Item(&links)));
+        let render = || -> Result<Rendered<T>> {
+            let body = match renderer.render_raw_item(&item) {
+                Some(body) => body?,
+                None => {
+                    let item: Item = serde_json::from_value(serde_json::to_value(&item)?)?;
+                    renderer.render(Output::Item(&item))?
+                }
+            };
+            Rendered::new(media_type, Output::Item(&links), Body::Bytes(body))
+        };
+        render().map_err(internal)
+    }
+
+    /// Serves a prerendered output in the negotiated format, or `304 Not
+    /// Modified` if the request's `If-None-Match` has the document's ETag.
+    ///
+    /// The output is only rendered again if its renderer wasn't around when
+    /// it was prerendered.
+    pub(crate) fn render_prerendered<T>(
+        &self,
+        output: Output<'_>,
+        prerendered: &Prerendered,
+        headers: &HeaderMap,
+    ) -> std::result::Result<Rendered<T>, (StatusCode, String)> {
+        let (renderer, _) = self.select(output)?;
+        let document = self
+            .renderers
+            .0
+            .iter()
+            .position(|r| Arc::ptr_eq(r, renderer))
+            .and_then(|i| prerendered.0.get(i))
+            .and_then(Option::as_ref);
+        match document {
+            Some(document) => Ok(document.respond(headers)),
+            None => self.render(output),
+        }
+    }
+
+    fn select(
+        &self,
+        output: Output<'_>,
+    ) -> std::result::Result<Selected<'_>, (StatusCode, String)> {
+        self.renderers
+            .select(&self.accept, self.format.as_deref(), output)
+            .ok_or_else(|| {
+                let message = match &self.format {
+                    Some(format) => format!(
+                        "unsupported format: {} (supported formats: {})",
+                        format,
+                        self.renderers.formats(output).join(", ")
+                    ),
+                    None => format!(
+                        "unsupported media type: {} (supported media types: {})",
+                        self.accept.join(", "),
+                        self.renderers.media_types(output).join(", ")
+                    ),
+                };
+                (StatusCode::NOT_ACCEPTABLE, message)
+            })
+    }
+}
+
+impl<T> Rendered<T> {
+    fn new(media_type: &str, output: Output<'_>, body: Body) -> Result<Rendered<T>> {
+        let mut headers = HeaderMap::new();
+        if let Output::Items(items) = output {
+            for link in &items.links {
+                if PAGING_RELS.contains(&link.rel.as_str()) {
+                    let value =
+                        HeaderValue::from_str(&format!("<{}>; rel=\"{}\"", link.href, link.rel))?;
+                    let _ = headers.append(LINK, value);
+                }
+            }
+        }
+        Ok(Rendered {
+            status: StatusCode::OK,
+            content_type: HeaderValue::from_str(media_type)?,
+            headers,
+            body,
+            output: PhantomData,
+        })
+    }
+
+    /// A `304 Not Modified` response, with the representation's validators.
+    pub(crate) fn not_modified(headers: HeaderMap) -> Rendered<T> {
+        Rendered {
+            status: StatusCode::NOT_MODIFIED,
+            content_type: HeaderValue::from_static(""),
+            headers,
+            body: Body::Shared(Bytes::new()),
+            output: PhantomData,
+        }
+    }
+
+    /// Adds headers to the response, e.g. its validators.
+    pub(crate) fn with_headers(mut self, headers: HeaderMap) -> Rendered<T> {
+        self.headers.extend(headers);
+        self
+    }
+}
+
+impl Document {
+    /// Wraps a serialized body, with a strong ETag from its hash, and
+    /// compresses it.
+    ///
+    /// Compressed variants that aren't smaller than the body are dropped.
+    pub(crate) fn new(content_type: &str, body: Vec<u8>) -> Result<Document> {
+        let hex: String = Sha256::digest(&body)[..16]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        let mut encoded = Vec::new();
+        for encoding in Encoding::ALL {
+            let compressed = encoding.compress(&body)?;
+            if compressed.len() < body.len() {
+                encoded.push(Encoded {
+                    encoding,
+                    body: compressed.into(),
+                    etag: HeaderValue::from_str(&format!("\"{}-{}\"", hex, encoding.token()))?,
+                });
+            }
+        }
+        Ok(Document {
+            content_type: HeaderValue::from_str(content_type)?,
+            body: body.into(),
+            etag: HeaderValue::from_str(&format!("\"{}\"", hex))?,
+            encoded,
+        })
+    }
+
+    /// Responds with the document, compressed if the request's
+    /// `Accept-Encoding` allows, or with `304 Not Modified` if the request's
+    /// `If-None-Match` has the ETag of the variant it would get.
+    pub(crate) fn respond<T>(&self, headers: &HeaderMap) -> Rendered<T> {
+        let available: Vec<Encoding> = self
+            .encoded
+            .iter()
+            .map(|encoded| encoded.encoding)
+            .collect();
+        let encoded = compression::negotiate(headers, &available).and_then(|encoding| {
+            self.encoded
+                .iter()
+                .find(|encoded| encoded.encoding == encoding)
+        });
+        let (body, etag) = match encoded {
+            Some(encoded) => (&encoded.body, &encoded.etag),
+            None => (&self.body, &self.etag),
+        };
+        let not_modified = etag_matches(headers, etag);
+        let mut response_headers = HeaderMap::new();
+        let _ = response_headers.insert(ETAG, etag.clone());
+        if !self.encoded.is_empty() {
+            let _ = response_headers.insert(VARY, HeaderValue::from_static("accept-encoding"));
+        }
+        if let Some(encoded) = encoded {
+            if !not_modified {
+                let _ = response_headers.insert(CONTENT_ENCODING, encoded.encoding.header_value());
+            }
+        }
+        Rendered {
+            status: if not_modified {
+                StatusCode::NOT_MODIFIED
+            } else {
+                StatusCode::OK
+            },
+            content_type: self.content_type.clone(),
+            headers: response_headers,
+            body: Body::Shared(if not_modified {
+                Bytes::new()
+            } else {
+                body.clone()
+            }),
+            output: PhantomData,
+        }
+    }
+}
+
+fn internal(err: crate::Error) -> (StatusCode, String) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        format!("internal server error: {}", err),
+    )
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Negotiate {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _: &S,
+    ) -> std::result::Result<Negotiate, (StatusCode, String)> {
+        let renderers = parts
+            .extensions
+            .get::<Renderers>()
+            .cloned()
+            .ok_or_else(|| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "internal server error: no renderers".to_string(),
+                )
+            })?;
+        Ok(Negotiate::new(renderers, &parts.headers, parts.uri.query()))
+    }
+}
+
+impl OperationInput for Negotiate {}
+
+/// Returns the href of the output's `self` link.
+fn self_href(output: Output<'_>) -> Option<&str> {
+    let links = match output {
+        Output::Root(root) => &root.catalog.links,
+        Output::Conformance(_) => return None,
+        Output::Collections(collections) => &collections.links,
+        Output::Collection(collection) => &collection.links,
+        Output::Items(items) => &items.links,
+        Output::Item(item) => &item.links,
+    };
+    links
+        .iter()
+        .find(|link| link.rel == "self")
+        .map(|link| link.href.as_str())
+}
+
+impl Default for Renderers {
+    fn default() -> Self {
+        Renderers(vec![
+            Arc::new(JsonRenderer),
+            Arc::new(GeoJsonSeqRenderer),
+            Arc::new(NdjsonRenderer),
+            Arc::new(HtmlRenderer),
+            Arc::new(JsonLdRenderer),
+        ])
+    }
+}
+
+impl<T> IntoResponse for Rendered<T> {
+    fn into_response(self) -> Response {
+        let mut response = match self.body {
+            Body::Bytes(bytes) => bytes.into_response(),
+            Body::Shared(bytes) => bytes.into_response(),
+            Body::Chunks(chunks) => {
+                StreamBody::new(futures_util::stream::iter(chunks)).into_response()
+            }
+        };
+        *response.status_mut() = self.status;
+        let headers = response.headers_mut();
+        headers.extend(self.headers);
+        // Bodiless 304s don't have a negotiated content type.
+        if self.content_type.is_empty() {
+            let _ = headers.remove(CONTENT_TYPE);
+        } else {
+            let _ = headers.insert(CONTENT_TYPE, self.content_type);
+        }
+        response
+    }
+}
+
+impl<T: JsonSchema> OperationOutput for Rendered<T> {
+    type Inner = T;
+
+    fn operation_response(
+        ctx: &mut GenContext,
+        operation: &mut Operation,
+    ) -> Option<OpenApiResponse> {
+        Json::<T>::operation_response(ctx, operation)
+    }
+
+    fn inferred_responses(
+        ctx: &mut GenContext,
+        operation: &mut Operation,
+    ) -> Vec<(Option<u16>, OpenApiResponse)> {
+        Json::<T>::inferred_responses(ctx, operation)
+    }
+}
+
+/// Reads the media ranges from a request's `Accept` headers, most preferred
+/// first.
+///
+/// Parameters are dropped, except for the `q` weight that orders the ranges.
+/// Ranges with equal weights keep their order, and ranges with a weight of
+/// zero aren't acceptable at all, so they're left out.
+fn accept(headers: &HeaderMap) -> Vec<String> {
+    let mut media_ranges: Vec<(String, f32)> = headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|media_range| {
+            let mut parts = media_range.split(';');
+            let media_range = parts.next().unwrap_or_default().trim();
+            if media_range.is_empty() {
+                return None;
+            }
+            let q = parts
+                .filter_map(|parameter| parameter.split_once('='))
+                .find(|(key, _)| key.trim().eq_ignore_ascii_case("q"))
+                .and_then(|(_, value)| value.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            Some((media_range.to_string(), q))
+        })
+        .filter(|(_, q)| *q > 0.0)
+        .collect();
+    media_ranges.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    media_ranges
+        .into_iter()
+        .map(|(media_range, _)| media_range)
+        .collect()
+}
+
+/// Returns true if a media range from an `Accept` header matches a media type.
+///
+/// `application/json` also matches `+json` types, like `application/geo+json`.
+fn matches(media_range: &str, media_type: &str) -> bool {
+    let media_type = media_type.split(';').next().unwrap_or_default().trim();
+    if media_range == "*/*" || media_range.eq_ignore_ascii_case(media_type) {
+        return true;
+    }
+    if let Some(type_) = media_range.strip_suffix("/*") {
+        return media_type
+            .split('/')
+            .next()
+            .map(|t| t.eq_ignore_ascii_case(type_))
+            .unwrap_or_default();
+    }
+    media_range.eq_ignore_ascii_case("application/json")
+        && media_type.starts_with("application/")
+        && media_type.ends_with("+json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Body, Negotiate, Output, Renderer, Renderers};
+    use axum::http::{
+        header::{ACCEPT, ETAG, IF_NONE_MATCH, LINK},
+        HeaderMap, StatusCode,
+    };
+    use serde_json::{json, value::RawValue};
+    use stac_api::Conformance;
+    use stac_api_backend::{RawItem, RawItemCollection};
+    use std::sync::Arc;
+
+    #[derive(Debug)]
+    struct Text;
+
+    impl Renderer for Text {
+        fn media_type(&self, output: Output<'_>) -> Option<&str> {
+            match output {
+                Output::Conformance(_) => Some("text/plain"),
+                _ => None,
+            }
+        }
+
+        fn render(&self, output: Output<'_>) -> crate::Result<Vec<u8>> {
+            match output {
+                Output::Conformance(conformance) => {
+                    Ok(conformance.conforms_to.join("\n").into_bytes())
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    fn content_type(renderers: &Renderers, accept: Option<&str>) -> String {
+        let mut headers = HeaderMap::new();
+        if let Some(accept) = accept {
+            let _ = headers.insert(ACCEPT, accept.parse().unwrap());
+        }
+        let conformance = Conformance {
+            conforms_to: vec!["a-class".to_string()],
+        };
+        let rendered = Negotiate::new(renderers.clone(), &headers, None)
+            .render::<Conformance>(Output::Conformance(&conformance))
+            .unwrap();
+        rendered.content_type.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn negotiate() {
+        let mut renderers = Renderers::default();
+        renderers.push(Arc::new(Text));
+        assert_eq!(content_type(&renderers, None), "application/json");
+        assert_eq!(content_type(&renderers, Some("text/plain")), "text/plain");
+        assert_eq!(
+            content_type(&renderers, Some("text/html, text/*;q=0.9")),
+            "text/plain"
+        );
+        assert_eq!(
+            content_type(&renderers, Some("text/plain;q=0.5, application/json")),
+            "application/json"
+        );
+        assert_eq!(
+            content_type(&renderers, Some("text/plain;q=0, application/json;q=0.1")),
+            "application/json"
+        );
+        assert_eq!(
+            content_type(&renderers, Some("application/json; charset=utf-8")),
+            "application/json"
+        );
+        assert_eq!(
+            content_type(&renderers, Some("image/png, */*")),
+            "application/json"
+        );
+        let mut headers = HeaderMap::new();
+        let _ = headers.insert(ACCEPT, "image/png".parse().unwrap());
+        let conformance = Conformance {
+            conforms_to: vec!["a-class".to_string()],
+        };
+        let err = Negotiate::new(renderers, &headers, None)
+            .render::<Conformance>(Output::Conformance(&conformance))
+            .unwrap_err();
+        assert_eq!(err.0, StatusCode::NOT_ACCEPTABLE);
+        assert!(err.1.contains("image/png"), "{}", err.1);
+    }
+
+    #[test]
+    fn feature_lines() {
+        let items = RawItemCollection::new(vec![
+            serde_json::value::to_raw_value(&json!({"type": "Feature", "id": "a"})).unwrap(),
+            RawValue::from_string("{\n  \"type\": \"Feature\",\n  \"id\": \"b\"\n}".to_string())
+                .unwrap(),
+        ]);
+        let mut headers = HeaderMap::new();
+        let _ = headers.insert(ACCEPT, "application/geo+json-seq".parse().unwrap());
+        let rendered = Negotiate::new(Renderers::default(), &headers, None)
+            .render::<()>(Output::Items(&items))
+            .unwrap();
+        assert_eq!(rendered.content_type, "application/geo+json-seq");
+        assert_eq!(
+            bytes(rendered.body),
+            b"\x1e{\"type\":\"Feature\",\"id\":\"a\"}\n\x1e{\"type\":\"Feature\",\"id\":\"b\"}\n"
+        );
+
+        let rendered = Negotiate::new(Renderers::default(), &headers, Some("limit=1&f=ndjson"))
+            .render::<()>(Output::Items(&items))
+            .unwrap();
+        assert_eq!(rendered.content_type, "application/x-ndjson");
+        assert_eq!(
+            bytes(rendered.body).iter().filter(|&&b| b == b'\n').count(),
+            2
+        );
+    }
+
+    #[test]
+    fn render_items() {
+        let items = || {
+            let mut items = RawItemCollection::new(vec![
+                serde_json::value::to_raw_value(&json!({"type": "Feature", "id": "a"})).unwrap(),
+                serde_json::value::to_raw_value(&json!({"type": "Feature", "id": "b"})).unwrap(),
+            ]);
+            items.links.push(stac::Link::new(
+                "http://stac-server.test/collections/an-id/items?limit=2",
+                "next",
+            ));
+            items.number_returned = Some(2);
+            items
+        };
+        for format in ["json", "geojsonseq", "ndjson"] {
+            let negotiate = Negotiate::new(
+                Renderers::default(),
+                &HeaderMap::new(),
+                Some(&format!("f={}", format)),
+            );
+            let rendered = negotiate.render_items::<()>(items()).unwrap();
+            assert!(matches!(rendered.body, Body::Chunks(_)), "{}", format);
+            assert!(rendered.headers.contains_key(LINK));
+            let buffered = negotiate.render::<()>(Output::Items(&items())).unwrap();
+            assert_eq!(bytes(rendered.body), bytes(buffered.body), "{}", format);
+        }
+    }
+
+    #[test]
+    fn render_raw_item() {
+        let item = || {
+            let mut item =
+                RawItem::new(serde_json::value::to_raw_value(&stac::Item::new("an-item")).unwrap());
+            item.links.push(stac::Link::self_(
+                "http://stac-server.test/collections/an-id/items/an-item",
+            ));
+            item
+        };
+        let rendered = Negotiate::new(Renderers::default(), &HeaderMap::new(), None)
+            .render_raw_item::<()>(item())
+            .unwrap();
+        assert_eq!(rendered.content_type, "application/geo+json");
+        let value: serde_json::Value = serde_json::from_slice(&bytes(rendered.body)).unwrap();
+        assert_eq!(value["id"], "an-item");
+        assert!(value["links"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|link| link["rel"] == "alternate" && link["type"] == "text/html"));
+
+        let rendered = Negotiate::new(Renderers::default(), &HeaderMap::new(), Some("f=html"))
+            .render_raw_item::<()>(item())
+            .unwrap();
+        assert_eq!(rendered.content_type, "text/html");
+    }
+
+    #[test]
+    fn render_prerendered() {
+        let mut renderers = Renderers::default();
+        renderers.push(Arc::new(Text));
+        let conformance = Conformance {
+            conforms_to: vec!["a-class".to_string()],
+        };
+        let output = Output::Conformance(&conformance);
+        let prerendered = renderers.prerender(output).unwrap();
+        let mut headers = HeaderMap::new();
+        let _ = headers.insert(ACCEPT, "text/plain".parse().unwrap());
+        let rendered = Negotiate::new(renderers.clone(), &headers, None)
+            .render_prerendered::<()>(output, &prerendered, &headers)
+            .unwrap();
+        assert_eq!(rendered.status, StatusCode::OK);
+        assert_eq!(rendered.content_type, "text/plain");
+        let etag = rendered.headers[ETAG].clone();
+        assert_eq!(bytes(rendered.body), b"a-class");
+
+        let _ = headers.insert(IF_NONE_MATCH, etag);
+        let rendered = Negotiate::new(renderers.clone(), &headers, None)
+            .render_prerendered::<()>(output, &prerendered, &headers)
+            .unwrap();
+        assert_eq!(rendered.status, StatusCode::NOT_MODIFIED);
+        assert!(bytes(rendered.body).is_empty());
+
+        // The JSON document has its own ETag.
+        let rendered = Negotiate::new(renderers, &HeaderMap::new(), None)
+            .render_prerendered::<()>(output, &prerendered, &headers)
+            .unwrap();
+        assert_eq!(rendered.status, StatusCode::OK);
+        assert_eq!(rendered.content_type, "application/json");
+    }
+
+    fn bytes(body: Body) -> Vec<u8> {
+        match body {
+            Body::Bytes(bytes) => bytes,
+            Body::Shared(bytes) => bytes.to_vec(),
+            Body::Chunks(chunks) => chunks.flat_map(Result::unwrap).collect(),
+        }
+    }
+
+    #[test]
+    fn alternates() {
+        let mut items = RawItemCollection::new(Vec::new());
+        items.links.push(stac::Link::self_(
+            "http://stac-server.test/collections/an-id/items?limit=1&f=json",
+        ));
+        let negotiate = Negotiate::new(Renderers::default(), &HeaderMap::new(), Some("f=json"));
+        let alternates = negotiate.alternates(Output::Items(&items));
+        assert_eq!(alternates.len(), 2);
+        assert_eq!(
+            alternates[1].href,
+            "http://stac-server.test/collections/an-id/items?limit=1&f=ndjson"
+        );
+        assert_eq!(
+            alternates[1].r#type.as_deref(),
+            Some("application/x-ndjson")
+        );
+
+        let negotiate = Negotiate::new(Renderers::default(), &HeaderMap::new(), Some("f=xml"));
+        let err = negotiate.render::<()>(Output::Items(&items)).unwrap_err();
+        assert_eq!(err.0, StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[test]
+    fn json_matches_geojson() {
+        assert!(super::matches("application/json", "application/geo+json"));
+        assert!(!super::matches("application/json", "text/html"));
+        assert!(super::matches("*/*", "text/html"));
+    }
+}
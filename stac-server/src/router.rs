@@ -1,16 +1,36 @@
-use crate::{Config, Error};
+use crate::{
+    assets::{AssetHrefs, Assets},
+    conditional::{self, Validators},
+    extension::{ExtensionRoutes, Registered},
+    jobs::Jobs,
+    precision::Precision,
+    render::{Document, Negotiate, Output, Prerendered, Rendered, Renderers},
+    thumbnail::{Cache, PreviewLinks, Thumbnails},
+    AssetSigner, Config, Error, ExportFormat, ExportStore, NdjsonExport, Records, Renderer,
+};
 use aide::{
-    axum::{routing::get, ApiRouter, IntoApiResponse},
+    axum::{
+        routing::{get, post},
+        ApiRouter, IntoApiResponse,
+    },
     openapi::{Info, OpenApi},
 };
 use axum::{
-    extract::{Path, Query, State},
-    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
-    response::Html,
+    extract::{Path, RawQuery, State},
+    http::{header::CONTENT_TYPE, HeaderMap, HeaderValue, StatusCode},
     Extension, Json, Router,
 };
-use stac_api::{GetItems, Root};
-use stac_api_backend::{Api, Backend, Items};
+use hyper::server::conn::AddrIncoming;
+use stac::{Collection, Link};
+use stac_api::{Collections, Conformance, Root, Sortby, UrlBuilder};
+use stac_api_backend::{Api, Items, RawItemCollection, ReadBackend};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::net::TcpSocket;
+use tower_http::trace::TraceLayer;
 
 /// Creates a new STAC API router.
 ///
@@ -25,67 +45,391 @@ use stac_api_backend::{Api, Backend, Items};
 ///     addr: "http://localhost:7822".to_string(),
 ///     features: true,
 ///     catalog: Catalog::new("an-id", "A description"),
+///     default_limit: 10,
+///     max_limit: Some(1000),
+///     ..Default::default()
 /// };
 /// let backend = MemoryBackend::new();
 /// let api = stac_server::api(backend, config).unwrap();
 /// ```
-pub fn api<B: Backend + 'static>(backend: B, config: Config) -> crate::Result<Router>
+pub fn api<B: ReadBackend + 'static>(backend: B, config: Config) -> crate::Result<Router>
 where
-    stac_api_backend::Error: From<<B as Backend>::Error>,
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
 {
-    // Need to build the OpenApi now so we can consume the catalog in the
-    // Api::new call
-    let mut open_api = build_openapi(&config.catalog.description);
-    let root_url = config.root_url();
-    let api = Api::new(backend, config.catalog, &root_url)?.features(config.features);
-    let mut router = ApiRouter::new()
-        .api_route("/", get(root))
-        .api_route("/conformance", get(conformance));
-    if api.features {
-        router = router
-            .api_route("/collections", get(collections))
-            .api_route("/collections/:collection_id", get(collection))
-            .api_route("/collections/:collection_id/items", get(items))
-            .api_route("/collections/:collection_id/items/:item_id", get(item));
-    } else {
-        router = router
-            .api_route("/collections", get(not_implemented))
-            .api_route("/collections/:collection_id", get(not_implemented))
-            .api_route("/collections/:collection_id/items", get(not_implemented))
-            .api_route(
-                "/collections/:collection_id/items/:item_id",
-                get(not_implemented),
+    RouterBuilder::new(backend, config).build()
+}
+
+/// Creates a new STAC API router with extensions that serve their own routes.
+///
+/// Each extension is registered with the [Api], so its conformance classes,
+/// parameters, and hooks apply, and then its routes are added after the core routes.
+pub fn api_with_extensions<B: ReadBackend + 'static>(
+    backend: B,
+    config: Config,
+    extensions: Vec<Arc<dyn ExtensionRoutes<B>>>,
+) -> crate::Result<Router>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    let mut builder = RouterBuilder::new(backend, config);
+    builder.extensions = extensions;
+    builder.build()
+}
+
+/// Builds a STAC API router with extensions and extra response formats.
+///
+/// # Examples
+///
+/// ```
+/// use stac_api_backend::MemoryBackend;
+/// use stac_server::{Config, JsonRenderer, RouterBuilder};
+///
+/// let router = RouterBuilder::new(MemoryBackend::new(), Config::default())
+///     .renderer(JsonRenderer)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct RouterBuilder<B: ReadBackend> {
+    backend: B,
+    config: Config,
+    extensions: Vec<Arc<dyn ExtensionRoutes<B>>>,
+    renderers: Renderers,
+    asset_signer: Option<Arc<dyn AssetSigner>>,
+    export_formats: Vec<Arc<dyn ExportFormat>>,
+    export_store: Option<Arc<dyn ExportStore>>,
+}
+
+impl<B: ReadBackend + 'static> RouterBuilder<B>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    /// Creates a new router builder that renders JSON.
+    pub fn new(backend: B, config: Config) -> RouterBuilder<B> {
+        RouterBuilder {
+            backend,
+            config,
+            extensions: Vec::new(),
+            renderers: Renderers::default(),
+            asset_signer: None,
+            export_formats: vec![Arc::new(NdjsonExport)],
+            export_store: None,
+        }
+    }
+
+    /// Adds an extension that serves its own routes.
+    ///
+    /// The extension is registered with the [Api], so its conformance classes,
+    /// parameters, and hooks apply, and then its routes are added after the core routes.
+    pub fn extension(mut self, extension: impl ExtensionRoutes<B> + 'static) -> RouterBuilder<B> {
+        self.extensions.push(Arc::new(extension));
+        self
+    }
+
+    /// Adds a response format.
+    ///
+    /// Renderers are tried in the order they're added, after the default JSON
+    /// renderer, against each media range in the request's `Accept` header.
+    pub fn renderer(mut self, renderer: impl Renderer + 'static) -> RouterBuilder<B> {
+        self.renderers.push(Arc::new(renderer));
+        self
+    }
+
+    /// Sets the signer for asset hrefs.
+    ///
+    /// The signer is only used if the asset or thumbnail endpoints are
+    /// enabled with [Config::assets] or [Config::thumbnails].
+    pub fn asset_signer(mut self, asset_signer: impl AssetSigner + 'static) -> RouterBuilder<B> {
+        self.asset_signer = Some(Arc::new(asset_signer));
+        self
+    }
+
+    /// Adds a file format for export jobs.
+    ///
+    /// Newline-delimited JSON is always available. Formats are only used if
+    /// export jobs are enabled with [Config::jobs].
+    pub fn export_format(mut self, export_format: impl ExportFormat + 'static) -> RouterBuilder<B> {
+        self.export_formats.push(Arc::new(export_format));
+        self
+    }
+
+    /// Sets the store that finished exports are uploaded to.
+    ///
+    /// The store is only used if [JobsConfig::destination](crate::JobsConfig::destination) is set.
+    pub fn export_store(mut self, export_store: impl ExportStore + 'static) -> RouterBuilder<B> {
+        self.export_store = Some(Arc::new(export_store));
+        self
+    }
+
+    /// Builds the router.
+    pub fn build(self) -> crate::Result<Router> {
+        let RouterBuilder {
+            backend,
+            config,
+            mut extensions,
+            renderers,
+            asset_signer,
+            export_formats,
+            export_store,
+        } = self;
+        // Need to build the OpenApi now so we can consume the catalog in the
+        // Api::builder call
+        let mut open_api = build_openapi(&config.catalog.description);
+        let root_url = config.root_url();
+        let mut builder = Api::builder(backend, config.catalog, &root_url)
+            .features(config.features)
+            .default_limit(config.default_limit)
+            .max_limit(config.max_limit)
+            .context(config.context)
+            .strict(config.strict_parameters)
+            // Handled by the server, and kept in paging links.
+            .parameter("f")
+            .parameter("precision");
+        if let Some(limit_ceiling) = config.limit_ceiling {
+            builder = builder.limit_ceiling(limit_ceiling);
+        }
+        if let Some(collections_ttl) = config.collections_ttl {
+            builder = builder.collections_ttl(Duration::from_secs(collections_ttl));
+        }
+        for (collection_id, collection_config) in &config.collections {
+            builder =
+                builder.collection_defaults(collection_id, collection_config.collection_defaults());
+        }
+        if config.records {
+            extensions.push(Arc::new(Records));
+        }
+        for extension in &extensions {
+            builder = builder.extension(Registered(Arc::clone(extension)));
+        }
+        if config.browser.is_some() {
+            builder = builder.landing_link(
+                Link::new(format!("{}/browser/", root_url), "alternate")
+                    .r#type("text/html".to_string())
+                    .title("STAC Browser".to_string()),
+            );
+        }
+        if config
+            .assets
+            .as_ref()
+            .is_some_and(|assets| assets.rewrite_hrefs)
+        {
+            builder = builder.link_decorator(AssetHrefs(UrlBuilder::new(&root_url)?));
+        }
+        if config.thumbnails.is_some() {
+            builder = builder.link_decorator(PreviewLinks(UrlBuilder::new(&root_url)?));
+        }
+        let api = builder.build()?;
+        // The conformance classes never change at runtime, so they're only
+        // rendered once.
+        let conformance_classes = api.conformance();
+        let prerendered = renderers.prerender(Output::Conformance(&conformance_classes))?;
+        let static_conformance = StaticConformance {
+            conformance: Arc::new(conformance_classes),
+            prerendered,
+        };
+        let mut router = ApiRouter::new()
+            .api_route("/", get(root))
+            .api_route("/conformance", get(conformance));
+        if api.features {
+            router = router
+                .api_route("/collections", get(collections))
+                .api_route("/collections/:collection_id", get(collection))
+                .api_route("/collections/:collection_id/items", get(items))
+                .api_route("/collections/:collection_id/feed.xml", get(feed))
+                .api_route("/collections/:collection_id/items/:item_id", get(item))
+                .route("/queryables", get(queryables))
+                .route(
+                    "/collections/:collection_id/queryables",
+                    get(collection_queryables),
+                );
+        } else {
+            router = router
+                .api_route("/collections", get(not_implemented))
+                .api_route("/collections/:collection_id", get(not_implemented))
+                .api_route("/collections/:collection_id/items", get(not_implemented))
+                .api_route("/collections/:collection_id/feed.xml", get(not_implemented))
+                .api_route(
+                    "/collections/:collection_id/items/:item_id",
+                    get(not_implemented),
+                );
+        }
+        if api.features && config.assets.is_some() {
+            router = router.route(
+                "/collections/:collection_id/items/:item_id/assets/:key",
+                get(crate::assets::asset),
             );
+        }
+        if api.features && config.thumbnails.is_some() {
+            router = router.route(
+                "/collections/:collection_id/items/:item_id/thumbnail",
+                get(crate::thumbnail::thumbnail),
+            );
+        }
+        if api.features && config.sitemap {
+            router = router
+                .route("/sitemap.xml", get(sitemap_index))
+                .route("/collections/:collection_id/sitemap.xml", get(sitemap));
+        }
+        if config.metrics {
+            router = router.route("/metrics", get(metrics));
+        }
+        if api.features && config.jobs.is_some() {
+            router = router
+                .route("/jobs/export", post(crate::jobs::create))
+                .route("/jobs/:job_id", get(crate::jobs::status))
+                .route("/jobs/:job_id/download", get(crate::jobs::download));
+        }
+        #[cfg(feature = "profiling")]
+        if config.profiling.is_some() {
+            router = router.route("/admin/pprof/profile", get(crate::profiling::profile));
+        }
+        #[cfg(not(feature = "profiling"))]
+        if config.profiling.is_some() {
+            return Err(Error::MissingFeature("profiling"));
+        }
+        for extension in &extensions {
+            router = extension.routes(router);
+        }
+        let static_service_doc = ServiceDoc(Document::new(
+            "text/html; charset=utf-8",
+            service_doc_html(api.url_builder.service_desc().as_str()).into_bytes(),
+        )?);
+        let mut router = router
+            .route("/api", get(service_desc))
+            .route("/api.html", get(service_doc))
+            .with_state(Arc::new(api))
+            .finish_api(&mut open_api);
+        let service_desc = Document::new(
+            "application/vnd.oai.openapi+json;version=3.1",
+            serde_json::to_vec(&open_api)?,
+        )?;
+        if let Some(browser) = &config.browser {
+            router = router.merge(crate::browser::router(browser, &root_url));
+        }
+        router =
+            router.fallback(move |uri| async move { crate::paths::fallback(&root_url, uri).await });
+        if let Some(jobs) = config.jobs {
+            std::fs::create_dir_all(&jobs.directory)?;
+            router = router.layer(Extension(Jobs {
+                config: jobs,
+                formats: export_formats,
+                store: export_store,
+                signer: asset_signer.clone(),
+                jobs: Arc::new(Mutex::new(HashMap::new())),
+            }));
+        }
+        if let Some(profiling) = config.profiling {
+            router = router.layer(Extension(profiling));
+        }
+        if config.sitemap {
+            router = router.layer(Extension(crate::sitemap::IndexCache::new(
+                Duration::from_secs(config.sitemap_ttl),
+            )));
+        }
+        if let Some(threshold) = config
+            .slow_log
+            .as_ref()
+            .and_then(|slow_log| slow_log.request_ms)
+        {
+            // Layered before the trace layer, so the warnings are in the
+            // request's span.
+            router = router.layer(axum::middleware::from_fn(move |request, next| {
+                crate::slow::log(Duration::from_millis(threshold), request, next)
+            }));
+        }
+        let client = reqwest::Client::new();
+        if let Some(thumbnails) = config.thumbnails {
+            router = router.layer(Extension(Thumbnails {
+                cache: Arc::new(Mutex::new(Cache::new(thumbnails.cache_size))),
+                config: thumbnails,
+                signer: asset_signer.clone(),
+                client: client.clone(),
+            }));
+        }
+        if let Some(assets) = config.assets {
+            router = router.layer(Extension(Assets {
+                config: assets,
+                signer: asset_signer,
+                client,
+            }));
+        }
+        Ok(router
+            .layer(Extension(Precision(config.precision)))
+            .layer(Extension(service_desc))
+            .layer(Extension(static_service_doc))
+            .layer(Extension(static_conformance))
+            .layer(Extension(renderers))
+            .layer(TraceLayer::new_for_http()))
     }
-    Ok(router
-        .route("/api", get(service_desc))
-        .route("/api.html", get(service_doc))
-        .with_state(api)
-        .finish_api(&mut open_api)
-        .layer(Extension(open_api)))
+
+    /// Builds the router and serves it at the config's address, with the
+    /// config's [HttpConfig](crate::HttpConfig) settings.
+    pub async fn serve(self) -> crate::Result<()> {
+        let addr = self.config.addr.parse::<std::net::SocketAddr>()?;
+        let http = self.config.http.clone();
+        let router = self.build()?;
+        let socket = if addr.is_ipv4() {
+            TcpSocket::new_v4()?
+        } else {
+            TcpSocket::new_v6()?
+        };
+        // Like tokio's own listeners, so a restarted server can rebind right away.
+        #[cfg(unix)]
+        socket.set_reuseaddr(true)?;
+        socket.bind(addr)?;
+        let mut incoming = AddrIncoming::from_listener(socket.listen(http.backlog)?)?;
+        let _ = incoming.set_nodelay(http.nodelay);
+        tracing::info!("serving on http://{}", addr);
+        axum::Server::builder(incoming)
+            .http1_keepalive(http.keep_alive)
+            .http1_only(!http.http2)
+            .http2_max_concurrent_streams(http.max_concurrent_streams)
+            .serve(router.into_make_service())
+            .await
+            .map_err(Error::from)
+    }
+}
+
+/// The service documentation page, which only links to the OpenAPI document.
+#[derive(Clone, Debug)]
+struct ServiceDoc(Document);
+
+/// The conformance classes, prerendered in every format.
+#[derive(Clone, Debug)]
+struct StaticConformance {
+    conformance: Arc<Conformance>,
+    prerendered: Prerendered,
 }
 
-async fn root<B: Backend>(State(api): State<Api<B>>) -> Result<Json<Root>, (StatusCode, String)>
+async fn root<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+    negotiate: Negotiate,
+) -> Result<Rendered<Root>, (StatusCode, String)>
 where
-    stac_api_backend::Error: From<<B as Backend>::Error>,
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
 {
-    let root = api.root().await.map_err(internal_server_error)?;
-    Ok(Json(root))
+    let mut root = api.root().await.map_err(error_response)?;
+    let alternates = negotiate.alternates(Output::Root(&root));
+    root.catalog.links.extend(alternates);
+    negotiate.render(Output::Root(&root))
 }
 
-async fn service_desc(Extension(api): Extension<OpenApi>) -> impl IntoApiResponse {
-    let mut headers = HeaderMap::new();
-    let _ = headers.insert(
-        CONTENT_TYPE,
-        "application/vnd.oai.openapi+json;version=3.1"
-            .parse()
-            .unwrap(),
-    );
-    (headers, Json(api))
+async fn service_desc(
+    Extension(service_desc): Extension<Document>,
+    headers: HeaderMap,
+) -> Rendered<OpenApi> {
+    service_desc.respond(&headers)
+}
+
+async fn service_doc(
+    Extension(service_doc): Extension<ServiceDoc>,
+    headers: HeaderMap,
+) -> Rendered<()> {
+    service_doc.0.respond(&headers)
 }
 
-async fn service_doc<B: Backend>(State(api): State<Api<B>>) -> Html<String> {
-    Html(format!("<!DOCTYPE html>
+fn service_doc_html(service_desc: &str) -> String {
+    format!("<!DOCTYPE html>
     <html>
       <head>
         <title>Redoc</title>
@@ -109,39 +453,71 @@ async fn service_doc<B: Backend>(State(api): State<Api<B>>) -> Html<String> {
         <script src=\"https://cdn.redoc.ly/redoc/latest/bundles/redoc.standalone.js\"> </script>
       </body>
     </html>
-    ", api.url_builder.service_desc()))
+    ", service_desc)
 }
 
-async fn conformance<B: Backend>(State(api): State<Api<B>>) -> impl IntoApiResponse
-where
-    stac_api_backend::Error: From<<B as Backend>::Error>,
-{
-    Json(api.conformance())
+async fn conformance(
+    Extension(conformance): Extension<StaticConformance>,
+    negotiate: Negotiate,
+    headers: HeaderMap,
+) -> Result<Rendered<Conformance>, (StatusCode, String)> {
+    negotiate.render_prerendered(
+        Output::Conformance(&conformance.conformance),
+        &conformance.prerendered,
+        &headers,
+    )
 }
 
-async fn collections<B: Backend>(State(api): State<Api<B>>) -> impl IntoApiResponse
+async fn collections<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+    negotiate: Negotiate,
+) -> Result<Rendered<Collections>, (StatusCode, String)>
 where
-    stac_api_backend::Error: From<<B as Backend>::Error>,
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
 {
-    api.collections()
-        .await
-        .map(Json)
-        .map_err(internal_server_error)
+    let mut collections = api.collections().await.map_err(error_response)?;
+    let alternates = negotiate.alternates(Output::Collections(&collections));
+    collections.links.extend(alternates);
+    negotiate.render(Output::Collections(&collections))
 }
 
-async fn collection<B: Backend>(
-    State(api): State<Api<B>>,
+async fn collection<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+    negotiate: Negotiate,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
     Path(collection_id): Path<String>,
-) -> impl IntoApiResponse
+) -> Result<Rendered<Collection>, (StatusCode, String)>
 where
-    stac_api_backend::Error: From<<B as Backend>::Error>,
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
 {
-    if let Some(collection) = api
+    if conditional::is_conditional(&headers) {
+        let updated = api
+            .backend
+            .collection_updated(&collection_id)
+            .await
+            .map_err(|err| error_response(err.into()))?;
+        if let Some(updated) = updated {
+            let validators = Validators::new(updated, &headers, query.as_deref());
+            if validators.not_modified(&headers) {
+                return Ok(Rendered::not_modified(validators.headers()));
+            }
+        }
+    }
+    if let Some(mut collection) = api
         .collection(&collection_id)
         .await
-        .map_err(internal_server_error)?
+        .map_err(error_response)?
     {
-        return Ok(Json(collection));
+        let validators = conditional::updated(&collection.additional_fields)
+            .map(|updated| Validators::new(updated, &headers, query.as_deref()));
+        let alternates = negotiate.alternates(Output::Collection(&collection));
+        collection.links.extend(alternates);
+        let rendered = negotiate.render(Output::Collection(&collection))?;
+        return Ok(match validators {
+            Some(validators) => rendered.with_headers(validators.headers()),
+            None => rendered,
+        });
     } else {
         return Err((
             StatusCode::NOT_FOUND,
@@ -150,58 +526,302 @@ where
     }
 }
 
-async fn items<B: Backend>(
-    State(api): State<Api<B>>,
+async fn items<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+    negotiate: Negotiate,
+    precision: Precision,
+    Path(collection_id): Path<String>,
+    RawQuery(query): RawQuery,
+) -> Result<Rendered<RawItemCollection>, (StatusCode, String)>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    // The format and precision stay in the query, so paging links keep them.
+    let items: Items<B::Paging> = Items::from_get_query(query.as_deref().unwrap_or_default())
+        .and_then(|items| items.validate(&api.limits).map(|()| items))
+        .map_err(error_response)?;
+    if let Some(mut items) = api
+        .raw_items(&collection_id, items)
+        .await
+        .map_err(error_response)?
+    {
+        precision.round_items(&mut items)?;
+        let alternates = negotiate.alternates(Output::Items(&items));
+        items.links.extend(alternates);
+        negotiate.render_items(items)
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            format!("no collection with id={}", collection_id),
+        ))
+    }
+}
+
+async fn feed<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
     Path(collection_id): Path<String>,
-    Query(get_items): Query<GetItems>,
 ) -> impl IntoApiResponse
 where
-    stac_api_backend::Error: From<<B as Backend>::Error>,
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
 {
-    match stac_api::Items::try_from(get_items)
-        .map_err(Error::from)
-        .and_then(|mut items| {
-            // TODO use serde_urlencoded
-            let paging: B::Paging = serde_qs::from_str(&serde_qs::to_string(&std::mem::take(
-                &mut items.additional_fields,
-            ))?)?;
-            Ok(Items { items, paging })
-        }) {
-        Ok(items) => {
-            if let Some(items) = api
-                .items(&collection_id, items)
-                .await
-                .map_err(internal_server_error)?
-            {
-                let mut headers = HeaderMap::new();
-                let _ = headers.insert(CONTENT_TYPE, "application/geo+json".parse().unwrap());
-                return Ok((headers, Json(items)));
+    let collection = api
+        .collection(&collection_id)
+        .await
+        .map_err(error_response)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("no collection with id={}", collection_id),
+            )
+        })?;
+    let mut items: Items<B::Paging> = Items::default();
+    // Backends that can't sort still give a feed, it's just not the most
+    // recent items overall.
+    items.items.sortby = Some(vec![Sortby::desc("properties.updated")]);
+    let item_collection = api
+        .items(&collection_id, items)
+        .await
+        .map_err(error_response)?
+        .unwrap_or_default();
+    let feed_url = format!(
+        "{}/feed.xml",
+        api.url_builder
+            .collection(&collection_id)
+            .map_err(|err| error_response(err.into()))?
+    );
+    let mut headers = HeaderMap::new();
+    let _ = headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/atom+xml"),
+    );
+    Ok::<_, (StatusCode, String)>((
+        headers,
+        crate::feed::atom(&collection, item_collection.items, &feed_url),
+    ))
+}
+
+async fn sitemap_index<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+    Extension(cache): Extension<crate::sitemap::IndexCache>,
+) -> impl IntoApiResponse
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    let index = cache.get_or_build(build_sitemap_index(&api)).await?;
+    Ok::<_, (StatusCode, String)>(xml(index.to_string()))
+}
+
+/// Builds the sitemap index by paging through every collection's items.
+async fn build_sitemap_index<B: ReadBackend>(api: &Api<B>) -> Result<String, (StatusCode, String)>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    let collections = api.collections().await.map_err(error_response)?;
+    let mut sitemaps = Vec::new();
+    for collection in collections.collections {
+        let url = sitemap_url(api, &collection.id)?;
+        // Sitemaps can't link to each other, so the index walks every page
+        // to find their tokens.
+        let mut query = String::new();
+        loop {
+            let items = match sitemap_page(api, &collection.id, &query).await? {
+                Some(items) => items,
+                None => break,
+            };
+            if query.is_empty() {
+                sitemaps.push(url.to_string());
             } else {
-                return Err((
-                    StatusCode::NOT_FOUND,
-                    format!("no collection with id={}", collection_id),
-                ));
+                sitemaps.push(format!("{}?{}", url, query));
+            }
+            if let Some(token) = crate::sitemap::next_token(&items) {
+                query = url::form_urlencoded::Serializer::new(String::new())
+                    .append_pair("token", &token)
+                    .finish();
+            } else {
+                break;
             }
         }
-        Err(err) => Err((StatusCode::BAD_REQUEST, format!("invalid query: {}", err))),
     }
+    Ok(crate::sitemap::index(&sitemaps))
 }
 
-async fn item<B: Backend>(
-    State(api): State<Api<B>>,
-    Path((collection_id, item_id)): Path<(String, String)>,
+async fn metrics<B: ReadBackend>(State(api): State<Arc<Api<B>>>) -> impl IntoApiResponse
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    let mut headers = HeaderMap::new();
+    let _ = headers.insert(
+        CONTENT_TYPE,
+        crate::metrics::METRICS_MEDIA_TYPE.parse().unwrap(),
+    );
+    (headers, crate::metrics::render(&api.backend.metrics()))
+}
+
+async fn sitemap<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+    Path(collection_id): Path<String>,
+    RawQuery(query): RawQuery,
 ) -> impl IntoApiResponse
 where
-    stac_api_backend::Error: From<<B as Backend>::Error>,
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    let query = query.unwrap_or_default();
+    let items = sitemap_page(&api, &collection_id, &query)
+        .await?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("no collection with id={}", collection_id),
+            )
+        })?;
+    let mut urls = Vec::new();
+    // The collection goes on its first page.
+    if query.is_empty() {
+        urls.push(
+            api.url_builder
+                .collection(&collection_id)
+                .map_err(|err| error_response(err.into()))?
+                .to_string(),
+        );
+    }
+    for item in &items.items {
+        if let Some(item_id) = item.id().map_err(error_response)? {
+            urls.push(
+                api.url_builder
+                    .item(&collection_id, &item_id)
+                    .map_err(|err| error_response(err.into()))?
+                    .to_string(),
+            );
+        }
+    }
+    Ok::<_, (StatusCode, String)>(xml(crate::sitemap::urlset(&urls)))
+}
+
+fn sitemap_url<B: ReadBackend>(
+    api: &Api<B>,
+    collection_id: &str,
+) -> Result<String, (StatusCode, String)>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    Ok(format!(
+        "{}/sitemap.xml",
+        api.url_builder
+            .collection(collection_id)
+            .map_err(|err| error_response(err.into()))?
+    ))
+}
+
+/// Returns a page of items for a sitemap, where the query only holds a paging token.
+async fn sitemap_page<B: ReadBackend>(
+    api: &Api<B>,
+    collection_id: &str,
+    query: &str,
+) -> Result<Option<RawItemCollection>, (StatusCode, String)>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    if url::form_urlencoded::parse(query.as_bytes()).any(|(key, _)| key != "token") {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "sitemaps only take a token".to_string(),
+        ));
+    }
+    let mut items: Items<B::Paging> = Items::from_get_query(query).map_err(error_response)?;
+    // Clamped to the collection's max limit.
+    items.items.limit = Some(crate::sitemap::SITEMAP_LIMIT);
+    api.raw_items(collection_id, items)
+        .await
+        .map_err(error_response)
+}
+
+async fn queryables<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+) -> Result<(HeaderMap, Json<serde_json::Value>), (StatusCode, String)>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    let queryables = api.queryables(None).await.map_err(error_response)?;
+    schema_json(
+        queryables,
+        "this backend doesn't know its queryables".to_string(),
+    )
+}
+
+async fn collection_queryables<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+    Path(collection_id): Path<String>,
+) -> Result<(HeaderMap, Json<serde_json::Value>), (StatusCode, String)>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    let queryables = api
+        .queryables(Some(&collection_id))
+        .await
+        .map_err(error_response)?;
+    schema_json(
+        queryables,
+        format!("no queryables for collection with id={}", collection_id),
+    )
+}
+
+fn schema_json(
+    schema: Option<serde_json::Value>,
+    not_found: String,
+) -> Result<(HeaderMap, Json<serde_json::Value>), (StatusCode, String)> {
+    let schema = schema.ok_or((StatusCode::NOT_FOUND, not_found))?;
+    let mut headers = HeaderMap::new();
+    let _ = headers.insert(CONTENT_TYPE, "application/schema+json".parse().unwrap());
+    Ok((headers, Json(schema)))
+}
+
+fn xml(body: String) -> (HeaderMap, String) {
+    let mut headers = HeaderMap::new();
+    let _ = headers.insert(CONTENT_TYPE, "application/xml".parse().unwrap());
+    (headers, body)
+}
+
+async fn item<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+    negotiate: Negotiate,
+    precision: Precision,
+    headers: HeaderMap,
+    RawQuery(query): RawQuery,
+    Path((collection_id, item_id)): Path<(String, String)>,
+) -> Result<Rendered<stac_api::Item>, (StatusCode, String)>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
 {
-    if let Some(item) = api
-        .item(&collection_id, &item_id)
+    // Polling harvesters can be answered from just the item's timestamp,
+    // without fetching or rendering the item.
+    if conditional::is_conditional(&headers) {
+        let updated = api
+            .backend
+            .item_updated(&collection_id, &item_id)
+            .await
+            .map_err(|err| error_response(err.into()))?;
+        if let Some(updated) = updated {
+            let validators = Validators::new(updated, &headers, query.as_deref());
+            if validators.not_modified(&headers) {
+                return Ok(Rendered::not_modified(validators.headers()));
+            }
+        }
+    }
+    if let Some(mut item) = api
+        .raw_item(&collection_id, &item_id)
         .await
-        .map_err(internal_server_error)?
+        .map_err(error_response)?
     {
-        let mut headers = HeaderMap::new();
-        let _ = headers.insert(CONTENT_TYPE, "application/geo+json".parse().unwrap());
-        return Ok((headers, Json(item)));
+        let validators = item
+            .updated()
+            .map_err(error_response)?
+            .map(|updated| Validators::new(updated, &headers, query.as_deref()));
+        precision.round_raw_item(&mut item)?;
+        let rendered = negotiate.render_raw_item(item)?;
+        return Ok(match validators {
+            Some(validators) => rendered.with_headers(validators.headers()),
+            None => rendered,
+        });
     } else {
         return Err((
             StatusCode::NOT_FOUND,
@@ -213,11 +833,13 @@ where
     }
 }
 
-fn internal_server_error(err: stac_api_backend::Error) -> (StatusCode, String) {
-    (
-        StatusCode::INTERNAL_SERVER_ERROR,
-        format!("internal server error: {}", err),
-    )
+pub(crate) fn error_response(err: stac_api_backend::Error) -> (StatusCode, String) {
+    let status_code = err.status_code();
+    if status_code.is_server_error() {
+        (status_code, format!("internal server error: {}", err))
+    } else {
+        (status_code, err.to_string())
+    }
 }
 
 async fn not_implemented() -> (StatusCode, String) {
@@ -236,13 +858,25 @@ fn build_openapi(description: impl ToString) -> OpenApi {
 
 #[cfg(test)]
 mod tests {
-    use crate::Config;
+    use crate::{
+        AssetMode, AssetSigner, AssetsConfig, CollectionConfig, Config, ExtensionRoutes,
+        JobsConfig, Output, Renderer, RouterBuilder, ThumbnailsConfig,
+    };
+    use aide::axum::{routing::get, ApiRouter};
+    use async_trait::async_trait;
     use axum::{
         body::Body,
-        http::{header::CONTENT_TYPE, Request, StatusCode},
+        http::{
+            header::{
+                ACCEPT_ENCODING, ALLOW, CONTENT_ENCODING, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE,
+                IF_NONE_MATCH, LAST_MODIFIED, LINK, LOCATION, VARY,
+            },
+            HeaderName, HeaderValue, Request, StatusCode,
+        },
     };
-    use stac::{Catalog, Collection, Item};
-    use stac_api_backend::{Backend, MemoryBackend};
+    use stac::{Asset, Catalog, Collection, Item, Link};
+    use stac_api_backend::{Api, Instrumented, MemoryBackend, StacExtension, WriteBackend};
+    use std::{sync::Arc, time::Duration};
     use tower::ServiceExt;
 
     fn test_config() -> Config {
@@ -250,6 +884,9 @@ mod tests {
             addr: "http://localhost:7822".to_string(),
             features: true,
             catalog: Catalog::new("test-catalog", "A description"),
+            default_limit: 10,
+            max_limit: Some(100),
+            ..Default::default()
         }
     }
 
@@ -301,6 +938,100 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn service_desc() {
+        let api = super::api(MemoryBackend::new(), test_config()).unwrap();
+        let response = api
+            .clone()
+            .oneshot(Request::builder().uri("/api").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/vnd.oai.openapi+json;version=3.1"
+        );
+        let etag = response.headers().get(ETAG).unwrap().clone();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let _: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .uri("/api")
+                    .header(IF_NONE_MATCH, etag)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn precompressed() {
+        use std::io::Read;
+
+        let api = super::api(MemoryBackend::new(), test_config()).unwrap();
+        let request = |uri: &str, accept_encoding: &str| {
+            Request::builder()
+                .uri(uri)
+                .header(ACCEPT_ENCODING, accept_encoding)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = api
+            .clone()
+            .oneshot(request("/api", "gzip, deflate"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[VARY], "accept-encoding");
+        let gzip_etag = response.headers()[ETAG].clone();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let mut json = String::new();
+        let _ = flate2::read::GzDecoder::new(&body[..])
+            .read_to_string(&mut json)
+            .unwrap();
+        let _: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let response = api
+            .clone()
+            .oneshot(request("/api", "identity"))
+            .await
+            .unwrap();
+        assert!(!response.headers().contains_key(CONTENT_ENCODING));
+        assert_ne!(response.headers()[ETAG], gzip_etag);
+
+        let response = api
+            .clone()
+            .oneshot(request("/api.html", "br"))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[CONTENT_ENCODING], "br");
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/html; charset=utf-8");
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let mut html = String::new();
+        let _ = brotli::Decompressor::new(&body[..], 4096)
+            .read_to_string(&mut html)
+            .unwrap();
+        assert!(html.contains("/api'></redoc>"));
+
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .uri("/conformance")
+                    .header(ACCEPT_ENCODING, "br")
+                    .header(IF_NONE_MATCH, "W/\"nope\"")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers()[CONTENT_ENCODING], "br");
+    }
+
     #[tokio::test]
     async fn collection() {
         let mut backend = MemoryBackend::new();
@@ -322,6 +1053,57 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn write_methods_not_allowed() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let api = super::api(backend, test_config()).unwrap();
+        for (method, uri) in [
+            ("POST", "/collections"),
+            ("PUT", "/collections/an-id"),
+            ("DELETE", "/collections/an-id"),
+            ("POST", "/collections/an-id/items"),
+            ("PUT", "/collections/an-id/items/an-item"),
+            ("PATCH", "/collections/an-id/items/an-item"),
+            ("DELETE", "/collections/an-id/items/an-item"),
+        ] {
+            let response = api
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(method)
+                        .uri(uri)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(
+                response.status(),
+                StatusCode::METHOD_NOT_ALLOWED,
+                "{} {}",
+                method,
+                uri
+            );
+            assert_eq!(response.headers()[ALLOW], "GET,HEAD", "{} {}", method, uri);
+        }
+
+        let response = api
+            .oneshot(Request::builder().uri("/api").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let open_api: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        for (path, operations) in open_api["paths"].as_object().unwrap() {
+            for method in ["post", "put", "patch", "delete"] {
+                assert!(operations.get(method).is_none(), "{} {}", method, path);
+            }
+        }
+    }
+
     #[tokio::test]
     async fn items() {
         let mut backend = MemoryBackend::new();
@@ -348,32 +1130,1275 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn item() {
+    async fn items_invalid_token() {
         let mut backend = MemoryBackend::new();
         let _ = backend
             .add_collection(Collection::new("an-id", "a description"))
             .await
             .unwrap();
-        backend
-            .add_items(vec![Item::new("item-id").collection("an-id")])
-            .await
-            .unwrap();
         let api = super::api(backend, test_config()).unwrap();
         let response = api
             .oneshot(
                 Request::builder()
                     .method("GET")
-                    .uri("/collections/an-id/items/item-id")
+                    .uri("/collections/an-id/items?token=bm90IGpzb24")
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
-        assert_eq!(response.status(), StatusCode::OK,);
-        assert_eq!(
-            response.headers().get(CONTENT_TYPE).unwrap(),
-            "application/geo+json"
-        );
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn items_invalid_bbox() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let api = super::api(backend, test_config()).unwrap();
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/collections/an-id/items?bbox=0,1,1,0")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(String::from_utf8(body.to_vec()).unwrap().contains("bbox"));
+    }
+
+    #[tokio::test]
+    async fn link_types_match_content_types() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        backend
+            .add_items(vec![Item::new("item-a").collection("an-id")])
+            .await
+            .unwrap();
+        let config = Config {
+            addr: "stac-server-rs.test".to_string(),
+            ..test_config()
+        };
+        let root = "http://stac-server-rs.test";
+        let api = super::api(backend, config).unwrap();
+        let get = |path: String| {
+            api.clone()
+                .oneshot(Request::builder().uri(path).body(Body::empty()).unwrap())
+        };
+        for path in [
+            "/",
+            "/collections",
+            "/collections/an-id",
+            "/collections/an-id/items",
+            "/collections/an-id/items/item-a",
+        ] {
+            let response = get(path.to_string()).await.unwrap();
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            let nested = ["collections", "features"]
+                .into_iter()
+                .filter_map(|key| value[key].as_array())
+                .flatten();
+            let links: Vec<_> = std::iter::once(&value)
+                .chain(nested)
+                .filter_map(|value| value["links"].as_array())
+                .flatten()
+                .collect();
+            assert!(!links.is_empty(), "{} has no links", path);
+            for link in links {
+                let Some(href) = link["href"].as_str().unwrap().strip_prefix(root) else {
+                    continue;
+                };
+                let Some(link_type) = link["type"].as_str() else {
+                    let rel = link["rel"].as_str().unwrap();
+                    assert!(
+                        !["root", "self", "parent", "collection", "items", "data"].contains(&rel),
+                        "{} link to {} has no type",
+                        rel,
+                        href
+                    );
+                    continue;
+                };
+                let response = get(href.to_string()).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK, "{} from {}", href, path);
+                let content_type = response.headers()[CONTENT_TYPE].to_str().unwrap();
+                assert!(
+                    content_type == link_type
+                        || content_type.split(';').next().unwrap().trim() == link_type,
+                    "{} link to {} is {}, but the response is {}",
+                    link["rel"],
+                    href,
+                    link_type,
+                    content_type
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn items_strict_parameters() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let mut config = test_config();
+        config.strict_parameters = true;
+        let api = super::api(backend, config).unwrap();
+        for (query, status) in [
+            ("limit=1&f=json&precision=2&count=none", StatusCode::OK),
+            ("limt=1", StatusCode::BAD_REQUEST),
+        ] {
+            let response = api
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("GET")
+                        .uri(format!("/collections/an-id/items?{}", query))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status, "{}", query);
+        }
+    }
+
+    #[tokio::test]
+    async fn items_invalid_limit() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let api = super::api(backend, test_config()).unwrap();
+        for limit in ["-1", "abc", "0"] {
+            let response = api
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("GET")
+                        .uri(format!("/collections/an-id/items?limit={}", limit))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            assert!(String::from_utf8(body.to_vec()).unwrap().contains("limit"));
+        }
+    }
+
+    #[tokio::test]
+    async fn items_max_limit() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        backend
+            .add_items(vec![
+                Item::new("item-a").collection("an-id"),
+                Item::new("item-b").collection("an-id"),
+            ])
+            .await
+            .unwrap();
+        let mut config = test_config();
+        config.max_limit = Some(1);
+        let api = super::api(backend, config).unwrap();
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/collections/an-id/items?limit=5")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let items: stac_api::ItemCollection = serde_json::from_slice(&body).unwrap();
+        assert_eq!(items.items.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn items_limit_ceiling() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let mut config = test_config();
+        config.limit_ceiling = Some(50);
+        let api = super::api(backend, config).unwrap();
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/collections/an-id/items?limit=51")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn items_collection_config() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        backend
+            .add_items(vec![
+                Item::new("item-a").collection("an-id"),
+                Item::new("item-b").collection("an-id"),
+            ])
+            .await
+            .unwrap();
+        let mut config = test_config();
+        let _ = config.collections.insert(
+            "an-id".to_string(),
+            CollectionConfig {
+                default_limit: Some(1),
+                ..Default::default()
+            },
+        );
+        let api = super::api(backend, config).unwrap();
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .uri("/collections/an-id/items")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let items: stac_api::ItemCollection = serde_json::from_slice(&body).unwrap();
+        assert_eq!(items.items.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn items_ndjson() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        backend
+            .add_items(vec![
+                Item::new("item-a").collection("an-id"),
+                Item::new("item-b").collection("an-id"),
+            ])
+            .await
+            .unwrap();
+        let api = super::api(backend, test_config()).unwrap();
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .uri("/collections/an-id/items?limit=1&f=ndjson")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/x-ndjson"
+        );
+        assert!(response
+            .headers()
+            .get_all(LINK)
+            .iter()
+            .any(|link| link.to_str().unwrap().contains("f=ndjson&token=")));
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let item: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(item["id"], "item-a");
+    }
+
+    #[tokio::test]
+    async fn precision() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let mut item = Item::new("item-id").collection("an-id");
+        item.geometry = Some(
+            serde_json::from_value(serde_json::json!({
+                "type": "Point",
+                "coordinates": [-105.123456, 40.987654],
+            }))
+            .unwrap(),
+        );
+        backend.add_items(vec![item]).await.unwrap();
+        let mut config = test_config();
+        config.precision = Some(3);
+        let api = super::api(backend, config).unwrap();
+        let get = |uri: &'static str| {
+            api.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+        let response = get("/collections/an-id/items/item-id").await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let item: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            item["geometry"]["coordinates"],
+            serde_json::json!([-105.123, 40.988])
+        );
+        let response = get("/collections/an-id/items?precision=1").await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let items: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            items["features"][0]["geometry"]["coordinates"],
+            serde_json::json!([-105.1, 41.0])
+        );
+        let response = get("/collections/an-id/items?precision=-1").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn feed() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let items = ["2023-01-01", "2023-03-01", "2023-02-01"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, updated)| {
+                let mut item = Item::new(format!("item-{}", i)).collection("an-id");
+                let _ = item.properties.additional_fields.insert(
+                    "updated".to_string(),
+                    format!("{}T00:00:00Z", updated).into(),
+                );
+                item
+            })
+            .collect();
+        backend.add_items(items).await.unwrap();
+        let mut config = test_config();
+        config.max_limit = Some(2);
+        let api = super::api(backend, config).unwrap();
+        let response = api
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/collections/an-id/feed.xml")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/atom+xml"
+        );
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let feed = String::from_utf8(body.to_vec()).unwrap();
+        let entries: Vec<_> = feed
+            .split("<entry>")
+            .skip(1)
+            .map(|entry| {
+                let id = entry.split("</id>").next().unwrap();
+                id.rsplit('/').next().unwrap()
+            })
+            .collect();
+        assert_eq!(entries, ["item-1", "item-2"]);
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .uri("/collections/not-an-id/feed.xml")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn sitemap() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        backend
+            .add_items(vec![
+                Item::new("item-a").collection("an-id"),
+                Item::new("item-b").collection("an-id"),
+            ])
+            .await
+            .unwrap();
+        let mut config = test_config();
+        config.sitemap = true;
+        let _ = config.collections.insert(
+            "an-id".to_string(),
+            CollectionConfig {
+                max_limit: Some(1),
+                ..Default::default()
+            },
+        );
+        let api = super::api(backend.clone(), config).unwrap();
+        let get = |uri: String| {
+            api.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+
+        let response = get("/sitemap.xml".to_string()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/xml"
+        );
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let index = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(index.matches("<sitemap>").count(), 2);
+        let start = index.find("/collections/an-id/sitemap.xml?token=").unwrap();
+        let end = start + index[start..].find("</loc>").unwrap();
+        let second = index[start..end].to_string();
+
+        // The index is cached, so it doesn't page through the backend again.
+        let _ = backend
+            .add_collection(Collection::new("another-id", "a description"))
+            .await
+            .unwrap();
+        let response = get("/sitemap.xml".to_string()).await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(String::from_utf8(body.to_vec()).unwrap(), index);
+
+        let response = get("/collections/an-id/sitemap.xml".to_string())
+            .await
+            .unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let first = String::from_utf8(body.to_vec()).unwrap();
+        assert!(first.contains("/collections/an-id</loc>"));
+        assert!(first.contains("/collections/an-id/items/item-a</loc>"));
+        let response = get(second).await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let second = String::from_utf8(body.to_vec()).unwrap();
+        assert!(!second.contains("/collections/an-id</loc>"));
+        assert!(second.contains("/collections/an-id/items/item-b</loc>"));
+
+        let response = get("/collections/an-id/sitemap.xml?limit=2".to_string())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = get("/collections/not-an-id/sitemap.xml".to_string())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn sitemap_disabled() {
+        // Sitemaps are off by default.
+        let response = super::api(MemoryBackend::new(), test_config())
+            .unwrap()
+            .oneshot(
+                Request::builder()
+                    .uri("/sitemap.xml")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(not(feature = "profiling"))]
+    #[test]
+    fn profiling_without_feature() {
+        let mut config = test_config();
+        config.profiling = Some(crate::ProfilingConfig {
+            token: None,
+            max_seconds: 60,
+        });
+        assert!(matches!(
+            super::api(MemoryBackend::new(), config),
+            Err(crate::Error::MissingFeature("profiling"))
+        ));
+    }
+
+    #[tokio::test]
+    async fn metrics() {
+        let mut config = test_config();
+        config.metrics = true;
+        let api = super::api(Instrumented::new(MemoryBackend::new()), config).unwrap();
+        let response = api
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/collections")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "text/plain; version=0.0.4"
+        );
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body
+            .contains("stac_backend_request_duration_seconds_count{method=\"collections\"} 1\n"));
+
+        let response = super::api(MemoryBackend::new(), test_config())
+            .unwrap()
+            .oneshot(
+                Request::builder()
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn browser() {
+        let dist = std::env::temp_dir().join(format!("stac-server-browser-{}", std::process::id()));
+        std::fs::create_dir_all(&dist).unwrap();
+        std::fs::write(dist.join("index.html"), "<html>stac browser</html>").unwrap();
+        std::fs::write(dist.join("config.js"), "window.STAC_BROWSER_CONFIG = {};").unwrap();
+        let mut config = test_config();
+        config.browser = Some(dist.clone());
+        let api = super::api(MemoryBackend::new(), config).unwrap();
+        let get = |uri: &'static str| {
+            api.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+
+        let response = get("/browser/config.js").await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(String::from_utf8(body.to_vec())
+            .unwrap()
+            .contains("\"catalogUrl\":\"http://http://localhost:7822/\""));
+        for uri in ["/browser/", "/browser/index.html", "/browser/a/route"] {
+            let response = get(uri).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            assert_eq!(body, "<html>stac browser</html>");
+        }
+        let response = get("/").await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(String::from_utf8(body.to_vec())
+            .unwrap()
+            .contains("STAC Browser"));
+        std::fs::remove_dir_all(dist).unwrap();
+    }
+
+    #[tokio::test]
+    async fn item() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        backend
+            .add_items(vec![Item::new("item-id").collection("an-id")])
+            .await
+            .unwrap();
+        let api = super::api(backend, test_config()).unwrap();
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .method("GET")
+                    .uri("/collections/an-id/items/item-id")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK,);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/geo+json"
+        );
+    }
+
+    #[tokio::test]
+    async fn item_conditional() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let mut item = Item::new("item-id").collection("an-id");
+        let _ = item
+            .properties
+            .additional_fields
+            .insert("updated".to_string(), "2023-10-07T12:34:56Z".into());
+        backend
+            .add_items(vec![item, Item::new("no-updated").collection("an-id")])
+            .await
+            .unwrap();
+        let api = super::api(backend, test_config()).unwrap();
+        let get = |uri: &'static str, header: Option<(HeaderName, HeaderValue)>| {
+            let mut request = Request::builder().uri(uri);
+            if let Some((name, value)) = header {
+                request = request.header(name, value);
+            }
+            api.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+        let response = get("/collections/an-id/items/item-id", None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(LAST_MODIFIED).unwrap(),
+            "Sat, 07 Oct 2023 12:34:56 GMT"
+        );
+        let etag = response.headers().get(ETAG).unwrap().clone();
+
+        let response = get(
+            "/collections/an-id/items/item-id",
+            Some((IF_NONE_MATCH, etag.clone())),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers().get(ETAG).unwrap(), etag);
+        assert!(response.headers().get(CONTENT_TYPE).is_none());
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(body.is_empty());
+
+        // A different format is a different representation.
+        let response = get(
+            "/collections/an-id/items/item-id?f=html",
+            Some((IF_NONE_MATCH, etag)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = get(
+            "/collections/an-id/items/item-id",
+            Some((
+                IF_MODIFIED_SINCE,
+                HeaderValue::from_static("Sun, 08 Oct 2023 00:00:00 GMT"),
+            )),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let response = get(
+            "/collections/an-id/items/item-id",
+            Some((
+                IF_MODIFIED_SINCE,
+                HeaderValue::from_static("Fri, 06 Oct 2023 00:00:00 GMT"),
+            )),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = get(
+            "/collections/an-id/items/no-updated",
+            Some((
+                IF_MODIFIED_SINCE,
+                HeaderValue::from_static("Sun, 08 Oct 2023 00:00:00 GMT"),
+            )),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(LAST_MODIFIED).is_none());
+    }
+
+    #[tokio::test]
+    async fn item_html() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        backend
+            .add_items(vec![Item::new("item-id").collection("an-id")])
+            .await
+            .unwrap();
+        let api = super::api(backend, test_config()).unwrap();
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .uri("/collections/an-id/items/item-id")
+                    .header("Accept", "text/html,application/xhtml+xml,*/*;q=0.8")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get(CONTENT_TYPE).unwrap(), "text/html");
+    }
+
+    #[tokio::test]
+    async fn collection_json_ld() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let api = super::api(backend, test_config()).unwrap();
+        let response = api
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/collections/an-id?f=jsonld")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/ld+json"
+        );
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let dataset: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(dataset["@type"], "Dataset");
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .uri("/collections/an-id")
+                    .header("Accept", "text/html")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers().get(CONTENT_TYPE).unwrap(), "text/html");
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(String::from_utf8(body.to_vec())
+            .unwrap()
+            .contains("\"@type\":\"Dataset\""));
+    }
+
+    #[tokio::test]
+    async fn queryables() {
+        let api = super::api(asset_backend("data.tif").await, test_config()).unwrap();
+        let get = |uri: &'static str| {
+            api.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+        let response = get("/collections/an-id/queryables").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/schema+json"
+        );
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let queryables: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(queryables["properties"]["datetime"]["format"], "date-time");
+        let response = get("/queryables").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = get("/collections/not-an-id/queryables").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn alternate_links() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let api = super::api(backend, test_config()).unwrap();
+        let get = |uri: &'static str| {
+            api.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+        let response = get("/collections/an-id").await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let collection: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let alternates: Vec<_> = collection["links"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|link| link["rel"] == "alternate")
+            .map(|link| link["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(alternates, ["text/html", "application/ld+json"]);
+
+        let response = get("/collections/an-id?f=ndjson").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+        let response = get("/conformance?f=json").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn records() {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let mut config = test_config();
+        config.records = true;
+        let api = super::api(backend, config).unwrap();
+        let get = |uri: &'static str| {
+            let api = api.clone();
+            async move {
+                let response = api
+                    .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+                (status, serde_json::from_slice(&body).unwrap_or_default())
+            }
+        };
+
+        let (_, root): (_, serde_json::Value) = get("/").await;
+        assert!(root["links"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|link| link["rel"] == "http://www.opengis.net/def/rel/ogc/1.0/ogc-catalog"));
+        let (_, conformance) = get("/conformance").await;
+        assert!(conformance["conformsTo"]
+            .as_array()
+            .unwrap()
+            .contains(&"http://www.opengis.net/spec/ogcapi-records-1/1.0/conf/record-core".into()));
+        let (_, catalog) = get("/records").await;
+        assert_eq!(catalog["itemType"], "record");
+        let (_, records) = get("/records/items").await;
+        assert_eq!(records["features"][0]["id"], "an-id");
+        let (_, record) = get("/records/items/an-id").await;
+        assert_eq!(record["properties"]["description"], "a description");
+        let (status, _) = get("/records/items/not-an-id").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[derive(Debug)]
+    struct Signed;
+
+    #[async_trait]
+    impl AssetSigner for Signed {
+        async fn sign(&self, href: &str, expires_in: Duration) -> crate::Result<Option<String>> {
+            Ok(Some(format!("{}?expires={}", href, expires_in.as_secs())))
+        }
+    }
+
+    async fn asset_backend(href: &str) -> MemoryBackend {
+        let mut backend = MemoryBackend::new();
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let mut item = Item::new("item-id").collection("an-id");
+        let _ = item.assets.insert("data".to_string(), Asset::new(href));
+        backend.add_items(vec![item]).await.unwrap();
+        backend
+    }
+
+    #[tokio::test]
+    async fn asset_redirect() {
+        let mut config = test_config();
+        config.assets = Some(AssetsConfig {
+            expires_in: 60,
+            rewrite_hrefs: true,
+            ..Default::default()
+        });
+        let api = RouterBuilder::new(asset_backend("s3://bucket/data.tif").await, config)
+            .asset_signer(Signed)
+            .build()
+            .unwrap();
+        let get = |uri: &'static str| {
+            api.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+
+        let response = get("/collections/an-id/items/item-id/assets/data")
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(
+            response.headers().get(LOCATION).unwrap(),
+            "s3://bucket/data.tif?expires=60"
+        );
+        let response = get("/collections/an-id/items/item-id").await.unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let item: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(item["assets"]["data"]["href"]
+            .as_str()
+            .unwrap()
+            .ends_with("/collections/an-id/items/item-id/assets/data"));
+        let response = get("/collections/an-id/items/item-id/assets/not-a-key")
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn asset_proxy() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let href = format!("http://{}/data.tif", listener.local_addr().unwrap());
+        let origin = axum::Router::new().route(
+            "/data.tif",
+            axum::routing::get(|headers: axum::http::HeaderMap| async move {
+                format!(
+                    "range={}",
+                    headers
+                        .get("range")
+                        .and_then(|range| range.to_str().ok())
+                        .unwrap_or_default()
+                )
+            }),
+        );
+        let _origin = tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(origin.into_make_service()),
+        );
+        let mut config = test_config();
+        config.assets = Some(AssetsConfig {
+            mode: AssetMode::Proxy,
+            ..Default::default()
+        });
+        let response = super::api(asset_backend(&href).await, config)
+            .unwrap()
+            .oneshot(
+                Request::builder()
+                    .uri("/collections/an-id/items/item-id/assets/data")
+                    .header("Range", "bytes=0-9")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, "range=bytes=0-9");
+    }
+
+    #[tokio::test]
+    async fn asset_relative_href() {
+        let mut config = test_config();
+        config.assets = Some(AssetsConfig::default());
+        let response = super::api(asset_backend("../data.tif").await, config)
+            .unwrap()
+            .oneshot(
+                Request::builder()
+                    .uri("/collections/an-id/items/item-id/assets/data")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert!(response
+            .headers()
+            .get(LOCATION)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .ends_with("/collections/an-id/data.tif"));
+    }
+
+    #[tokio::test]
+    async fn asset_proxy_relative_href() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let root = format!("http://{}", listener.local_addr().unwrap());
+        let origin =
+            axum::Router::new().route("/catalog/data.tif", axum::routing::get(|| async { "data" }));
+        let _origin = tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(origin.into_make_service()),
+        );
+        let mut backend = MemoryBackend::new().preserve_links(true);
+        let _ = backend
+            .add_collection(Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        let mut item = Item::new("item-id").collection("an-id");
+        item.links
+            .push(Link::new(format!("{}/catalog/item-id.json", root), "self"));
+        let _ = item
+            .assets
+            .insert("data".to_string(), Asset::new("./data.tif"));
+        backend.add_items(vec![item]).await.unwrap();
+        let mut config = test_config();
+        config.assets = Some(AssetsConfig {
+            mode: AssetMode::Proxy,
+            ..Default::default()
+        });
+        let response = super::api(backend, config)
+            .unwrap()
+            .oneshot(
+                Request::builder()
+                    .uri("/collections/an-id/items/item-id/assets/data")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, "data");
+    }
+
+    #[tokio::test]
+    async fn asset_proxy_unsupported_scheme() {
+        let mut config = test_config();
+        config.assets = Some(AssetsConfig {
+            mode: AssetMode::Proxy,
+            ..Default::default()
+        });
+        let response = super::api(asset_backend("s3://bucket/data.tif").await, config)
+            .unwrap()
+            .oneshot(
+                Request::builder()
+                    .uri("/collections/an-id/items/item-id/assets/data")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn thumbnail() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let href = format!("http://{}/thumbnail.png", listener.local_addr().unwrap());
+        let hits = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let origin_hits = Arc::clone(&hits);
+        let origin = axum::Router::new().route(
+            "/thumbnail.png",
+            axum::routing::get(move || async move {
+                let _ = origin_hits.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                ([(CONTENT_TYPE, "image/png")], "png")
+            }),
+        );
+        let _origin = tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(origin.into_make_service()),
+        );
+        let mut backend = asset_backend("s3://bucket/data.tif").await;
+        let mut item = Item::new("with-thumbnail").collection("an-id");
+        let _ = item
+            .assets
+            .insert("thumbnail".to_string(), Asset::new(href));
+        backend.add_items(vec![item]).await.unwrap();
+        let mut config = test_config();
+        config.thumbnails = Some(ThumbnailsConfig::default());
+        let api = super::api(backend, config).unwrap();
+        let get = |uri: &'static str| {
+            api.clone()
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+        };
+
+        for _ in 0..2 {
+            let response = get("/collections/an-id/items/with-thumbnail/thumbnail")
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers().get(CONTENT_TYPE).unwrap(), "image/png");
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            assert_eq!(body, "png");
+        }
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 1);
+        let response = get("/collections/an-id/items/item-id/thumbnail")
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = get("/collections/an-id/items/with-thumbnail")
+            .await
+            .unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let item: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(item["links"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|link| link["rel"] == "preview"));
+    }
+
+    #[tokio::test]
+    async fn export_job() {
+        let mut backend = asset_backend("data.tif").await;
+        backend
+            .add_items(vec![
+                Item::new("another-item").collection("an-id"),
+                Item::new("a-third-item").collection("an-id"),
+            ])
+            .await
+            .unwrap();
+        let directory = std::env::temp_dir().join(format!("export-job-{}", std::process::id()));
+        let mut config = test_config();
+        config.max_limit = Some(2);
+        config.jobs = Some(JobsConfig {
+            directory: directory.clone(),
+            destination: None,
+            max_items: None,
+            max_concurrent: None,
+            retention: 3600,
+        });
+        let api = super::api(backend, config).unwrap();
+        let send = |request: Request<Body>| api.clone().oneshot(request);
+        let get = |uri: String| send(Request::builder().uri(uri).body(Body::empty()).unwrap());
+
+        let response = send(
+            Request::builder()
+                .method("POST")
+                .uri("/jobs/export")
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(
+                    r#"{"collections": ["an-id"], "format": "geotiff"}"#,
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let response = send(
+            Request::builder()
+                .method("POST")
+                .uri("/jobs/export")
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(r#"{"collections": ["an-id"]}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let job: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let uri = format!("/jobs/{}", job["jobID"].as_str().unwrap());
+
+        let mut job = job;
+        for _ in 0..500 {
+            if job["status"] == "successful" || job["status"] == "failed" {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            let response = get(uri.clone()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            job = serde_json::from_slice(&body).unwrap();
+        }
+        assert_eq!(job["status"], "successful", "{}", job);
+        assert_eq!(job["itemsWritten"], 3);
+        assert!(job["links"][1]["href"]
+            .as_str()
+            .unwrap()
+            .ends_with(&format!("{}/download", uri)));
+
+        let response = get(format!("{}/download", uri)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "application/x-ndjson"
+        );
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let ids: Vec<String> = std::str::from_utf8(&body)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let item: serde_json::Value = serde_json::from_str(line).unwrap();
+                item["id"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(ids.len(), 3);
+        assert!(ids.contains(&"a-third-item".to_string()));
+        let response = get("/jobs/not-a-job".to_string()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn export_job_retention() {
+        let directory =
+            std::env::temp_dir().join(format!("export-job-retention-{}", std::process::id()));
+        let mut config = test_config();
+        config.jobs = Some(JobsConfig {
+            directory: directory.clone(),
+            destination: None,
+            max_items: None,
+            max_concurrent: None,
+            retention: 0,
+        });
+        let api = super::api(asset_backend("data.tif").await, config).unwrap();
+        let response = api
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/jobs/export")
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(Body::from(r#"{"collections": ["an-id"]}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let job: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let uri = format!("/jobs/{}", job["jobID"].as_str().unwrap());
+
+        let mut status = StatusCode::OK;
+        for _ in 0..500 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            status = api
+                .clone()
+                .oneshot(Request::builder().uri(&uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap()
+                .status();
+            if status != StatusCode::OK {
+                break;
+            }
+        }
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 0);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn no_assets() {
+        let response = super::api(asset_backend("data.tif").await, test_config())
+            .unwrap()
+            .oneshot(
+                Request::builder()
+                    .uri("/collections/an-id/items/item-id/assets/data")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
 
     #[tokio::test]
@@ -442,4 +2467,116 @@ mod tests {
             .unwrap();
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    #[derive(Debug)]
+    struct Hello;
+
+    impl StacExtension for Hello {
+        fn name(&self) -> &str {
+            "hello"
+        }
+
+        fn conformance_classes(&self) -> Vec<String> {
+            vec!["https://stac-server.test/hello".to_string()]
+        }
+    }
+
+    impl ExtensionRoutes<MemoryBackend> for Hello {
+        fn routes(
+            &self,
+            router: ApiRouter<Arc<Api<MemoryBackend>>>,
+        ) -> ApiRouter<Arc<Api<MemoryBackend>>> {
+            router.api_route("/hello", get(|| async { "hello" }))
+        }
+    }
+
+    #[tokio::test]
+    async fn extension_routes() {
+        let api =
+            super::api_with_extensions(MemoryBackend::new(), test_config(), vec![Arc::new(Hello)])
+                .unwrap();
+        let response = api
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/hello")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .uri("/conformance")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let conformance: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(conformance["conformsTo"]
+            .as_array()
+            .unwrap()
+            .contains(&"https://stac-server.test/hello".into()));
+    }
+
+    #[derive(Debug)]
+    struct Text;
+
+    impl Renderer for Text {
+        fn media_type(&self, output: Output<'_>) -> Option<&str> {
+            match output {
+                Output::Conformance(_) => Some("text/plain"),
+                _ => None,
+            }
+        }
+
+        fn render(&self, output: Output<'_>) -> crate::Result<Vec<u8>> {
+            match output {
+                Output::Conformance(conformance) => {
+                    Ok(conformance.conforms_to.join("\n").into_bytes())
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn renderer() {
+        let api = RouterBuilder::new(MemoryBackend::new(), test_config())
+            .renderer(Text)
+            .build()
+            .unwrap();
+        let response = api
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/conformance")
+                    .header("Accept", "text/plain")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get(CONTENT_TYPE).unwrap(), "text/plain");
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert!(String::from_utf8(body.to_vec())
+            .unwrap()
+            .contains("https://api.stacspec.org/v1.0.0/core"));
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header("Accept", "text/plain")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+    }
 }
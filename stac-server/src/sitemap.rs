@@ -0,0 +1,111 @@
+use crate::html::escape;
+use stac_api_backend::RawItemCollection;
+use std::{
+    fmt::Write,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use url::Url;
+
+/// The most urls allowed in one sitemap by the [sitemaps
+/// protocol](https://www.sitemaps.org/protocol.html).
+///
+/// Collections with a smaller max limit get more, smaller sitemaps.
+pub(crate) const SITEMAP_LIMIT: u64 = 50_000;
+
+/// The sitemap index, reused until it's older than its time to live.
+///
+/// The lock is held while the index is built, so concurrent requests wait
+/// for one build instead of each paging through the backend.
+#[derive(Clone, Debug)]
+pub(crate) struct IndexCache {
+    ttl: Duration,
+    index: Arc<Mutex<Option<Built>>>,
+}
+
+/// A sitemap index and when it was built.
+type Built = (Instant, Arc<str>);
+
+impl IndexCache {
+    pub(crate) fn new(ttl: Duration) -> IndexCache {
+        IndexCache {
+            ttl,
+            index: Default::default(),
+        }
+    }
+
+    /// Returns the cached index, or builds and caches a new one if it's
+    /// missing or stale.
+    pub(crate) async fn get_or_build<F, E>(&self, build: F) -> Result<Arc<str>, E>
+    where
+        F: Future<Output = Result<String, E>>,
+    {
+        let mut index = self.index.lock().await;
+        if let Some((built, index)) = &*index {
+            if built.elapsed() < self.ttl {
+                return Ok(Arc::clone(index));
+            }
+        }
+        let built: Arc<str> = build.await?.into();
+        *index = Some((Instant::now(), Arc::clone(&built)));
+        Ok(built)
+    }
+}
+
+/// Writes a sitemap index that points to other sitemaps.
+pub(crate) fn index(sitemaps: &[String]) -> String {
+    let mut index = String::new();
+    index.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    index.push_str("<sitemapindex xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    for sitemap in sitemaps {
+        writeln!(index, "  <sitemap><loc>{}</loc></sitemap>", escape(sitemap))
+            .expect("writing to a string is infallible");
+    }
+    index.push_str("</sitemapindex>\n");
+    index
+}
+
+/// Writes a sitemap of page urls.
+pub(crate) fn urlset(urls: &[String]) -> String {
+    let mut urlset = String::new();
+    urlset.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    urlset.push_str("<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n");
+    for url in urls {
+        writeln!(urlset, "  <url><loc>{}</loc></url>", escape(url))
+            .expect("writing to a string is infallible");
+    }
+    urlset.push_str("</urlset>\n");
+    urlset
+}
+
+/// Returns the paging token from a page's `next` link, if there is one.
+pub(crate) fn next_token(items: &RawItemCollection) -> Option<String> {
+    let next = items.links.iter().find(|link| link.rel == "next")?;
+    let url = Url::parse(&next.href).ok()?;
+    url.query_pairs()
+        .find(|(key, _)| key == "token")
+        .map(|(_, value)| value.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn index() {
+        let index = super::index(&[
+            "http://stac-server.test/collections/an-id/sitemap.xml".to_string(),
+            "http://stac-server.test/collections/an-id/sitemap.xml?token=a&b".to_string(),
+        ]);
+        assert!(index.starts_with("<?xml"));
+        assert!(index.contains(
+            "<sitemap><loc>http://stac-server.test/collections/an-id/sitemap.xml?token=a&amp;b</loc></sitemap>"
+        ));
+    }
+
+    #[test]
+    fn urlset() {
+        let urlset = super::urlset(&["http://stac-server.test/collections/an-id".to_string()]);
+        assert!(urlset.contains("<url><loc>http://stac-server.test/collections/an-id</loc></url>"));
+    }
+}
@@ -0,0 +1,103 @@
+use axum::{extract::MatchedPath, http::Request, middleware::Next, response::Response};
+use std::time::{Duration, Instant};
+
+/// Query parameters whose values are never logged.
+const SECRETS: [&str; 9] = [
+    "access_token",
+    "api_key",
+    "apikey",
+    "key",
+    "password",
+    "secret",
+    "sig",
+    "signature",
+    "token",
+];
+
+/// The most characters of a query value that are logged.
+const MAX_VALUE_LEN: usize = 500;
+
+/// Runs a request, logging a warning if it takes at least `threshold`.
+pub(crate) async fn log<B>(threshold: Duration, request: Request<B>, next: Next<B>) -> Response {
+    let start = Instant::now();
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let query = request.uri().query().map(sanitize).unwrap_or_default();
+    let response = next.run(request).await;
+    let elapsed = start.elapsed();
+    if elapsed >= threshold {
+        tracing::warn!(
+            method = %method,
+            route,
+            query,
+            status = response.status().as_u16(),
+            elapsed_ms = elapsed.as_millis() as u64,
+            "slow request"
+        );
+    }
+    response
+}
+
+/// Redacts credentials from a query string and truncates long values, e.g.
+/// CQL2 filters, so it can be logged.
+fn sanitize(query: &str) -> String {
+    url::form_urlencoded::parse(query.as_bytes())
+        .map(|(key, value)| {
+            let value = if SECRETS.contains(&key.to_ascii_lowercase().as_str()) {
+                "REDACTED".into()
+            } else if let Some((i, _)) = value.char_indices().nth(MAX_VALUE_LEN) {
+                format!("{}...", &value[..i]).into()
+            } else {
+                value
+            };
+            format!("{}={}", key, value)
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MAX_VALUE_LEN;
+    use crate::{Config, SlowLogConfig};
+    use axum::{body::Body, http::Request};
+    use stac_api_backend::MemoryBackend;
+    use tower::ServiceExt;
+
+    #[test]
+    fn sanitize() {
+        assert_eq!(
+            super::sanitize("limit=10&token=abc&API_KEY=def&filter=id%3D%27a%27"),
+            "limit=10&token=REDACTED&API_KEY=REDACTED&filter=id='a'"
+        );
+        let long = super::sanitize(&format!("filter={}", "a".repeat(MAX_VALUE_LEN + 1)));
+        assert_eq!(long.len(), "filter=".len() + MAX_VALUE_LEN + 3);
+        assert!(long.ends_with("..."));
+    }
+
+    #[tokio::test]
+    async fn slow_requests_are_served() {
+        let config = Config {
+            slow_log: Some(SlowLogConfig {
+                request_ms: Some(0),
+                backend_ms: None,
+            }),
+            ..Default::default()
+        };
+        let api = crate::api(MemoryBackend::new(), config).unwrap();
+        let response = api
+            .oneshot(
+                Request::builder()
+                    .uri("/collections?token=abc")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+    }
+}
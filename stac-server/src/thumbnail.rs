@@ -0,0 +1,310 @@
+use crate::{assets::sign, router::error_response, AssetSigner, ThumbnailsConfig};
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{
+        header::{CACHE_CONTROL, CONTENT_TYPE},
+        HeaderMap, HeaderValue, StatusCode,
+    },
+    Extension,
+};
+use serde_json::{json, Value};
+use stac::{Asset, Item};
+use stac_api::UrlBuilder;
+use stac_api_backend::{Api, LinkDecorator, ReadBackend};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use url::Url;
+
+/// Signed thumbnail urls are fetched right away, so they don't need to last long.
+const SIGNED_URL_LIFETIME: Duration = Duration::from_secs(60);
+
+/// Everything the thumbnail endpoint needs besides the [Api].
+#[derive(Clone, Debug)]
+pub(crate) struct Thumbnails {
+    pub(crate) config: ThumbnailsConfig,
+    pub(crate) signer: Option<Arc<dyn AssetSigner>>,
+    pub(crate) client: reqwest::Client,
+    pub(crate) cache: Arc<Mutex<Cache>>,
+}
+
+/// The most recently fetched thumbnails, by collection and item id.
+#[derive(Debug)]
+pub(crate) struct Cache {
+    capacity: usize,
+    order: VecDeque<(String, String)>,
+    thumbnails: HashMap<(String, String), Thumbnail>,
+}
+
+#[derive(Clone, Debug)]
+struct Thumbnail {
+    content_type: Option<HeaderValue>,
+    body: Bytes,
+}
+
+/// Where an item's thumbnail comes from.
+#[derive(Debug, PartialEq)]
+enum Source {
+    /// The item's own thumbnail asset.
+    Thumbnail(String),
+
+    /// A preview of a cloud-optimized GeoTIFF asset.
+    Cog(String),
+}
+
+/// Adds a `preview` link to items, pointing at the thumbnail endpoint.
+#[derive(Debug)]
+pub(crate) struct PreviewLinks(pub(crate) UrlBuilder);
+
+impl LinkDecorator for PreviewLinks {
+    fn item(&self, item: &mut stac_api::Item) {
+        let collection_id = item.get("collection").and_then(Value::as_str);
+        let item_id = item.get("id").and_then(Value::as_str);
+        let url = match (collection_id, item_id) {
+            (Some(collection_id), Some(item_id)) => self.0.item(collection_id, item_id).ok(),
+            _ => None,
+        };
+        if let (Some(url), Some(Value::Array(links))) = (url, item.get_mut("links")) {
+            links.push(json!({
+                "rel": "preview",
+                "href": format!("{}/thumbnail", url),
+                "title": "Thumbnail",
+            }));
+        }
+    }
+}
+
+/// Returns an item's thumbnail.
+pub(crate) async fn thumbnail<B: ReadBackend>(
+    State(api): State<Arc<Api<B>>>,
+    Extension(thumbnails): Extension<Thumbnails>,
+    Path((collection_id, item_id)): Path<(String, String)>,
+) -> Result<(HeaderMap, Bytes), (StatusCode, String)>
+where
+    stac_api_backend::Error: From<<B as ReadBackend>::Error>,
+{
+    let key = (collection_id, item_id);
+    let cached = thumbnails.cache.lock().unwrap().get(&key);
+    let thumbnail = if let Some(thumbnail) = cached {
+        thumbnail
+    } else {
+        let (collection_id, item_id) = &key;
+        // The backend's item, so hrefs aren't rewritten to point back at the server.
+        let item = api
+            .backend
+            .item(collection_id, item_id)
+            .await
+            .map_err(|err| error_response(err.into()))?
+            .ok_or_else(|| {
+                (
+                    StatusCode::NOT_FOUND,
+                    format!(
+                        "no item with id={} in collection={}",
+                        item_id, collection_id
+                    ),
+                )
+            })?;
+        let url = match source(&item, thumbnails.config.titiler.is_some()) {
+            Some(Source::Thumbnail(href)) => {
+                sign(thumbnails.signer.as_ref(), href, SIGNED_URL_LIFETIME).await?
+            }
+            Some(Source::Cog(href)) => preview_url(
+                thumbnails.config.titiler.as_deref().unwrap_or_default(),
+                &sign(thumbnails.signer.as_ref(), href, SIGNED_URL_LIFETIME).await?,
+                thumbnails.config.max_size,
+            )
+            .map_err(|err| error_response(err.into()))?,
+            None => {
+                return Err((
+                    StatusCode::NOT_FOUND,
+                    format!("item={} has no thumbnail", item_id),
+                ))
+            }
+        };
+        let thumbnail = fetch(&thumbnails.client, &url).await?;
+        thumbnails
+            .cache
+            .lock()
+            .unwrap()
+            .insert(key, thumbnail.clone());
+        thumbnail
+    };
+    let mut headers = HeaderMap::new();
+    if let Some(content_type) = thumbnail.content_type {
+        let _ = headers.insert(CONTENT_TYPE, content_type);
+    }
+    let _ = headers.insert(
+        CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=3600"),
+    );
+    Ok((headers, thumbnail.body))
+}
+
+async fn fetch(client: &reqwest::Client, url: &str) -> Result<Thumbnail, (StatusCode, String)> {
+    let bad_gateway = |err: reqwest::Error| {
+        (
+            StatusCode::BAD_GATEWAY,
+            format!("could not fetch thumbnail: {}", err),
+        )
+    };
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(bad_gateway)?;
+    let content_type = response.headers().get(CONTENT_TYPE).cloned();
+    let body = response.bytes().await.map_err(bad_gateway)?;
+    Ok(Thumbnail { content_type, body })
+}
+
+/// Picks the thumbnail asset, or a cloud-optimized GeoTIFF to render a preview from.
+fn source(item: &Item, render: bool) -> Option<Source> {
+    let mut assets: Vec<_> = item.assets.iter().collect();
+    assets.sort_by_key(|(key, _)| key.as_str());
+    if let Some((_, asset)) = assets
+        .iter()
+        .find(|(key, asset)| has_role(asset, "thumbnail") || key.as_str() == "thumbnail")
+    {
+        return Some(Source::Thumbnail(asset.href.clone()));
+    }
+    if !render {
+        return None;
+    }
+    let cogs: Vec<_> = assets
+        .into_iter()
+        .filter(|(_, asset)| {
+            asset
+                .r#type
+                .as_deref()
+                .map(|t| t.starts_with("image/tiff"))
+                .unwrap_or_default()
+        })
+        .collect();
+    cogs.iter()
+        .find(|(_, asset)| has_role(asset, "visual"))
+        .or_else(|| cogs.iter().find(|(_, asset)| has_role(asset, "data")))
+        .or_else(|| cogs.first())
+        .map(|(_, asset)| Source::Cog(asset.href.clone()))
+}
+
+fn has_role(asset: &Asset, role: &str) -> bool {
+    asset
+        .roles
+        .as_ref()
+        .map(|roles| roles.iter().any(|r| r == role))
+        .unwrap_or_default()
+}
+
+/// Returns the titiler url for a preview of a cloud-optimized GeoTIFF.
+fn preview_url(titiler: &str, href: &str, max_size: u32) -> Result<String, url::ParseError> {
+    let mut url = Url::parse(&format!(
+        "{}/cog/preview.png",
+        titiler.trim_end_matches('/')
+    ))?;
+    let _ = url
+        .query_pairs_mut()
+        .append_pair("url", href)
+        .append_pair("max_size", &max_size.to_string());
+    Ok(url.to_string())
+}
+
+impl Cache {
+    pub(crate) fn new(capacity: usize) -> Cache {
+        Cache {
+            capacity,
+            order: VecDeque::new(),
+            thumbnails: HashMap::new(),
+        }
+    }
+
+    fn get(&self, key: &(String, String)) -> Option<Thumbnail> {
+        self.thumbnails.get(key).cloned()
+    }
+
+    /// Adds a thumbnail, evicting the oldest one if the cache is full.
+    fn insert(&mut self, key: (String, String), thumbnail: Thumbnail) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.thumbnails.insert(key.clone(), thumbnail).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                let _ = self.thumbnails.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cache, Source, Thumbnail};
+    use axum::body::Bytes;
+    use stac::{Asset, Item};
+
+    fn asset(r#type: &str, role: &str) -> Asset {
+        let mut asset = Asset::new(format!("http://stac-server.test/{}.tif", role));
+        asset.r#type = Some(r#type.to_string());
+        asset.roles = Some(vec![role.to_string()]);
+        asset
+    }
+
+    #[test]
+    fn source() {
+        let mut item = Item::new("an-id");
+        let _ = item.assets.insert(
+            "data".to_string(),
+            asset("image/tiff; application=geotiff", "data"),
+        );
+        let _ = item.assets.insert(
+            "visual".to_string(),
+            asset("image/tiff; application=geotiff", "visual"),
+        );
+        assert_eq!(super::source(&item, false), None);
+        assert_eq!(
+            super::source(&item, true),
+            Some(Source::Cog(
+                "http://stac-server.test/visual.tif".to_string()
+            ))
+        );
+        let _ = item
+            .assets
+            .insert("preview".to_string(), asset("image/png", "thumbnail"));
+        assert_eq!(
+            super::source(&item, true),
+            Some(Source::Thumbnail(
+                "http://stac-server.test/thumbnail.tif".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn preview_url() {
+        assert_eq!(
+            super::preview_url("https://titiler.test/", "s3://bucket/a b.tif", 256).unwrap(),
+            "https://titiler.test/cog/preview.png?url=s3%3A%2F%2Fbucket%2Fa+b.tif&max_size=256"
+        );
+    }
+
+    #[test]
+    fn cache() {
+        let thumbnail = Thumbnail {
+            content_type: None,
+            body: Bytes::from_static(b"png"),
+        };
+        let key = |id: &str| ("a-collection".to_string(), id.to_string());
+        let mut cache = Cache::new(2);
+        cache.insert(key("a"), thumbnail.clone());
+        cache.insert(key("b"), thumbnail.clone());
+        cache.insert(key("a"), thumbnail.clone());
+        cache.insert(key("c"), thumbnail);
+        assert!(cache.get(&key("a")).is_none());
+        assert!(cache.get(&key("b")).is_some());
+        assert!(cache.get(&key("c")).is_some());
+    }
+}
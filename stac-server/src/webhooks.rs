@@ -0,0 +1,249 @@
+use crate::WebhooksConfig;
+use async_trait::async_trait;
+use axum::http::header::CONTENT_TYPE;
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+use stac_api_backend::{Event, EventListener};
+use std::time::Duration;
+
+/// The header that holds a delivery's signature.
+pub const SIGNATURE_HEADER: &str = "x-stac-signature";
+
+/// How long to wait before the first retry. The wait doubles after every attempt.
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// POSTs catalog changes to webhook urls.
+///
+/// Every write is one delivery per url, whose body is a batch of
+/// [CloudEvent](stac_api_backend::CloudEvent)s (content type
+/// `application/cloudevents-batch+json`). If there's a secret, the body's
+/// HMAC-SHA256 is sent as `X-Stac-Signature: sha256=<hex>`. Failed deliveries are retried with
+/// exponential backoff, and deliveries that still fail are logged (with their
+/// body) as dead letters.
+///
+/// Deliveries happen in the background, so writes aren't held up by slow
+/// receivers. Register with
+/// [Notifying::listener](stac_api_backend::Notifying::listener).
+///
+/// # Examples
+///
+/// ```
+/// use stac_api_backend::{MemoryBackend, Notifying};
+/// use stac_server::{Webhooks, WebhooksConfig};
+///
+/// let config = WebhooksConfig {
+///     urls: vec!["http://stac-server.test/hook".to_string()],
+///     ..Default::default()
+/// };
+/// let webhooks = Webhooks::new(config, "http://stac-server.test");
+/// let backend = Notifying::new(MemoryBackend::new()).listener(webhooks);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Webhooks {
+    config: WebhooksConfig,
+    source: String,
+    client: reqwest::Client,
+    retry_delay: Duration,
+}
+
+impl Webhooks {
+    /// Creates webhooks from their configuration.
+    ///
+    /// The source identifies this server in every event, e.g. its root url.
+    pub fn new(config: WebhooksConfig, source: impl ToString) -> Webhooks {
+        Webhooks {
+            config,
+            source: source.to_string(),
+            client: reqwest::Client::new(),
+            retry_delay: RETRY_DELAY,
+        }
+    }
+}
+
+#[async_trait]
+impl EventListener for Webhooks {
+    async fn notify(&self, events: &[Event]) {
+        let events: Vec<_> = events
+            .iter()
+            .map(|event| event.to_cloud_event(&self.source))
+            .collect();
+        let body = match serde_json::to_vec(&events) {
+            Ok(body) => body,
+            Err(err) => {
+                tracing::error!("could not serialize webhook events: {}", err);
+                return;
+            }
+        };
+        let signature = self
+            .config
+            .secret
+            .as_deref()
+            .map(|secret| signature(secret, &body));
+        for url in &self.config.urls {
+            let delivery = Delivery {
+                client: self.client.clone(),
+                url: url.clone(),
+                body: body.clone(),
+                signature: signature.clone(),
+            };
+            let _delivery = tokio::spawn(delivery.send(self.config.retries, self.retry_delay));
+        }
+    }
+}
+
+/// One POST to one url.
+#[derive(Debug)]
+struct Delivery {
+    client: reqwest::Client,
+    url: String,
+    body: Vec<u8>,
+    signature: Option<String>,
+}
+
+impl Delivery {
+    async fn send(self, retries: u32, mut retry_delay: Duration) {
+        let mut attempt = 0;
+        loop {
+            let mut request = self
+                .client
+                .post(&self.url)
+                .header(CONTENT_TYPE, "application/cloudevents-batch+json")
+                .body(self.body.clone());
+            if let Some(signature) = &self.signature {
+                request = request.header(SIGNATURE_HEADER, signature);
+            }
+            let result = request
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            match result {
+                Ok(_) => return,
+                Err(err) if attempt < retries => {
+                    tracing::warn!(
+                        "webhook delivery to {} failed, retrying in {:?}: {}",
+                        self.url,
+                        retry_delay,
+                        err
+                    );
+                }
+                Err(err) => {
+                    tracing::error!(
+                        url = self.url,
+                        body = %String::from_utf8_lossy(&self.body),
+                        "dead letter: webhook delivery failed after {} attempts: {}",
+                        attempt + 1,
+                        err
+                    );
+                    return;
+                }
+            }
+            tokio::time::sleep(retry_delay).await;
+            retry_delay *= 2;
+            attempt += 1;
+        }
+    }
+}
+
+/// Returns the `sha256=<hex>` HMAC of a body.
+fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac keys can be any length");
+    mac.update(body);
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("sha256={}", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Webhooks, SIGNATURE_HEADER};
+    use crate::WebhooksConfig;
+    use axum::{
+        body::Bytes, extract::State, http::HeaderMap, http::StatusCode, routing::post, Router,
+    };
+    use stac_api_backend::{MemoryBackend, Notifying, WriteBackend};
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    #[test]
+    fn signature() {
+        // From https://en.wikipedia.org/wiki/HMAC#Examples
+        assert_eq!(
+            super::signature("key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[tokio::test]
+    async fn deliver() {
+        type Received = Arc<Mutex<Vec<(Option<String>, Bytes)>>>;
+        let received: Received = Default::default();
+        let attempts = Arc::new(Mutex::new(0));
+        let router = Router::new()
+            .route(
+                "/hook",
+                post(
+                    |State((received, attempts)): State<(Received, Arc<Mutex<u32>>)>,
+                     headers: HeaderMap,
+                     body: Bytes| async move {
+                        let mut attempts = attempts.lock().unwrap();
+                        *attempts += 1;
+                        if *attempts == 1 {
+                            // Fail once, to make sure deliveries are retried.
+                            return StatusCode::SERVICE_UNAVAILABLE;
+                        }
+                        let signature = headers
+                            .get(SIGNATURE_HEADER)
+                            .map(|value| value.to_str().unwrap().to_string());
+                        received.lock().unwrap().push((signature, body));
+                        StatusCode::OK
+                    },
+                ),
+            )
+            .with_state((Arc::clone(&received), attempts));
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let _server = tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(router.into_make_service()),
+        );
+
+        let mut webhooks = Webhooks::new(
+            WebhooksConfig {
+                urls: vec![format!("http://{}/hook", addr)],
+                secret: Some("a secret".to_string()),
+                ..Default::default()
+            },
+            "http://stac-server.test",
+        );
+        webhooks.retry_delay = Duration::from_millis(10);
+        let mut backend = Notifying::new(MemoryBackend::new()).listener(webhooks);
+        let _ = backend
+            .add_collection(stac::Collection::new("an-id", "a description"))
+            .await
+            .unwrap();
+        for _ in 0..100 {
+            if !received.lock().unwrap().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let received = received.lock().unwrap();
+        let (signature, body) = received.first().expect("the webhook was delivered");
+        assert_eq!(
+            signature.as_deref().unwrap(),
+            super::signature("a secret", body)
+        );
+        let events: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(events[0]["type"], "org.stacspec.collection.created");
+        assert_eq!(events[0]["source"], "http://stac-server.test");
+        assert_eq!(events[0]["data"]["collection"], "an-id");
+    }
+}
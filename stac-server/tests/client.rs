@@ -3,7 +3,7 @@ use futures_util::stream::StreamExt;
 use geojson::{Geometry, Value};
 use stac::{Catalog, Collection, Item};
 use stac_api::Items;
-use stac_api_backend::{Backend, Error, MemoryBackend, PgstacBackend};
+use stac_api_backend::{Backend, Error, MemoryBackend, PgstacBackend, ReadBackend};
 use stac_async::ApiClient;
 use stac_server::Config;
 use stac_validate::Validate;
@@ -27,16 +27,25 @@ async fn pgstac() {
 async fn test<B>(mut backend: B)
 where
     B: Backend + 'static,
-    Error: From<<B as Backend>::Error>,
-    <B as Backend>::Paging: Send + Sync,
+    Error: From<<B as ReadBackend>::Error>,
+    <B as ReadBackend>::Paging: Send + Sync,
 {
-    if let Some(_) = backend.collection("collection-id").await.unwrap() {
-        backend.delete_collection("collection-id").await.unwrap();
+    for id in ["collection-id", "empty-collection-id"] {
+        if let Some(_) = backend.collection(id).await.unwrap() {
+            backend.delete_collection(id).await.unwrap();
+        }
     }
     backend
         .add_collection(Collection::new("collection-id", "A test collection"))
         .await
         .unwrap();
+    backend
+        .add_collection(Collection::new(
+            "empty-collection-id",
+            "A test collection without items",
+        ))
+        .await
+        .unwrap();
     let mut items = Vec::new();
     for i in 0..10 {
         let mut item = Item::new(format!("item-{}", i)).collection("collection-id");
@@ -56,6 +65,7 @@ where
         addr: "127.0.0.1:7822".to_string(),
         features: true,
         catalog: Catalog::new("a-catalog", "A test catalog"),
+        ..Default::default()
     };
 
     let listener = TcpListener::bind(&config.addr).unwrap();
@@ -142,4 +152,24 @@ where
         .collect()
         .await;
     assert_eq!(items.len(), 3);
+
+    let response = reqwest::get("http://127.0.0.1:7822/collections/collection-id/items?limit=2")
+        .await
+        .unwrap();
+    let item_collection: serde_json::Value =
+        serde_json::from_str(&response.text().await.unwrap()).unwrap();
+    assert_eq!(item_collection["numberReturned"], 2);
+
+    let items: Vec<_> = client
+        .items("empty-collection-id", None)
+        .await
+        .unwrap()
+        .map(|result| result.unwrap())
+        .collect()
+        .await;
+    assert!(items.is_empty());
+    let response = reqwest::get("http://127.0.0.1:7822/collections/not-an-id/items")
+        .await
+        .unwrap();
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
 }